@@ -0,0 +1,62 @@
+//! Compares generating keypairs with `generate_batch` against calling `generate` in a loop,
+//! which creates a manager for every keypair
+//!
+//! Run with `cargo bench --bench key_gen_batch`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use qubitcrypt::dsas::{DsaAlgorithm, DsaKeyGenerator};
+use qubitcrypt::kems::{KemAlgorithm, KemKeyGenerator};
+
+/// The number of keypairs of a batch
+const BATCH: usize = 200;
+
+/// Run `f` once to warm up, then again and return the time per keypair
+fn measure(f: impl Fn()) -> Duration {
+    f();
+    let start = Instant::now();
+    f();
+    start.elapsed() / BATCH as u32
+}
+
+/// Print the time per keypair of the loop and of the batch
+fn report(name: &str, naive: Duration, batch: Duration) {
+    println!(
+        "{:<24} loop {:>10.2?}  batch {:>10.2?}  speedup {:>6.2}x",
+        name,
+        naive,
+        batch,
+        naive.as_secs_f64() / batch.as_secs_f64()
+    );
+}
+
+fn main() {
+    for alg in [KemAlgorithm::MlKem768, KemAlgorithm::X25519] {
+        let naive = measure(|| {
+            let mut key_generator = KemKeyGenerator::new(alg);
+            for _ in 0..BATCH {
+                black_box(key_generator.generate().unwrap());
+            }
+        });
+        let batch = measure(|| {
+            let mut key_generator = KemKeyGenerator::new(alg);
+            black_box(key_generator.generate_batch(BATCH).unwrap());
+        });
+        report(&format!("{:?}", alg), naive, batch);
+    }
+
+    for alg in [DsaAlgorithm::MlDsa44, DsaAlgorithm::MlDsa44Ed25519SHA512] {
+        let naive = measure(|| {
+            let mut key_generator = DsaKeyGenerator::new(alg);
+            for _ in 0..BATCH {
+                black_box(key_generator.generate().unwrap());
+            }
+        });
+        let batch = measure(|| {
+            let mut key_generator = DsaKeyGenerator::new(alg);
+            black_box(key_generator.generate_batch(BATCH).unwrap());
+        });
+        report(&format!("{:?}", alg), naive, batch);
+    }
+}
@@ -0,0 +1,72 @@
+//! Compares resolving algorithms by OID with the lookup maps against scanning the algorithm
+//! tables, which is what `from_oid` did before the maps
+//!
+//! Run with `cargo bench --bench oid_lookup`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use qubitcrypt::dsas::DsaAlgorithm;
+use qubitcrypt::kems::KemAlgorithm;
+use strum::IntoEnumIterator;
+
+/// The number of times every OID is resolved
+const ROUNDS: usize = 2_000;
+
+/// Run `f` for every OID `ROUNDS` times and return the time per lookup
+fn measure<T>(oids: &[String], f: impl Fn(&str) -> Option<T>) -> Duration {
+    // Warm up, which also builds the lookup maps
+    for oid in oids {
+        black_box(f(black_box(oid)));
+    }
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for oid in oids {
+            black_box(f(black_box(oid)));
+        }
+    }
+    start.elapsed() / (ROUNDS * oids.len()) as u32
+}
+
+/// Print the time per lookup of the scan and of the map
+fn report(name: &str, scan: Duration, map: Duration) {
+    println!(
+        "{:<24} scan {:>10.2?}  map {:>10.2?}  speedup {:>6.1}x",
+        name,
+        scan,
+        map,
+        scan.as_secs_f64() / map.as_secs_f64()
+    );
+}
+
+fn main() {
+    let dsa_oids: Vec<String> = DsaAlgorithm::iter().map(|a| a.get_oid()).collect();
+    let scan = measure(&dsa_oids, |oid| {
+        DsaAlgorithm::iter().find(|a| a.get_oid() == oid)
+    });
+    let map = measure(&dsa_oids, DsaAlgorithm::from_oid);
+    report("DsaAlgorithm::from_oid", scan, map);
+
+    let kem_oids: Vec<String> = KemAlgorithm::iter().map(|a| a.get_oid()).collect();
+    let scan = measure(&kem_oids, |oid| {
+        KemAlgorithm::iter().find(|a| a.get_oid() == oid)
+    });
+    let map = measure(&kem_oids, KemAlgorithm::from_oid);
+    report("KemAlgorithm::from_oid", scan, map);
+
+    // An unknown OID is the worst case of a scan
+    let unknown = vec!["1.2.3.4".to_string()];
+    let scan = measure(&unknown, |oid| {
+        DsaAlgorithm::iter()
+            .map(|a| a.get_oid())
+            .chain(KemAlgorithm::iter().map(|a| a.get_oid()))
+            .find(|known| known == oid)
+    });
+    let map = measure(&unknown, |oid| {
+        DsaAlgorithm::from_oid(oid)
+            .map(|a| a.get_oid())
+            .or_else(|| KemAlgorithm::from_oid(oid).map(|a| a.get_oid()))
+    });
+    report("unknown OID", scan, map);
+}
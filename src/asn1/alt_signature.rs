@@ -0,0 +1,143 @@
+//! The alternative signature extensions defined in ITU-T X.509 (10/2019) Section 9.8
+//!
+//! These carry a second public key and a second signature in a certificate, so that a
+//! certificate can be signed with both a traditional and a post-quantum algorithm. Relying
+//! parties which don't understand the extensions ignore them, as they are non-critical.
+
+use const_oid::{AssociatedOid, ObjectIdentifier};
+use der::asn1::BitString;
+use der::{Decode, Encode, Reader, Sequence, Writer};
+use spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+use x509_cert::certificate::{TbsCertificate, Version};
+use x509_cert::ext::{AsExtension, Extension, Extensions};
+use x509_cert::name::Name;
+use x509_cert::serial_number::SerialNumber;
+use x509_cert::time::Validity;
+
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// Implement the traits needed to use a newtype as a non-critical extension
+macro_rules! impl_alt_extension {
+    ($name:ident, $inner:ty, $oid:expr) => {
+        impl AssociatedOid for $name {
+            const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap($oid);
+        }
+
+        impl Encode for $name {
+            fn encoded_len(&self) -> der::Result<der::Length> {
+                self.0.encoded_len()
+            }
+
+            fn encode(&self, writer: &mut impl Writer) -> der::Result<()> {
+                self.0.encode(writer)
+            }
+        }
+
+        impl<'a> Decode<'a> for $name {
+            fn decode<R: Reader<'a>>(reader: &mut R) -> der::Result<Self> {
+                Ok($name(<$inner>::decode(reader)?))
+            }
+        }
+
+        impl AsExtension for $name {
+            fn critical(&self, _subject: &Name, _extensions: &[Extension]) -> bool {
+                false
+            }
+        }
+    };
+}
+
+/// The `subjectAltPublicKeyInfo` extension
+/// ```text
+///   SubjectAltPublicKeyInfo ::= SEQUENCE {
+///     algorithm AlgorithmIdentifier{{SupportedAlgorithms}},
+///     subjectAltPublicKey BIT STRING }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubjectAltPublicKeyInfo(pub SubjectPublicKeyInfoOwned);
+impl_alt_extension!(
+    SubjectAltPublicKeyInfo,
+    SubjectPublicKeyInfoOwned,
+    "2.5.29.72"
+);
+
+/// The `altSignatureAlgorithm` extension
+/// ```text
+///   AltSignatureAlgorithm ::= AlgorithmIdentifier{{SupportedAlgorithms}}
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AltSignatureAlgorithm(pub AlgorithmIdentifierOwned);
+impl_alt_extension!(AltSignatureAlgorithm, AlgorithmIdentifierOwned, "2.5.29.73");
+
+/// The `altSignatureValue` extension
+/// ```text
+///   AltSignatureValue ::= BIT STRING
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AltSignatureValue(pub BitString);
+impl_alt_extension!(AltSignatureValue, BitString, "2.5.29.74");
+
+/// The `PreTBSCertificate`, which is the message signed by the alternative signature. It is the
+/// TBSCertificate without the `signature` field and without the `altSignatureValue` extension.
+/// ```text
+///   PreTBSCertificate ::= SEQUENCE {
+///     version [0] Version DEFAULT v1,
+///     serialNumber CertificateSerialNumber,
+///     issuer Name,
+///     validity Validity,
+///     subject Name,
+///     subjectPublicKeyInfo SubjectPublicKeyInfo,
+///     issuerUniqueIdentifier [1] IMPLICIT UniqueIdentifier OPTIONAL,
+///     subjectUniqueIdentifier [2] IMPLICIT UniqueIdentifier OPTIONAL,
+///     extensions [3] Extensions OPTIONAL }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct PreTbsCertificate {
+    #[asn1(context_specific = "0", default = "Default::default")]
+    version: Version,
+    serial_number: SerialNumber,
+    issuer: Name,
+    validity: Validity,
+    subject: Name,
+    subject_public_key_info: SubjectPublicKeyInfoOwned,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    issuer_unique_id: Option<BitString>,
+    #[asn1(context_specific = "2", tag_mode = "IMPLICIT", optional = "true")]
+    subject_unique_id: Option<BitString>,
+    #[asn1(context_specific = "3", tag_mode = "EXPLICIT", optional = "true")]
+    extensions: Option<Extensions>,
+}
+
+/// Get the DER encoded `PreTBSCertificate` of a TBSCertificate
+///
+/// # Arguments
+///
+/// * `tbs` - The TBSCertificate
+///
+/// # Returns
+///
+/// The message to sign or verify the alternative signature with
+pub fn get_pre_tbs_der(tbs: &TbsCertificate) -> Result<Vec<u8>> {
+    let extensions = tbs.extensions.as_ref().map(|extensions| {
+        extensions
+            .iter()
+            .filter(|ext| ext.extn_id != AltSignatureValue::OID)
+            .cloned()
+            .collect::<Extensions>()
+    });
+    PreTbsCertificate {
+        version: tbs.version,
+        serial_number: tbs.serial_number.clone(),
+        issuer: tbs.issuer.clone(),
+        validity: tbs.validity,
+        subject: tbs.subject.clone(),
+        subject_public_key_info: tbs.subject_public_key_info.clone(),
+        issuer_unique_id: tbs.issuer_unique_id.clone(),
+        subject_unique_id: tbs.subject_unique_id.clone(),
+        extensions,
+    }
+    .to_der()
+    .map_err(|_| QubitCryptError::InvalidCertificate)
+}
@@ -1,5 +1,8 @@
-use der::{oid::ObjectIdentifier, Encode};
+use der::{asn1::BitString, oid::ObjectIdentifier, Encode};
 
+use crate::dsa::common::config::security_category::SecurityCategory as _;
+use crate::kem::common::config::security_category::SecurityCategory as _;
+use crate::utils::registry;
 use crate::{
     dsa::common::dsa_type::DsaType, dsas::DsaAlgorithm, errors, kem::common::kem_type::KemType,
     kems::KemAlgorithm,
@@ -38,16 +41,36 @@ pub fn oid_to_der(oid: &str) -> Result<Vec<u8>> {
 /// # Returns
 ///
 /// True if the OID is valid, false otherwise
-pub fn is_valid_kem_or_dsa_oid(oid: &String) -> bool {
-    // Get all oids based on dsa and kem types as a string array
-    let dsa_oids = DsaAlgorithm::all();
-    let kem_oids = KemAlgorithm::all();
-
-    let all_dsa_oids: Vec<String> = dsa_oids.iter().map(|x| x.get_oid()).collect();
-    let all_kem_oids: Vec<String> = kem_oids.iter().map(|x| x.get_oid()).collect();
+pub fn is_valid_kem_or_dsa_oid(oid: &str) -> bool {
+    DsaAlgorithm::from_oid(oid).is_some()
+        || KemAlgorithm::from_oid(oid).is_some()
+        || registry::is_custom_kem_oid(oid)
+        || registry::is_custom_dsa_oid(oid)
+}
 
-    // Check if oid is valid
-    all_dsa_oids.contains(oid) || all_kem_oids.contains(oid)
+/// Get the octets of a BIT STRING holding key material
+///
+/// Public keys are always a whole number of octets, so `to_bitstring` encodes them with 0
+/// unused bits. Some encoders get this wrong and set the unused bits field anyway, which is
+/// rejected here rather than silently dropping the trailing bits.
+///
+/// # Arguments
+///
+/// * `bits` - The BIT STRING
+///
+/// # Returns
+///
+/// The key material
+///
+/// # Errors
+///
+/// `QubitCryptError::UnusedKeyBits` will be returned if the BIT STRING has non-zero unused
+/// bits
+pub(crate) fn get_key_octets(bits: &BitString) -> Result<&[u8]> {
+    if bits.unused_bits() != 0 {
+        return Err(errors::QubitCryptError::UnusedKeyBits(bits.unused_bits()));
+    }
+    Ok(bits.raw_bytes())
 }
 
 /// Check if an OID is a composite KEM / DSA OID
@@ -83,9 +106,9 @@ pub fn is_composite_kem_or_dsa_oid(oid: &str) -> bool {
 ///
 /// # Returns
 ///
-/// True if the OID is a KEM OID, false otherwise
+/// True if the OID is a KEM OID or the OID of a custom KEM, false otherwise
 pub fn is_kem_oid(oid: &str) -> bool {
-    KemAlgorithm::from_oid(oid).is_some()
+    KemAlgorithm::from_oid(oid).is_some() || registry::is_custom_kem_oid(oid)
 }
 
 /// Check if an OID is a DSA OID
@@ -96,14 +119,129 @@ pub fn is_kem_oid(oid: &str) -> bool {
 ///
 /// # Returns
 ///
-/// True if the OID is a DSA OID, false otherwise
+/// True if the OID is a DSA OID or the OID of a custom DSA, false otherwise
 pub fn is_dsa_oid(oid: &str) -> bool {
-    DsaAlgorithm::from_oid(oid).is_some()
+    DsaAlgorithm::from_oid(oid).is_some() || registry::is_custom_dsa_oid(oid)
+}
+
+/// Get the NIST security category of a KEM / DSA OID
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM / DSA
+///
+/// # Returns
+///
+/// The NIST security category (1 to 5, or 0 if it doesn't reach category 1), or None if the OID
+/// is not a KEM / DSA OID
+pub fn get_security_category(oid: &str) -> Option<u8> {
+    if let Some(d_type) = DsaType::from_oid(oid) {
+        Some(d_type.get_security_category())
+    } else {
+        KemType::from_oid(oid).map(|k_type| k_type.get_security_category())
+    }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_get_key_octets() {
+        let bits = BitString::new(0, vec![1u8, 2, 3]).unwrap();
+        assert_eq!(get_key_octets(&bits).unwrap(), &[1, 2, 3]);
+
+        let bits = BitString::new(3, vec![1u8, 2, 8]).unwrap();
+        let err = get_key_octets(&bits).unwrap_err();
+        assert_eq!(err, errors::QubitCryptError::UnusedKeyBits(3));
+        assert_eq!(
+            err.to_string(),
+            "The BIT STRING of the key has 3 unused bits, but keys are a whole number of octets"
+        );
+    }
+
+    #[test]
+    fn test_oid_lookup_matches_scan() {
+        use crate::dsa::common::config::oids::Oid as _;
+        use crate::kem::common::config::oids::Oid as _;
+
+        // The lookup maps give the same results as scanning the tables
+        for dsa_type in DsaType::all() {
+            let oid = dsa_type.get_oid();
+            let scanned = DsaType::all().into_iter().find(|t| t.get_oid() == oid);
+            assert_eq!(DsaType::from_oid(&oid), scanned);
+        }
+        for kem_type in KemType::all() {
+            let oid = kem_type.get_oid();
+            let scanned = KemType::all().into_iter().find(|t| t.get_oid() == oid);
+            assert_eq!(KemType::from_oid(&oid), scanned);
+        }
+        for algorithm in DsaAlgorithm::all() {
+            let oid = algorithm.get_oid();
+            let scanned = DsaAlgorithm::all().into_iter().find(|a| a.get_oid() == oid);
+            assert_eq!(DsaAlgorithm::from_oid(&oid), scanned);
+            assert!(is_valid_kem_or_dsa_oid(&oid));
+            assert!(is_dsa_oid(&oid) && !is_kem_oid(&oid));
+        }
+        for algorithm in KemAlgorithm::all() {
+            let oid = algorithm.get_oid();
+            let scanned = KemAlgorithm::all().into_iter().find(|a| a.get_oid() == oid);
+            assert_eq!(KemAlgorithm::from_oid(&oid), scanned);
+            assert!(is_valid_kem_or_dsa_oid(&oid));
+            assert!(is_kem_oid(&oid) && !is_dsa_oid(&oid));
+        }
+
+        let unknown = "1.2.3.4".to_string();
+        assert_eq!(DsaType::from_oid(&unknown), None);
+        assert_eq!(KemType::from_oid(&unknown), None);
+        assert!(!is_valid_kem_or_dsa_oid(&unknown));
+        assert!(!is_valid_kem_or_dsa_oid(""));
+
+        // Concurrent first lookups all see the complete map
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    DsaAlgorithm::all()
+                        .iter()
+                        .all(|a| DsaAlgorithm::from_oid(&a.get_oid()) == Some(*a))
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_is_composite() {
+        use crate::dsa::common::config::oids::Oid as _;
+
+        // The traditional, ML-DSA and SLH-DSA types are pure, only the ML-DSA composites aren't
+        for dsa_type in [
+            DsaType::Rsa2048PssSHA256,
+            DsaType::EcdsaP384SHA512,
+            DsaType::Ed448SHA512,
+            DsaType::MlDsa44,
+            DsaType::SlhDsaSha2_128s,
+            DsaType::SlhDsaShake256f,
+        ] {
+            assert!(!dsa_type.is_composite());
+            assert!(!is_composite_kem_or_dsa_oid(&dsa_type.get_oid()));
+        }
+        assert!(DsaType::MlDsa87Ed448SHA512.is_composite());
+        assert!(is_composite_kem_or_dsa_oid(
+            &DsaType::MlDsa44Rsa2048PssSha256.get_oid()
+        ));
+        let composites = DsaType::all().into_iter().filter(DsaType::is_composite);
+        assert_eq!(composites.count(), 13);
+
+        for algorithm in DsaAlgorithm::all() {
+            assert_eq!(
+                algorithm.is_composite(),
+                algorithm.get_dsa_type().is_composite()
+            );
+        }
+    }
+
     #[test]
     fn test_oid_to_der() {
         // This tests the Domain separator encoding:
@@ -0,0 +1,271 @@
+use der::asn1::ObjectIdentifier;
+use pkcs8::spki::DynSignatureAlgorithmIdentifier;
+use signature::{Keypair, Signer};
+use spki::AlgorithmIdentifierOwned;
+use x509_cert::builder::{Builder, Profile};
+
+use crate::asn1::asn_util::is_dsa_oid;
+use crate::asn1::cert_builder::{CertValidity, CertificateBuilder, SerialStrategy};
+use crate::asn1::certificate::Certificate;
+use crate::asn1::distinguished_name::IntoSubject;
+use crate::asn1::private_key::PrivateKey;
+use crate::asn1::public_key::PublicKey;
+use crate::asn1::signature::DsaSignature;
+use crate::dsa::common::dsa_trait::Dsa;
+use crate::dsa::common::dsa_type::DsaType;
+use crate::dsa::dsa_manager::DsaManager;
+use crate::dsa::ml_dsa::PreparedMlDsaKey;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The signing state of the issuer, created once for all certificates
+enum PreparedKey {
+    /// A parsed ML-DSA private key
+    MlDsa(PreparedMlDsaKey),
+    /// Any other DSA, signing with the raw private key
+    Other(DsaManager),
+}
+
+/// An issuer's private key prepared for signing many certificates
+struct PreparedSigner<'a> {
+    sk: &'a PrivateKey,
+    key: PreparedKey,
+    public_key: PublicKey,
+    algorithm: AlgorithmIdentifierOwned,
+}
+
+impl Signer<DsaSignature> for PreparedSigner<'_> {
+    fn try_sign(&self, tbs: &[u8]) -> core::result::Result<DsaSignature, signature::Error> {
+        let sig = match &self.key {
+            PreparedKey::MlDsa(key) => key.sign(tbs),
+            PreparedKey::Other(dsa) => dsa.sign(self.sk.get_key(), tbs),
+        };
+        let sig = sig.map_err(|_| signature::Error::new())?;
+        Ok(DsaSignature(sig))
+    }
+}
+
+impl Keypair for PreparedSigner<'_> {
+    type VerifyingKey = PublicKey;
+
+    fn verifying_key(&self) -> <Self as Keypair>::VerifyingKey {
+        self.public_key.clone()
+    }
+}
+
+impl DynSignatureAlgorithmIdentifier for PreparedSigner<'_> {
+    fn signature_algorithm_identifier(
+        &self,
+    ) -> core::result::Result<AlgorithmIdentifierOwned, spki::Error> {
+        Ok(self.algorithm.clone())
+    }
+}
+
+/// Issue many certificates with the same signer
+///
+/// `CertificateBuilder` parses the signer's private key and derives its public key for every
+/// certificate. A `BatchIssuer` does this once, which matters when thousands of leaf
+/// certificates are issued under one CA, as parsing an ML-DSA private key costs more than
+/// signing a certificate with it.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::certificates::{BatchIssuer, CertValidity, CertificateBuilder, Profile};
+/// use qubitcrypt::dsas::{DsaAlgorithm, DsaKeyGenerator};
+/// use qubitcrypt::kems::{KemAlgorithm, KemKeyGenerator};
+///
+/// let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44).generate().unwrap();
+/// let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+/// let cert_root = CertificateBuilder::new(Profile::Root, None, validity.clone(), "CN=Root CA", pk_root, &sk_root)
+///     .unwrap()
+///     .build()
+///     .unwrap();
+///
+/// let issuer = BatchIssuer::new(&sk_root).unwrap();
+/// for i in 0..3 {
+///     let (pk, _) = KemKeyGenerator::new(KemAlgorithm::MlKem768).generate().unwrap();
+///     let profile = Profile::Leaf {
+///         issuer: cert_root.get_subject(),
+///         enable_key_agreement: false,
+///         enable_key_encipherment: true,
+///     };
+///     let cert = issuer.issue(profile, format!("CN=leaf {}", i), pk, validity.clone()).unwrap();
+///     assert!(cert_root.verify_child(&cert).unwrap());
+/// }
+/// ```
+pub struct BatchIssuer<'a> {
+    signer: PreparedSigner<'a>,
+}
+
+impl<'a> BatchIssuer<'a> {
+    /// Prepare a signer for issuing certificates
+    ///
+    /// # Arguments
+    ///
+    /// * `signer_sk` - The private key used to sign the certificates
+    ///
+    /// # Returns
+    ///
+    /// A new batch issuer
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::KeyUsageViolation` will be returned if the signer is not a DSA key,
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the key can't be parsed
+    pub fn new(signer_sk: &'a PrivateKey) -> Result<BatchIssuer<'a>> {
+        if !is_dsa_oid(signer_sk.get_oid()) {
+            return Err(QubitCryptError::KeyUsageViolation);
+        }
+        let (key, pk) = match DsaType::from_oid(signer_sk.get_oid()) {
+            Some(dsa_type @ (DsaType::MlDsa44 | DsaType::MlDsa65 | DsaType::MlDsa87)) => {
+                let key = PreparedMlDsaKey::new(&dsa_type, signer_sk.get_key())?;
+                let pk = key.get_public_key();
+                (PreparedKey::MlDsa(key), pk)
+            }
+            _ => {
+                let dsa = DsaManager::new_from_oid(signer_sk.get_oid())?;
+                let pk = match signer_sk.embedded_public_key() {
+                    Some(pk) => pk.get_key().to_vec(),
+                    None => dsa
+                        .get_public_key(signer_sk.get_key())
+                        .map_err(|_| QubitCryptError::InvalidPrivateKey)?,
+                };
+                (PreparedKey::Other(dsa), pk)
+            }
+        };
+
+        let oid: ObjectIdentifier = signer_sk
+            .get_oid()
+            .parse()
+            .map_err(|_| QubitCryptError::InvalidOid)?;
+        let signer = PreparedSigner {
+            sk: signer_sk,
+            key,
+            public_key: PublicKey::new(signer_sk.get_oid(), &pk)?,
+            algorithm: AlgorithmIdentifierOwned {
+                oid,
+                parameters: None,
+            },
+        };
+        Ok(BatchIssuer { signer })
+    }
+
+    /// Issue a certificate with a random serial number
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The certificate profile
+    /// * `subject` - The subject name of the certificate, as a string or a `DistinguishedName`
+    /// * `subject_pk` - The public key of the subject
+    /// * `validity` - The validity period of the certificate
+    ///
+    /// # Returns
+    ///
+    /// The signed certificate
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `CertificateBuilder::new` and `CertificateBuilder::build`
+    pub fn issue(
+        &self,
+        profile: Profile,
+        subject: impl IntoSubject,
+        subject_pk: PublicKey,
+        validity: CertValidity,
+    ) -> Result<Certificate> {
+        let (serial_number, validity, subject, spki) = CertificateBuilder::get_tbs_parts(
+            &profile,
+            SerialStrategy::Random,
+            validity,
+            subject,
+            &subject_pk,
+            self.signer.sk,
+        )?;
+
+        let builder = x509_cert::builder::CertificateBuilder::new(
+            profile,
+            serial_number,
+            validity,
+            subject,
+            spki,
+            &self.signer,
+        )
+        .map_err(|_| QubitCryptError::Unknown)?;
+        let cert_inner = builder.build().map_err(|_| QubitCryptError::Unknown)?;
+        Ok(Certificate::new(cert_inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    #[test]
+    fn test_batch_issuer() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        for dsa_alg in [
+            DsaAlgorithm::MlDsa65,
+            DsaAlgorithm::SlhDsaSha2_128f,
+            DsaAlgorithm::MlDsa44EcdsaP256SHA256,
+        ] {
+            let (pk_root, sk_root) = DsaKeyGenerator::new(dsa_alg).generate().unwrap();
+            let root = CertificateBuilder::new(
+                Profile::Root,
+                None,
+                validity.clone(),
+                "CN=root",
+                pk_root,
+                &sk_root,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+            let issuer = BatchIssuer::new(&sk_root).unwrap();
+            let mut serials = HashSet::new();
+            for i in 0..3 {
+                let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+                    .generate()
+                    .unwrap();
+                let profile = Profile::Leaf {
+                    issuer: root.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: false,
+                };
+                let cert = issuer
+                    .issue(profile, format!("CN=leaf {}", i), pk, validity.clone())
+                    .unwrap();
+                assert!(root.verify_child(&cert).unwrap());
+                serials.insert(cert.get_serial_number().as_bytes().to_vec());
+            }
+            assert_eq!(serials.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_batch_issuer_errors() {
+        let (pk_kem, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        assert!(matches!(
+            BatchIssuer::new(&sk_kem),
+            Err(QubitCryptError::KeyUsageViolation)
+        ));
+
+        // The KEM key can't be used in a CA certificate
+        let (_, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let issuer = BatchIssuer::new(&sk).unwrap();
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        assert!(matches!(
+            issuer.issue(Profile::Root, "CN=root", pk_kem, validity),
+            Err(QubitCryptError::KeyUsageViolation)
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::asn1::certificate::Certificate;
+use crate::keys::{PrivateKey, PublicKey};
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The keys and certificates read from a file containing several PEM blocks
+#[derive(Default)]
+pub struct MixedBundle {
+    /// The private keys, in the order they appear in the file
+    pub private_keys: Vec<PrivateKey>,
+    /// The public keys, in the order they appear in the file
+    pub public_keys: Vec<PublicKey>,
+    /// The certificates, in the order they appear in the file
+    pub certificates: Vec<Certificate>,
+}
+
+impl MixedBundle {
+    /// Read the keys and certificates from a string containing any mix of PEM blocks
+    ///
+    /// The blocks are dispatched on their tag: `PRIVATE KEY`, `PUBLIC KEY` and `CERTIFICATE`.
+    /// Blocks with any other tag and text between the blocks are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `pem` - The PEM blocks
+    ///
+    /// # Returns
+    ///
+    /// The keys and certificates
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::SerializationFailed` will be returned if the PEM is malformed.
+    /// `QubitCryptError::InvalidPrivateKey`, `QubitCryptError::InvalidPublicKey` or
+    /// `QubitCryptError::InvalidCertificate` will be returned if a block with a known tag can't
+    /// be decoded
+    pub fn from_pem(pem: &str) -> Result<MixedBundle> {
+        let blocks = pem::parse_many(pem).map_err(|_| QubitCryptError::SerializationFailed)?;
+
+        let mut bundle = MixedBundle::default();
+        for block in blocks {
+            match block.tag() {
+                "PRIVATE KEY" => bundle
+                    .private_keys
+                    .push(PrivateKey::from_der(block.contents())?),
+                "PUBLIC KEY" => bundle
+                    .public_keys
+                    .push(PublicKey::from_der(block.contents())?),
+                "CERTIFICATE" => bundle
+                    .certificates
+                    .push(Certificate::from_der(block.contents())?),
+                _ => {}
+            }
+        }
+        Ok(bundle)
+    }
+}
+
+/// Read the keys and certificates from a file containing any mix of PEM blocks
+///
+/// See `MixedBundle::from_pem` for the details.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file
+///
+/// # Returns
+///
+/// The keys and certificates
+///
+/// # Errors
+///
+/// `QubitCryptError::FileReadError` will be returned if the file can't be read, otherwise the
+/// same errors as `MixedBundle::from_pem`
+pub fn read_all(path: &str) -> Result<MixedBundle> {
+    let contents = std::fs::read_to_string(path).map_err(|_| QubitCryptError::FileReadError)?;
+    MixedBundle::from_pem(&contents)
+}
+
+/// Write private keys and certificates to a single PEM file
+///
+/// This is the inverse of `read_all`. The private keys are written first, followed by the
+/// certificates in the given order, which is the layout servers expect for a combined key and
+/// chain file: the private key, the end-entity certificate, then the intermediate CAs up to
+/// (optionally) the root. The blocks use the `PRIVATE KEY` and `CERTIFICATE` tags.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file
+/// * `private_keys` - The private keys
+/// * `certs` - The certificates, starting with the end-entity certificate
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidPrivateKey` or `QubitCryptError::InvalidCertificate` will be
+/// returned if a key or certificate can't be encoded, `QubitCryptError::FileWriteError` if the
+/// file can't be written
+pub fn write_bundle(path: &str, private_keys: &[PrivateKey], certs: &[Certificate]) -> Result<()> {
+    let mut contents = String::new();
+    for sk in private_keys {
+        push_block(&mut contents, &sk.to_pem()?);
+    }
+    for cert in certs {
+        push_block(&mut contents, &cert.to_pem()?);
+    }
+    std::fs::write(path, contents).map_err(|_| QubitCryptError::FileWriteError)
+}
+
+/// Append a PEM block, making sure it ends with a line break
+fn push_block(contents: &mut String, block: &str) {
+    contents.push_str(block);
+    if !block.ends_with('\n') {
+        contents.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::{CertValidity, CertificateBuilder, Profile};
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    #[test]
+    fn test_read_all() {
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (pk_kem, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let cert = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity,
+            "CN=example.com".to_string(),
+            pk.clone(),
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let ta = Certificate::from_file(
+            "test/data/MlDsa44EcdsaP256SHA256-2.16.840.1.114027.80.8.1.4_ta.pem",
+        )
+        .unwrap();
+
+        let unknown = pem::encode(&pem::Pem::new("X509 CRL", vec![0x30, 0x00]));
+        let contents = [
+            "Bag Attributes: some tooling writes this\n".to_string(),
+            sk_kem.to_pem().unwrap(),
+            cert.to_pem().unwrap(),
+            unknown,
+            pk_kem.to_pem().unwrap(),
+            ta.to_pem().unwrap(),
+            sk.to_pem().unwrap(),
+        ]
+        .join("\n");
+
+        let path = std::env::temp_dir().join("qubitcrypt_test_bundle.pem");
+        std::fs::write(&path, contents).unwrap();
+        let bundle = read_all(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(bundle.private_keys.len(), 2);
+        assert_eq!(
+            bundle.private_keys[0].to_der().unwrap(),
+            sk_kem.to_der().unwrap()
+        );
+        assert_eq!(
+            bundle.private_keys[1].to_der().unwrap(),
+            sk.to_der().unwrap()
+        );
+        assert_eq!(bundle.public_keys.len(), 1);
+        assert!(bundle.public_keys[0].equals(&pk_kem));
+        assert_eq!(bundle.certificates.len(), 2);
+        assert_eq!(
+            bundle.certificates[0].to_der().unwrap(),
+            cert.to_der().unwrap()
+        );
+        assert!(bundle.certificates[1].verify_self_signed().unwrap());
+
+        // A known tag with bad contents is an error
+        let bad = pem::encode(&pem::Pem::new("CERTIFICATE", vec![0x30, 0x00]));
+        assert!(matches!(
+            MixedBundle::from_pem(&bad),
+            Err(QubitCryptError::InvalidCertificate)
+        ));
+
+        assert!(MixedBundle::from_pem("").unwrap().certificates.is_empty());
+        assert!(matches!(
+            read_all("does/not/exist.pem"),
+            Err(QubitCryptError::FileReadError)
+        ));
+    }
+
+    #[test]
+    fn test_write_bundle() {
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let ta = CertificateBuilder::new(Profile::Root, None, validity, "CN=ta", pk, &sk)
+            .unwrap()
+            .build()
+            .unwrap();
+        let (ee_pk, ee_sk) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let ee = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: ta.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: true,
+            },
+            None,
+            validity,
+            "CN=ee",
+            ee_pk,
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let path = std::env::temp_dir().join("qubitcrypt_test_write_bundle.pem");
+        let path = path.to_str().unwrap();
+        write_bundle(
+            path,
+            std::slice::from_ref(&ee_sk),
+            &[ee.clone(), ta.clone()],
+        )
+        .unwrap();
+
+        let contents = std::fs::read_to_string(path).unwrap();
+        let tags: Vec<String> = pem::parse_many(&contents)
+            .unwrap()
+            .iter()
+            .map(|block| block.tag().to_string())
+            .collect();
+        assert_eq!(tags, ["PRIVATE KEY", "CERTIFICATE", "CERTIFICATE"]);
+
+        let bundle = read_all(path).unwrap();
+        assert_eq!(
+            bundle.private_keys[0].to_der().unwrap(),
+            ee_sk.to_der().unwrap()
+        );
+        assert_eq!(
+            bundle.certificates[0].to_der().unwrap(),
+            ee.to_der().unwrap()
+        );
+        assert!(bundle.certificates[1]
+            .verify_child(&bundle.certificates[0])
+            .unwrap());
+
+        assert!(matches!(
+            write_bundle("does/not/exist/bundle.pem", &[], &[ta]),
+            Err(QubitCryptError::FileWriteError)
+        ));
+    }
+}
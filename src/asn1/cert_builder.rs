@@ -1,19 +1,31 @@
 use std::error::Error;
-use std::str::FromStr;
 
 use chrono::{DateTime, Datelike, TimeZone, Timelike};
-use pkcs8::spki::SubjectPublicKeyInfo;
+use der::asn1::BitString;
+use der::{Decode, Encode};
+use pkcs8::spki::DynSignatureAlgorithmIdentifier;
 use rand::RngCore;
 use rand_core::OsRng;
+use sha2::{Digest, Sha256};
 use x509_cert::builder::Builder;
 pub use x509_cert::builder::Profile;
+use x509_cert::certificate::TbsCertificate;
+use x509_cert::ext::pkix::certpolicy::PolicyInformation;
+use x509_cert::ext::pkix::CertificatePolicies;
 use x509_cert::ext::AsExtension;
+use x509_cert::name::Name;
+use x509_cert::spki::SubjectPublicKeyInfoOwned;
 use x509_cert::time::Time;
-use x509_cert::{name::Name, serial_number::SerialNumber, time::Validity};
+use x509_cert::{serial_number::SerialNumber, time::Validity};
 
 use crate::{errors::QubitCryptError, keys::PrivateKey, keys::PublicKey};
 
+use crate::asn1::alt_signature::{
+    get_pre_tbs_der, AltSignatureAlgorithm, AltSignatureValue, SubjectAltPublicKeyInfo,
+};
 use crate::asn1::certificate::Certificate;
+use crate::asn1::distinguished_name::IntoSubject;
+use crate::asn1::name_constraints::{build_name_constraints, NameConstraint};
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
@@ -47,6 +59,7 @@ impl CertValidity {
     /// # Arguments
     ///
     /// * `not_before` - The not before date of the certificate. If None, the current time is used. The date should be in RFC3339 format.
+    ///   It can be in the future, to issue a certificate ahead of the time it becomes valid.
     /// * `not_after` - The not after date of the certificate. The date should be in RFC3339 format.
     ///
     /// # Returns
@@ -55,9 +68,37 @@ impl CertValidity {
     ///
     /// # Errors
     ///
-    /// `QubitCryptError::InvalidNotBefore` if the not before date is in the future
-    /// `QubitCryptError::InvalidNotAfter` if the not after date is in the past
+    /// `QubitCryptError::InvalidNotBefore` if the not before date can't be parsed or is after the
+    /// not after date
+    /// `QubitCryptError::InvalidNotAfter` if the not after date can't be parsed or is in the past
     pub fn new(not_before: Option<&str>, not_after: &str) -> Result<CertValidity> {
+        CertValidity::new_at(chrono::Utc::now(), not_before, not_after)
+    }
+
+    /// Create a new CertValidity struct, as if the current time was `now`
+    ///
+    /// This is `new` with a fixed reference time, e.g. to create certificates which are
+    /// already expired in tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The reference time, which is used as the not before date if it is None and
+    ///   which the not after date must be after
+    /// * `not_before` - The not before date of the certificate, in RFC3339 format
+    /// * `not_after` - The not after date of the certificate, in RFC3339 format
+    ///
+    /// # Returns
+    ///
+    /// A new CertValidity struct
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `new`
+    pub fn new_at(
+        now: DateTime<chrono::Utc>,
+        not_before: Option<&str>,
+        not_after: &str,
+    ) -> Result<CertValidity> {
         let not_after = DateTime::parse_from_rfc3339(not_after)
             .map_err(|_| QubitCryptError::InvalidNotAfter)?;
 
@@ -65,7 +106,7 @@ impl CertValidity {
         let not_after = chrono::Utc.from_utc_datetime(&not_after.naive_utc());
 
         // Check if not after is in the past
-        if not_after <= chrono::Utc::now() {
+        if not_after <= now {
             return Err(QubitCryptError::InvalidNotAfter);
         }
 
@@ -92,7 +133,7 @@ impl CertValidity {
             })
         } else {
             // Use now as not_before
-            let not_before = chrono::Utc::now();
+            let not_before = now;
 
             if not_before > not_after {
                 return Err(QubitCryptError::InvalidNotAfter);
@@ -109,6 +150,19 @@ impl CertValidity {
     }
 }
 
+/// How the serial number of a certificate is chosen
+#[derive(Clone, Debug, PartialEq)]
+pub enum SerialStrategy {
+    /// A random serial number
+    Random,
+    /// The specified serial number
+    Explicit([u8; 20]),
+    /// A serial number derived from a SHA-256 hash of the subject public key info and
+    /// the subject name, so re-issuing a certificate for the same subject and key
+    /// results in the same serial number
+    DeterministicFromSpki,
+}
+
 /// A builder for creating X.509 certificates
 ///
 /// # Example:
@@ -160,35 +214,101 @@ impl CertValidity {
 /// // But it should verify against the root
 /// assert!(cert_root.verify_child(&cert_kem).unwrap());
 /// ```
+///
+/// Intermediate CA certificates are built with `Profile::SubCA`, which sets the basic
+/// constraints to cA=TRUE with the given pathLenConstraint and enables the keyCertSign and
+/// cRLSign key usages:
+/// ```
+/// use qubitcrypt::certificates::{CertValidity, CertificateBuilder, Profile};
+/// use qubitcrypt::dsas::{DsaAlgorithm, DsaKeyGenerator};
+///
+/// let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44).generate().unwrap();
+/// let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+/// let cert_root = CertificateBuilder::new(
+///   Profile::Root,
+///   None,
+///   validity.clone(),
+///   "CN=Root CA".to_string(),
+///   pk_root,
+///   &sk_root).unwrap().build().unwrap();
+///
+/// let (pk_int, _sk_int) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65).generate().unwrap();
+/// let cert_int = CertificateBuilder::new(Profile::SubCA {
+///   issuer: cert_root.get_subject(),
+///   path_len_constraint: Some(0),
+/// }, None,
+///   validity,
+///   "CN=Intermediate CA".to_string(),
+///   pk_int,
+///   &sk_root).unwrap().build().unwrap();
+///
+/// assert!(cert_root.verify_child(&cert_int).unwrap());
+/// assert_eq!(cert_int.get_basic_constraints().unwrap().path_len_constraint, Some(0));
+/// ```
 pub struct CertificateBuilder<'a> {
     builder: x509_cert::builder::CertificateBuilder<'a, PrivateKey>,
+    /// The issuer's key for the alternative signature, if one is added
+    alt_signer: Option<&'a PrivateKey>,
+    /// The issuer name which replaces the profile's issuer, if one is set
+    issuer: Option<Name>,
 }
 
 impl<'a> CertificateBuilder<'a> {
     /// Create a new certificate builder
+    ///
+    /// If `serial_number` is `None`, a random serial number is used
     pub fn new(
         profile: Profile,
         serial_number: Option<[u8; 20]>,
         validity: CertValidity,
-        subject: String,
+        subject: impl IntoSubject,
         cert_public_key: PublicKey,
         signer: &'a PrivateKey,
     ) -> Result<CertificateBuilder<'a>> {
-        let subject = Name::from_str(&subject).map_err(|_| QubitCryptError::BadSubject)?;
-
-        let spki = SubjectPublicKeyInfo::from_key(cert_public_key)
-            .map_err(|_| QubitCryptError::BadPublicKey)?;
-
-        let validity = Validity {
-            not_before: Time::UtcTime(validity.not_before),
-            not_after: Time::UtcTime(validity.not_after),
+        let serial_strategy = match serial_number {
+            Some(serial_number) => SerialStrategy::Explicit(serial_number),
+            None => SerialStrategy::Random,
         };
+        CertificateBuilder::new_with_serial_strategy(
+            profile,
+            serial_strategy,
+            validity,
+            subject,
+            cert_public_key,
+            signer,
+        )
+    }
 
-        let serial_number = if let Some(serial_number) = serial_number {
-            SerialNumber::new(&serial_number).map_err(|_| QubitCryptError::BadSerialNumber)?
-        } else {
-            CertificateBuilder::get_random_serial()?
-        };
+    /// Create a new certificate builder, choosing the serial number with the specified strategy
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The certificate profile
+    /// * `serial_strategy` - How the serial number is chosen
+    /// * `validity` - The validity period of the certificate
+    /// * `subject` - The subject name of the certificate, as a string or a `DistinguishedName`
+    /// * `cert_public_key` - The public key of the subject
+    /// * `signer` - The private key used to sign the certificate
+    ///
+    /// # Returns
+    ///
+    /// A new certificate builder
+    pub fn new_with_serial_strategy(
+        profile: Profile,
+        serial_strategy: SerialStrategy,
+        validity: CertValidity,
+        subject: impl IntoSubject,
+        cert_public_key: PublicKey,
+        signer: &'a PrivateKey,
+    ) -> Result<CertificateBuilder<'a>> {
+        let (serial_number, validity, subject, spki) = CertificateBuilder::get_tbs_parts(
+            &profile,
+            serial_strategy,
+            validity,
+            subject,
+            &cert_public_key,
+            signer,
+        )?;
 
         let builder = x509_cert::builder::CertificateBuilder::new(
             profile,
@@ -200,7 +320,60 @@ impl<'a> CertificateBuilder<'a> {
         )
         .map_err(|_| QubitCryptError::Unknown)?;
 
-        Ok(CertificateBuilder { builder })
+        Ok(CertificateBuilder {
+            builder,
+            alt_signer: None,
+            issuer: None,
+        })
+    }
+
+    /// Check the key usage and get the serial number, validity, subject and subject public key
+    /// info of a new certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The certificate profile
+    /// * `serial_strategy` - How the serial number is chosen
+    /// * `validity` - The validity period of the certificate
+    /// * `subject` - The subject name of the certificate
+    /// * `cert_public_key` - The public key of the subject
+    /// * `signer` - The private key used to sign the certificate
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the fields of the certificate (serial_number, validity, subject, spki)
+    pub(crate) fn get_tbs_parts(
+        profile: &Profile,
+        serial_strategy: SerialStrategy,
+        validity: CertValidity,
+        subject: impl IntoSubject,
+        cert_public_key: &PublicKey,
+        signer: &PrivateKey,
+    ) -> Result<(SerialNumber, Validity, Name, SubjectPublicKeyInfoOwned)> {
+        CertificateBuilder::check_key_usage(profile, cert_public_key, signer)?;
+
+        let subject = subject.into_subject()?;
+
+        let spki = cert_public_key.to_spki()?;
+
+        let validity = Validity {
+            not_before: Time::UtcTime(validity.not_before),
+            not_after: Time::UtcTime(validity.not_after),
+        };
+
+        let serial_number = match serial_strategy {
+            SerialStrategy::Random => CertificateBuilder::get_random_serial()?,
+            SerialStrategy::Explicit(serial_number) => {
+                SerialNumber::new(&serial_number).map_err(|_| QubitCryptError::BadSerialNumber)?
+            }
+            SerialStrategy::DeterministicFromSpki => {
+                let spki_der = spki.to_der().map_err(|_| QubitCryptError::BadPublicKey)?;
+                let subject_der = subject.to_der().map_err(|_| QubitCryptError::BadSubject)?;
+                CertificateBuilder::get_deterministic_serial(&spki_der, &subject_der)?
+            }
+        };
+
+        Ok((serial_number, validity, subject, spki))
     }
 
     pub fn add_extension(&mut self, extension: impl AsExtension) -> Result<&mut Self> {
@@ -211,8 +384,145 @@ impl<'a> CertificateBuilder<'a> {
         Ok(self)
     }
 
+    /// Add the certificate policies extension with the specified policy OIDs
+    ///
+    /// The policies have no qualifiers and the extension is not critical.
+    ///
+    /// # Arguments
+    ///
+    /// * `policies` - The OIDs of the policies, e.g. `2.5.29.32.0` for anyPolicy
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidOid` will be returned if an OID can't be parsed,
+    /// `QubitCryptError::BadExtension` will be returned if there are no policies or the
+    /// extension can't be added
+    pub fn add_certificate_policies(&mut self, policies: &[&str]) -> Result<&mut Self> {
+        if policies.is_empty() {
+            return Err(QubitCryptError::BadExtension);
+        }
+        let policies = policies
+            .iter()
+            .map(|oid| {
+                Ok(PolicyInformation {
+                    policy_identifier: oid.parse().map_err(|_| QubitCryptError::InvalidOid)?,
+                    policy_qualifiers: None,
+                })
+            })
+            .collect::<Result<Vec<PolicyInformation>>>()?;
+
+        self.add_extension(CertificatePolicies(policies))
+    }
+
+    /// Add the name constraints extension, which restricts the names of the certificates issued
+    /// by a CA
+    ///
+    /// The extension is critical, as required by RFC 5280. `Certificate::verify_child` checks the
+    /// subject and the DNS, RFC 822, IP address and URI subject alternative names of a child
+    /// against the constraints of its issuer.
+    ///
+    /// # Arguments
+    ///
+    /// * `permitted` - The permitted subtrees, empty to permit all names
+    /// * `excluded` - The excluded subtrees, which take precedence over the permitted subtrees
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::BadExtension` will be returned if both lists are empty, a DNS name,
+    /// mailbox or URI host is not ASCII, an address range can't be parsed or the extension can't
+    /// be added, `QubitCryptError::BadSubject` will be returned if a Distinguished Name can't be
+    /// parsed
+    pub fn add_name_constraints(
+        &mut self,
+        permitted: &[NameConstraint],
+        excluded: &[NameConstraint],
+    ) -> Result<&mut Self> {
+        self.add_extension(build_name_constraints(permitted, excluded)?)
+    }
+
+    /// Set the issuer name of the certificate, replacing the issuer derived from the profile
+    ///
+    /// The profile's issuer is the subject for `Profile::Root` and the given issuer otherwise.
+    /// An explicit issuer is needed for cross-signing and bridge CAs, where the issuing key's
+    /// certificate has a name that doesn't match these assumptions. The extensions are still
+    /// the ones of the profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuer` - The issuer name, either as a string or a `DistinguishedName`
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::BadSubject` will be returned if the name can't be parsed
+    pub fn with_issuer(&mut self, issuer: impl IntoSubject) -> Result<&mut Self> {
+        self.issuer = Some(issuer.into_subject()?);
+        Ok(self)
+    }
+
+    /// Add an alternative signature to the certificate, as defined in ITU-T X.509 (10/2019)
+    ///
+    /// This adds the `subjectAltPublicKeyInfo` and `altSignatureAlgorithm` extensions now, and
+    /// the `altSignatureValue` extension when the certificate is built. The alternative
+    /// signature covers the whole certificate apart from the primary signature, so any
+    /// extensions that are added afterwards are covered as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `alt_signer` - The issuer's private key for the alternative signature
+    /// * `alt_public_key` - The subject's alternative public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the alternative signer is not
+    /// a DSA key, `QubitCryptError::BadExtension` will be returned if an alternative signature was
+    /// already added
+    pub fn with_alternative_signature(
+        &mut self,
+        alt_signer: &'a PrivateKey,
+        alt_public_key: &PublicKey,
+    ) -> Result<&mut Self> {
+        if !alt_signer.capabilities().can_sign {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
+        if self.alt_signer.is_some() {
+            return Err(QubitCryptError::BadExtension);
+        }
+
+        let alt_algorithm = alt_signer
+            .signature_algorithm_identifier()
+            .map_err(|_| QubitCryptError::BadExtension)?;
+        self.add_extension(SubjectAltPublicKeyInfo(alt_public_key.to_spki()?))?;
+        self.add_extension(AltSignatureAlgorithm(alt_algorithm))?;
+        self.alt_signer = Some(alt_signer);
+
+        Ok(self)
+    }
+
+    /// Check that the profile makes sense for the subject's public key and that the signer can sign
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::KeyUsageViolation` will be returned if a KEM key is used in a root or
+    /// subordinate CA certificate, as a KEM key can't sign other certificates, or if the signer is
+    /// not a DSA key
+    fn check_key_usage(
+        profile: &Profile,
+        cert_public_key: &PublicKey,
+        signer: &PrivateKey,
+    ) -> Result<()> {
+        if !signer.capabilities().can_sign {
+            return Err(QubitCryptError::KeyUsageViolation);
+        }
+
+        let is_ca = matches!(profile, Profile::Root | Profile::SubCA { .. });
+        if is_ca && cert_public_key.capabilities().can_encap {
+            return Err(QubitCryptError::KeyUsageViolation);
+        }
+        Ok(())
+    }
+
     /// Return a random SerialNumber value
-    fn get_random_serial() -> Result<SerialNumber> {
+    pub(crate) fn get_random_serial() -> Result<SerialNumber> {
         let mut serial = [0u8; 20];
         OsRng.fill_bytes(&mut serial);
         serial[0] = 0x01;
@@ -220,8 +530,62 @@ impl<'a> CertificateBuilder<'a> {
         Ok(serial)
     }
 
-    pub fn build(self) -> Result<Certificate> {
-        let cert_inner = self.builder.build().map_err(|_| QubitCryptError::Unknown)?;
+    /// Return a SerialNumber value derived from the subject public key info and subject name
+    fn get_deterministic_serial(spki_der: &[u8], subject_der: &[u8]) -> Result<SerialNumber> {
+        let mut hasher = Sha256::new();
+        hasher.update(spki_der);
+        hasher.update(subject_der);
+        let hash = hasher.finalize();
+
+        // Same as the random serial, the leading byte keeps the value positive and 20 bytes long
+        let mut serial = [0u8; 20];
+        serial[1..].copy_from_slice(&hash[..19]);
+        serial[0] = 0x01;
+        let serial = SerialNumber::new(&serial).map_err(|_| QubitCryptError::BadSerialNumber)?;
+        Ok(serial)
+    }
+
+    /// Get the TBSCertificate as it will be signed, with the issuer replaced if one is set
+    fn finalize_tbs(&mut self) -> Result<TbsCertificate> {
+        let tbs = self
+            .builder
+            .finalize()
+            .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        let mut tbs =
+            TbsCertificate::from_der(&tbs).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        if let Some(issuer) = &self.issuer {
+            tbs.issuer = issuer.clone();
+        }
+        Ok(tbs)
+    }
+
+    pub fn build(mut self) -> Result<Certificate> {
+        if let Some(alt_signer) = self.alt_signer {
+            let tbs = self.finalize_tbs()?;
+            let alt_signature = alt_signer.sign(&get_pre_tbs_der(&tbs)?)?;
+            let alt_signature = BitString::from_bytes(&alt_signature)
+                .map_err(|_| QubitCryptError::SignatureFailed)?;
+            self.add_extension(AltSignatureValue(alt_signature))?;
+        }
+
+        // The x509-cert builder always signs the profile's issuer, so a certificate with an
+        // explicit issuer is signed here
+        let cert_inner = if self.issuer.is_some() {
+            let tbs = self.finalize_tbs()?;
+            let tbs_der = tbs
+                .to_der()
+                .map_err(|_| QubitCryptError::InvalidCertificate)?;
+            let signature = self.builder.signer().sign(&tbs_der)?;
+            let signature =
+                BitString::from_bytes(&signature).map_err(|_| QubitCryptError::SignatureFailed)?;
+            x509_cert::Certificate {
+                signature_algorithm: tbs.signature.clone(),
+                tbs_certificate: tbs,
+                signature,
+            }
+        } else {
+            self.builder.build().map_err(|_| QubitCryptError::Unknown)?
+        };
         let cert = Certificate::new(cert_inner);
         Ok(cert)
     }
@@ -230,7 +594,9 @@ impl<'a> CertificateBuilder<'a> {
 #[cfg(test)]
 mod test {
 
+    use crate::asn1::distinguished_name::DistinguishedName;
     use crate::{dsas::DsaAlgorithm, dsas::DsaKeyGenerator};
+    use crate::{kems::KemAlgorithm, kems::KemKeyGenerator};
 
     use super::*;
 
@@ -277,6 +643,733 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_future_not_before() {
+        use crate::certificates::FixedClock;
+        use chrono::{Duration, SecondsFormat};
+
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+
+        // Scheduled rotation: the certificate becomes valid in a year
+        let now = chrono::Utc::now();
+        let not_before = now + Duration::days(365);
+        let not_after = now + Duration::days(2 * 365);
+        let validity = CertValidity::new(
+            Some(&not_before.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            &not_after.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+        .unwrap();
+        let cert = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity,
+            "CN=Next Root CA".to_string(),
+            pk_root,
+            &sk_root,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let encoded = cert.to_x509_cert().tbs_certificate.validity;
+        assert_eq!(
+            encoded.not_before.to_unix_duration().as_secs(),
+            not_before.timestamp() as u64
+        );
+        assert_eq!(
+            encoded.not_after.to_unix_duration().as_secs(),
+            not_after.timestamp() as u64
+        );
+        assert!(cert.verify_self_signed().unwrap());
+        assert!(!cert.is_valid());
+        assert!(cert.is_valid_with(&FixedClock(not_before + Duration::days(1))));
+
+        // The not before date must still not be after the not after date
+        assert_eq!(
+            CertValidity::new(
+                Some(&not_after.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                &not_before.to_rfc3339_opts(SecondsFormat::Secs, true),
+            )
+            .err(),
+            Some(QubitCryptError::InvalidNotBefore)
+        );
+    }
+
+    #[test]
+    fn test_deterministic_serial() {
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (pk_other, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+
+        let build = |subject: &str, pk: &PublicKey| {
+            CertificateBuilder::new_with_serial_strategy(
+                Profile::Root,
+                SerialStrategy::DeterministicFromSpki,
+                validity.clone(),
+                subject.to_string(),
+                pk.clone(),
+                &sk_root,
+            )
+            .unwrap()
+            .build()
+            .unwrap()
+        };
+
+        let cert1 = build("CN=example.com", &pk_root);
+        let cert2 = build("CN=example.com", &pk_root);
+        assert_eq!(cert1.get_serial_number(), cert2.get_serial_number());
+        assert_eq!(cert1.get_serial_number().as_bytes().len(), 20);
+        assert_eq!(cert1.get_serial_number().as_bytes()[0], 0x01);
+
+        let cert3 = build("CN=other.example.com", &pk_root);
+        assert_ne!(cert1.get_serial_number(), cert3.get_serial_number());
+
+        let cert4 = build("CN=example.com", &pk_other);
+        assert_ne!(cert1.get_serial_number(), cert4.get_serial_number());
+
+        // The random strategy is still random
+        let random = || {
+            CertificateBuilder::new_with_serial_strategy(
+                Profile::Root,
+                SerialStrategy::Random,
+                validity.clone(),
+                "CN=example.com".to_string(),
+                pk_root.clone(),
+                &sk_root,
+            )
+            .unwrap()
+            .build()
+            .unwrap()
+            .get_serial_number()
+        };
+        assert_ne!(random(), random());
+    }
+
+    #[test]
+    fn test_three_tier_pki() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa87)
+            .generate()
+            .unwrap();
+        let cert_root = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root CA".to_string(),
+            pk_root,
+            &sk_root,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert!(cert_root.verify_self_signed().unwrap());
+
+        // The intermediate can only issue end entity certificates
+        let (pk_int, sk_int) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65)
+            .generate()
+            .unwrap();
+        let cert_int = CertificateBuilder::new(
+            Profile::SubCA {
+                issuer: cert_root.get_subject(),
+                path_len_constraint: Some(0),
+            },
+            None,
+            validity.clone(),
+            "CN=Intermediate CA".to_string(),
+            pk_int,
+            &sk_root,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let bc = cert_int.get_basic_constraints().unwrap();
+        assert!(bc.ca);
+        assert_eq!(bc.path_len_constraint, Some(0));
+        assert!(!cert_int.verify_self_signed().unwrap());
+        assert!(cert_root.verify_child(&cert_int).unwrap());
+
+        let (pk_kem, _) = KemKeyGenerator::new(KemAlgorithm::MlKem768)
+            .generate()
+            .unwrap();
+        let cert_leaf = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: cert_int.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: true,
+            },
+            None,
+            validity,
+            "CN=example.com".to_string(),
+            pk_kem,
+            &sk_int,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let bc = cert_leaf.get_basic_constraints().unwrap();
+        assert!(!bc.ca);
+        assert!(cert_int.verify_child(&cert_leaf).unwrap());
+        assert!(!cert_root.verify_child(&cert_leaf).unwrap());
+
+        // A root certificate has no path length constraint
+        assert_eq!(
+            cert_root
+                .get_basic_constraints()
+                .unwrap()
+                .path_len_constraint,
+            None
+        );
+    }
+
+    #[test]
+    fn test_key_usage_violation() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let (pk_dsa, sk_dsa) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (pk_kem, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let issuer = DistinguishedName::new().cn("Root CA").to_name().unwrap();
+        let leaf = |enable_key_agreement: bool, enable_key_encipherment: bool| Profile::Leaf {
+            issuer: issuer.clone(),
+            enable_key_agreement,
+            enable_key_encipherment,
+        };
+        let build = |profile: Profile, pk: &PublicKey, signer: &PrivateKey| {
+            CertificateBuilder::new(
+                profile,
+                None,
+                validity.clone(),
+                "CN=example.com",
+                pk.clone(),
+                signer,
+            )
+            .map(|_| ())
+        };
+
+        // A KEM key can't be in a CA certificate
+        let sub_ca = Profile::SubCA {
+            issuer: issuer.clone(),
+            path_len_constraint: None,
+        };
+        for profile in [Profile::Root, sub_ca] {
+            assert_eq!(
+                build(profile, &pk_kem, &sk_dsa),
+                Err(QubitCryptError::KeyUsageViolation)
+            );
+        }
+        assert!(build(leaf(false, true), &pk_kem, &sk_dsa).is_ok());
+        assert!(build(leaf(true, false), &pk_kem, &sk_dsa).is_ok());
+
+        // The key usages of a leaf certificate are not checked against the key
+        assert!(build(leaf(false, false), &pk_kem, &sk_dsa).is_ok());
+        assert!(build(leaf(true, false), &pk_dsa, &sk_dsa).is_ok());
+        assert!(build(leaf(false, false), &pk_dsa, &sk_dsa).is_ok());
+
+        // A KEM key can't sign
+        assert_eq!(
+            build(leaf(false, true), &pk_kem, &sk_kem),
+            Err(QubitCryptError::KeyUsageViolation)
+        );
+    }
+
+    #[test]
+    fn test_with_issuer() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let (pk_bridge, sk_bridge) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let cert_bridge = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Bridge CA,O=Example",
+            pk_bridge,
+            &sk_bridge,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        // The profile names another issuer, which is overridden
+        let (pk_leaf, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65)
+            .generate()
+            .unwrap();
+        let (alt_pk_leaf, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: "CN=Other CA".into_subject().unwrap(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            validity.clone(),
+            "CN=example.com",
+            pk_leaf,
+            &sk_bridge,
+        )
+        .unwrap();
+        builder.with_issuer("CN=Bridge CA,O=Example").unwrap();
+        builder
+            .with_alternative_signature(&sk_bridge, &alt_pk_leaf)
+            .unwrap();
+        let cert_leaf = builder.build().unwrap();
+        assert_eq!(cert_leaf.get_issuer(), cert_bridge.get_subject());
+        assert_eq!(cert_leaf.get_issuer().to_string(), "CN=Bridge CA,O=Example");
+        assert!(cert_bridge.verify_child(&cert_leaf).unwrap());
+
+        let cert_leaf = Certificate::from_der(&cert_leaf.to_der().unwrap()).unwrap();
+        assert!(cert_bridge.verify_child(&cert_leaf).unwrap());
+        let alt_pk_bridge = cert_bridge.get_public_key().unwrap();
+        assert!(cert_leaf
+            .verify_alternative_signature(&alt_pk_bridge)
+            .unwrap());
+
+        // A root with an explicit issuer is no longer self-issued
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity,
+            "CN=Root CA",
+            pk_root,
+            &sk_root,
+        )
+        .unwrap();
+        assert_eq!(
+            builder.with_issuer("not a name").err(),
+            Some(QubitCryptError::BadSubject)
+        );
+        builder.with_issuer("CN=Bridge CA,O=Example").unwrap();
+        let cert_root = builder.build().unwrap();
+        assert_eq!(cert_root.get_issuer(), cert_bridge.get_subject());
+        assert!(!cert_root.verify_self_signed().unwrap());
+    }
+
+    #[test]
+    fn test_alternative_signature() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+
+        // A root with an alternative key of a different algorithm, signed with both
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44EcdsaP256SHA256)
+            .generate()
+            .unwrap();
+        let (alt_pk_root, alt_sk_root) = DsaKeyGenerator::new(DsaAlgorithm::SlhDsaSha2_128f)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root CA".to_string(),
+            pk_root,
+            &sk_root,
+        )
+        .unwrap();
+        builder
+            .with_alternative_signature(&alt_sk_root, &alt_pk_root)
+            .unwrap();
+        assert_eq!(
+            builder
+                .with_alternative_signature(&alt_sk_root, &alt_pk_root)
+                .err(),
+            Some(QubitCryptError::BadExtension)
+        );
+        let cert_root = builder.build().unwrap();
+        assert!(cert_root.verify_self_signed().unwrap());
+
+        let alt_pk = cert_root.get_alternative_public_key().unwrap().unwrap();
+        assert!(alt_pk.equals(&alt_pk_root));
+        assert!(cert_root.verify_alternative_signature(&alt_pk).unwrap());
+
+        // A leaf issued by the root, with its own alternative key
+        let (pk_leaf, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44Ed25519SHA512)
+            .generate()
+            .unwrap();
+        let (alt_pk_leaf, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: cert_root.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            validity.clone(),
+            "CN=example.com".to_string(),
+            pk_leaf,
+            &sk_root,
+        )
+        .unwrap();
+        builder
+            .with_alternative_signature(&alt_sk_root, &alt_pk_leaf)
+            .unwrap();
+        let cert_leaf = builder.build().unwrap();
+        let cert_leaf = Certificate::from_der(&cert_leaf.to_der().unwrap()).unwrap();
+
+        assert!(cert_root.verify_child(&cert_leaf).unwrap());
+        assert!(cert_leaf.verify_alternative_signature(&alt_pk).unwrap());
+        assert!(!cert_leaf
+            .verify_alternative_signature(&alt_pk_leaf)
+            .unwrap());
+        assert!(cert_leaf
+            .get_alternative_public_key()
+            .unwrap()
+            .unwrap()
+            .equals(&alt_pk_leaf));
+
+        // A certificate without an alternative signature
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let cert = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity,
+            "CN=Root CA".to_string(),
+            pk.clone(),
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert!(cert.get_alternative_public_key().unwrap().is_none());
+        assert!(!cert.verify_alternative_signature(&pk).unwrap());
+    }
+
+    #[test]
+    fn test_slh_dsa_certificates() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+
+        // A hash-based root which issues a hash-based leaf, e.g. for signing firmware
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::SlhDsaShake128f)
+            .generate()
+            .unwrap();
+        let builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Firmware Root CA".to_string(),
+            pk_root,
+            &sk_root,
+        )
+        .unwrap();
+        let cert_root = builder.build().unwrap();
+        assert!(cert_root.verify_self_signed().unwrap());
+
+        let (pk_leaf, _) = DsaKeyGenerator::new(DsaAlgorithm::SlhDsaSha2_128f)
+            .generate()
+            .unwrap();
+        let builder = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: cert_root.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            validity,
+            "CN=Firmware Signer".to_string(),
+            pk_leaf,
+            &sk_root,
+        )
+        .unwrap();
+        let cert_leaf = builder.build().unwrap();
+        let cert_leaf = Certificate::from_der(&cert_leaf.to_der().unwrap()).unwrap();
+        assert!(cert_root.verify_child(&cert_leaf).unwrap());
+
+        // The signature algorithm is the one of the issuer, without parameters
+        // (draft-ietf-lamps-x509-slhdsa)
+        let x509 = cert_leaf.to_x509_cert();
+        assert_eq!(
+            cert_leaf.get_signature_oid(),
+            DsaAlgorithm::SlhDsaShake128f.get_oid()
+        );
+        assert!(x509.signature_algorithm.parameters.is_none());
+        assert_eq!(x509.signature.raw_bytes().len(), 17088);
+        assert_eq!(
+            cert_leaf.get_public_key_oid(),
+            DsaAlgorithm::SlhDsaSha2_128f.get_oid()
+        );
+        assert_eq!(cert_leaf.get_public_key().unwrap().get_key().len(), 32);
+    }
+
+    #[test]
+    fn test_policies_and_name_constraints() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root CA,O=Example,C=US",
+            pk_root,
+            &sk_root,
+        )
+        .unwrap();
+        builder
+            .add_certificate_policies(&["2.23.140.1.2.1", "2.5.29.32.0"])
+            .unwrap()
+            .add_name_constraints(
+                &[
+                    NameConstraint::DirectoryName("O=Example,C=US".to_string()),
+                    NameConstraint::DnsName("example.com".to_string()),
+                ],
+                &[NameConstraint::DnsName("internal.example.com".to_string())],
+            )
+            .unwrap();
+        assert_eq!(
+            builder.add_certificate_policies(&["not an oid"]).err(),
+            Some(QubitCryptError::InvalidOid)
+        );
+        assert_eq!(
+            builder.add_certificate_policies(&[]).err(),
+            Some(QubitCryptError::BadExtension)
+        );
+        assert_eq!(
+            builder.add_name_constraints(&[], &[]).err(),
+            Some(QubitCryptError::BadExtension)
+        );
+        let cert_root = builder.build().unwrap();
+        let cert_root = Certificate::from_der(&cert_root.to_der().unwrap()).unwrap();
+        assert!(cert_root.verify_self_signed().unwrap());
+
+        assert_eq!(
+            cert_root.get_certificate_policies(),
+            vec!["2.23.140.1.2.1".to_string(), "2.5.29.32.0".to_string()]
+        );
+        let constraints = cert_root.get_name_constraints().unwrap();
+        assert_eq!(constraints.permitted_subtrees.unwrap().len(), 2);
+        assert_eq!(constraints.excluded_subtrees.unwrap().len(), 1);
+        let ext = cert_root
+            .to_x509_cert()
+            .tbs_certificate
+            .extensions
+            .unwrap()
+            .into_iter()
+            .find(|ext| ext.extn_id == const_oid::db::rfc5280::ID_CE_NAME_CONSTRAINTS)
+            .unwrap();
+        assert!(ext.critical);
+
+        let issue = |subject: &str, dns_name: Option<&str>| {
+            let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+                .generate()
+                .unwrap();
+            let mut builder = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: cert_root.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: false,
+                },
+                None,
+                validity.clone(),
+                subject,
+                pk,
+                &sk_root,
+            )
+            .unwrap();
+            if let Some(dns_name) = dns_name {
+                let dns_name = der::asn1::Ia5String::new(dns_name).unwrap();
+                builder
+                    .add_extension(x509_cert::ext::pkix::SubjectAltName(vec![
+                        x509_cert::ext::pkix::name::GeneralName::DnsName(dns_name),
+                    ]))
+                    .unwrap();
+            }
+            builder.build().unwrap()
+        };
+
+        assert!(cert_root
+            .verify_child(&issue("CN=Leaf,O=Example,C=US", None))
+            .unwrap());
+        assert!(cert_root
+            .verify_child(&issue("CN=Leaf,O=Example,C=US", Some("www.example.com")))
+            .unwrap());
+        assert!(!cert_root
+            .verify_child(&issue("CN=Leaf,O=Other,C=US", None))
+            .unwrap());
+        assert!(!cert_root
+            .verify_child(&issue("CN=Leaf,O=Example,C=US", Some("example.org")))
+            .unwrap());
+        assert!(!cert_root
+            .verify_child(&issue(
+                "CN=Leaf,O=Example,C=US",
+                Some("db.internal.example.com")
+            ))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_name_constraints_chain() {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root CA,O=Example,C=US",
+            pk_root,
+            &sk_root,
+        )
+        .unwrap();
+        builder
+            .add_name_constraints(
+                &[NameConstraint::DirectoryName("O=Example,C=US".to_string())],
+                &[],
+            )
+            .unwrap();
+        let cert_root = builder.build().unwrap();
+
+        // The intermediate CA is permitted by the root and has no name constraints itself
+        let (pk_int, sk_int) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let cert_int = CertificateBuilder::new(
+            Profile::SubCA {
+                issuer: cert_root.get_subject(),
+                path_len_constraint: Some(0),
+            },
+            None,
+            validity.clone(),
+            "CN=Intermediate CA,O=Example,C=US",
+            pk_int,
+            &sk_root,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let issue = |subject: &str| {
+            let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+                .generate()
+                .unwrap();
+            CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: cert_int.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: false,
+                },
+                None,
+                validity.clone(),
+                subject,
+                pk,
+                &sk_int,
+            )
+            .unwrap()
+            .build()
+            .unwrap()
+        };
+        let permitted = issue("CN=Leaf,O=Example,C=US");
+        let violating = issue("CN=Leaf,O=Other,C=US");
+
+        assert!(cert_root
+            .verify_chain(&[cert_int.clone(), permitted])
+            .unwrap());
+
+        // The intermediate has no constraints, so only the whole path catches the violation
+        assert!(cert_int.verify_child(&violating).unwrap());
+        assert_eq!(
+            cert_root.verify_chain(&[cert_int.clone(), violating.clone()]),
+            Err(QubitCryptError::InvalidChain(vec![(
+                2,
+                QubitCryptError::NameConstraintsViolation
+            )]))
+        );
+
+        // The chain has to be in order
+        assert_eq!(
+            cert_root.verify_chain(&[violating]),
+            Err(QubitCryptError::InvalidChain(vec![
+                (1, QubitCryptError::IssuerMismatch),
+                (1, QubitCryptError::NameConstraintsViolation)
+            ]))
+        );
+        assert!(cert_root.verify_chain(&[]).unwrap());
+    }
+
+    #[test]
+    fn test_name_constraints_ip_address() {
+        use x509_cert::ext::pkix::name::GeneralName;
+        use x509_cert::ext::pkix::SubjectAltName;
+
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let (pk_root, sk_root) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root CA,O=Example,C=US",
+            pk_root,
+            &sk_root,
+        )
+        .unwrap();
+        builder
+            .add_name_constraints(
+                &[NameConstraint::IpAddress("192.0.2.0/24".to_string())],
+                &[],
+            )
+            .unwrap();
+        let cert_root = builder.build().unwrap();
+
+        let issue = |address: [u8; 4]| {
+            let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+                .generate()
+                .unwrap();
+            let mut builder = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: cert_root.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: false,
+                },
+                None,
+                validity.clone(),
+                "CN=Leaf,O=Example,C=US",
+                pk,
+                &sk_root,
+            )
+            .unwrap();
+            let address = der::asn1::OctetString::new(address).unwrap();
+            builder
+                .add_extension(SubjectAltName(vec![GeneralName::IpAddress(address)]))
+                .unwrap();
+            builder.build().unwrap()
+        };
+
+        let permitted = issue([192, 0, 2, 7]);
+        assert!(cert_root.verify_child(&permitted).unwrap());
+        assert!(cert_root.verify_chain(&[permitted]).unwrap());
+
+        let violating = issue([198, 51, 100, 7]);
+        assert!(!cert_root.verify_child(&violating).unwrap());
+        assert_eq!(
+            cert_root.verify_chain(&[violating]),
+            Err(QubitCryptError::InvalidChain(vec![(
+                1,
+                QubitCryptError::NameConstraintsViolation
+            )]))
+        );
+    }
+
     #[test]
     fn gen_pq_hackathon_artifacts_r3() {
         // Generate R3 artifacts for the hackathon
@@ -1,19 +1,44 @@
 use crate::{
-    dsa::{common::dsa_trait::Dsa, dsa_manager::DsaManager},
+    dsa::{
+        common::dsa_trait::Dsa, common::dsa_type::DsaType, composite_dsa::CompositeDsaManager,
+        dsa_manager::DsaManager,
+    },
     kem::{common::kem_trait::Kem, kem_manager::KemManager},
     keys::PublicKey,
 };
+use base64ct::{Base64, Encoding};
 use chrono::{DateTime, Utc};
 use cms::enveloped_data::RecipientIdentifier;
-use der::{Decode, DecodePem, Encode, EncodePem};
-use spki::ObjectIdentifier;
+use const_oid::AssociatedOid;
+use der::asn1::{AnyRef, BitString};
+use der::{Decode, Encode, EncodePem, Reader, SliceReader};
+use sha2::{Digest, Sha256};
+use spki::{DynSignatureAlgorithmIdentifier, ObjectIdentifier};
 use x509_cert::{
-    ext::pkix::{AuthorityKeyIdentifier, KeyUsage, SubjectKeyIdentifier},
+    ext::pkix::{
+        AuthorityKeyIdentifier, BasicConstraints, CertificatePolicies, KeyUsage, NameConstraints,
+        SubjectAltName, SubjectKeyIdentifier,
+    },
     name::RdnSequence,
     serial_number::SerialNumber,
+    time::{Time, Validity},
 };
 
+use crate::asn1::alt_signature::{
+    get_pre_tbs_der, AltSignatureAlgorithm, AltSignatureValue, SubjectAltPublicKeyInfo,
+};
+use crate::asn1::asn_util::{
+    get_security_category, is_composite_kem_or_dsa_oid, is_dsa_oid, is_kem_oid,
+};
+use crate::asn1::cert_builder::{CertValidity, CertificateBuilder};
+use crate::asn1::composite_public_key::CompositeComponent;
+use crate::asn1::name_constraints;
+use crate::asn1::private_key::PrivateKey;
+use crate::asn1::signature::DsaSignature;
 use crate::errors::QubitCryptError;
+use crate::kdf::api::KdfType;
+use crate::utils::clock::{Clock, SystemClock};
+use crate::wrap::api::WrapType;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
@@ -29,6 +54,32 @@ type Result<T> = std::result::Result<T, QubitCryptError>;
 #[derive(Clone)]
 pub struct Certificate {
     cert: x509_cert::Certificate,
+    /// The certificate exactly as it was encoded, if the certificate was decoded
+    raw_der: Option<Vec<u8>>,
+}
+
+/// Certificates are equal if their contents are equal, regardless of how they were encoded
+impl PartialEq for Certificate {
+    fn eq(&self, other: &Certificate) -> bool {
+        self.cert == other.cert
+    }
+}
+
+/// Wrap an `x509_cert` certificate without re-serializing it
+///
+/// The certificate has no original encoding, so its TBSCertificate is re-encoded in DER when it
+/// is verified. A certificate whose TBSCertificate wasn't encoded canonically should be loaded
+/// with `Certificate::from_der` instead.
+impl From<x509_cert::Certificate> for Certificate {
+    fn from(cert: x509_cert::Certificate) -> Certificate {
+        Certificate::new(cert)
+    }
+}
+
+impl From<Certificate> for x509_cert::Certificate {
+    fn from(cert: Certificate) -> x509_cert::Certificate {
+        cert.cert
+    }
 }
 
 impl Certificate {
@@ -42,7 +93,90 @@ impl Certificate {
     ///
     /// The new certificate
     pub(crate) fn new(cert: x509_cert::Certificate) -> Certificate {
-        Certificate { cert }
+        Certificate {
+            cert,
+            raw_der: None,
+        }
+    }
+
+    /// Get the encoded TBSCertificate, which is the message signed by the issuer
+    ///
+    /// For a decoded certificate these are the original bytes, not a re-encoding, so that a
+    /// certificate whose TBSCertificate wasn't encoded canonically can still be verified.
+    ///
+    /// # Returns
+    ///
+    /// The TBSCertificate bytes
+    fn get_tbs_bytes(&self) -> Result<Vec<u8>> {
+        if let Some(raw_der) = &self.raw_der {
+            return Certificate::get_raw_tbs(raw_der);
+        }
+        self.cert
+            .tbs_certificate
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidCertificate)
+    }
+
+    /// Get the TBSCertificate bytes as they appear in an encoded certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The encoded certificate
+    ///
+    /// # Returns
+    ///
+    /// The encoded TBSCertificate, i.e. the first element of the certificate sequence
+    fn get_raw_tbs(der: &[u8]) -> Result<Vec<u8>> {
+        let cert = AnyRef::from_der(der).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        let mut reader =
+            SliceReader::new(cert.value()).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        let tbs = reader
+            .tlv_bytes()
+            .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        Ok(tbs.to_vec())
+    }
+
+    /// Get the encoded SubjectPublicKeyInfo
+    ///
+    /// For a decoded certificate these are the original bytes within its TBSCertificate. For
+    /// other certificates the SubjectPublicKeyInfo is encoded in DER.
+    ///
+    /// # Returns
+    ///
+    /// The SubjectPublicKeyInfo bytes
+    fn get_spki_bytes(&self) -> Result<Vec<u8>> {
+        let Some(raw_der) = &self.raw_der else {
+            return self
+                .cert
+                .tbs_certificate
+                .subject_public_key_info
+                .to_der()
+                .map_err(|_| QubitCryptError::InvalidCertificate);
+        };
+        let tbs = Certificate::get_raw_tbs(raw_der)?;
+        let tbs = AnyRef::from_der(&tbs).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        let mut reader =
+            SliceReader::new(tbs.value()).map_err(|_| QubitCryptError::InvalidCertificate)?;
+
+        // The optional version [0], then serialNumber, signature, issuer, validity and subject
+        if reader
+            .peek_tag()
+            .map_err(|_| QubitCryptError::InvalidCertificate)?
+            .is_context_specific()
+        {
+            reader
+                .tlv_bytes()
+                .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        }
+        for _ in 0..5 {
+            reader
+                .tlv_bytes()
+                .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        }
+        let spki = reader
+            .tlv_bytes()
+            .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        Ok(spki.to_vec())
     }
 
     /// Convert the certificate to DER format bytes
@@ -58,6 +192,85 @@ impl Certificate {
         Ok(result)
     }
 
+    /// Get the encoded certificate as it was decoded
+    ///
+    /// For a decoded certificate these are the bytes it was decoded from. Other certificates
+    /// are encoded in DER.
+    ///
+    /// # Returns
+    ///
+    /// The encoded certificate
+    fn get_encoded_bytes(&self) -> Result<Vec<u8>> {
+        match &self.raw_der {
+            Some(raw_der) => Ok(raw_der.clone()),
+            None => self.to_der(),
+        }
+    }
+
+    /// Get the SHA-256 pin of the whole certificate
+    ///
+    /// The hash is computed over the encoded certificate. For a decoded certificate that's the
+    /// encoding it was decoded from, so the pin matches the hash of the certificate as it's
+    /// sent by a server.
+    ///
+    /// # Returns
+    ///
+    /// The SHA-256 hash of the certificate
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidCertificate` will be returned if the certificate can't be encoded
+    pub fn pin_sha256(&self) -> Result<[u8; 32]> {
+        Ok(Sha256::digest(self.get_encoded_bytes()?).into())
+    }
+
+    /// Get the SHA-256 pin of the whole certificate, encoded in base64
+    ///
+    /// # Returns
+    ///
+    /// The base64 encoded SHA-256 hash of the certificate
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidCertificate` will be returned if the certificate can't be encoded
+    pub fn pin_sha256_base64(&self) -> Result<String> {
+        Ok(Base64::encode_string(&self.pin_sha256()?))
+    }
+
+    /// Get the SHA-256 pin of the SubjectPublicKeyInfo
+    ///
+    /// This is the `pin-sha256` of HPKP (RFC 7469): the hash of the encoded SubjectPublicKeyInfo.
+    /// For a decoded certificate these are the original bytes of the SubjectPublicKeyInfo, as
+    /// for `pin_sha256`. It stays the same when the certificate is reissued for the same key.
+    ///
+    /// # Returns
+    ///
+    /// The SHA-256 hash of the SubjectPublicKeyInfo
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidCertificate` will be returned if the SubjectPublicKeyInfo can't
+    /// be encoded
+    pub fn spki_pin_sha256(&self) -> Result<[u8; 32]> {
+        Ok(Sha256::digest(self.get_spki_bytes()?).into())
+    }
+
+    /// Get the SHA-256 pin of the SubjectPublicKeyInfo, encoded in base64
+    ///
+    /// This is the value of a `pin-sha256` directive of HPKP (RFC 7469).
+    ///
+    /// # Returns
+    ///
+    /// The base64 encoded SHA-256 hash of the SubjectPublicKeyInfo
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidCertificate` will be returned if the SubjectPublicKeyInfo can't
+    /// be encoded
+    pub fn spki_pin_sha256_base64(&self) -> Result<String> {
+        Ok(Base64::encode_string(&self.spki_pin_sha256()?))
+    }
+
     /// Convert the certificate to PEM format
     ///
     /// # Returns
@@ -71,6 +284,15 @@ impl Certificate {
         Ok(result)
     }
 
+    /// Get the underlying `x509_cert` certificate
+    ///
+    /// # Returns
+    ///
+    /// A copy of the `x509_cert` certificate
+    pub fn to_x509_cert(&self) -> x509_cert::Certificate {
+        self.cert.clone()
+    }
+
     /// Create a certificate from DER format bytes
     ///
     /// # Arguments
@@ -98,7 +320,10 @@ impl Certificate {
             .parse()
             .map_err(|_| QubitCryptError::InvalidCertificate)?;
         cert.tbs_certificate.subject_public_key_info.algorithm.oid = new_oid;
-        Ok(Certificate::new(cert))
+        Ok(Certificate {
+            cert,
+            raw_der: Some(der.to_vec()),
+        })
     }
 
     /// Create a certificate from a PEM format string
@@ -115,9 +340,11 @@ impl Certificate {
     ///
     /// `CertificateError::InvalidCertificate` will be returned if the certificate is invalid
     pub fn from_pem(pem: &str) -> Result<Certificate> {
-        let cert = x509_cert::Certificate::from_pem(pem)
-            .map_err(|_| QubitCryptError::InvalidCertificate)?;
-        Ok(Certificate::new(cert))
+        let pem = pem::parse(pem).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        if pem.tag() != "CERTIFICATE" {
+            return Err(QubitCryptError::InvalidCertificate);
+        }
+        Certificate::from_der(pem.contents())
     }
 
     /// Get the subject name
@@ -147,6 +374,15 @@ impl Certificate {
         self.cert.tbs_certificate.serial_number.clone()
     }
 
+    /// Get the signature of the certificate
+    ///
+    /// # Returns
+    ///
+    /// The signature made by the issuer over the TBS certificate
+    pub fn get_signature(&self) -> DsaSignature {
+        DsaSignature(self.cert.signature.raw_bytes().to_vec())
+    }
+
     /// Get the subject key identifier
     ///
     /// # Returns
@@ -194,11 +430,7 @@ impl Certificate {
             return Ok(false);
         }
 
-        let msg = self
-            .cert
-            .tbs_certificate
-            .to_der()
-            .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        let msg = self.get_tbs_bytes()?;
 
         let sig = self.cert.signature.raw_bytes();
 
@@ -227,11 +459,36 @@ impl Certificate {
         Ok(pk)
     }
 
+    /// Get the raw public key of the subject, without the SubjectPublicKeyInfo wrapping
+    ///
+    /// These are the same bytes as `get_public_key()?.get_key()`, and can be passed straight to
+    /// a KEM's `encap` or a DSA's `verify`. For composite keys this is the encoding of the
+    /// composite key, which is taken as a whole by the composite algorithm, not one of its
+    /// components.
+    ///
+    /// # Returns
+    ///
+    /// The contents of the subjectPublicKey BIT STRING
+    pub fn raw_public_key_bytes(&self) -> Vec<u8> {
+        self.cert
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .raw_bytes()
+            .to_vec()
+    }
+
     /// Verify that the specified certificate is a child of this certificate.
     ///
     /// This checks that the specified child certificate has the same issuer as this certificate's subject,
     /// that the child's Subject Key Identifier matches the Authority Key Identifier of this certificate,
-    /// and that the child's signature is valid.
+    /// and that the child's signature is valid. If this certificate has name constraints, the
+    /// child's subject and subject alternative names also have to satisfy them, unless the child is
+    /// self-issued.
+    ///
+    /// Only the name constraints of this certificate are checked. In RFC 5280 the constraints of
+    /// a CA apply to the whole path below it, so a leaf also has to satisfy the constraints of
+    /// the CAs above its issuer. Use `verify_chain` to check a path.
     ///
     /// # Arguments
     ///
@@ -240,7 +497,39 @@ impl Certificate {
     /// # Returns
     ///
     /// True if the child certificate is a child of this certificate, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedNameConstraint` will be returned if the name constraints of
+    /// this certificate have a subtree which can't be checked
     pub fn verify_child(&self, child: &Certificate) -> Result<bool> {
+        if !self.is_issuer_of(child)? {
+            return Ok(false);
+        }
+
+        // The names of the child must satisfy the name constraints of the parent
+        if let Some(constraints) = self.get_extension_value(NameConstraints::OID) {
+            let constraints = NameConstraints::from_der(constraints)
+                .map_err(|_| QubitCryptError::InvalidCertificate)?;
+            if !child.satisfies_name_constraints(&constraints)? {
+                return Ok(false);
+            }
+        }
+
+        self.verify_child_signature(child)
+    }
+
+    /// Check that the issuer name of the child is the subject of this certificate, and that the
+    /// authority key identifier of the child, if any, matches the SKID of this certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The child certificate
+    ///
+    /// # Returns
+    ///
+    /// True if the child names this certificate as its issuer, false otherwise
+    fn is_issuer_of(&self, child: &Certificate) -> Result<bool> {
         // If the child has a different issuer than the parent's subject, it cannot be a child
         if self.get_subject() != child.get_issuer() {
             return Ok(false);
@@ -267,13 +556,20 @@ impl Certificate {
                 }
             }
         }
+        Ok(true)
+    }
 
-        // Verify the signature of the child
-        let msg = child
-            .cert
-            .tbs_certificate
-            .to_der()
-            .map_err(|_| QubitCryptError::InvalidCertificate)?;
+    /// Verify the signature of the child with the public key of this certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The child certificate
+    ///
+    /// # Returns
+    ///
+    /// True if the signature is valid, false otherwise
+    fn verify_child_signature(&self, child: &Certificate) -> Result<bool> {
+        let msg = child.get_tbs_bytes()?;
         let sig = child.cert.signature.raw_bytes();
         let pk = self.get_public_key()?;
 
@@ -284,125 +580,674 @@ impl Certificate {
         Ok(result)
     }
 
-    /// Load a certificate from the specified file. The file can be in either DER or PEM format.
+    /// Verify one component of the composite signature of this certificate with the issuer's
+    /// public key of that component
+    ///
+    /// # Security
+    ///
+    /// Only half of the composite signature is checked, so a forgery of this component is
+    /// accepted whatever the other half is. With the traditional component this is not
+    /// quantum-safe, with the post-quantum component it relies on ML-DSA alone. Use it only
+    /// when the issuer's other component key is not available, e.g. during a migration, and
+    /// `verify_child` otherwise. Unlike `verify_child`, the names and key identifiers are not
+    /// checked either.
     ///
     /// # Arguments
     ///
-    /// * `path` - The path to the file
+    /// * `which` - The component to verify
+    /// * `issuer_component_pk` - The issuer's public key of the component, in the encoding of
+    ///   the composite public key, e.g. `CompositePublicKey::get_trad_pk`
     ///
     /// # Returns
     ///
-    /// The certificate
-    pub fn from_file(path: &str) -> Result<Certificate> {
-        // Read the contents of the file as bytes
-        let contents = std::fs::read(path).map_err(|_| QubitCryptError::FileReadError)?;
-
-        // Try to interpret as DER
-        let result = Certificate::from_der(&contents);
+    /// True if the component signature is valid, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the certificate is not
+    /// signed with a composite DSA, and the errors of the component DSA, e.g. for an invalid
+    /// public key
+    pub fn verify_signature_component(
+        &self,
+        which: CompositeComponent,
+        issuer_component_pk: &[u8],
+    ) -> Result<bool> {
+        let oid = self.get_signature_oid();
+        let dsa = DsaType::from_oid(&oid)
+            .filter(|_| is_composite_kem_or_dsa_oid(&oid))
+            .and_then(|dsa_type| CompositeDsaManager::new(dsa_type).ok())
+            .ok_or(QubitCryptError::UnsupportedOperation)?;
 
-        if let Ok(cert) = result {
-            Ok(cert)
-        } else {
-            // Try to interpret as PEM
-            let pem =
-                std::str::from_utf8(&contents).map_err(|_| QubitCryptError::InvalidCertificate)?;
-            if let Ok(cert) = Certificate::from_pem(pem) {
-                Ok(cert)
-            } else {
-                Err(QubitCryptError::InvalidCertificate)
-            }
-        }
+        let msg = self.get_tbs_bytes()?;
+        let sig = self.cert.signature.raw_bytes();
+        dsa.verify_component(which, issuer_component_pk, &msg, sig)
     }
 
-    /// Save the certificate to the specified file in DER format
+    /// Get the subject's alternative public key from the `subjectAltPublicKeyInfo` extension
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `path` - The path to the file
-    pub fn to_der_file(&self, path: &str) -> Result<()> {
-        let der = self.to_der()?;
-        std::fs::write(path, der).map_err(|_| QubitCryptError::InvalidCertificate)?;
-        Ok(())
-    }
-
-    /// Save the certificate to the specified file in PEM format
+    /// The alternative public key, or `None` if the certificate doesn't have one
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `path` - The path to the file
-    pub fn to_pem_file(&self, path: &str) -> Result<()> {
-        let pem = self.to_pem()?;
-        std::fs::write(path, pem).map_err(|_| QubitCryptError::InvalidCertificate)?;
-        Ok(())
+    /// `QubitCryptError::BadExtension` will be returned if the extension can't be decoded
+    pub fn get_alternative_public_key(&self) -> Result<Option<PublicKey>> {
+        let alt_spki = self
+            .cert
+            .tbs_certificate
+            .get::<SubjectAltPublicKeyInfo>()
+            .map_err(|_| QubitCryptError::BadExtension)?;
+        let alt_spki = match alt_spki {
+            Some((_, alt_spki)) => alt_spki.0,
+            None => return Ok(None),
+        };
+        let alt_spki = alt_spki
+            .to_der()
+            .map_err(|_| QubitCryptError::BadExtension)?;
+        let pk = PublicKey::from_der(&alt_spki).map_err(|_| QubitCryptError::BadExtension)?;
+        Ok(Some(pk))
     }
 
-    /// Check if this certificate is identified by the specified recipient identifier
+    /// Verify the alternative signature of the certificate, as defined in ITU-T X.509 (10/2019)
     ///
-    /// This could match by either issuer and serial number or subject key identifier
+    /// The alternative signature is over the certificate without the primary signature and
+    /// without the `altSignatureValue` extension, it is independent of the primary signature.
     ///
     /// # Arguments
     ///
-    /// * `rid` - The recipient identifier
+    /// * `alt_issuer_pk` - The issuer's alternative public key. For a self-signed certificate
+    ///   this is the certificate's own alternative public key, otherwise it is the alternative
+    ///   public key of the issuer's certificate
     ///
     /// # Returns
     ///
-    /// True if the certificate is identified by the recipient identifier, false otherwise
-    pub fn is_identified_by(&self, rid: &RecipientIdentifier) -> bool {
-        match rid {
-            cms::enveloped_data::RecipientIdentifier::IssuerAndSerialNumber(issuer) => {
-                if self.get_issuer() == issuer.issuer
-                    && self.get_serial_number() == issuer.serial_number
-                {
-                    return true;
-                }
-            }
-            cms::enveloped_data::RecipientIdentifier::SubjectKeyIdentifier(ski) => {
-                if let Ok(cert_ski) = self.get_subject_key_identifier() {
-                    if cert_ski == *ski {
-                        return true;
-                    }
-                }
-            }
+    /// True if the alternative signature is valid, false if it is invalid, was made with a
+    /// different algorithm or the certificate doesn't have an alternative signature
+    pub fn verify_alternative_signature(&self, alt_issuer_pk: &PublicKey) -> Result<bool> {
+        let tbs = &self.cert.tbs_certificate;
+        let alt_algorithm = tbs
+            .get::<AltSignatureAlgorithm>()
+            .map_err(|_| QubitCryptError::BadExtension)?;
+        let alt_signature = tbs
+            .get::<AltSignatureValue>()
+            .map_err(|_| QubitCryptError::BadExtension)?;
+        let (alt_algorithm, alt_signature) = match (alt_algorithm, alt_signature) {
+            (Some((_, alt_algorithm)), Some((_, alt_signature))) => (alt_algorithm, alt_signature),
+            _ => return Ok(false),
+        };
+
+        if alt_algorithm.0.oid.to_string() != alt_issuer_pk.get_oid() {
+            return Ok(false);
         }
-        false
+
+        let msg = get_pre_tbs_der(tbs)?;
+        let result = alt_issuer_pk
+            .verify(&msg, alt_signature.0.raw_bytes())
+            .unwrap_or(false);
+        Ok(result)
     }
 
-    /// Check if this certificate is valid
+    /// Reissue the certificate with a new validity period, e.g. to renew it before it expires
+    ///
+    /// The new certificate keeps the subject, public key, issuer and extensions of this one,
+    /// gets a new random serial number and the new validity, and is signed again by the issuer.
+    /// The new certificate is checked against the issuer certificate like `verify_child`, so a
+    /// signer which is not the issuer's key is rejected instead of producing a certificate
+    /// which no one can verify.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuer` - The certificate of the issuer, this certificate itself if it is self-signed
+    /// * `signer` - The private key of the issuer
+    /// * `validity` - The new validity period
     ///
     /// # Returns
     ///
-    /// True if the certificate is valid, false otherwise
-    pub fn is_valid(&self) -> bool {
-        // Get the notBefore and notAfter fields as DateTime
-        let not_before = self.cert.tbs_certificate.validity.not_before.to_date_time();
-        let not_after = self.cert.tbs_certificate.validity.not_after.to_date_time();
-
-        // Interpret the times as UTC
-        let not_before: DateTime<Utc> = not_before.to_system_time().into();
-        let not_after: DateTime<Utc> = not_after.to_system_time().into();
+    /// The reissued certificate
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the signer is not of the
+    /// algorithm the certificate was signed with or doesn't belong to the issuer certificate,
+    /// `QubitCryptError::InvalidCertificate` if the issuer certificate is not the issuer of this
+    /// one, `QubitCryptError::UnsupportedOperation` if the certificate has an alternative
+    /// signature, which only the alternative issuer can renew
+    pub fn reissue_with_new_validity(
+        &self,
+        issuer: &Certificate,
+        signer: &PrivateKey,
+        validity: CertValidity,
+    ) -> Result<Certificate> {
+        let algorithm = signer
+            .signature_algorithm_identifier()
+            .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+        if algorithm.oid != self.cert.signature_algorithm.oid {
+            return Err(QubitCryptError::InvalidPrivateKey);
+        }
+        if issuer.get_subject() != self.get_issuer() {
+            return Err(QubitCryptError::InvalidCertificate);
+        }
+        let issuer_pk = issuer.get_public_key()?;
+        if let Some(signer_pk) = signer.embedded_public_key() {
+            if !signer_pk.equals(&issuer_pk) {
+                return Err(QubitCryptError::InvalidPrivateKey);
+            }
+        }
 
-        // Get the current time
-        let now = chrono::Utc::now();
+        let mut tbs = self.cert.tbs_certificate.clone();
+        if tbs
+            .get::<AltSignatureValue>()
+            .map_err(|_| QubitCryptError::BadExtension)?
+            .is_some()
+        {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
+        tbs.serial_number = CertificateBuilder::get_random_serial()?;
+        tbs.validity = Validity {
+            not_before: Time::UtcTime(validity.not_before),
+            not_after: Time::UtcTime(validity.not_after),
+        };
+        tbs.signature = algorithm.clone();
 
-        // Check if the current time is within the validity period
-        let result = now >= not_before && now <= not_after;
+        let tbs_der = tbs
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidCertificate)?;
+        let signature = signer.sign(&tbs_der)?;
+        let signature =
+            BitString::from_bytes(&signature).map_err(|_| QubitCryptError::SignatureFailed)?;
+        let reissued = Certificate::new(x509_cert::Certificate {
+            tbs_certificate: tbs,
+            signature_algorithm: algorithm,
+            signature,
+        });
 
-        // Certificate sig oid must match the expected sig oid
-        let oid = self.cert.signature_algorithm.oid;
-        let expected_oid = self.cert.tbs_certificate.signature.oid;
-        if oid != expected_oid {
-            return false;
+        // The signature only verifies under the issuer's key if the signer is the issuer's
+        // private key. This also checks the authority key identifier against the issuer's SKI.
+        if !issuer.verify_child(&reissued)? {
+            return Err(QubitCryptError::InvalidPrivateKey);
         }
-
-        result
+        Ok(reissued)
     }
 
-    /// Check if key encipherment is enabled
+    /// Verify a signature made with the certificate's key, and that the certificate is trusted
+    ///
+    /// This bundles the checks which are needed before trusting signed data:
+    ///
+    /// 1. There must be a certification path from one of the trust anchors to the certificate,
+    ///    through the intermediate CA certificates, and the path must pass `verify_chain`. The
+    ///    certificate may also be a trust anchor itself.
+    /// 2. The key usage of the certificate must allow digital signatures, see
+    ///    `is_digital_signature_enabled`.
+    /// 3. The signature must be valid for the data under the certificate's public key.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The signed data
+    /// * `signature` - The signature
+    /// * `intermediates` - The intermediate CA certificates which can be used in the path
+    /// * `trust_anchors` - The trusted CA certificates
     ///
     /// # Returns
     ///
-    /// True if key encipherment is enabled, false otherwise
-    pub fn is_key_encipherment_enabled(&self) -> bool {
+    /// True if the certificate is trusted and the signature is valid, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::KeyUsageViolation` will be returned if the certificate is trusted but
+    /// its key usage doesn't allow digital signatures
+    pub fn verify_signed_data(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+        intermediates: &[Certificate],
+        trust_anchors: &[Certificate],
+    ) -> Result<bool> {
+        self.verify_signed_data_with_clock(
+            data,
+            signature,
+            intermediates,
+            trust_anchors,
+            &SystemClock,
+        )
+    }
+
+    /// Verify a signature over data like `verify_signed_data`, taking the current time from a
+    /// clock
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The signed data
+    /// * `signature` - The signature
+    /// * `intermediates` - The intermediate CA certificates which can be used in the path
+    /// * `trust_anchors` - The trusted CA certificates
+    /// * `clock` - The clock the validity periods are checked against
+    ///
+    /// # Returns
+    ///
+    /// True if the certificate is trusted and the signature is valid, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `verify_signed_data`
+    pub fn verify_signed_data_with_clock(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+        intermediates: &[Certificate],
+        trust_anchors: &[Certificate],
+        clock: &dyn Clock,
+    ) -> Result<bool> {
+        // 1. The path to a trust anchor
+        if !self.is_trusted_by(intermediates, trust_anchors, clock) {
+            return Ok(false);
+        }
+
+        // 2. The key usage
+        if !self.is_digital_signature_enabled() {
+            return Err(QubitCryptError::KeyUsageViolation);
+        }
+
+        // 3. The signature
+        let pk = self.get_public_key()?;
+        Ok(pk.verify(data, signature).unwrap_or(false))
+    }
+
+    /// Verify a certification path which starts at this certificate
+    ///
+    /// Every certificate of the path is checked, and every failure is reported, not only the
+    /// first one:
+    ///
+    /// 1. Each certificate, including this one, must be valid now.
+    /// 2. Each certificate of the chain must name the one before it as its issuer, including
+    ///    the authority key identifier, and be signed by it.
+    /// 3. Each certificate above the leaf must be a CA, and the number of intermediate CAs below
+    ///    it must not exceed its path length constraint. Self-issued CAs are not counted.
+    /// 4. The name constraints of every CA in the path apply to all certificates below it, not
+    ///    only to the ones it issued directly (RFC 5280, Section 6.1). Self-issued certificates
+    ///    are not constrained.
+    ///
+    /// Key usages are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - The certificates below this one, the one issued by this certificate first
+    ///   and the leaf last
+    ///
+    /// # Returns
+    ///
+    /// True if the path is valid. An invalid path is reported as an error, never as false.
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidChain` will be returned if the path is invalid. It holds every
+    /// failure with the position of the certificate in the path, 0 for this certificate and
+    /// `i` for `chain[i - 1]`: `QubitCryptError::CertificateExpired`,
+    /// `QubitCryptError::IssuerMismatch`, `QubitCryptError::SignatureVerificationFailed`,
+    /// `QubitCryptError::BasicConstraintsViolation`,
+    /// `QubitCryptError::NameConstraintsViolation`,
+    /// `QubitCryptError::UnsupportedNameConstraint`, or another error if a certificate is
+    /// malformed
+    pub fn verify_chain(&self, chain: &[Certificate]) -> Result<bool> {
+        self.verify_chain_with_clock(chain, &SystemClock)
+    }
+
+    /// Verify a certification path like `verify_chain`, taking the current time from a clock
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - The certificates below this one, the one issued by this certificate first
+    ///   and the leaf last
+    /// * `clock` - The clock the validity periods are checked against
+    ///
+    /// # Returns
+    ///
+    /// True if the path is valid
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `verify_chain`
+    pub fn verify_chain_with_clock(
+        &self,
+        chain: &[Certificate],
+        clock: &dyn Clock,
+    ) -> Result<bool> {
+        let path: Vec<&Certificate> = std::iter::once(self).chain(chain).collect();
+        let mut failures = Vec::new();
+        let mut constraints: Vec<NameConstraints> = Vec::new();
+        for (index, cert) in path.iter().enumerate() {
+            if !cert.is_valid_with(clock) {
+                failures.push((index, QubitCryptError::CertificateExpired));
+            }
+            if index > 0 {
+                if let Err(error) = path[index - 1].check_issued(cert) {
+                    failures.push((index, error));
+                }
+                if let Err(error) = cert.check_name_constraints(&constraints) {
+                    failures.push((index, error));
+                }
+            }
+            if index + 1 < path.len() {
+                if let Err(error) = cert.check_basic_constraints(&path[index + 1..]) {
+                    failures.push((index, error));
+                }
+            }
+            constraints.extend(cert.get_name_constraints());
+        }
+        if !failures.is_empty() {
+            return Err(QubitCryptError::InvalidChain(failures));
+        }
+        Ok(true)
+    }
+
+    /// Check that the child names this certificate as its issuer and is signed by it
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::IssuerMismatch` will be returned if the names or key identifiers
+    /// don't match, `QubitCryptError::SignatureVerificationFailed` if the signature is invalid
+    fn check_issued(&self, child: &Certificate) -> Result<()> {
+        if !self.is_issuer_of(child)? {
+            return Err(QubitCryptError::IssuerMismatch);
+        }
+        if !self.verify_child_signature(child).unwrap_or(false) {
+            return Err(QubitCryptError::SignatureVerificationFailed);
+        }
+        Ok(())
+    }
+
+    /// Check that the certificate satisfies the name constraints of all CAs above it
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::NameConstraintsViolation` will be returned if the names of the
+    /// certificate are not permitted by one of the constraints
+    fn check_name_constraints(&self, constraints: &[NameConstraints]) -> Result<()> {
+        for ancestor_constraints in constraints {
+            if !self.satisfies_name_constraints(ancestor_constraints)? {
+                return Err(QubitCryptError::NameConstraintsViolation);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the certificate may issue the certificates below it in a path
+    ///
+    /// # Arguments
+    ///
+    /// * `below` - The certificates below this one in the path, the leaf last
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::BasicConstraintsViolation` will be returned if the certificate is not
+    /// a CA, or more intermediate CAs follow it than its path length constraint allows
+    fn check_basic_constraints(&self, below: &[&Certificate]) -> Result<()> {
+        let basic_constraints = self
+            .get_basic_constraints()
+            .filter(|bc| bc.ca)
+            .ok_or(QubitCryptError::BasicConstraintsViolation)?;
+        if let Some(max_path_len) = basic_constraints.path_len_constraint {
+            let intermediates = below[..below.len().saturating_sub(1)]
+                .iter()
+                .filter(|cert| cert.get_subject() != cert.get_issuer())
+                .count();
+            if intermediates > max_path_len as usize {
+                return Err(QubitCryptError::BasicConstraintsViolation);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the subject and subject alternative names of the certificate satisfy name
+    /// constraints. A self-issued certificate always satisfies them.
+    ///
+    /// # Arguments
+    ///
+    /// * `constraints` - The name constraints of a CA above the certificate
+    ///
+    /// # Returns
+    ///
+    /// True if the names satisfy the constraints, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedNameConstraint` will be returned if the constraints can't be
+    /// checked
+    fn satisfies_name_constraints(&self, constraints: &NameConstraints) -> Result<bool> {
+        let subject = self.get_subject();
+        if subject == self.get_issuer() {
+            return Ok(true);
+        }
+        let alt_names = match self.get_extension_value(SubjectAltName::OID) {
+            Some(alt_names) => {
+                SubjectAltName::from_der(alt_names)
+                    .map_err(|_| QubitCryptError::InvalidCertificate)?
+                    .0
+            }
+            None => Vec::new(),
+        };
+        name_constraints::is_permitted(constraints, &subject, &alt_names)
+    }
+
+    /// Check if there is a valid certification path from one of the trust anchors to the
+    /// certificate
+    ///
+    /// The path is built with `build_path` and then checked as a whole with
+    /// `verify_chain_with_clock`, so the name constraints of a trust anchor also apply to the
+    /// certificates below an unconstrained intermediate CA.
+    ///
+    /// # Arguments
+    ///
+    /// * `intermediates` - The certificates that can be used as intermediate CAs
+    /// * `trust_anchors` - The trusted CA certificates
+    /// * `clock` - The clock the validity periods are checked against
+    ///
+    /// # Returns
+    ///
+    /// True if the certificate is trusted, false otherwise
+    pub(crate) fn is_trusted_by(
+        &self,
+        intermediates: &[Certificate],
+        trust_anchors: &[Certificate],
+        clock: &dyn Clock,
+    ) -> bool {
+        match self.build_path(intermediates, trust_anchors, clock) {
+            Some(path) => path[0].verify_chain_with_clock(&path[1..], clock).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Build a certification path from one of the trust anchors to the certificate
+    ///
+    /// Starting at this certificate, the issuer of each certificate is looked up among the
+    /// trust anchors and then among the intermediates, until a trust anchor is reached. An
+    /// issuer must be a valid CA certificate which verifies its child with `verify_child`. Each
+    /// intermediate is used at most once. The path is not checked as a whole, see
+    /// `verify_chain_with_clock`.
+    ///
+    /// # Arguments
+    ///
+    /// * `intermediates` - The certificates that can be used as intermediate CAs
+    /// * `trust_anchors` - The trusted CA certificates
+    /// * `clock` - The clock the validity periods of the issuers are checked against
+    ///
+    /// # Returns
+    ///
+    /// The path with the trust anchor first and this certificate last, or `None` if there is
+    /// no path. If this certificate is a trust anchor itself, the path only contains it.
+    pub(crate) fn build_path(
+        &self,
+        intermediates: &[Certificate],
+        trust_anchors: &[Certificate],
+        clock: &dyn Clock,
+    ) -> Option<Vec<Certificate>> {
+        let is_issuer = |ca: &Certificate, cert: &Certificate| {
+            ca != cert
+                && ca.is_valid_with(clock)
+                && ca.is_ca()
+                && ca.verify_child(cert).unwrap_or(false)
+        };
+
+        // Each intermediate is used at most once, so this terminates
+        let mut intermediates: Vec<&Certificate> =
+            intermediates.iter().filter(|ca| *ca != self).collect();
+        let mut path = vec![self.clone()];
+        loop {
+            let current = &path[path.len() - 1];
+            if trust_anchors.contains(current) {
+                break;
+            }
+            if let Some(ta) = trust_anchors.iter().find(|ta| is_issuer(ta, current)) {
+                path.push(ta.clone());
+                break;
+            }
+            let index = intermediates.iter().position(|ca| is_issuer(ca, current))?;
+            path.push(intermediates.swap_remove(index).clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Check if the certificate is a CA certificate, i.e. the cA flag of its basic constraints
+    /// is set
+    pub(crate) fn is_ca(&self) -> bool {
+        self.get_basic_constraints()
+            .map(|bc| bc.ca)
+            .unwrap_or(false)
+    }
+
+    /// Load a certificate from the specified file. The file can be in either DER or PEM format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file
+    ///
+    /// # Returns
+    ///
+    /// The certificate
+    pub fn from_file(path: &str) -> Result<Certificate> {
+        // Read the contents of the file as bytes
+        let contents = std::fs::read(path).map_err(|_| QubitCryptError::FileReadError)?;
+
+        // Try to interpret as DER
+        let result = Certificate::from_der(&contents);
+
+        if let Ok(cert) = result {
+            Ok(cert)
+        } else {
+            // Try to interpret as PEM
+            let pem =
+                std::str::from_utf8(&contents).map_err(|_| QubitCryptError::InvalidCertificate)?;
+            if let Ok(cert) = Certificate::from_pem(pem) {
+                Ok(cert)
+            } else {
+                Err(QubitCryptError::InvalidCertificate)
+            }
+        }
+    }
+
+    /// Save the certificate to the specified file in DER format
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file
+    pub fn to_der_file(&self, path: &str) -> Result<()> {
+        let der = self.to_der()?;
+        std::fs::write(path, der).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        Ok(())
+    }
+
+    /// Save the certificate to the specified file in PEM format
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file
+    pub fn to_pem_file(&self, path: &str) -> Result<()> {
+        let pem = self.to_pem()?;
+        std::fs::write(path, pem).map_err(|_| QubitCryptError::InvalidCertificate)?;
+        Ok(())
+    }
+
+    /// Check if this certificate is identified by the specified recipient identifier
+    ///
+    /// This could match by either issuer and serial number or subject key identifier
+    ///
+    /// # Arguments
+    ///
+    /// * `rid` - The recipient identifier
+    ///
+    /// # Returns
+    ///
+    /// True if the certificate is identified by the recipient identifier, false otherwise
+    pub fn is_identified_by(&self, rid: &RecipientIdentifier) -> bool {
+        match rid {
+            cms::enveloped_data::RecipientIdentifier::IssuerAndSerialNumber(issuer) => {
+                if self.get_issuer() == issuer.issuer
+                    && self.get_serial_number() == issuer.serial_number
+                {
+                    return true;
+                }
+            }
+            cms::enveloped_data::RecipientIdentifier::SubjectKeyIdentifier(ski) => {
+                if let Ok(cert_ski) = self.get_subject_key_identifier() {
+                    if cert_ski == *ski {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Check if this certificate is valid
+    ///
+    /// # Returns
+    ///
+    /// True if the certificate is valid, false otherwise
+    pub fn is_valid(&self) -> bool {
+        self.is_valid_with(&SystemClock)
+    }
+
+    /// Check if this certificate is valid at the current time of a clock
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock the validity period is checked against
+    ///
+    /// # Returns
+    ///
+    /// True if the certificate is valid, false otherwise
+    pub fn is_valid_with(&self, clock: &dyn Clock) -> bool {
+        // Get the notBefore and notAfter fields as DateTime
+        let not_before = self.cert.tbs_certificate.validity.not_before.to_date_time();
+        let not_after = self.cert.tbs_certificate.validity.not_after.to_date_time();
+
+        // Interpret the times as UTC
+        let not_before: DateTime<Utc> = not_before.to_system_time().into();
+        let not_after: DateTime<Utc> = not_after.to_system_time().into();
+
+        // Get the current time
+        let now = clock.now();
+
+        // Check if the current time is within the validity period
+        let result = now >= not_before && now <= not_after;
+
+        // Certificate sig oid must match the expected sig oid
+        let oid = self.cert.signature_algorithm.oid;
+        let expected_oid = self.cert.tbs_certificate.signature.oid;
+        if oid != expected_oid {
+            return false;
+        }
+
+        result
+    }
+
+    /// Check if key encipherment is enabled
+    ///
+    /// # Returns
+    ///
+    /// True if key encipherment is enabled, false otherwise
+    pub fn is_key_encipherment_enabled(&self) -> bool {
         if let Some(exts) = self.cert.tbs_certificate.extensions.clone() {
             for ext in exts {
                 if ext.extn_id == const_oid::db::rfc5280::ID_CE_KEY_USAGE {
@@ -415,6 +1260,144 @@ impl Certificate {
         false
     }
 
+    /// Check if the certificate holds a KEM key, including composite and custom KEM keys
+    ///
+    /// # Returns
+    ///
+    /// True if the public key is a KEM key, false otherwise
+    pub fn is_kem(&self) -> bool {
+        is_kem_oid(&self.get_public_key_oid())
+    }
+
+    /// Check if the certificate holds a signing key, including composite and custom DSA keys
+    ///
+    /// # Returns
+    ///
+    /// True if the public key is a DSA key, false otherwise
+    pub fn is_signing(&self) -> bool {
+        is_dsa_oid(&self.get_public_key_oid())
+    }
+
+    /// Get the KDF and key wrap combinations which are suitable for a KEM recipient with this
+    /// certificate
+    ///
+    /// A combination is suitable if both the KDF and the key wrap have at least the security
+    /// strength of the KEM's NIST security category: 128 bits for categories 0 to 2, 192 bits
+    /// for categories 3 and 4 and 256 bits for category 5. Traditional KEMs are category 0.
+    /// Custom KEMs, whose category is unknown, are treated as category 5.
+    ///
+    /// # Returns
+    ///
+    /// The combinations in the order of `KdfType::all` and `WrapType::all`, or an empty vector
+    /// if the certificate doesn't hold a KEM key
+    pub fn suitable_kem_parameters(&self) -> Vec<(KdfType, WrapType)> {
+        if !self.is_kem() {
+            return Vec::new();
+        }
+        let required_strength = match get_security_category(&self.get_public_key_oid()) {
+            Some(0..=2) => 128,
+            Some(3 | 4) => 192,
+            _ => 256,
+        };
+
+        let mut parameters = Vec::new();
+        for kdf in KdfType::all() {
+            if kdf.security_strength() < required_strength {
+                continue;
+            }
+            for wrap in WrapType::all() {
+                if wrap.security_strength() >= required_strength {
+                    parameters.push((kdf.clone(), wrap));
+                }
+            }
+        }
+        parameters
+    }
+
+    /// Check if digital signatures are enabled
+    ///
+    /// A certificate without a key usage extension places no restriction on the key (RFC 5280,
+    /// Section 4.2.1.3), so it may sign.
+    ///
+    /// # Returns
+    ///
+    /// True if the digitalSignature bit is set in the key usage or there is no key usage
+    /// extension, false otherwise
+    pub fn is_digital_signature_enabled(&self) -> bool {
+        match self.get_extension_value(KeyUsage::OID) {
+            Some(ku) => KeyUsage::from_der(ku)
+                .map(|ku| ku.digital_signature())
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
+    /// Get the basic constraints of the certificate
+    ///
+    /// # Returns
+    ///
+    /// The basic constraints (cA flag and pathLenConstraint), or `None` if the certificate
+    /// doesn't contain a basic constraints extension
+    pub fn get_basic_constraints(&self) -> Option<BasicConstraints> {
+        if let Some(exts) = self.cert.tbs_certificate.extensions.as_ref() {
+            for ext in exts {
+                if ext.extn_id == const_oid::db::rfc5280::ID_CE_BASIC_CONSTRAINTS {
+                    return BasicConstraints::from_der(ext.extn_value.as_bytes()).ok();
+                }
+            }
+        }
+        None
+    }
+
+    /// Get the policies of the certificate policies extension
+    ///
+    /// # Returns
+    ///
+    /// The OIDs of the policies, empty if the certificate doesn't contain a certificate policies
+    /// extension
+    pub fn get_certificate_policies(&self) -> Vec<String> {
+        self.get_extension_value(CertificatePolicies::OID)
+            .and_then(|value| CertificatePolicies::from_der(value).ok())
+            .map(|policies| {
+                policies
+                    .0
+                    .iter()
+                    .map(|policy| policy.policy_identifier.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the name constraints of the certificate
+    ///
+    /// # Returns
+    ///
+    /// The permitted and excluded subtrees, or `None` if the certificate doesn't contain a name
+    /// constraints extension
+    pub fn get_name_constraints(&self) -> Option<NameConstraints> {
+        self.get_extension_value(NameConstraints::OID)
+            .and_then(|value| NameConstraints::from_der(value).ok())
+    }
+
+    /// Get the value of an extension
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the extension
+    ///
+    /// # Returns
+    ///
+    /// The DER encoded value of the extension, or `None` if the certificate doesn't contain it
+    fn get_extension_value(&self, oid: ObjectIdentifier) -> Option<&[u8]> {
+        self.cert
+            .tbs_certificate
+            .extensions
+            .as_ref()?
+            .iter()
+            .find(|ext| ext.extn_id == oid)
+            .map(|ext| ext.extn_value.as_bytes())
+    }
+
     /// Get the OID of algorithm used for the public key
     ///
     /// # Returns
@@ -459,9 +1442,83 @@ impl Certificate {
 
 #[cfg(test)]
 mod tests {
+    use der::asn1::{AnyRef, BitString};
+    use der::{Decode, Encode, Tag, TagNumber};
+    use sha2::{Digest, Sha256};
+
     use crate::{certificates::CertValidity, certificates::Certificate};
 
     //const USE_OLD_VERSION: bool = true;
+    use crate::asn1::macros::test_asn1_roundtrip;
+
+    #[test]
+    fn test_verify_signature_component() {
+        use crate::certificates::Pki;
+        use crate::dsas::DsaAlgorithm;
+        use crate::kems::KemAlgorithm;
+        use crate::keys::{CompositeComponent, CompositePublicKey};
+        use crate::QubitCryptError;
+
+        let pki = Pki::generate(
+            DsaAlgorithm::MlDsa44EcdsaP256SHA256,
+            KemAlgorithm::MlKem512,
+            1,
+        )
+        .unwrap();
+        let issuer_pk = pki.root_cert.get_public_key().unwrap();
+        let c_pk = CompositePublicKey::from_der(issuer_pk.get_oid(), issuer_pk.get_key()).unwrap();
+        let pq_pk = c_pk.get_pq_pk();
+        let trad_pk = c_pk.get_trad_pk();
+
+        let cert = &pki.intermediate_cert;
+        assert!(cert
+            .verify_signature_component(CompositeComponent::Pq, &pq_pk)
+            .unwrap());
+        assert!(cert
+            .verify_signature_component(CompositeComponent::Trad, &trad_pk)
+            .unwrap());
+        assert!(!cert
+            .verify_signature_component(CompositeComponent::Trad, &pq_pk)
+            .unwrap_or(false));
+
+        // The leaf is issued by the intermediate CA, not the root
+        let leaf = &pki.leaves[0].0;
+        assert!(!leaf
+            .verify_signature_component(CompositeComponent::Pq, &pq_pk)
+            .unwrap());
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 1).unwrap();
+        assert_eq!(
+            pki.intermediate_cert
+                .verify_signature_component(CompositeComponent::Pq, &pq_pk),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+    }
+
+    #[test]
+    fn test_certificate_roundtrip() {
+        let cert = Certificate::from_file(
+            "test/data/MlDsa44EcdsaP256SHA256-2.16.840.1.114027.80.8.1.4_ta.pem",
+        )
+        .unwrap();
+        test_asn1_roundtrip!(Certificate, cert);
+
+        let (pk, sk) = crate::dsas::DsaKeyGenerator::new(crate::dsas::DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let cert = crate::certificates::CertificateBuilder::new(
+            crate::certificates::Profile::Root,
+            None,
+            CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap(),
+            "CN=example.com",
+            pk,
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        test_asn1_roundtrip!(Certificate, cert);
+    }
 
     #[test]
     fn test_ml_dsa44_ecdsa_p256_sha256_self_signed_cert() {
@@ -480,7 +1537,7 @@ mod tests {
             "../../test/data/MlDsa44Rsa2048PssSha256-2.16.840.1.114027.80.8.1.1_ta.pem"
         );
         let pem = std::str::from_utf8(pem_bytes).unwrap().trim();
-        let cert = Certificate::from_pem(&pem).unwrap();
+        let cert = Certificate::from_pem(pem).unwrap();
         assert!(cert.verify_self_signed().unwrap());
     }
 
@@ -490,10 +1547,148 @@ mod tests {
             "../../test/data/MlDsa44Rsa2048Pkcs15Sha256-2.16.840.1.114027.80.8.1.2_ta.pem"
         );
         let pem = std::str::from_utf8(pem_bytes).unwrap().trim();
-        let cert = Certificate::from_pem(&pem).unwrap();
+        let cert = Certificate::from_pem(pem).unwrap();
+        assert!(cert.verify_self_signed().unwrap());
+    }
+
+    #[test]
+    fn test_verify_non_canonical_tbs() {
+        fn tlv(tag: Tag, value: &[u8]) -> Vec<u8> {
+            AnyRef::new(tag, value).unwrap().to_der().unwrap()
+        }
+
+        let (pk, sk) = crate::dsas::DsaKeyGenerator::new(crate::dsas::DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+        let cert = crate::certificates::CertificateBuilder::new(
+            crate::certificates::Profile::Root,
+            None,
+            validity,
+            "CN=example.com".to_string(),
+            pk,
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        // Re-encode the TBSCertificate with critical explicitly set to its default value of
+        // FALSE, which DER requires to be omitted, and sign that encoding
+        let mut tbs = cert.cert.tbs_certificate.clone();
+        let exts = tbs.extensions.take().unwrap();
+        let tbs_der = tbs.to_der().unwrap();
+        let mut tbs_content = AnyRef::from_der(&tbs_der).unwrap().value().to_vec();
+        let mut exts_content = Vec::new();
+        for ext in exts.iter() {
+            let mut ext_content = ext.extn_id.to_der().unwrap();
+            ext_content.extend_from_slice(&[0x01, 0x01, if ext.critical { 0xFF } else { 0x00 }]);
+            ext_content.extend(ext.extn_value.to_der().unwrap());
+            exts_content.extend(tlv(Tag::Sequence, &ext_content));
+        }
+        let exts_tag = Tag::ContextSpecific {
+            constructed: true,
+            number: TagNumber::N3,
+        };
+        tbs_content.extend(tlv(exts_tag, &tlv(Tag::Sequence, &exts_content)));
+        let raw_tbs = tlv(Tag::Sequence, &tbs_content);
+        assert_ne!(raw_tbs, cert.cert.tbs_certificate.to_der().unwrap());
+
+        let sig = sk.sign(&raw_tbs).unwrap();
+        let mut cert_content = raw_tbs.clone();
+        cert_content.extend(cert.cert.signature_algorithm.to_der().unwrap());
+        cert_content.extend(BitString::from_bytes(&sig).unwrap().to_der().unwrap());
+        let cert_der = tlv(Tag::Sequence, &cert_content);
+
+        let quirky = Certificate::from_der(&cert_der).unwrap();
+        assert_eq!(quirky.raw_der.as_ref(), Some(&cert_der));
+        assert_eq!(quirky.get_tbs_bytes().unwrap(), raw_tbs);
+        assert_eq!(quirky.get_encoded_bytes().unwrap(), cert_der);
+        assert_eq!(
+            quirky.pin_sha256().unwrap().as_slice(),
+            Sha256::digest(&cert_der).as_slice()
+        );
+        assert_ne!(quirky.pin_sha256().unwrap(), cert.pin_sha256().unwrap());
+        assert_eq!(
+            quirky.spki_pin_sha256().unwrap(),
+            cert.spki_pin_sha256().unwrap()
+        );
+        let spki_der = cert
+            .cert
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .unwrap();
+        assert_eq!(quirky.get_spki_bytes().unwrap(), spki_der);
+        assert!(quirky.verify_self_signed().unwrap());
+        assert!(quirky.verify_child(&quirky).unwrap());
+
+        // The same certificate loaded from PEM
+        let pem = pem::encode(&pem::Pem::new("CERTIFICATE", cert_der));
+        let quirky = Certificate::from_pem(&pem).unwrap();
+        assert!(quirky.verify_self_signed().unwrap());
+
+        // A certificate created directly from the builder has no raw TBS
+        assert!(cert.raw_der.is_none());
         assert!(cert.verify_self_signed().unwrap());
     }
 
+    #[test]
+    fn test_pins() {
+        // Computed with openssl over the DER of the certificate and of its SubjectPublicKeyInfo
+        let cert_path = "test/data/MlDsa44EcdsaP256SHA256-2.16.840.1.114027.80.8.1.4_ta.pem";
+        let cert = Certificate::from_file(cert_path).unwrap();
+        assert_eq!(
+            hex::encode(cert.pin_sha256().unwrap()),
+            "3ecec63e610b25bb44db313856f86c82970fbdc72ea7f13585e3279bbb106d68"
+        );
+        assert_eq!(
+            cert.pin_sha256_base64().unwrap(),
+            "Ps7GPmELJbtE2zE4VvhsgpcPvccup/E1heMnm7sQbWg="
+        );
+        assert_eq!(
+            hex::encode(cert.spki_pin_sha256().unwrap()),
+            "13752d12bc2e0b9f1c6bdf3fe7601f5d5d1782bc6696e68927be606cffd25a28"
+        );
+        assert_eq!(
+            cert.spki_pin_sha256_base64().unwrap(),
+            "E3UtErwuC58ca98/52AfXV0XgrxmluaJJ75gbP/SWig="
+        );
+
+        // The pins don't depend on how the certificate was loaded
+        let converted = Certificate::from(cert.to_x509_cert());
+        assert_eq!(converted.pin_sha256().unwrap(), cert.pin_sha256().unwrap());
+        assert_eq!(
+            converted.spki_pin_sha256().unwrap(),
+            cert.spki_pin_sha256().unwrap()
+        );
+
+        // A certificate of another key has another SPKI pin
+        let other_path = "test/data/MlDsa44Rsa2048PssSha256-2.16.840.1.114027.80.8.1.1_ta.pem";
+        let other = Certificate::from_file(other_path).unwrap();
+        assert_ne!(
+            other.spki_pin_sha256().unwrap(),
+            cert.spki_pin_sha256().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_x509_cert_conversion() {
+        let cert_path = "test/data/MlDsa44EcdsaP256SHA256-2.16.840.1.114027.80.8.1.4_ta.pem";
+        let cert = Certificate::from_file(cert_path).unwrap();
+
+        let inner = cert.to_x509_cert();
+        assert_eq!(inner.to_der().unwrap(), cert.to_der().unwrap());
+
+        let converted = Certificate::from(inner.clone());
+        assert!(converted.raw_der.is_none());
+        assert!(converted == cert);
+        assert!(converted.verify_self_signed().unwrap());
+
+        let back: x509_cert::Certificate = converted.into();
+        assert_eq!(back, inner);
+    }
+
     #[test]
     fn test_akid_skid() {
         // First generate a TA cert
@@ -542,6 +1737,279 @@ mod tests {
         assert!(cert.verify_child(&cert_kem).unwrap());
     }
 
+    #[test]
+    fn test_verify_signed_data() {
+        use crate::certificates::{CertificateBuilder, Profile};
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::QubitCryptError;
+        use const_oid::AssociatedOid;
+        use x509_cert::ext::pkix::KeyUsage;
+
+        let validity = || CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+        let (ta_pk, ta_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let ta = CertificateBuilder::new(Profile::Root, None, validity(), "CN=ta", ta_pk, &ta_sk)
+            .unwrap()
+            .build()
+            .unwrap();
+        let (other_pk, other_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let other_ta = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity(),
+            "CN=other",
+            other_pk,
+            &other_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let (ee_pk, ee_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let ee = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: ta.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            validity(),
+            "CN=ee",
+            ee_pk,
+            &ta_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert!(ee.is_digital_signature_enabled());
+        assert!(!ta.is_digital_signature_enabled());
+
+        let data = b"signed data";
+        let sig = ee_sk.sign(data).unwrap();
+        let anchors = [other_ta.clone(), ta.clone()];
+        assert!(ee.verify_signed_data(data, &sig, &[], &anchors).unwrap());
+        assert!(!ee
+            .verify_signed_data(b"other data", &sig, &[], &anchors)
+            .unwrap());
+        assert!(!ee
+            .verify_signed_data(data, &sig, &[], &[other_ta.clone()])
+            .unwrap());
+        assert!(!ee.verify_signed_data(data, &sig, &[], &[]).unwrap());
+
+        // The trust anchor itself is trusted, but its key usage doesn't allow signing data
+        let sig = ta_sk.sign(data).unwrap();
+        assert!(matches!(
+            ta.verify_signed_data(data, &sig, &[], &anchors),
+            Err(QubitCryptError::KeyUsageViolation)
+        ));
+
+        // A certificate without a key usage extension may sign
+        let mut no_key_usage = ee.to_x509_cert();
+        no_key_usage
+            .tbs_certificate
+            .extensions
+            .as_mut()
+            .unwrap()
+            .retain(|ext| ext.extn_id != KeyUsage::OID);
+        let tbs = no_key_usage.tbs_certificate.to_der().unwrap();
+        no_key_usage.signature = BitString::from_bytes(&ta_sk.sign(&tbs).unwrap()).unwrap();
+        let no_key_usage = Certificate::from(no_key_usage);
+        assert!(no_key_usage.is_digital_signature_enabled());
+        let sig = ee_sk.sign(data).unwrap();
+        assert!(no_key_usage
+            .verify_signed_data(data, &sig, &[], &anchors)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_verify_signed_data_intermediates() {
+        use crate::certificates::{CertificateBuilder, Pki, Profile};
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::KemAlgorithm;
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        let (ee_pk, ee_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let ee = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: pki.intermediate_cert.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap(),
+            "CN=ee",
+            ee_pk,
+            &pki.intermediate_key,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let data = b"signed data";
+        let sig = ee_sk.sign(data).unwrap();
+        let intermediates = [pki.intermediate_cert.clone()];
+        let anchors = [pki.root_cert.clone()];
+        assert!(ee
+            .verify_signed_data(data, &sig, &intermediates, &anchors)
+            .unwrap());
+        assert!(!ee.verify_signed_data(data, &sig, &[], &anchors).unwrap());
+        assert!(ee
+            .verify_signed_data(data, &sig, &[], &intermediates)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_verify_chain_all_failures() {
+        use crate::asn1::name_constraints::NameConstraint;
+        use crate::certificates::{CertificateBuilder, FixedClock, Profile};
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::QubitCryptError;
+
+        let validity = || CertValidity::new(None, "2040-01-01T00:00:00Z").unwrap();
+        let keys = || {
+            DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+                .generate()
+                .unwrap()
+        };
+
+        let (root_pk, root_sk) = keys();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity(),
+            "CN=Root,O=Example",
+            root_pk,
+            &root_sk,
+        )
+        .unwrap();
+        builder
+            .add_name_constraints(
+                &[NameConstraint::DirectoryName("O=Example".to_string())],
+                &[],
+            )
+            .unwrap();
+        let root = builder.build().unwrap();
+
+        // Expires before the clock, and allows no intermediate CA below it
+        let (int1_pk, int1_sk) = keys();
+        let int1 = CertificateBuilder::new(
+            Profile::SubCA {
+                issuer: root.get_subject(),
+                path_len_constraint: Some(0),
+            },
+            None,
+            CertValidity::new(None, "2030-01-01T00:00:00Z").unwrap(),
+            "CN=Int1,O=Example",
+            int1_pk,
+            &root_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let (int2_pk, int2_sk) = keys();
+        let int2 = CertificateBuilder::new(
+            Profile::SubCA {
+                issuer: int1.get_subject(),
+                path_len_constraint: None,
+            },
+            None,
+            validity(),
+            "CN=Int2,O=Example",
+            int2_pk,
+            &int1_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        // Outside the root's name constraints, and with a broken signature
+        let (leaf_pk, _) = keys();
+        let leaf = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: int2.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            validity(),
+            "CN=Leaf,O=Other",
+            leaf_pk,
+            &int2_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let mut tampered = leaf.to_x509_cert();
+        let mut signature = tampered.signature.raw_bytes().to_vec();
+        signature[0] ^= 1;
+        tampered.signature = BitString::from_bytes(&signature).unwrap();
+        let leaf = Certificate::from(tampered);
+
+        let clock = FixedClock(
+            chrono::DateTime::parse_from_rfc3339("2031-01-01T00:00:00Z")
+                .unwrap()
+                .into(),
+        );
+        assert_eq!(
+            root.verify_chain_with_clock(&[int1.clone(), int2.clone(), leaf], &clock),
+            Err(QubitCryptError::InvalidChain(vec![
+                (1, QubitCryptError::CertificateExpired),
+                (1, QubitCryptError::BasicConstraintsViolation),
+                (3, QubitCryptError::SignatureVerificationFailed),
+                (3, QubitCryptError::NameConstraintsViolation),
+            ]))
+        );
+
+        // A leaf can't issue certificates
+        let leaf_profile = |issuer: &Certificate| Profile::Leaf {
+            issuer: issuer.get_subject(),
+            enable_key_agreement: false,
+            enable_key_encipherment: false,
+        };
+        let (ee_pk, ee_sk) = keys();
+        let ee = CertificateBuilder::new(
+            leaf_profile(&root),
+            None,
+            validity(),
+            "CN=EE,O=Example",
+            ee_pk,
+            &root_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let (child_pk, _) = keys();
+        let child = CertificateBuilder::new(
+            leaf_profile(&ee),
+            None,
+            validity(),
+            "CN=Child,O=Example",
+            child_pk,
+            &ee_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert_eq!(
+            root.verify_chain_with_clock(&[ee.clone(), child], &clock),
+            Err(QubitCryptError::InvalidChain(vec![(
+                1,
+                QubitCryptError::BasicConstraintsViolation
+            )]))
+        );
+
+        assert!(root.verify_chain_with_clock(&[ee], &clock).unwrap());
+        assert!(int1.verify_chain(&[int2]).unwrap());
+    }
+
     #[test]
     fn test_certificate_expiry() {
         // Get now plus 2 secs as UTC String
@@ -574,4 +2042,211 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_secs(5));
         assert!(!cert.is_valid());
     }
+
+    #[test]
+    fn test_raw_public_key_bytes() {
+        use crate::kem::common::kem_trait::Kem;
+        use crate::kem::kem_manager::KemManager;
+        use crate::kems::KemAlgorithm;
+
+        for kem_alg in [KemAlgorithm::MlKem768, KemAlgorithm::MlKem768X25519] {
+            let pki =
+                crate::certificates::Pki::generate(crate::dsas::DsaAlgorithm::MlDsa44, kem_alg, 1)
+                    .unwrap();
+            let (cert, sk) = &pki.leaves[0];
+            let raw = cert.raw_public_key_bytes();
+            assert_eq!(raw, cert.get_public_key().unwrap().get_key());
+
+            let mut kem = KemManager::new(kem_alg.get_kem_type()).unwrap();
+            let (ss, ct) = kem.encap(&raw).unwrap();
+            assert_eq!(kem.decap(sk.get_key(), &ct).unwrap(), ss);
+        }
+
+        let ta = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.3.17_MlDsa44_ta.der"
+        ))
+        .unwrap();
+        assert_eq!(ta.raw_public_key_bytes().len(), 1312);
+    }
+
+    #[test]
+    fn test_suitable_kem_parameters() {
+        use crate::kdfs::KdfType;
+        use crate::kems::KemAlgorithm;
+        use crate::wraps::WrapType;
+
+        for (kem_alg, expected) in [
+            (KemAlgorithm::MlKem512, 14),
+            (KemAlgorithm::MlKem768, 5),
+            (KemAlgorithm::MlKem1024, 5),
+        ] {
+            let pki =
+                crate::certificates::Pki::generate(crate::dsas::DsaAlgorithm::MlDsa44, kem_alg, 1)
+                    .unwrap();
+            let (cert, _) = &pki.leaves[0];
+            assert!(cert.is_kem());
+            assert!(!cert.is_signing());
+            let parameters = cert.suitable_kem_parameters();
+            assert_eq!(parameters.len(), expected);
+            assert_eq!(
+                parameters.contains(&(KdfType::Kmac128, WrapType::Aes128)),
+                kem_alg == KemAlgorithm::MlKem512
+            );
+            assert!(parameters.contains(&(KdfType::HkdfWithSha256, WrapType::Aes256)));
+
+            assert!(pki.root_cert.is_signing());
+            assert!(!pki.root_cert.is_kem());
+            assert!(pki.root_cert.suitable_kem_parameters().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_certificate_expiry_with_clock() {
+        use crate::certificates::{FixedClock, SystemClock};
+        use crate::QubitCryptError;
+        use chrono::{DateTime, Utc};
+
+        let at =
+            |time: &str| -> DateTime<Utc> { DateTime::parse_from_rfc3339(time).unwrap().into() };
+
+        // A certificate which expired long ago, issued at a fixed reference time
+        let validity = CertValidity::new_at(
+            at("2020-01-01T00:00:00Z"),
+            Some("2020-01-01T00:00:00Z"),
+            "2021-01-01T00:00:00Z",
+        )
+        .unwrap();
+        assert_eq!(
+            CertValidity::new(None, "2021-01-01T00:00:00Z").err(),
+            Some(QubitCryptError::InvalidNotAfter)
+        );
+        assert_eq!(
+            CertValidity::new_at(at("2021-01-02T00:00:00Z"), None, "2021-01-01T00:00:00Z").err(),
+            Some(QubitCryptError::InvalidNotAfter)
+        );
+
+        let (pk, sk) = crate::dsas::DsaKeyGenerator::new(crate::dsas::DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let cert = crate::certificates::CertificateBuilder::new(
+            crate::certificates::Profile::Root,
+            None,
+            validity,
+            "CN=example.com".to_string(),
+            pk,
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        assert!(!cert.is_valid());
+        assert!(!cert.is_valid_with(&SystemClock));
+        assert!(!cert.is_valid_with(&FixedClock(at("2019-12-31T23:59:59Z"))));
+        assert!(cert.is_valid_with(&FixedClock(at("2020-01-01T00:00:00Z"))));
+        assert!(cert.is_valid_with(&FixedClock(at("2020-06-01T00:00:00Z"))));
+        assert!(cert.is_valid_with(&FixedClock(at("2021-01-01T00:00:00Z"))));
+        assert!(!cert.is_valid_with(&FixedClock(at("2021-01-01T00:00:01Z"))));
+
+        // The trust anchor is checked against the same clock
+        let (pk_ee, sk_ee) = crate::dsas::DsaKeyGenerator::new(crate::dsas::DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let ee = crate::certificates::CertificateBuilder::new(
+            crate::certificates::Profile::Leaf {
+                issuer: cert.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            CertValidity::new_at(at("2020-01-01T00:00:00Z"), None, "2020-12-01T00:00:00Z").unwrap(),
+            "CN=signer".to_string(),
+            pk_ee,
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let data = b"data";
+        let sig = sk_ee.sign(data).unwrap();
+        let anchors = [cert];
+        assert!(!ee.verify_signed_data(data, &sig, &[], &anchors).unwrap());
+        let clock = FixedClock(at("2020-06-01T00:00:00Z"));
+        assert!(ee
+            .verify_signed_data_with_clock(data, &sig, &[], &anchors, &clock)
+            .unwrap());
+        let clock = FixedClock(at("2020-12-15T00:00:00Z"));
+        assert!(!ee
+            .verify_signed_data_with_clock(data, &sig, &[], &anchors, &clock)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_reissue_with_new_validity() {
+        use crate::certificates::FixedClock;
+        use crate::kems::KemAlgorithm;
+        use crate::QubitCryptError;
+        use chrono::{DateTime, Utc};
+
+        let at =
+            |time: &str| -> DateTime<Utc> { DateTime::parse_from_rfc3339(time).unwrap().into() };
+
+        let pki = crate::certificates::Pki::generate(
+            crate::dsas::DsaAlgorithm::MlDsa44,
+            KemAlgorithm::MlKem512,
+            1,
+        )
+        .unwrap();
+        let (leaf, _) = &pki.leaves[0];
+
+        let validity = CertValidity::new(None, "2040-01-01T00:00:00Z").unwrap();
+        let renewed = leaf
+            .reissue_with_new_validity(&pki.intermediate_cert, &pki.intermediate_key, validity)
+            .unwrap();
+        assert!(pki.intermediate_cert.verify_child(&renewed).unwrap());
+        assert_eq!(renewed.get_subject(), leaf.get_subject());
+        assert_eq!(renewed.get_issuer(), leaf.get_issuer());
+        assert_eq!(renewed.raw_public_key_bytes(), leaf.raw_public_key_bytes());
+        assert_eq!(
+            renewed.cert.tbs_certificate.extensions,
+            leaf.cert.tbs_certificate.extensions
+        );
+        assert_ne!(renewed.get_serial_number(), leaf.get_serial_number());
+        assert!(renewed.is_valid_with(&FixedClock(at("2039-12-31T00:00:00Z"))));
+        assert!(!leaf.is_valid_with(&FixedClock(at("2039-12-31T00:00:00Z"))));
+
+        // A self-signed certificate is renewed with its own key
+        let validity = CertValidity::new(None, "2040-01-01T00:00:00Z").unwrap();
+        let root = pki
+            .root_cert
+            .reissue_with_new_validity(&pki.root_cert, &pki.root_key, validity)
+            .unwrap();
+        assert!(root.verify_self_signed().unwrap());
+        assert!(root.verify_child(&pki.intermediate_cert).unwrap());
+
+        // The leaf's own ML-KEM key can't sign
+        let (_, leaf_key) = &pki.leaves[0];
+        let validity = CertValidity::new(None, "2040-01-01T00:00:00Z").unwrap();
+        assert_eq!(
+            leaf.reissue_with_new_validity(&pki.intermediate_cert, leaf_key, validity)
+                .err(),
+            Some(QubitCryptError::InvalidPrivateKey)
+        );
+
+        // The root key has the right algorithm, but is not the intermediate's key
+        let validity = CertValidity::new(None, "2040-01-01T00:00:00Z").unwrap();
+        assert_eq!(
+            leaf.reissue_with_new_validity(&pki.intermediate_cert, &pki.root_key, validity)
+                .err(),
+            Some(QubitCryptError::InvalidPrivateKey)
+        );
+
+        // The root is not the issuer of the leaf
+        let validity = CertValidity::new(None, "2040-01-01T00:00:00Z").unwrap();
+        assert_eq!(
+            leaf.reissue_with_new_validity(&pki.root_cert, &pki.root_key, validity)
+                .err(),
+            Some(QubitCryptError::InvalidCertificate)
+        );
+    }
 }
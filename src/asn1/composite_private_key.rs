@@ -3,6 +3,13 @@ use der::{Decode, Encode};
 use der_derive::Sequence;
 use pkcs8::PrivateKeyInfo;
 
+use crate::asn1::composite_public_key::CompositePublicKey;
+use crate::dsa::common::dsa_trait::Dsa;
+use crate::dsa::common::dsa_type::DsaType;
+use crate::dsa::composite_dsa::CompositeDsaManager;
+use crate::kem::common::kem_trait::Kem;
+use crate::kem::common::kem_type::KemType;
+use crate::kem::composite_kem::CompositeKemManager;
 use crate::QubitCryptError;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
@@ -87,6 +94,39 @@ impl CompositePrivateKey {
         Ok(res)
     }
 
+    /// Check that the components of the composite private key belong together
+    ///
+    /// This catches keys which were assembled with components from different key pairs. For a
+    /// composite DSA the public key is derived from each component and compared with the
+    /// public keys embedded in the components and with `public_key`. For a composite KEM the
+    /// lengths of the components are checked and each component for which a public key is
+    /// known (from `public_key` or embedded in the component) must decapsulate a ciphertext
+    /// encapsulated to that public key. Without `public_key` a post-quantum KEM component
+    /// can only be checked for its length.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key` - The composite public key which should belong to the private key, if any
+    ///
+    /// # Returns
+    ///
+    /// True if the components are consistent with each other and with `public_key`, false
+    /// otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidOid` will be returned if the OID is not a composite DSA / KEM
+    /// OID, `QubitCryptError::InvalidPrivateKey` if a component can't be decoded
+    pub fn validate(&self, public_key: Option<&CompositePublicKey>) -> Result<bool> {
+        if DsaType::from_oid(&self.oid).is_some_and(|dsa_type| dsa_type.is_composite()) {
+            CompositeDsaManager::new_from_oid(&self.oid)?.validate_private_key(self, public_key)
+        } else if KemType::from_oid(&self.oid).is_some_and(|kem_type| kem_type.is_composite()) {
+            CompositeKemManager::new_from_oid(&self.oid)?.validate_private_key(self, public_key)
+        } else {
+            Err(QubitCryptError::InvalidOid)
+        }
+    }
+
     /// Create a new composite private key from a DER-encoded private key
     ///
     /// # Arguments
@@ -123,3 +163,92 @@ impl CompositePrivateKey {
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::common::config::oids::Oid as _;
+    use crate::kem::common::config::oids::Oid as _;
+
+    /// Assemble a composite private key from the post-quantum component of one key and the
+    /// traditional component of another
+    fn mix(oid: &str, sk_a: &[u8], sk_b: &[u8]) -> CompositePrivateKey {
+        let sk_a = CompositePrivateKey::from_der(oid, sk_a).unwrap();
+        let sk_b = CompositePrivateKey::from_der(oid, sk_b).unwrap();
+        CompositePrivateKey::new(
+            oid,
+            &sk_a.get_pq_sk().unwrap(),
+            &sk_b.get_trad_sk().unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_composite_dsa() {
+        let dsa_type = DsaType::MlDsa44EcdsaP256SHA256;
+        let oid = dsa_type.get_oid();
+        let mut dsa = CompositeDsaManager::new(dsa_type).unwrap();
+        let (pk_a, sk_a) = dsa.key_gen().unwrap();
+        let (_, sk_b) = dsa.key_gen().unwrap();
+        let c_pk = CompositePublicKey::from_der(&oid, &pk_a).unwrap();
+
+        let c_sk = CompositePrivateKey::from_der(&oid, &sk_a).unwrap();
+        assert!(c_sk.validate(None).unwrap());
+        assert!(c_sk.validate(Some(&c_pk)).unwrap());
+
+        let mixed = mix(&oid, &sk_a, &sk_b);
+        assert!(mixed.validate(None).unwrap());
+        assert!(!mixed.validate(Some(&c_pk)).unwrap());
+
+        // The components must be of the right algorithms
+        let other = DsaType::MlDsa65EcdsaP256SHA512.get_oid();
+        let (_, sk_other) = CompositeDsaManager::new_from_oid(&other)
+            .unwrap()
+            .key_gen()
+            .unwrap();
+        let sk_other = CompositePrivateKey::from_der(&other, &sk_other).unwrap();
+        let wrong = CompositePrivateKey::new(
+            &oid,
+            &sk_other.get_pq_sk().unwrap(),
+            &c_sk.get_trad_sk().unwrap(),
+        )
+        .unwrap();
+        assert!(!wrong.validate(None).unwrap());
+    }
+
+    #[test]
+    fn test_validate_composite_kem() {
+        let kem_type = KemType::MlKem768X25519;
+        let oid = kem_type.get_oid();
+        let mut kem = CompositeKemManager::new(kem_type).unwrap();
+        let (pk_a, sk_a) = kem.key_gen().unwrap();
+        let (pk_b, sk_b) = kem.key_gen().unwrap();
+        let c_pk_a = CompositePublicKey::from_der(&oid, &pk_a).unwrap();
+        let c_pk_b = CompositePublicKey::from_der(&oid, &pk_b).unwrap();
+
+        let c_sk = CompositePrivateKey::from_der(&oid, &sk_a).unwrap();
+        assert!(c_sk.validate(None).unwrap());
+        assert!(c_sk.validate(Some(&c_pk_a)).unwrap());
+        assert!(!c_sk.validate(Some(&c_pk_b)).unwrap());
+
+        let mixed = mix(&oid, &sk_a, &sk_b);
+        assert!(!mixed.validate(Some(&c_pk_a)).unwrap());
+        assert!(!mixed.validate(Some(&c_pk_b)).unwrap());
+
+        // A post-quantum public key paired with an unrelated post-quantum private key
+        let mismatched = CompositePublicKey::new(&oid, &c_pk_b.get_pq_pk(), &c_pk_a.get_trad_pk());
+        assert!(!c_sk.validate(Some(&mismatched)).unwrap());
+
+        // Not a composite OID
+        let c_sk = CompositePrivateKey::new(
+            &KemType::MlKem768.get_oid(),
+            &c_sk.get_pq_sk().unwrap(),
+            &c_sk.get_trad_sk().unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(
+            c_sk.validate(None),
+            Err(QubitCryptError::InvalidOid)
+        ));
+    }
+}
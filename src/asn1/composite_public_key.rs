@@ -1,19 +1,66 @@
 use der::{asn1::BitString, Decode, Encode};
 use der_derive::Sequence;
 
+use crate::asn1::asn_util::get_key_octets;
+use crate::dsa::common::config::pk_len::PKLen as _;
+use crate::dsa::common::dsa_type::DsaType;
+use crate::kem::common::config::pk_len::PKLen as _;
+use crate::kem::common::kem_type::KemType;
 use crate::QubitCryptError;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
+/// Get the length of the post-quantum public key of a composite DSA / KEM
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the composite DSA / KEM
+///
+/// # Returns
+///
+/// The length of the ML-DSA / ML-KEM public key, or None if the OID is not a known composite
+fn pq_pk_len(oid: &str) -> Option<usize> {
+    if let Some(dsa_type) = DsaType::from_oid(oid) {
+        dsa_type.get_pq_type().and_then(|t| t.get_pk_len())
+    } else {
+        KemType::from_oid(oid)
+            .and_then(|t| t.get_pq_type())
+            .and_then(|t| t.get_pk_len())
+    }
+}
+
 /// CompositeSignaturePublicKey ::= SEQUENCE SIZE (2) OF BIT STRING
 /// CompositeKEMPublicKey ::= SEQUENCE SIZE (2) OF BIT STRING
+///
+/// The components are always encoded post-quantum first, then traditional
 #[derive(Debug, Clone, Sequence)]
 struct CompositeSigKemPublicKey {
     pq_pk: BitString,
     trad_pk: BitString,
 }
 
-#[derive(Debug, Clone)]
+/// The order of the components in an encoded composite public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ComponentOrder {
+    /// The post-quantum component first, then the traditional component. This is the
+    /// order used by the composite drafts and the default for this library
+    #[default]
+    PqFirst,
+    /// The traditional component first, then the post-quantum component. Only use this
+    /// to interoperate with implementations which use this order
+    TradFirst,
+}
+
+/// A component of a composite DSA / KEM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeComponent {
+    /// The post-quantum component, e.g. ML-DSA-65
+    Pq,
+    /// The traditional component, e.g. ECDSA P-256
+    Trad,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 /// A public key for a composite DSA / KEM
 pub struct CompositePublicKey {
     /// The OID for the composite DSA / KEM
@@ -73,43 +120,102 @@ impl CompositePublicKey {
 
     /// Create a new composite public key from a DER-encoded public key
     ///
+    /// The components are expected in the `ComponentOrder::PqFirst` order. Use
+    /// `from_der_with_order` for keys encoded in the opposite order.
+    ///
     /// # Arguments
     ///
+    /// * `oid` - The OID for the composite DSA / KEM
     /// * `der` - The DER-encoded public key
     ///
     /// # Returns
     ///
     /// A new composite public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` will be returned if the key can't be decoded, or if
+    /// the post-quantum component doesn't have the length of the composite's ML-DSA / ML-KEM
+    /// public key, e.g. because the key is encoded in the `ComponentOrder::TradFirst` order.
+    /// `QubitCryptError::UnusedKeyBits` will be returned if the BIT STRING of a component has
+    /// non-zero unused bits.
     pub fn from_der(oid: &str, der: &[u8]) -> Result<Self> {
+        Self::from_der_with_order(oid, der, ComponentOrder::PqFirst)
+    }
+
+    /// Create a new composite public key from a DER-encoded public key, with the
+    /// components in the specified order
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID for the composite DSA / KEM
+    /// * `der` - The DER-encoded public key
+    /// * `order` - The order of the components in the encoding
+    ///
+    /// # Returns
+    ///
+    /// A new composite public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` will be returned if the key can't be decoded, or if
+    /// the post-quantum component doesn't have the length of the composite's ML-DSA / ML-KEM
+    /// public key. The components are never swapped to fit. `QubitCryptError::UnusedKeyBits`
+    /// will be returned if the BIT STRING of a component has non-zero unused bits.
+    pub fn from_der_with_order(oid: &str, der: &[u8], order: ComponentOrder) -> Result<Self> {
         // Parse as compressed public key
         let comp_pk = CompositeSigKemPublicKey::from_der(der)
             .map_err(|_| QubitCryptError::InvalidPublicKey)?;
 
-        let pq_pk = if let Some(pq_pk) = comp_pk.pq_pk.as_bytes() {
-            pq_pk
-        } else {
-            return Err(QubitCryptError::InvalidPublicKey);
+        let first = get_key_octets(&comp_pk.pq_pk)?;
+        let second = get_key_octets(&comp_pk.trad_pk)?;
+        let (pq_pk, trad_pk) = match order {
+            ComponentOrder::PqFirst => (first, second),
+            ComponentOrder::TradFirst => (second, first),
         };
 
-        let trad_pk = if let Some(trad_pk) = comp_pk.trad_pk.as_bytes() {
-            trad_pk
-        } else {
-            return Err(QubitCryptError::InvalidPublicKey);
-        };
+        // A key in the other order has the traditional key where the ML-DSA / ML-KEM key
+        // is expected
+        if let Some(pq_len) = pq_pk_len(oid) {
+            if pq_pk.len() != pq_len {
+                return Err(QubitCryptError::InvalidPublicKey);
+            }
+        }
 
         Ok(CompositePublicKey::new(oid, pq_pk, trad_pk))
     }
 
     /// Encode the composite public key as a DER-encoded public key
     ///
+    /// The components are encoded in the `ComponentOrder::PqFirst` order
+    ///
     /// # Returns
     ///
     /// The DER-encoded public key
     pub fn to_der(&self) -> Result<Vec<u8>> {
+        self.to_der_with_order(ComponentOrder::PqFirst)
+    }
+
+    /// Encode the composite public key as a DER-encoded public key, with the
+    /// components in the specified order
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the components in the encoding
+    ///
+    /// # Returns
+    ///
+    /// The DER-encoded public key
+    pub fn to_der_with_order(&self, order: ComponentOrder) -> Result<Vec<u8>> {
+        let (first, second) = match order {
+            ComponentOrder::PqFirst => (&self.pq_pk, &self.trad_pk),
+            ComponentOrder::TradFirst => (&self.trad_pk, &self.pq_pk),
+        };
+        // The fields of the sequence are positional, so they are swapped for TradFirst
         let comp_sig_pk = CompositeSigKemPublicKey {
-            pq_pk: BitString::new(0, self.pq_pk.as_slice())
+            pq_pk: BitString::new(0, first.as_slice())
                 .map_err(|_| QubitCryptError::InvalidPublicKey)?,
-            trad_pk: BitString::new(0, self.trad_pk.as_slice())
+            trad_pk: BitString::new(0, second.as_slice())
                 .map_err(|_| QubitCryptError::InvalidPublicKey)?,
         };
 
@@ -120,3 +226,60 @@ impl CompositePublicKey {
         Ok(comp_sig_pk.as_slice().to_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1::macros::test_asn1_roundtrip;
+
+    #[test]
+    fn test_composite_public_key_roundtrip() {
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        let (pk_dsa, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65Ed25519SHA512)
+            .generate()
+            .unwrap();
+        let (pk_kem, _) = KemKeyGenerator::new(KemAlgorithm::MlKem1024P384)
+            .generate()
+            .unwrap();
+        for pk in [pk_dsa, pk_kem] {
+            let oid = pk.get_oid().to_string();
+            let c_pk = CompositePublicKey::from_der(&oid, pk.get_key()).unwrap();
+            test_asn1_roundtrip!(c_pk, |der| CompositePublicKey::from_der(&oid, der));
+        }
+    }
+
+    #[test]
+    fn test_component_order() {
+        use crate::dsa::common::config::oids::Oid as _;
+
+        let oid = &DsaType::MlDsa44Ed25519SHA512.get_oid();
+        let pk = CompositePublicKey::new(oid, &[1u8; 1312], &[2u8; 32]);
+
+        let pq_first = pk.to_der().unwrap();
+        assert_eq!(
+            pq_first,
+            pk.to_der_with_order(ComponentOrder::PqFirst).unwrap()
+        );
+
+        let trad_first = pk.to_der_with_order(ComponentOrder::TradFirst).unwrap();
+        assert_ne!(pq_first, trad_first);
+
+        let pk2 =
+            CompositePublicKey::from_der_with_order(oid, &trad_first, ComponentOrder::TradFirst)
+                .unwrap();
+        assert_eq!(pk2.get_pq_pk(), pk.get_pq_pk());
+        assert_eq!(pk2.get_trad_pk(), pk.get_trad_pk());
+
+        // A key in the other order is rejected, not silently swapped
+        assert_eq!(
+            CompositePublicKey::from_der(oid, &trad_first),
+            Err(QubitCryptError::InvalidPublicKey)
+        );
+        assert_eq!(
+            CompositePublicKey::from_der_with_order(oid, &pq_first, ComponentOrder::TradFirst),
+            Err(QubitCryptError::InvalidPublicKey)
+        );
+    }
+}
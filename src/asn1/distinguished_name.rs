@@ -0,0 +1,275 @@
+use std::str::FromStr;
+
+use const_oid::db::rfc4519;
+use der::asn1::{Any, Ia5StringRef, PrintableStringRef, SetOfVec, Utf8StringRef};
+use der::Tag;
+use spki::ObjectIdentifier;
+use x509_cert::attr::AttributeTypeAndValue;
+use x509_cert::name::{Name, RelativeDistinguishedName};
+
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// A builder for a Distinguished Name, used as the subject of a certificate
+///
+/// Unlike parsing a string, the attribute values are taken literally: commas, plus signs and
+/// other characters which have a special meaning in RFC 4514 strings don't need to be escaped,
+/// and every attribute becomes its own single-valued RDN.
+///
+/// The attributes are listed in the same order as in a string, i.e. the most specific attribute
+/// first, so `DistinguishedName::new().cn("example.com").org("Acme")` is encoded the same as
+/// `"CN=example.com,O=Acme"`.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::certificates::DistinguishedName;
+///
+/// let dn = DistinguishedName::new()
+///     .cn("Acme, Inc. Root CA")
+///     .org("Acme, Inc.")
+///     .country("US");
+/// let name = dn.to_name().unwrap();
+/// assert_eq!(name.to_string(), "CN=Acme\\, Inc. Root CA,O=Acme\\, Inc.,C=US");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DistinguishedName {
+    /// The attributes in string order
+    attributes: Vec<(ObjectIdentifier, String)>,
+}
+
+impl DistinguishedName {
+    /// Create a new empty Distinguished Name
+    pub fn new() -> DistinguishedName {
+        DistinguishedName::default()
+    }
+
+    /// Add a common name (CN) attribute
+    pub fn cn(self, value: &str) -> DistinguishedName {
+        self.add_oid(rfc4519::CN, value)
+    }
+
+    /// Add an organization (O) attribute
+    pub fn org(self, value: &str) -> DistinguishedName {
+        self.add_oid(rfc4519::O, value)
+    }
+
+    /// Add an organizational unit (OU) attribute
+    pub fn org_unit(self, value: &str) -> DistinguishedName {
+        self.add_oid(rfc4519::OU, value)
+    }
+
+    /// Add a country (C) attribute. The value must be a two letter country code
+    pub fn country(self, value: &str) -> DistinguishedName {
+        self.add_oid(rfc4519::C, value)
+    }
+
+    /// Add a state or province (ST) attribute
+    pub fn state(self, value: &str) -> DistinguishedName {
+        self.add_oid(rfc4519::ST, value)
+    }
+
+    /// Add a locality (L) attribute
+    pub fn locality(self, value: &str) -> DistinguishedName {
+        self.add_oid(rfc4519::L, value)
+    }
+
+    /// Add an attribute with an arbitrary attribute type
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the attribute type
+    /// * `value` - The value of the attribute
+    ///
+    /// # Returns
+    ///
+    /// The Distinguished Name with the attribute added
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidOid` will be returned if the OID is invalid
+    pub fn add(self, oid: &str, value: &str) -> Result<DistinguishedName> {
+        let oid = ObjectIdentifier::new(oid).map_err(|_| QubitCryptError::InvalidOid)?;
+        Ok(self.add_oid(oid, value))
+    }
+
+    fn add_oid(mut self, oid: ObjectIdentifier, value: &str) -> DistinguishedName {
+        self.attributes.push((oid, value.to_string()));
+        self
+    }
+
+    /// Convert to a `Name`
+    ///
+    /// The value of an attribute is encoded with the same string type that parsing it from a
+    /// string would use: PrintableString for the country and serial number, IA5String for domain
+    /// components and UTF8String for everything else.
+    ///
+    /// # Returns
+    ///
+    /// The Name
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::BadSubject` will be returned if the Distinguished Name is empty or if a
+    /// value can't be encoded with the string type of its attribute
+    pub fn to_name(&self) -> Result<Name> {
+        if self.attributes.is_empty() {
+            return Err(QubitCryptError::BadSubject);
+        }
+
+        let mut rdns = Vec::with_capacity(self.attributes.len());
+        for (oid, value) in self.attributes.iter().rev() {
+            let value = match *oid {
+                rfc4519::C => {
+                    if value.len() != 2 {
+                        return Err(QubitCryptError::BadSubject);
+                    }
+                    Any::from(
+                        PrintableStringRef::new(value).map_err(|_| QubitCryptError::BadSubject)?,
+                    )
+                }
+                rfc4519::SERIAL_NUMBER => Any::from(
+                    PrintableStringRef::new(value).map_err(|_| QubitCryptError::BadSubject)?,
+                ),
+                rfc4519::DOMAIN_COMPONENT => Any::new(
+                    Tag::Ia5String,
+                    Ia5StringRef::new(value)
+                        .map_err(|_| QubitCryptError::BadSubject)?
+                        .as_bytes(),
+                )
+                .map_err(|_| QubitCryptError::BadSubject)?,
+                _ => Any::from(Utf8StringRef::new(value).map_err(|_| QubitCryptError::BadSubject)?),
+            };
+
+            let mut atvs = SetOfVec::new();
+            atvs.insert(AttributeTypeAndValue { oid: *oid, value })
+                .map_err(|_| QubitCryptError::BadSubject)?;
+            rdns.push(RelativeDistinguishedName(atvs));
+        }
+
+        Ok(Name::from(rdns))
+    }
+}
+
+/// A value which can be used as the subject of a certificate
+///
+/// This is implemented for strings, which are parsed according to RFC 4514, and for
+/// `DistinguishedName`.
+pub trait IntoSubject {
+    /// Convert to a `Name`
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::BadSubject` will be returned if the value is not a valid name
+    fn into_subject(self) -> Result<Name>;
+}
+
+impl IntoSubject for &str {
+    fn into_subject(self) -> Result<Name> {
+        Name::from_str(self).map_err(|_| QubitCryptError::BadSubject)
+    }
+}
+
+impl IntoSubject for String {
+    fn into_subject(self) -> Result<Name> {
+        self.as_str().into_subject()
+    }
+}
+
+impl IntoSubject for DistinguishedName {
+    fn into_subject(self) -> Result<Name> {
+        self.to_name()
+    }
+}
+
+impl IntoSubject for &DistinguishedName {
+    fn into_subject(self) -> Result<Name> {
+        self.to_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use der::Encode;
+
+    use super::*;
+    use crate::certificates::{CertValidity, CertificateBuilder, Profile};
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+
+    #[test]
+    fn test_distinguished_name() {
+        // Simple names are encoded the same way as the equivalent string
+        let dn = DistinguishedName::new()
+            .cn("example.com")
+            .org_unit("Engineering")
+            .org("Acme")
+            .locality("Springfield")
+            .state("Illinois")
+            .country("US");
+        let expected =
+            Name::from_str("CN=example.com,OU=Engineering,O=Acme,L=Springfield,ST=Illinois,C=US")
+                .unwrap();
+        assert_eq!(
+            dn.to_name().unwrap().to_der().unwrap(),
+            expected.to_der().unwrap()
+        );
+
+        // Special characters are taken literally, each attribute is its own RDN
+        let name = DistinguishedName::new()
+            .cn("a+b, c=d")
+            .org("Acme")
+            .to_name()
+            .unwrap();
+        assert_eq!(name.0.len(), 2);
+        assert_eq!(name.0[0].0.len(), 1);
+        assert_eq!(name.0[1].0.get(0).unwrap().oid, rfc4519::CN);
+        assert_eq!(
+            Utf8StringRef::try_from(&name.0[1].0.get(0).unwrap().value)
+                .unwrap()
+                .as_str(),
+            "a+b, c=d"
+        );
+
+        let name = DistinguishedName::new()
+            .add("2.5.4.3", "example.com")
+            .unwrap()
+            .to_name()
+            .unwrap();
+        assert_eq!(name, Name::from_str("CN=example.com").unwrap());
+    }
+
+    #[test]
+    fn test_distinguished_name_certificate_subject() {
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let dn = DistinguishedName::new().cn("Root, Inc.").country("US");
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let cert = CertificateBuilder::new(Profile::Root, None, validity, &dn, pk, &sk)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(cert.get_subject(), dn.to_name().unwrap());
+        assert!(cert.verify_self_signed().unwrap());
+    }
+
+    #[test]
+    fn test_distinguished_name_invalid() {
+        assert_eq!(
+            DistinguishedName::new().to_name(),
+            Err(QubitCryptError::BadSubject)
+        );
+        assert_eq!(
+            DistinguishedName::new().country("USA").to_name(),
+            Err(QubitCryptError::BadSubject)
+        );
+        assert_eq!(
+            DistinguishedName::new().country("U_").to_name(),
+            Err(QubitCryptError::BadSubject)
+        );
+        assert_eq!(
+            DistinguishedName::new().add("not an oid", "value"),
+            Err(QubitCryptError::InvalidOid)
+        );
+    }
+}
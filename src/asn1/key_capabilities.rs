@@ -0,0 +1,93 @@
+use crate::asn1::asn_util::{is_dsa_oid, is_kem_oid};
+
+/// The operations which a key supports
+///
+/// The capabilities are derived from the class of the key's OID, a composite key has the
+/// capabilities of the composite algorithm (e.g. a composite KEM key can encapsulate).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyCapabilities {
+    /// The key can be used to sign messages
+    pub can_sign: bool,
+    /// The key can be used to verify signatures
+    pub can_verify: bool,
+    /// The key can be used to encapsulate a shared secret
+    pub can_encap: bool,
+    /// The key can be used to decapsulate a shared secret
+    pub can_decap: bool,
+}
+
+impl KeyCapabilities {
+    /// Get the capabilities of a public key
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the public key
+    ///
+    /// # Returns
+    ///
+    /// The capabilities of the public key
+    pub(crate) fn for_public_key(oid: &str) -> KeyCapabilities {
+        KeyCapabilities {
+            can_verify: is_dsa_oid(oid),
+            can_encap: is_kem_oid(oid),
+            ..Default::default()
+        }
+    }
+
+    /// Get the capabilities of a private key
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the private key
+    ///
+    /// # Returns
+    ///
+    /// The capabilities of the private key
+    pub(crate) fn for_private_key(oid: &str) -> KeyCapabilities {
+        KeyCapabilities {
+            can_sign: is_dsa_oid(oid),
+            can_decap: is_kem_oid(oid),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    #[test]
+    fn test_key_capabilities() {
+        for dsa_alg in [DsaAlgorithm::MlDsa44, DsaAlgorithm::MlDsa44EcdsaP256SHA256] {
+            let (pk, sk) = DsaKeyGenerator::new(dsa_alg).generate().unwrap();
+            let expected = KeyCapabilities {
+                can_verify: true,
+                ..Default::default()
+            };
+            assert_eq!(pk.capabilities(), expected);
+            let expected = KeyCapabilities {
+                can_sign: true,
+                ..Default::default()
+            };
+            assert_eq!(sk.capabilities(), expected);
+        }
+
+        for kem_alg in [KemAlgorithm::MlKem512, KemAlgorithm::MlKem768X25519] {
+            let (pk, sk) = KemKeyGenerator::new(kem_alg).generate().unwrap();
+            let expected = KeyCapabilities {
+                can_encap: true,
+                ..Default::default()
+            };
+            assert_eq!(pk.capabilities(), expected);
+            assert!(pk.encap().is_ok());
+            let expected = KeyCapabilities {
+                can_decap: true,
+                ..Default::default()
+            };
+            assert_eq!(sk.capabilities(), expected);
+            assert!(sk.sign(b"message").is_err());
+        }
+    }
+}
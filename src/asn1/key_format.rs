@@ -0,0 +1,174 @@
+use der::asn1::OctetString;
+use der::Sequence;
+
+use crate::asn1::asn_util::is_composite_kem_or_dsa_oid;
+use crate::dsa::common::config::sk_len::SKLen as _;
+use crate::dsa::common::dsa_type::DsaType;
+use crate::kem::common::config::sk_len::SKLen as _;
+use crate::kem::common::kem_type::KemType;
+
+/// The length of the DER headers of the `both` form: the SEQUENCE, the seed OCTET STRING and
+/// the expanded key OCTET STRING (the expanded keys of all ML-DSA types are 256 to 65535 bytes)
+const BOTH_HEADERS_LEN: usize = 4 + 2 + 4;
+
+/// The `both` form of an ML-DSA private key, from the ML-DSA private key format of
+/// [draft-ietf-lamps-dilithium-certificates]
+/// ```text
+///   ML-DSA-PrivateKey ::= CHOICE {
+///     seed [0] OCTET STRING (SIZE (32)),
+///     expandedKey OCTET STRING,
+///     both SEQUENCE {
+///         seed OCTET STRING (SIZE (32)),
+///         expandedKey OCTET STRING
+///     }
+///   }
+/// ```
+/// [draft-ietf-lamps-dilithium-certificates]: https://datatracker.ietf.org/doc/draft-ietf-lamps-dilithium-certificates/
+#[derive(Clone, Sequence)]
+pub(crate) struct MlDsaBothKey {
+    /// The 32 byte seed (ξ)
+    pub seed: OctetString,
+    /// The expanded private key
+    pub expanded_key: OctetString,
+}
+
+/// The form of the key material of a private key
+///
+/// ML-DSA, ML-KEM and X-Wing private keys can be stored as the seed they are generated from,
+/// or (except for X-Wing) as the expanded key which the algorithm operates on. ML-DSA keys can
+/// also hold both. The form is inferred from the length of the key material and the sizes
+/// expected for the OID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// A seed, which is expanded to the private key: 32 bytes (ξ) for ML-DSA, 64 bytes (d ‖ z)
+    /// for ML-KEM and 32 bytes for X-Wing
+    Seed,
+    /// The expanded private key, or the private key of an algorithm without a seed form
+    Expanded,
+    /// An ML-DSA seed and the expanded key, as the `both` SEQUENCE of the IETF ML-DSA private
+    /// key format
+    Both,
+    /// A composite private key, which is a DER encoded sequence of the component keys
+    Composite,
+    /// The length doesn't match any form of the OID, or the OID is a custom algorithm whose
+    /// sizes are unknown
+    Unknown,
+}
+
+impl KeyFormat {
+    /// Get the form of the key material of a private key
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the private key
+    /// * `key` - The key material
+    ///
+    /// # Returns
+    ///
+    /// The form of the key material
+    pub(crate) fn for_private_key(oid: &str, key: &[u8]) -> KeyFormat {
+        if is_composite_kem_or_dsa_oid(oid) {
+            return KeyFormat::Composite;
+        }
+
+        let (seed_len, sk_len) = if let Some(dsa_type) = DsaType::from_oid(oid) {
+            let seed_len = match dsa_type {
+                DsaType::MlDsa44 | DsaType::MlDsa65 | DsaType::MlDsa87 => Some(32),
+                _ => None,
+            };
+            (seed_len, dsa_type.get_sk_len())
+        } else if let Some(kem_type) = KemType::from_oid(oid) {
+            let seed_len = match kem_type {
+                KemType::MlKem512 | KemType::MlKem768 | KemType::MlKem1024 => Some(64),
+                KemType::XWing => Some(32),
+                _ => None,
+            };
+            (seed_len, kem_type.get_sk_len())
+        } else {
+            return KeyFormat::Unknown;
+        };
+
+        match (seed_len, sk_len) {
+            // The X-Wing private key is always the seed
+            (Some(seed_len), _) if key.len() == seed_len => KeyFormat::Seed,
+            (_, Some(sk_len)) if key.len() == sk_len => KeyFormat::Expanded,
+            // Only ML-DSA has the `both` form
+            (Some(seed_len), Some(sk_len))
+                if DsaType::from_oid(oid).is_some()
+                    && key.len() == BOTH_HEADERS_LEN + seed_len + sk_len =>
+            {
+                KeyFormat::Both
+            }
+            // The length of e.g. RSA keys is not fixed
+            (_, None) => KeyFormat::Expanded,
+            _ => KeyFormat::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsa::common::config::oids::Oid as _;
+    use crate::kem::common::config::oids::Oid as _;
+
+    #[test]
+    fn test_for_private_key() {
+        let ml_dsa = DsaType::MlDsa44.get_oid();
+        assert_eq!(
+            KeyFormat::for_private_key(&ml_dsa, &[0u8; 32]),
+            KeyFormat::Seed
+        );
+        assert_eq!(
+            KeyFormat::for_private_key(&ml_dsa, &[0u8; 2560]),
+            KeyFormat::Expanded
+        );
+        assert_eq!(
+            KeyFormat::for_private_key(&ml_dsa, &[0u8; 2559]),
+            KeyFormat::Unknown
+        );
+        assert_eq!(
+            KeyFormat::for_private_key(&ml_dsa, &[0u8; 2602]),
+            KeyFormat::Both
+        );
+
+        let ml_kem = KemType::MlKem768.get_oid();
+        assert_eq!(
+            KeyFormat::for_private_key(&ml_kem, &[0u8; 64]),
+            KeyFormat::Seed
+        );
+        assert_eq!(
+            KeyFormat::for_private_key(&ml_kem, &[0u8; 2400]),
+            KeyFormat::Expanded
+        );
+
+        let xwing = KemType::XWing.get_oid();
+        assert_eq!(
+            KeyFormat::for_private_key(&xwing, &[0u8; 32]),
+            KeyFormat::Seed
+        );
+
+        let ed25519 = DsaType::Ed25519SHA512.get_oid();
+        assert_eq!(
+            KeyFormat::for_private_key(&ed25519, &[0u8; 32]),
+            KeyFormat::Expanded
+        );
+
+        // SLH-DSA keys are not composite
+        let slh_dsa = DsaType::SlhDsaSha2_128s.get_oid();
+        assert_eq!(
+            KeyFormat::for_private_key(&slh_dsa, &[0u8; 64]),
+            KeyFormat::Expanded
+        );
+
+        let composite = KemType::MlKem768X25519.get_oid();
+        assert_eq!(
+            KeyFormat::for_private_key(&composite, &[0u8; 10]),
+            KeyFormat::Composite
+        );
+        assert_eq!(
+            KeyFormat::for_private_key("1.2.3.4", &[0u8; 32]),
+            KeyFormat::Unknown
+        );
+    }
+}
@@ -0,0 +1,38 @@
+/// Test that an object survives a round trip through its encodings
+///
+/// `test_asn1_roundtrip!(Type, object)` checks that `Type::from_der(object.to_der())` and
+/// `Type::from_pem(object.to_pem())` are both equal to the object.
+///
+/// `test_asn1_roundtrip!(object, |der| decode(der))` only checks the DER round trip, using
+/// the given decoder. This is for types which need more than the DER to be decoded, e.g. the OID.
+#[cfg(test)]
+macro_rules! test_asn1_roundtrip {
+    ($obj:expr, |$der:ident| $from_der:expr) => {{
+        let obj = &$obj;
+
+        let der = obj.to_der().unwrap();
+        let decoded = {
+            let $der: &[u8] = &der;
+            $from_der
+        }
+        .unwrap();
+        assert!(decoded == *obj, "DER round trip changed the object");
+        assert_eq!(decoded.to_der().unwrap(), der);
+    }};
+    ($ty:ty, $obj:expr) => {{
+        let obj = &$obj;
+
+        let der = obj.to_der().unwrap();
+        let decoded = <$ty>::from_der(&der).unwrap();
+        assert!(decoded == *obj, "DER round trip changed the object");
+        assert_eq!(decoded.to_der().unwrap(), der);
+
+        let pem = obj.to_pem().unwrap();
+        let decoded = <$ty>::from_pem(&pem).unwrap();
+        assert!(decoded == *obj, "PEM round trip changed the object");
+        assert_eq!(decoded.to_pem().unwrap(), pem);
+    }};
+}
+
+#[cfg(test)]
+pub(crate) use test_asn1_roundtrip;
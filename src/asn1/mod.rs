@@ -1,8 +1,17 @@
+pub mod alt_signature;
 pub mod asn_util;
+pub mod batch_issuer;
+pub mod bundle;
 pub mod cert_builder;
 pub mod certificate;
 pub mod composite_private_key;
 pub mod composite_public_key;
+pub mod distinguished_name;
+pub mod key_capabilities;
+pub mod key_format;
+pub mod macros;
+pub mod name_constraints;
+pub mod pki;
 pub mod private_key;
 pub mod public_key;
 pub mod public_key_info;
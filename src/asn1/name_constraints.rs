@@ -0,0 +1,468 @@
+//! Building and enforcing the name constraints extension (RFC 5280 § 4.2.1.10)
+
+use std::net::IpAddr;
+
+use der::asn1::{Ia5String, OctetString};
+use x509_cert::ext::pkix::constraints::name::{GeneralSubtree, GeneralSubtrees};
+use x509_cert::ext::pkix::name::GeneralName;
+use x509_cert::ext::pkix::NameConstraints;
+use x509_cert::name::RdnSequence;
+
+use crate::asn1::distinguished_name::IntoSubject;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// A subtree of names, permitted or excluded by the name constraints of a CA certificate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NameConstraint {
+    /// A DNS domain, e.g. `example.com`, which covers the domain and all of its subdomains
+    DnsName(String),
+    /// A mailbox (`user@example.com`), all mailboxes on a host (`example.com`) or all mailboxes
+    /// in a domain (`.example.com`)
+    Rfc822Name(String),
+    /// A Distinguished Name in RFC 4514 format, e.g. `O=Acme,C=US`, which covers all names
+    /// below it
+    DirectoryName(String),
+    /// An IPv4 or IPv6 address range in CIDR notation, e.g. `192.0.2.0/24` or `2001:db8::/32`
+    IpAddress(String),
+    /// The host of URIs: a single host (`www.example.com`) or all hosts in a domain
+    /// (`.example.com`)
+    Uri(String),
+}
+
+impl NameConstraint {
+    /// Convert the constraint to a general subtree
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::BadExtension` will be returned if a DNS name, mailbox or URI host is
+    /// not ASCII or an address range can't be parsed, `QubitCryptError::BadSubject` will be
+    /// returned if a Distinguished Name can't be parsed
+    fn to_subtree(&self) -> Result<GeneralSubtree> {
+        let base = match self {
+            NameConstraint::DnsName(name) => GeneralName::DnsName(
+                Ia5String::new(name).map_err(|_| QubitCryptError::BadExtension)?,
+            ),
+            NameConstraint::Rfc822Name(name) => GeneralName::Rfc822Name(
+                Ia5String::new(name).map_err(|_| QubitCryptError::BadExtension)?,
+            ),
+            NameConstraint::DirectoryName(name) => {
+                GeneralName::DirectoryName(name.as_str().into_subject()?)
+            }
+            NameConstraint::IpAddress(range) => GeneralName::IpAddress(
+                OctetString::new(parse_ip_range(range)?)
+                    .map_err(|_| QubitCryptError::BadExtension)?,
+            ),
+            NameConstraint::Uri(host) => GeneralName::UniformResourceIdentifier(
+                Ia5String::new(host).map_err(|_| QubitCryptError::BadExtension)?,
+            ),
+        };
+        Ok(GeneralSubtree {
+            base,
+            minimum: 0,
+            maximum: None,
+        })
+    }
+}
+
+/// Parse an address range in CIDR notation to the address followed by the mask, which is the
+/// encoding of an iPAddress constraint (RFC 5280 § 4.2.1.10)
+///
+/// # Errors
+///
+/// `QubitCryptError::BadExtension` will be returned if the range can't be parsed or the prefix
+/// length is longer than the address
+fn parse_ip_range(range: &str) -> Result<Vec<u8>> {
+    let (address, prefix_len) = range.split_once('/').ok_or(QubitCryptError::BadExtension)?;
+    let address: Vec<u8> = match address
+        .parse::<IpAddr>()
+        .map_err(|_| QubitCryptError::BadExtension)?
+    {
+        IpAddr::V4(address) => address.octets().to_vec(),
+        IpAddr::V6(address) => address.octets().to_vec(),
+    };
+    let prefix_len: usize = prefix_len
+        .parse()
+        .map_err(|_| QubitCryptError::BadExtension)?;
+    if prefix_len > address.len() * 8 {
+        return Err(QubitCryptError::BadExtension);
+    }
+
+    let mask: Vec<u8> = (0..address.len())
+        .map(|i| {
+            let bits = prefix_len.saturating_sub(i * 8).min(8);
+            (0xff00u16 >> bits) as u8
+        })
+        .collect();
+    let network = address.iter().zip(&mask).map(|(byte, mask)| byte & mask);
+    Ok(network.chain(mask.iter().copied()).collect())
+}
+
+/// Build the name constraints extension from permitted and excluded subtrees
+///
+/// # Arguments
+///
+/// * `permitted` - The permitted subtrees
+/// * `excluded` - The excluded subtrees
+///
+/// # Returns
+///
+/// The name constraints extension
+///
+/// # Errors
+///
+/// `QubitCryptError::BadExtension` will be returned if both lists are empty, otherwise the same
+/// errors as converting the constraints
+pub(crate) fn build_name_constraints(
+    permitted: &[NameConstraint],
+    excluded: &[NameConstraint],
+) -> Result<NameConstraints> {
+    if permitted.is_empty() && excluded.is_empty() {
+        return Err(QubitCryptError::BadExtension);
+    }
+
+    // GeneralSubtrees has at least one element, so empty lists are left out
+    let to_subtrees = |constraints: &[NameConstraint]| -> Result<Option<GeneralSubtrees>> {
+        if constraints.is_empty() {
+            return Ok(None);
+        }
+        let subtrees = constraints
+            .iter()
+            .map(NameConstraint::to_subtree)
+            .collect::<Result<GeneralSubtrees>>()?;
+        Ok(Some(subtrees))
+    };
+
+    Ok(NameConstraints {
+        permitted_subtrees: to_subtrees(permitted)?,
+        excluded_subtrees: to_subtrees(excluded)?,
+    })
+}
+
+/// Check that the names of a certificate satisfy the name constraints of its issuer
+///
+/// The subject is checked against the directory name subtrees, and the DNS names, mailboxes,
+/// IP addresses and URIs of the subject alternative names against the subtrees of their type.
+/// Each name has to be in one of the permitted subtrees of its type, if there are any, and in
+/// none of the excluded subtrees. A URI is matched by its host, so a URI without a host name
+/// is rejected if there are URI subtrees. Other name types are not constrained.
+///
+/// This checks a single set of constraints. In a path the certificate has to satisfy the
+/// constraints of every CA above it, which `Certificate::verify_chain` checks by calling this
+/// for each of them.
+///
+/// # Arguments
+///
+/// * `constraints` - The name constraints of the issuer
+/// * `subject` - The subject of the certificate
+/// * `alt_names` - The subject alternative names of the certificate
+///
+/// # Returns
+///
+/// True if all names satisfy the constraints, false otherwise
+///
+/// # Errors
+///
+/// `QubitCryptError::UnsupportedNameConstraint` will be returned if a subtree has a name type
+/// which can't be checked, e.g. an otherName, or a malformed address range. The extension is
+/// critical, so such constraints can't be ignored (RFC 5280 § 4.2.1.10).
+pub(crate) fn is_permitted(
+    constraints: &NameConstraints,
+    subject: &RdnSequence,
+    alt_names: &[GeneralName],
+) -> Result<bool> {
+    let permitted = constraints
+        .permitted_subtrees
+        .as_deref()
+        .unwrap_or_default();
+    let excluded = constraints.excluded_subtrees.as_deref().unwrap_or_default();
+    let subtrees = || permitted.iter().chain(excluded);
+    if !subtrees().all(|subtree| is_supported(&subtree.base)) {
+        return Err(QubitCryptError::UnsupportedNameConstraint);
+    }
+    let has_uri_subtrees =
+        subtrees().any(|subtree| matches!(subtree.base, GeneralName::UniformResourceIdentifier(_)));
+
+    // An empty subject is not constrained, the names are in the subject alternative names
+    let subject = GeneralName::DirectoryName(subject.clone());
+    let names = (!subject_is_empty(&subject))
+        .then_some(&subject)
+        .into_iter()
+        .chain(alt_names.iter());
+
+    for name in names {
+        if has_uri_subtrees && is_uri_without_host(name) {
+            return Ok(false);
+        }
+        let mut same_type = permitted
+            .iter()
+            .filter_map(|subtree| is_within(name, &subtree.base))
+            .peekable();
+        if same_type.peek().is_some() && !same_type.any(|within| within) {
+            return Ok(false);
+        }
+        if excluded
+            .iter()
+            .any(|subtree| is_within(name, &subtree.base) == Some(true))
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Check if the name type of a subtree can be checked by `is_within`
+fn is_supported(base: &GeneralName) -> bool {
+    match base {
+        GeneralName::DnsName(_)
+        | GeneralName::Rfc822Name(_)
+        | GeneralName::DirectoryName(_)
+        | GeneralName::UniformResourceIdentifier(_) => true,
+        // An IPv4 or IPv6 address followed by the mask
+        GeneralName::IpAddress(range) => matches!(range.as_bytes().len(), 8 | 32),
+        _ => false,
+    }
+}
+
+/// Check if a general name is a URI which doesn't have a host name
+fn is_uri_without_host(name: &GeneralName) -> bool {
+    matches!(name, GeneralName::UniformResourceIdentifier(uri) if uri_host(uri.as_str()).is_none())
+}
+
+/// Check if a general name is an empty directory name
+fn subject_is_empty(name: &GeneralName) -> bool {
+    matches!(name, GeneralName::DirectoryName(name) if name.0.is_empty())
+}
+
+/// Check if a name is within the subtree of a base name
+///
+/// # Returns
+///
+/// Whether the name is within the subtree, or `None` if the name and the base have different
+/// types or the type is not supported
+fn is_within(name: &GeneralName, base: &GeneralName) -> Option<bool> {
+    match (name, base) {
+        (GeneralName::DnsName(name), GeneralName::DnsName(base)) => {
+            Some(is_within_domain(name.as_str(), base.as_str()))
+        }
+        (GeneralName::Rfc822Name(name), GeneralName::Rfc822Name(base)) => {
+            Some(is_within_mailbox(name.as_str(), base.as_str()))
+        }
+        (GeneralName::DirectoryName(name), GeneralName::DirectoryName(base)) => {
+            Some(name.0.starts_with(&base.0))
+        }
+        (GeneralName::IpAddress(address), GeneralName::IpAddress(range)) => {
+            Some(is_within_ip_range(address.as_bytes(), range.as_bytes()))
+        }
+        (
+            GeneralName::UniformResourceIdentifier(uri),
+            GeneralName::UniformResourceIdentifier(base),
+        ) => Some(uri_host(uri.as_str()).is_some_and(|host| is_within_host(host, base.as_str()))),
+        _ => None,
+    }
+}
+
+/// Check if a DNS name is the domain or one of its subdomains, ignoring case
+fn is_within_domain(name: &str, domain: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+    domain.is_empty() || name == domain || name.ends_with(&format!(".{}", domain))
+}
+
+/// Check if a mailbox satisfies an RFC 822 constraint
+///
+/// A constraint with an `@` is a single mailbox, one starting with a `.` is a domain whose
+/// subdomains are covered and anything else is a host.
+fn is_within_mailbox(mailbox: &str, constraint: &str) -> bool {
+    if constraint.contains('@') {
+        return mailbox.eq_ignore_ascii_case(constraint);
+    }
+    match mailbox.rsplit_once('@') {
+        Some((_, host)) => is_within_host(host, constraint),
+        None => false,
+    }
+}
+
+/// Check if a host satisfies a host constraint of a mailbox or URI, ignoring case
+///
+/// A constraint starting with a `.` is a domain whose subdomains are covered, anything else is
+/// a single host.
+fn is_within_host(host: &str, constraint: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    let constraint = constraint.to_ascii_lowercase();
+    if constraint.starts_with('.') {
+        host.ends_with(&constraint)
+    } else {
+        host == constraint
+    }
+}
+
+/// Check if an IP address is in an address range, which is the network address followed by
+/// the mask. An IPv4 address is never in an IPv6 range and vice versa.
+fn is_within_ip_range(address: &[u8], range: &[u8]) -> bool {
+    if range.len() != 2 * address.len() {
+        return false;
+    }
+    let (network, mask) = range.split_at(address.len());
+    address
+        .iter()
+        .zip(network)
+        .zip(mask)
+        .all(|((byte, network), mask)| byte & mask == network & mask)
+}
+
+/// Get the host name of a URI
+///
+/// # Returns
+///
+/// The host of the authority component, without user information and port, or `None` if the
+/// URI has no authority or its host is an IP address, which a host constraint can't match
+fn uri_host(uri: &str) -> Option<&str> {
+    let (_, rest) = uri.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if host_port.starts_with('[') {
+        return None;
+    }
+    let host = host_port.split(':').next().unwrap_or_default();
+    if host.is_empty() || host.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+    Some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dns(name: &str) -> GeneralName {
+        GeneralName::DnsName(Ia5String::new(name).unwrap())
+    }
+
+    fn email(name: &str) -> GeneralName {
+        GeneralName::Rfc822Name(Ia5String::new(name).unwrap())
+    }
+
+    fn ip(address: &str) -> GeneralName {
+        let address: Vec<u8> = match address.parse::<IpAddr>().unwrap() {
+            IpAddr::V4(address) => address.octets().to_vec(),
+            IpAddr::V6(address) => address.octets().to_vec(),
+        };
+        GeneralName::IpAddress(OctetString::new(address).unwrap())
+    }
+
+    fn uri(uri: &str) -> GeneralName {
+        GeneralName::UniformResourceIdentifier(Ia5String::new(uri).unwrap())
+    }
+
+    #[test]
+    fn test_is_permitted() {
+        let constraints = build_name_constraints(
+            &[
+                NameConstraint::DnsName("example.com".to_string()),
+                NameConstraint::Rfc822Name(".example.com".to_string()),
+                NameConstraint::DirectoryName("O=Example,C=US".to_string()),
+            ],
+            &[
+                NameConstraint::DnsName("secret.example.com".to_string()),
+                NameConstraint::DirectoryName("OU=Secret,O=Example,C=US".to_string()),
+            ],
+        )
+        .unwrap();
+
+        let subject = "CN=Leaf,O=Example,C=US".into_subject().unwrap();
+        assert_eq!(is_permitted(&constraints, &subject, &[]), Ok(true));
+        let subject = "CN=Leaf,O=Other,C=US".into_subject().unwrap();
+        assert_eq!(is_permitted(&constraints, &subject, &[]), Ok(false));
+        let subject = "CN=Leaf,OU=Secret,O=Example,C=US".into_subject().unwrap();
+        assert_eq!(is_permitted(&constraints, &subject, &[]), Ok(false));
+
+        let subject = RdnSequence::default();
+        for (name, expected) in [
+            (dns("example.com"), true),
+            (dns("WWW.Example.com"), true),
+            (dns("badexample.com"), false),
+            (dns("a.secret.example.com"), false),
+            (email("user@mail.example.com"), true),
+            (email("user@example.com"), false),
+            // There are no URI subtrees, so URIs are not constrained
+            (uri("https://a.b"), true),
+        ] {
+            assert_eq!(is_permitted(&constraints, &subject, &[name]), Ok(expected));
+        }
+
+        assert_eq!(
+            build_name_constraints(&[], &[]).err(),
+            Some(QubitCryptError::BadExtension)
+        );
+        assert_eq!(
+            build_name_constraints(&[NameConstraint::DnsName("é.com".to_string())], &[]).err(),
+            Some(QubitCryptError::BadExtension)
+        );
+    }
+
+    #[test]
+    fn test_ip_address_and_uri_constraints() {
+        let constraints = build_name_constraints(
+            &[
+                NameConstraint::IpAddress("192.0.2.0/24".to_string()),
+                NameConstraint::IpAddress("2001:db8::/32".to_string()),
+                NameConstraint::Uri(".example.com".to_string()),
+            ],
+            &[
+                NameConstraint::IpAddress("192.0.2.128/25".to_string()),
+                NameConstraint::Uri("admin.example.com".to_string()),
+            ],
+        )
+        .unwrap();
+        let range = &constraints.permitted_subtrees.as_ref().unwrap()[0].base;
+        assert_eq!(
+            range,
+            &GeneralName::IpAddress(OctetString::new([192, 0, 2, 0, 255, 255, 255, 0]).unwrap())
+        );
+
+        let subject = RdnSequence::default();
+        for (name, expected) in [
+            (ip("192.0.2.7"), true),
+            (ip("192.0.2.200"), false),
+            (ip("198.51.100.7"), false),
+            (ip("2001:db8::1"), true),
+            (ip("2001:db9::1"), false),
+            (uri("https://www.example.com/path"), true),
+            (uri("https://user@WWW.Example.com:8443"), true),
+            (uri("https://example.com"), false),
+            (uri("https://admin.example.com"), false),
+            (uri("https://www.example.org"), false),
+            // A URI without a host name can't be checked against the URI subtrees
+            (uri("urn:example:a"), false),
+            (uri("https://192.0.2.7/"), false),
+        ] {
+            assert_eq!(is_permitted(&constraints, &subject, &[name]), Ok(expected));
+        }
+
+        for range in ["192.0.2.0", "192.0.2.0/33", "example.com/8", "::/129"] {
+            assert_eq!(
+                build_name_constraints(&[NameConstraint::IpAddress(range.to_string())], &[]).err(),
+                Some(QubitCryptError::BadExtension)
+            );
+        }
+
+        // A critical constraint that can't be checked rejects every name, not only the ones of
+        // its type
+        let unsupported = NameConstraints {
+            permitted_subtrees: None,
+            excluded_subtrees: Some(vec![GeneralSubtree {
+                base: GeneralName::RegisteredId("1.2.3.4".parse().unwrap()),
+                minimum: 0,
+                maximum: None,
+            }]),
+        };
+        assert_eq!(
+            is_permitted(&unsupported, &subject, &[dns("example.com")]),
+            Err(QubitCryptError::UnsupportedNameConstraint)
+        );
+    }
+}
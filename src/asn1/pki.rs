@@ -0,0 +1,171 @@
+use x509_cert::builder::Profile;
+
+use crate::asn1::batch_issuer::BatchIssuer;
+use crate::asn1::cert_builder::{CertValidity, CertificateBuilder};
+use crate::asn1::certificate::Certificate;
+use crate::asn1::private_key::PrivateKey;
+use crate::dsa::api::algorithm::DsaAlgorithm;
+use crate::dsa::api::key_generator::DsaKeyGenerator;
+use crate::kem::api::algorithm::KemAlgorithm;
+use crate::kem::api::key_generator::KemKeyGenerator;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The number of days the generated certificates are valid for
+const VALIDITY_DAYS: i64 = 365;
+
+/// A complete three tier PKI: a root CA, an intermediate CA and leaf certificates
+///
+/// This is meant for tests and demos, where a realistic chain is needed but the details of each
+/// certificate don't matter.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::certificates::Pki;
+/// use qubitcrypt::dsas::DsaAlgorithm;
+/// use qubitcrypt::kems::KemAlgorithm;
+///
+/// let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem768, 2).unwrap();
+/// assert!(pki.root_cert.verify_child(&pki.intermediate_cert).unwrap());
+/// for (leaf_cert, _) in pki.leaves.iter() {
+///     assert!(pki.intermediate_cert.verify_child(leaf_cert).unwrap());
+/// }
+/// ```
+pub struct Pki {
+    /// The self-signed root CA certificate
+    pub root_cert: Certificate,
+    /// The private key of the root CA
+    pub root_key: PrivateKey,
+    /// The intermediate CA certificate, issued by the root CA
+    pub intermediate_cert: Certificate,
+    /// The private key of the intermediate CA
+    pub intermediate_key: PrivateKey,
+    /// The leaf certificates and their private keys, issued by the intermediate CA
+    pub leaves: Vec<(Certificate, PrivateKey)>,
+}
+
+impl Pki {
+    /// Generate a root CA, an intermediate CA and leaf certificates
+    ///
+    /// The CAs use `dsa_alg` and the leaves hold `leaf_kem_alg` keys with the key encipherment
+    /// usage. The intermediate CA has a pathLenConstraint of 0. All certificates are valid from
+    /// now for a year, have random serial numbers and are linked by their authority and subject
+    /// key identifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `dsa_alg` - The DSA algorithm of the root and intermediate CAs
+    /// * `leaf_kem_alg` - The KEM algorithm of the leaves
+    /// * `num_leaves` - The number of leaf certificates
+    ///
+    /// # Returns
+    ///
+    /// The generated PKI
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::KeyPairGenerationFailed` will be returned if a key pair can't be
+    /// generated, otherwise the same errors as `CertificateBuilder`
+    pub fn generate(
+        dsa_alg: DsaAlgorithm,
+        leaf_kem_alg: KemAlgorithm,
+        num_leaves: usize,
+    ) -> Result<Pki> {
+        let not_after = chrono::Utc::now() + chrono::Duration::days(VALIDITY_DAYS);
+        let validity = CertValidity::new(None, &not_after.to_rfc3339())?;
+
+        let (root_pk, root_key) = DsaKeyGenerator::new(dsa_alg).generate()?;
+        let root_cert = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root CA",
+            root_pk,
+            &root_key,
+        )?
+        .build()?;
+
+        let (intermediate_pk, intermediate_key) = DsaKeyGenerator::new(dsa_alg).generate()?;
+        let intermediate_cert = CertificateBuilder::new(
+            Profile::SubCA {
+                issuer: root_cert.get_subject(),
+                path_len_constraint: Some(0),
+            },
+            None,
+            validity.clone(),
+            "CN=Intermediate CA",
+            intermediate_pk,
+            &root_key,
+        )?
+        .build()?;
+
+        let issuer = BatchIssuer::new(&intermediate_key)?;
+        let mut leaves = Vec::with_capacity(num_leaves);
+        for i in 0..num_leaves {
+            let (leaf_pk, leaf_key) = KemKeyGenerator::new(leaf_kem_alg).generate()?;
+            let profile = Profile::Leaf {
+                issuer: intermediate_cert.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: true,
+            };
+            let leaf_cert =
+                issuer.issue(profile, format!("CN=Leaf {}", i), leaf_pk, validity.clone())?;
+            leaves.push((leaf_cert, leaf_key));
+        }
+
+        Ok(Pki {
+            root_cert,
+            root_key,
+            intermediate_cert,
+            intermediate_key,
+            leaves,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{ContentEncryptionAlgorithm, EnvelopedDataContent};
+
+    #[test]
+    fn test_pki_generate() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 3).unwrap();
+
+        assert!(pki.root_cert.verify_self_signed().unwrap());
+        assert!(pki.root_cert.verify_child(&pki.intermediate_cert).unwrap());
+        let constraints = pki.intermediate_cert.get_basic_constraints().unwrap();
+        assert!(constraints.ca);
+        assert_eq!(constraints.path_len_constraint, Some(0));
+        assert!(pki.root_cert.get_basic_constraints().unwrap().ca);
+
+        assert_eq!(pki.leaves.len(), 3);
+        for (leaf_cert, leaf_key) in pki.leaves.iter() {
+            assert!(pki.intermediate_cert.verify_child(leaf_cert).unwrap());
+            assert!(!pki.root_cert.verify_child(leaf_cert).unwrap());
+            assert!(leaf_cert.is_key_encipherment_enabled());
+
+            // The leaves can receive encrypted content
+            let mut builder =
+                EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+            builder
+                .kem_recipient(
+                    leaf_cert,
+                    &crate::kdfs::KdfType::HkdfWithSha256,
+                    &crate::wraps::WrapType::Aes128,
+                    None,
+                )
+                .unwrap()
+                .content(b"abc")
+                .unwrap();
+            let ct = builder.build().unwrap();
+            let pt = EnvelopedDataContent::from_bytes_for_kem_recipient(&ct, leaf_cert, leaf_key)
+                .unwrap();
+            assert_eq!(pt.get_content(), b"abc");
+        }
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        assert!(pki.leaves.is_empty());
+    }
+}
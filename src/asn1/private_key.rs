@@ -1,3 +1,4 @@
+use der::asn1::OctetString;
 use der::{Decode, Encode};
 use pem::EncodeConfig;
 use pkcs8::spki::{self, AlgorithmIdentifierOwned, DynSignatureAlgorithmIdentifier};
@@ -5,14 +6,21 @@ use pkcs8::ObjectIdentifier;
 use pkcs8::{spki::AlgorithmIdentifier, PrivateKeyInfo};
 
 use crate::asn1::asn_util::{is_composite_kem_or_dsa_oid, is_valid_kem_or_dsa_oid};
+use crate::asn1::composite_public_key::CompositePublicKey;
+use crate::asn1::key_capabilities::KeyCapabilities;
+use crate::asn1::key_format::{KeyFormat, MlDsaBothKey};
 use crate::asn1::signature::DsaSignature;
 use crate::dsa::common::dsa_trait::Dsa;
+use crate::dsa::common::dsa_type::DsaType;
 use crate::dsa::dsa_manager::DsaManager;
+use crate::dsa::ml_dsa::MlDsaManager;
 use crate::kem::common::kem_trait::Kem;
+use crate::kem::common::shared_secret::SharedSecret;
 use crate::kem::kem_manager::KemManager;
 use crate::{asn1::composite_private_key::CompositePrivateKey, errors};
 use crate::{keys::PublicKey, QubitCryptError};
 use signature::{Keypair, Signer};
+use zeroize::Zeroize;
 
 use crate::asn1::asn_util::is_dsa_oid;
 
@@ -25,6 +33,26 @@ pub struct PrivateKey {
     private_key: Vec<u8>,
     /// Is it a composite key
     is_composite: bool,
+    /// The public key embedded in a OneAsymmetricKey (PKCS#8 v2), if any
+    public_key: Option<PublicKey>,
+    /// The seed of an ML-DSA key which was loaded in the `both` form, the key material is
+    /// then the expanded key
+    seed: Option<Vec<u8>>,
+}
+
+/// The key material is compared in constant time
+///
+/// Equality is over the OID, the key material and the embedded public key. The seed of a key
+/// loaded in the `both` form is not compared: its key material is the expanded key, which was
+/// checked to be the expansion of the seed, so it equals the same key loaded as an expanded
+/// key. A key held as its seed has other key material and is not equal to its expanded form.
+impl PartialEq for PrivateKey {
+    fn eq(&self, other: &PrivateKey) -> bool {
+        self.oid == other.oid
+            && self.private_key.len() == other.private_key.len()
+            && openssl::memcmp::eq(&self.private_key, &other.private_key)
+            && self.public_key == other.public_key
+    }
 }
 
 impl Signer<DsaSignature> for PrivateKey {
@@ -42,6 +70,10 @@ impl Keypair for PrivateKey {
             panic!("Unsupported operation");
         }
 
+        if let Some(pk) = &self.public_key {
+            return pk.clone();
+        }
+
         let dsa = DsaManager::new_from_oid(&self.oid).unwrap();
         let pk = dsa.get_public_key(&self.private_key).unwrap();
 
@@ -78,7 +110,7 @@ impl PrivateKey {
     ///
     /// `KeyError::InvalidPrivateKey` will be returned if the OID is invalid
     pub(crate) fn new(oid: &str, key: &[u8]) -> Result<Self> {
-        if !is_valid_kem_or_dsa_oid(&oid.to_string()) {
+        if !is_valid_kem_or_dsa_oid(oid) {
             return Err(errors::QubitCryptError::InvalidPrivateKey);
         }
         let is_composite = is_composite_kem_or_dsa_oid(oid);
@@ -86,6 +118,8 @@ impl PrivateKey {
             oid: oid.to_string(),
             private_key: key.to_vec(),
             is_composite,
+            public_key: None,
+            seed: None,
         })
     }
 
@@ -110,6 +144,8 @@ impl PrivateKey {
                 .to_der()
                 .map_err(|_| errors::QubitCryptError::InvalidPrivateKey)?,
             is_composite: true,
+            public_key: None,
+            seed: None,
         })
     }
 
@@ -127,8 +163,7 @@ impl PrivateKey {
     /// # Returns
     ///
     /// The key material
-    #[cfg(test)]
-    fn get_key(&self) -> &[u8] {
+    pub(crate) fn get_key(&self) -> &[u8] {
         &self.private_key
     }
 
@@ -141,8 +176,69 @@ impl PrivateKey {
         self.is_composite
     }
 
+    /// Get the public key embedded in the private key
+    ///
+    /// A OneAsymmetricKey (PKCS#8 v2, RFC 5958) can carry the public key alongside the private
+    /// key, which saves deriving it. This is useful for KEM keys, where the public key can't
+    /// always be cheaply derived from the private key.
+    ///
+    /// # Returns
+    ///
+    /// The embedded public key, or `None` if the key was loaded without one
+    pub fn embedded_public_key(&self) -> Option<PublicKey> {
+        self.public_key.clone()
+    }
+
+    /// Get the operations which this key supports
+    ///
+    /// # Returns
+    ///
+    /// The capabilities of the key, derived from its OID
+    pub fn capabilities(&self) -> KeyCapabilities {
+        KeyCapabilities::for_private_key(&self.oid)
+    }
+
+    /// Get the form of the key material: a seed, an expanded key, both or a composite key
+    ///
+    /// This only inspects the key as it was loaded, nothing is expanded or checked beyond
+    /// the length. A `KeyFormat::Unknown` key has a length which doesn't fit its OID.
+    ///
+    /// # Returns
+    ///
+    /// The form of the key material, derived from its length and its OID
+    pub fn key_format(&self) -> KeyFormat {
+        if self.seed.is_some() {
+            return KeyFormat::Both;
+        }
+        KeyFormat::for_private_key(&self.oid, &self.private_key)
+    }
+
+    /// Wipe the key material and consume the key
+    ///
+    /// The key material and the OID are overwritten with zeros using volatile writes, so the
+    /// wipe can't be optimized away. Use this to end the lifetime of a key at a precise point,
+    /// for example straight after the operation it was loaded for.
+    pub fn zeroize(mut self) {
+        self.wipe();
+    }
+
+    /// Overwrite the key material and the OID with zeros in place
+    fn wipe(&mut self) {
+        self.private_key.zeroize();
+        if let Some(seed) = self.seed.as_mut() {
+            seed.zeroize();
+        }
+        self.seed = None;
+        self.oid.zeroize();
+        self.is_composite = false;
+        self.public_key = None;
+    }
+
     /// Get the key material as a DER-encoded byte array
     ///
+    /// An ML-DSA key which was loaded in the `both` form is written as its seed, which is
+    /// much smaller. Use `to_der_both` to keep both.
+    ///
     /// # Returns
     ///
     /// The DER-encoded byte array
@@ -151,6 +247,53 @@ impl PrivateKey {
     ///
     /// `KeyError::InvalidPrivateKey` will be returned if the private key is invalid
     pub fn to_der(&self) -> Result<Vec<u8>> {
+        self.encode(self.seed.as_deref().unwrap_or(&self.private_key))
+    }
+
+    /// Get an ML-DSA key as a DER-encoded byte array, with both the seed and the expanded key
+    ///
+    /// This is the `both` form of the IETF ML-DSA private key format, for libraries which
+    /// want the expanded key but also keep the seed.
+    ///
+    /// # Returns
+    ///
+    /// The DER-encoded byte array
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the key is not an ML-DSA
+    /// key or is only the expanded key, from which the seed can't be recovered
+    pub fn to_der_both(&self) -> Result<Vec<u8>> {
+        let dsa_type = DsaType::from_oid(&self.oid).ok_or(QubitCryptError::UnsupportedOperation)?;
+        let (seed, expanded_key) = match self.key_format() {
+            KeyFormat::Both => (
+                self.seed
+                    .clone()
+                    .ok_or(QubitCryptError::InvalidPrivateKey)?,
+                self.private_key.clone(),
+            ),
+            KeyFormat::Seed => {
+                let expanded_key = MlDsaManager::new(dsa_type)?.expand_seed(&self.private_key)?;
+                (self.private_key.clone(), expanded_key)
+            }
+            _ => return Err(QubitCryptError::UnsupportedOperation),
+        };
+
+        let both = MlDsaBothKey {
+            seed: OctetString::new(seed).map_err(|_| QubitCryptError::InvalidPrivateKey)?,
+            expanded_key: OctetString::new(expanded_key)
+                .map_err(|_| QubitCryptError::InvalidPrivateKey)?,
+        };
+        let mut key = both
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+        let der = self.encode(&key);
+        key.zeroize();
+        der
+    }
+
+    /// Encode the PrivateKeyInfo (OneAsymmetricKey) with the given key material
+    fn encode(&self, key: &[u8]) -> Result<Vec<u8>> {
         let oid: ObjectIdentifier = self
             .oid
             .parse()
@@ -161,8 +304,8 @@ impl PrivateKey {
                 oid,
                 parameters: None,
             },
-            private_key: &self.private_key,
-            public_key: None,
+            private_key: key,
+            public_key: self.public_key.as_ref().map(|pk| pk.get_key()),
         };
         Ok(priv_key_info
             .to_der()
@@ -221,9 +364,16 @@ impl PrivateKey {
     ///
     /// A new private key
     ///
+    /// An ML-DSA key in the `both` form is checked, its expanded key must be the expansion of
+    /// its seed. The expanded key is used for signing and the seed is kept for `to_der`.
+    ///
+    /// A public key embedded in a OneAsymmetricKey (PKCS#8 v2) is checked against the private
+    /// key, so that `embedded_public_key` and `verifying_key` can't return an unrelated key.
+    ///
     /// # Errors
     ///
-    /// `KeyError::InvalidPrivateKey` will be returned if the private key is invalid
+    /// `KeyError::InvalidPrivateKey` will be returned if the private key is invalid or if the
+    /// embedded public key doesn't belong to it
     pub fn from_der(der: &[u8]) -> Result<Self> {
         let priv_key_info = PrivateKeyInfo::from_der(der)
             .map_err(|_| errors::QubitCryptError::InvalidPrivateKey)?;
@@ -238,13 +388,108 @@ impl PrivateKey {
         // Check if the OID is a composite key
         let is_composite = is_composite_kem_or_dsa_oid(&oid);
 
+        // Keep the public key of a OneAsymmetricKey, it uses the same algorithm as the private key
+        let public_key = priv_key_info
+            .public_key
+            .map(|pk| PublicKey::new(&oid, pk))
+            .transpose()
+            .map_err(|_| errors::QubitCryptError::InvalidPrivateKey)?;
+
+        let (private_key, seed) = match KeyFormat::for_private_key(&oid, priv_key_info.private_key)
+        {
+            KeyFormat::Both => {
+                let (expanded_key, seed) = Self::parse_both(&oid, priv_key_info.private_key)?;
+                (expanded_key, Some(seed))
+            }
+            _ => (priv_key_info.private_key.to_vec(), None),
+        };
+
+        if let Some(public_key) = &public_key {
+            Self::check_embedded_public_key(&oid, &private_key, public_key)?;
+        }
+
         Ok(Self {
             oid: oid.to_string(),
-            private_key: priv_key_info.private_key.to_vec(),
+            private_key,
             is_composite,
+            public_key,
+            seed,
         })
     }
 
+    /// Check that the public key embedded in a OneAsymmetricKey belongs to the private key
+    ///
+    /// The public key is derived from the private key and compared in constant time. A composite
+    /// key is checked component by component with `CompositePrivateKey::validate`. A custom KEM
+    /// can't derive its public key, so a ciphertext encapsulated to the public key must
+    /// decapsulate to the same shared secret instead.
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the public key doesn't belong to
+    /// the private key
+    fn check_embedded_public_key(oid: &str, sk: &[u8], public_key: &PublicKey) -> Result<()> {
+        let pk = public_key.get_key();
+        if is_composite_kem_or_dsa_oid(oid) {
+            let c_sk = CompositePrivateKey::from_der(oid, sk)?;
+            let c_pk = CompositePublicKey::from_der(oid, pk)
+                .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+            return match c_sk.validate(Some(&c_pk)) {
+                Ok(true) => Ok(()),
+                _ => Err(QubitCryptError::InvalidPrivateKey),
+            };
+        }
+
+        let derived = if is_dsa_oid(oid) {
+            DsaManager::new_from_oid(oid)?.get_public_key(sk)
+        } else {
+            let mut kem = KemManager::new_from_oid(oid)?;
+            match kem.get_public_key(sk) {
+                Err(QubitCryptError::NotImplemented) => {
+                    let (ss, ct) = kem
+                        .encap(pk)
+                        .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+                    let decapped = kem
+                        .decap(sk, &ct)
+                        .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+                    if ss.len() != decapped.len() || !openssl::memcmp::eq(&ss, &decapped) {
+                        return Err(QubitCryptError::InvalidPrivateKey);
+                    }
+                    return Ok(());
+                }
+                derived => derived,
+            }
+        };
+        let derived = derived.map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+
+        if derived.len() != pk.len() || !openssl::memcmp::eq(&derived, pk) {
+            return Err(QubitCryptError::InvalidPrivateKey);
+        }
+        Ok(())
+    }
+
+    /// Parse the `both` form of an ML-DSA private key and check that its expanded key is the
+    /// expansion of its seed
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the expanded key and the seed
+    fn parse_both(oid: &str, key: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let dsa_type = DsaType::from_oid(oid).ok_or(QubitCryptError::InvalidPrivateKey)?;
+        let both = MlDsaBothKey::from_der(key).map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+        let seed = both.seed.as_bytes();
+        let expanded_key = both.expanded_key.as_bytes();
+
+        let mut expected = MlDsaManager::new(dsa_type)?.expand_seed(seed)?;
+        let matches =
+            expected.len() == expanded_key.len() && openssl::memcmp::eq(&expected, expanded_key);
+        expected.zeroize();
+        if !matches {
+            return Err(QubitCryptError::InvalidPrivateKey);
+        }
+        Ok((expanded_key.to_vec(), seed.to_vec()))
+    }
+
     /// Sign a message
     ///
     /// # Arguments
@@ -267,6 +512,64 @@ impl PrivateKey {
         Ok(sig)
     }
 
+    /// Sign a message with an application context string
+    ///
+    /// Only ML-DSA has context strings (FIPS 204). The signature only verifies with the same
+    /// context, see `PublicKey::verify_with_context`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to sign
+    /// * `ctx` - The context string, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// The signature
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if this private key is not an
+    /// ML-DSA key, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    pub fn sign_with_context(&self, data: &[u8], ctx: &[u8]) -> Result<Vec<u8>> {
+        if !is_dsa_oid(&self.oid) {
+            return Err(errors::QubitCryptError::UnsupportedOperation);
+        }
+
+        let dsa = DsaManager::new_from_oid(&self.oid)?;
+        dsa.sign_with_context(&self.private_key, data, ctx)
+    }
+
+    /// Sign the digest of a message with HashML-DSA
+    ///
+    /// The message is hashed by the caller, which allows signing a large file without holding
+    /// it in memory. The OID of the hash function is part of the signed data, so the signature
+    /// only verifies with `PublicKey::verify_prehashed` and the same hash function and context.
+    ///
+    /// # Arguments
+    ///
+    /// * `digest` - The digest of the message
+    /// * `hash_oid` - The OID of the hash function, e.g. "2.16.840.1.101.3.4.2.3" for SHA-512
+    /// * `ctx` - The context string, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// The signature
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if this private key is not an
+    /// ML-DSA key, `QubitCryptError::InvalidOid` if the hash function is not permitted by FIPS
+    /// 204, `QubitCryptError::InvalidContent` if the digest doesn't have the length of the hash
+    /// function, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    pub fn sign_prehashed(&self, digest: &[u8], hash_oid: &str, ctx: &[u8]) -> Result<Vec<u8>> {
+        if !is_dsa_oid(&self.oid) {
+            return Err(errors::QubitCryptError::UnsupportedOperation);
+        }
+
+        let dsa = DsaManager::new_from_oid(&self.oid)?;
+        dsa.sign_prehashed(&self.private_key, digest, hash_oid, ctx)
+    }
+
     /// Use the private key to decapsulate a shared secret from a ciphertext
     ///
     /// # Arguments
@@ -289,6 +592,30 @@ impl PrivateKey {
         Ok(ss)
     }
 
+    /// Use the private key to decapsulate a shared secret from a ciphertext, returning it as a
+    /// `SharedSecret`
+    ///
+    /// The shared secret is zeroized when it is dropped, and isn't printed by `Debug`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ct` - The ciphertext
+    ///
+    /// # Returns
+    ///
+    /// The shared secret
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if this private key is not a KEM key
+    pub fn decap_typed(&self, ct: &[u8]) -> Result<SharedSecret> {
+        if is_dsa_oid(&self.oid) {
+            return Err(errors::QubitCryptError::UnsupportedOperation);
+        }
+        let kem = KemManager::new_from_oid(&self.oid)?;
+        kem.decap_typed(&self.private_key, ct)
+    }
+
     /// Load a private key from a file. The file can be in either DER or PEM format
     ///
     /// # Arguments
@@ -360,6 +687,55 @@ mod test {
     use crate::dsa::common::dsa_type::DsaType;
 
     use super::*;
+    use crate::asn1::macros::test_asn1_roundtrip;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    #[test]
+    fn test_private_key_roundtrip() {
+        let (_, sk_dsa) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (_, sk_comp_dsa) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44EcdsaP256SHA256)
+            .generate()
+            .unwrap();
+        let (_, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let (_, sk_comp_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem768X25519)
+            .generate()
+            .unwrap();
+        for sk in [sk_dsa, sk_comp_dsa, sk_kem, sk_comp_kem] {
+            test_asn1_roundtrip!(PrivateKey, sk);
+        }
+        let pem_bytes = include_bytes!("../../test/data/mldsa44_ecdsa_p256_sha256_sk.pem");
+        let sk = PrivateKey::from_pem(std::str::from_utf8(pem_bytes).unwrap()).unwrap();
+        test_asn1_roundtrip!(PrivateKey, sk);
+    }
+
+    #[test]
+    fn test_key_format() {
+        for (alg, expected) in [
+            (DsaAlgorithm::MlDsa65, KeyFormat::Expanded),
+            (DsaAlgorithm::SlhDsaShake128f, KeyFormat::Expanded),
+            (DsaAlgorithm::MlDsa44EcdsaP256SHA256, KeyFormat::Composite),
+        ] {
+            let (_, sk) = DsaKeyGenerator::new(alg).generate().unwrap();
+            assert_eq!(sk.key_format(), expected);
+        }
+        for (alg, expected) in [
+            (KemAlgorithm::MlKem1024, KeyFormat::Expanded),
+            (KemAlgorithm::MlKem768X25519, KeyFormat::Composite),
+        ] {
+            let (_, sk) = KemKeyGenerator::new(alg).generate().unwrap();
+            assert_eq!(sk.key_format(), expected);
+        }
+
+        let seed = PrivateKey::new(&DsaType::MlDsa44.get_oid(), &[1u8; 32]).unwrap();
+        assert_eq!(seed.key_format(), KeyFormat::Seed);
+        let truncated = PrivateKey::new(&DsaType::MlDsa44.get_oid(), &[1u8; 100]).unwrap();
+        assert_eq!(truncated.key_format(), KeyFormat::Unknown);
+    }
 
     #[test]
     fn test_composite_private_key() {
@@ -371,7 +747,7 @@ mod test {
         assert_eq!(pk.get_oid(), DsaType::MlDsa44EcdsaP256SHA256.get_oid());
 
         let key_bytes = pk.get_key();
-        let pk2 = CompositePrivateKey::from_der(&pk.oid, &key_bytes).unwrap();
+        let pk2 = CompositePrivateKey::from_der(&pk.oid, key_bytes).unwrap();
 
         assert_eq!(pk.oid, pk2.get_oid());
 
@@ -383,6 +759,125 @@ mod test {
         assert_eq!(pk.oid, oid);
     }
 
+    #[test]
+    fn test_embedded_public_key() {
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (pk_kem, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem768)
+            .generate()
+            .unwrap();
+        assert!(sk.embedded_public_key().is_none());
+
+        for (pk, sk) in [(pk, sk), (pk_kem, sk_kem)] {
+            let oid: ObjectIdentifier = sk.get_oid().parse().unwrap();
+            let der = PrivateKeyInfo {
+                algorithm: AlgorithmIdentifier {
+                    oid,
+                    parameters: None,
+                },
+                private_key: sk.get_key(),
+                public_key: Some(pk.get_key()),
+            }
+            .to_der()
+            .unwrap();
+
+            let sk2 = PrivateKey::from_der(&der).unwrap();
+            assert!(sk2.embedded_public_key().unwrap().equals(&pk));
+            assert_eq!(sk2.to_der().unwrap(), der);
+            if sk2.capabilities().can_sign {
+                assert!(sk2.verifying_key().equals(&pk));
+            }
+        }
+    }
+
+    #[test]
+    fn test_embedded_public_key_mismatch() {
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        let dsa = || DsaKeyGenerator::new(DsaAlgorithm::MlDsa44).generate();
+        let kem = |alg| KemKeyGenerator::new(alg).generate();
+        let key_pairs = [
+            (dsa(), dsa()),
+            (kem(KemAlgorithm::MlKem768), kem(KemAlgorithm::MlKem768)),
+            (kem(KemAlgorithm::X25519), kem(KemAlgorithm::X25519)),
+            (
+                kem(KemAlgorithm::MlKem768X25519),
+                kem(KemAlgorithm::MlKem768X25519),
+            ),
+        ];
+
+        // The public key of another key pair of the same algorithm is rejected
+        for (key_pair, other) in key_pairs {
+            let (_, sk) = key_pair.unwrap();
+            let (other_pk, _) = other.unwrap();
+            let oid: ObjectIdentifier = sk.get_oid().parse().unwrap();
+            let der = PrivateKeyInfo {
+                algorithm: AlgorithmIdentifier {
+                    oid,
+                    parameters: None,
+                },
+                private_key: sk.get_key(),
+                public_key: Some(other_pk.get_key()),
+            }
+            .to_der()
+            .unwrap();
+
+            assert!(matches!(
+                PrivateKey::from_der(&der),
+                Err(QubitCryptError::InvalidPrivateKey)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_zeroize() {
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+
+        let (_, mut sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        assert!(!sk.get_key().is_empty());
+        sk.wipe();
+        assert!(sk.get_key().is_empty());
+        assert!(sk.get_oid().is_empty());
+        assert!(sk.to_der().is_err());
+
+        let (_, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        sk.zeroize();
+    }
+
+    #[test]
+    fn test_standalone_ecdh_kem_keys() {
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        for alg in [KemAlgorithm::X25519, KemAlgorithm::X448] {
+            let (pk, sk) = KemKeyGenerator::new(alg).generate().unwrap();
+            assert!(!pk.is_composite());
+            assert!(!sk.is_composite());
+            assert_eq!(pk.get_oid(), alg.get_oid());
+
+            let (ss, ct) = pk.encap().unwrap();
+            let ss2 = sk.decap(&ct).unwrap();
+            assert_eq!(ss, ss2);
+
+            // The keys should survive a round trip through DER
+            let pk2 = PublicKey::from_der(&pk.to_der().unwrap()).unwrap();
+            let sk2 = PrivateKey::from_der(&sk.to_der().unwrap()).unwrap();
+            let (ss, ct) = pk2.encap().unwrap();
+            assert_eq!(ss, sk2.decap(&ct).unwrap());
+
+            let (ss, ct) = pk.encap_typed().unwrap();
+            assert_eq!(ss.expose(), sk.decap_typed(&ct).unwrap().expose());
+        }
+    }
+
     #[test]
     fn test_pk_no_headers() {
         let pem_bytes = include_bytes!("../../test/data/bad/no_headers.pem");
@@ -462,4 +957,128 @@ mod test {
         let der2 = pk2.to_der().unwrap();
         assert_eq!(der, der2);
     }
+
+    #[test]
+    fn test_sign_with_context() {
+        let msg = b"firmware image";
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65)
+            .generate()
+            .unwrap();
+        let sig = sk.sign_with_context(msg, b"update-v1").unwrap();
+        assert!(pk.verify_with_context(msg, &sig, b"update-v1").unwrap());
+        assert!(!pk.verify_with_context(msg, &sig, b"update-v2").unwrap());
+        assert!(!pk.verify(msg, &sig).unwrap());
+        assert_eq!(
+            pk.verify_with_context(msg, &sig, &[0u8; 256]),
+            Err(QubitCryptError::InvalidContext)
+        );
+
+        // Only pure ML-DSA has context strings
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44EcdsaP256SHA256)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            sk.sign_with_context(msg, b"update-v1"),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+        assert_eq!(
+            pk.verify_with_context(msg, &[0u8; 64], b"update-v1"),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+    }
+
+    #[test]
+    fn test_sign_prehashed() {
+        use sha2::{Digest, Sha512};
+
+        let sha512 = "2.16.840.1.101.3.4.2.3";
+        let digest = Sha512::digest(b"firmware image");
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa87)
+            .generate()
+            .unwrap();
+        let sig = sk.sign_prehashed(&digest, sha512, b"").unwrap();
+        assert!(pk.verify_prehashed(&digest, sha512, &sig, b"").unwrap());
+        assert!(!pk.verify_prehashed(&digest, sha512, &sig, b"v1").unwrap());
+        assert!(!pk.verify(b"firmware image", &sig).unwrap());
+        assert_eq!(
+            pk.verify_prehashed(&digest[..48], sha512, &sig, b""),
+            Err(QubitCryptError::InvalidContent)
+        );
+
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44EcdsaP256SHA256)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            sk.sign_prehashed(&digest, sha512, b""),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+        assert_eq!(
+            pk.verify_prehashed(&digest, sha512, &[0u8; 64], b""),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+    }
+
+    #[test]
+    fn test_both_key_format() {
+        use crate::dsa::common::config::oids::Oid as _;
+
+        for dsa_type in [DsaType::MlDsa44, DsaType::MlDsa65, DsaType::MlDsa87] {
+            let oid = dsa_type.get_oid();
+            let seed_key = PrivateKey::new(&oid, &[7u8; 32]).unwrap();
+            let expanded = MlDsaManager::new(dsa_type.clone())
+                .unwrap()
+                .expand_seed(&[7u8; 32])
+                .unwrap();
+
+            let both_der = seed_key.to_der_both().unwrap();
+            let both = PrivateKey::from_der(&both_der).unwrap();
+            assert_eq!(both.key_format(), KeyFormat::Both);
+            assert_eq!(both.get_key(), expanded.as_slice());
+            assert_eq!(both.to_der_both().unwrap(), both_der);
+            // The seed is preferred when writing the key
+            assert_eq!(both.to_der().unwrap(), seed_key.to_der().unwrap());
+
+            // The seed and the expanded key sign for the same public key
+            let pk = seed_key.verifying_key();
+            assert!(both.verifying_key() == pk);
+            let msg = b"Hello, world!";
+            assert!(pk.verify(msg, &both.sign(msg).unwrap()).unwrap());
+            assert!(pk.verify(msg, &seed_key.sign(msg).unwrap()).unwrap());
+
+            // The seed of an expanded key can't be recovered
+            let expanded_key = PrivateKey::new(&oid, &expanded).unwrap();
+            // Equality is over the expanded key, not the seed
+            assert!(both == expanded_key);
+            assert!(both != seed_key);
+            assert_eq!(
+                expanded_key.to_der_both().err(),
+                Some(QubitCryptError::UnsupportedOperation)
+            );
+        }
+
+        // The expanded key must belong to the seed
+        let oid = DsaType::MlDsa44.get_oid();
+        let other = MlDsaManager::new(DsaType::MlDsa44)
+            .unwrap()
+            .expand_seed(&[8u8; 32])
+            .unwrap();
+        let both = MlDsaBothKey {
+            seed: OctetString::new(vec![7u8; 32]).unwrap(),
+            expanded_key: OctetString::new(other).unwrap(),
+        };
+        let key = PrivateKey::new(&oid, &both.to_der().unwrap()).unwrap();
+        assert_eq!(key.key_format(), KeyFormat::Both);
+        assert_eq!(
+            PrivateKey::from_der(&key.to_der().unwrap()).err(),
+            Some(QubitCryptError::InvalidPrivateKey)
+        );
+
+        let (_, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            sk_kem.to_der_both().err(),
+            Some(QubitCryptError::UnsupportedOperation)
+        );
+    }
 }
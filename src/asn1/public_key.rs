@@ -1,14 +1,20 @@
-use crate::asn1::asn_util::{is_composite_kem_or_dsa_oid, is_valid_kem_or_dsa_oid};
+use crate::asn1::asn_util::{
+    get_key_octets, get_security_category, is_composite_kem_or_dsa_oid, is_valid_kem_or_dsa_oid,
+};
+use crate::asn1::key_capabilities::KeyCapabilities;
 use crate::dsa::common::dsa_trait::Dsa;
 use crate::dsa::dsa_manager::DsaManager;
 use crate::errors;
 use crate::kem::common::kem_trait::Kem;
+use crate::kem::common::shared_secret::SharedSecret;
 use crate::kem::kem_manager::KemManager;
 use der::{asn1::BitString, Document};
 use der::{Decode, Encode};
 use pem::EncodeConfig;
 use pkcs8::ObjectIdentifier;
 use pkcs8::{spki::AlgorithmIdentifierWithOid, EncodePublicKey};
+use spki::SubjectPublicKeyInfoOwned;
+use std::io::Read;
 
 use crate::asn1::composite_public_key::CompositePublicKey;
 
@@ -30,6 +36,12 @@ pub struct PublicKey {
     is_composite: bool,
 }
 
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &PublicKey) -> bool {
+        self.equals(other)
+    }
+}
+
 impl PublicKey {
     /// Create a new public key
     ///
@@ -47,7 +59,7 @@ impl PublicKey {
     /// `KeyError::InvalidPublicKey` will be returned if the OID is invalid
     /// or the key is invalid
     pub fn new(oid: &str, key: &[u8]) -> Result<Self> {
-        if !is_valid_kem_or_dsa_oid(&oid.to_string()) {
+        if !is_valid_kem_or_dsa_oid(oid) {
             return Err(errors::QubitCryptError::InvalidPublicKey);
         }
         let is_composite = is_composite_kem_or_dsa_oid(oid);
@@ -108,6 +120,74 @@ impl PublicKey {
         self.is_composite
     }
 
+    /// Get the operations which this key supports
+    ///
+    /// # Returns
+    ///
+    /// The capabilities of the key, derived from its OID
+    pub fn capabilities(&self) -> KeyCapabilities {
+        KeyCapabilities::for_public_key(&self.oid)
+    }
+
+    /// Get the NIST security category of the key's algorithm
+    ///
+    /// Traditional algorithms have no post-quantum security and are given 0, composite
+    /// algorithms the category of their post-quantum component.
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    pub fn security_category(&self) -> u8 {
+        get_security_category(&self.oid).unwrap_or(0)
+    }
+
+    /// Check that the key's algorithm meets a minimum NIST security category
+    ///
+    /// # Arguments
+    ///
+    /// * `min_category` - The minimum NIST security category (1 to 5)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InsufficientSecurityCategory` will be returned if the security category
+    /// of the key is below `min_category`
+    pub fn require_security_category(&self, min_category: u8) -> Result<()> {
+        if self.security_category() < min_category {
+            return Err(QubitCryptError::InsufficientSecurityCategory);
+        }
+        Ok(())
+    }
+
+    /// Check if two public keys are the same key, regardless of how they were encoded
+    ///
+    /// The OIDs and the raw key material are compared. For composite keys the
+    /// post-quantum and traditional components are compared individually, instead
+    /// of the encoding that wraps them.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The public key to compare with
+    ///
+    /// # Returns
+    ///
+    /// True if both public keys are the same key, false otherwise
+    pub fn equals(&self, other: &PublicKey) -> bool {
+        if self.oid != other.oid {
+            return false;
+        }
+
+        if self.is_composite {
+            let self_pk = CompositePublicKey::from_der(&self.oid, &self.key);
+            let other_pk = CompositePublicKey::from_der(&other.oid, &other.key);
+            if let (Ok(self_pk), Ok(other_pk)) = (self_pk, other_pk) {
+                return self_pk.get_pq_pk() == other_pk.get_pq_pk()
+                    && self_pk.get_trad_pk() == other_pk.get_trad_pk();
+            }
+        }
+
+        self.key == other.key
+    }
+
     /// Convert the public key to a PEM-encoded string
     ///
     /// # Returns
@@ -116,11 +196,10 @@ impl PublicKey {
     ///
     /// # Errors
     ///
-    /// `KeyError::InvalidPublicKey` will be returned if the public key is invalid
+    /// `QubitCryptError::PublicKeyEncodingFailed` will be returned if the public key cannot be
+    /// encoded
     pub fn to_pem(&self) -> Result<String> {
-        let der = self
-            .to_der()
-            .map_err(|_| errors::QubitCryptError::InvalidPublicKey)?;
+        let der = self.to_der()?;
         let pem_obj = pem::Pem::new("PUBLIC KEY", der);
         let encode_conf = EncodeConfig::default().set_line_ending(pem::LineEnding::LF);
         Ok(pem::encode_config(&pem_obj, encode_conf))
@@ -132,8 +211,9 @@ impl PublicKey {
     ///
     /// The public key as a BitString
     pub(crate) fn to_bitstring(&self) -> Result<BitString> {
-        let pk_bs = BitString::from_bytes(&self.key)
-            .map_err(|_| errors::QubitCryptError::InvalidPublicKey)?;
+        let pk_bs = BitString::from_bytes(&self.key).map_err(|e| {
+            QubitCryptError::PublicKeyEncodingFailed(format!("invalid key material: {}", e))
+        })?;
         Ok(pk_bs)
     }
 
@@ -146,14 +226,14 @@ impl PublicKey {
     ///
     /// # Errors
     ///
-    /// `KeyError::InvalidPublicKey` will be returned if the public key is invalid
+    /// `QubitCryptError::PublicKeyEncodingFailed` will be returned with the underlying reason
+    /// if the OID cannot be parsed or the key cannot be DER encoded
     pub fn to_der(&self) -> Result<Vec<u8>> {
         let pk_bs = self.to_bitstring()?;
 
-        let oid: ObjectIdentifier = self
-            .oid
-            .parse()
-            .map_err(|_| QubitCryptError::InvalidPublicKey)?;
+        let oid: ObjectIdentifier = self.oid.parse().map_err(|e| {
+            QubitCryptError::PublicKeyEncodingFailed(format!("invalid OID {}: {}", self.oid, e))
+        })?;
 
         let pub_key_info = PublicKeyInfo {
             algorithm: AlgorithmIdentifierWithOid {
@@ -162,12 +242,57 @@ impl PublicKey {
             },
             public_key: pk_bs,
         };
-        let der = pub_key_info
-            .to_der()
-            .map_err(|_| errors::QubitCryptError::InvalidPublicKey)?;
+        let der = pub_key_info.to_der().map_err(|e| {
+            QubitCryptError::PublicKeyEncodingFailed(format!("DER encoding failed: {}", e))
+        })?;
         Ok(der)
     }
 
+    /// Convert the public key to a DER-encoded SubjectPublicKeyInfo whose AlgorithmIdentifier
+    /// has an explicit ASN.1 NULL as parameters
+    ///
+    /// **This encoding is non-conformant.** The specifications of the post-quantum and composite
+    /// algorithms require the parameters to be absent, as `to_der` encodes them. This is only
+    /// for interoperability with legacy verifiers which expect the NULL parameters of
+    /// rsaEncryption for every algorithm. Conformant parsers may reject it, including
+    /// `from_der`.
+    ///
+    /// # Returns
+    ///
+    /// The DER-encoded byte array
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::PublicKeyEncodingFailed` will be returned with the underlying reason
+    /// if the OID cannot be parsed or the key cannot be DER encoded
+    pub fn to_der_with_null_params(&self) -> Result<Vec<u8>> {
+        let mut spki = self.to_spki()?;
+        spki.algorithm.parameters = Some(der::Any::null());
+        spki.to_der().map_err(|e| {
+            QubitCryptError::PublicKeyEncodingFailed(format!("DER encoding failed: {}", e))
+        })
+    }
+
+    /// Convert the public key to a SubjectPublicKeyInfo structure
+    ///
+    /// Unlike `EncodePublicKey::to_public_key_der`, which can only report a generic
+    /// `spki::Error::KeyMalformed`, this keeps the reason why encoding failed.
+    ///
+    /// # Returns
+    ///
+    /// The SubjectPublicKeyInfo for this public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::PublicKeyEncodingFailed` will be returned with the underlying reason
+    /// if the public key cannot be encoded
+    pub fn to_spki(&self) -> Result<SubjectPublicKeyInfoOwned> {
+        let der = self.to_der()?;
+        SubjectPublicKeyInfoOwned::from_der(&der).map_err(|e| {
+            QubitCryptError::PublicKeyEncodingFailed(format!("DER decoding failed: {}", e))
+        })
+    }
+
     /// Create a new public key from a PEM-encoded string
     ///
     /// # Arguments
@@ -180,7 +305,8 @@ impl PublicKey {
     ///
     /// # Errors
     ///
-    /// `KeyError::InvalidPublicKey` will be returned if the public key is invalid
+    /// `KeyError::InvalidPublicKey` will be returned if the public key is invalid,
+    /// `QubitCryptError::UnusedKeyBits` if its BIT STRING has non-zero unused bits
     pub fn from_pem(pem: &str) -> Result<Self> {
         let pem = pem::parse(pem).map_err(|_| errors::QubitCryptError::InvalidPublicKey)?;
         // Header should be "PUBLIC KEY"
@@ -204,15 +330,13 @@ impl PublicKey {
     ///
     /// # Errors
     ///
-    /// `KeyError::InvalidPublicKey` will be returned if the public key is invalid
+    /// `KeyError::InvalidPublicKey` will be returned if the public key is invalid,
+    /// `QubitCryptError::UnusedKeyBits` if its BIT STRING has non-zero unused bits
     pub fn from_der(der: &[u8]) -> Result<Self> {
         let pub_key_info =
             PublicKeyInfo::from_der(der).map_err(|_| errors::QubitCryptError::InvalidPublicKey)?;
-        let pk_bytes = if let Some(pk_bytes) = pub_key_info.public_key.as_bytes() {
-            pk_bytes
-        } else {
-            return Err(errors::QubitCryptError::InvalidPublicKey);
-        };
+        // The key must be a whole number of octets
+        let pk_bytes = get_key_octets(&pub_key_info.public_key)?;
 
         let oid = pub_key_info.algorithm.oid.to_string();
 
@@ -260,11 +384,139 @@ impl PublicKey {
         Ok(verified)
     }
 
+    /// Verify a signature made with an application context string, see
+    /// `PrivateKey::sign_with_context`
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to verify
+    /// * `signature` - The signature
+    /// * `ctx` - The context string the message was signed with, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the OID is not an ML-DSA
+    /// key, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    pub fn verify_with_context(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        ctx: &[u8],
+    ) -> Result<bool> {
+        if !is_dsa_oid(&self.oid) {
+            return Err(errors::QubitCryptError::UnsupportedOperation);
+        }
+
+        let dsa =
+            DsaManager::new_from_oid(&self.oid).map_err(|_| errors::QubitCryptError::InvalidOid)?;
+
+        match dsa.verify_with_context(self.get_key(), message, signature, ctx) {
+            Err(
+                err @ (QubitCryptError::UnsupportedOperation | QubitCryptError::InvalidContext),
+            ) => Err(err),
+            result => Ok(result.unwrap_or(false)),
+        }
+    }
+
+    /// Verify a HashML-DSA signature over the digest of a message, see
+    /// `PrivateKey::sign_prehashed`
+    ///
+    /// # Arguments
+    ///
+    /// * `digest` - The digest of the message
+    /// * `hash_oid` - The OID of the hash function the digest was computed with
+    /// * `signature` - The signature
+    /// * `ctx` - The context string the digest was signed with, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the OID is not an ML-DSA
+    /// key, `QubitCryptError::InvalidOid` if the hash function is not permitted by FIPS 204,
+    /// `QubitCryptError::InvalidContent` if the digest doesn't have the length of the hash
+    /// function, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    pub fn verify_prehashed(
+        &self,
+        digest: &[u8],
+        hash_oid: &str,
+        signature: &[u8],
+        ctx: &[u8],
+    ) -> Result<bool> {
+        if !is_dsa_oid(&self.oid) {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
+
+        match DsaManager::new_from_oid(&self.oid)?.verify_prehashed(
+            self.get_key(),
+            digest,
+            hash_oid,
+            signature,
+            ctx,
+        ) {
+            Err(
+                err @ (QubitCryptError::UnsupportedOperation
+                | QubitCryptError::InvalidOid
+                | QubitCryptError::InvalidContent
+                | QubitCryptError::InvalidContext),
+            ) => Err(err),
+            result => Ok(result.unwrap_or(false)),
+        }
+    }
+
+    /// Verify a signature over the content of a reader, e.g. a large file
+    ///
+    /// ECDSA and RSA hash the content in chunks as it's read, so it's never held in memory.
+    /// ML-DSA, SLH-DSA, EdDSA and the composites sign the message itself and their
+    /// implementations have no incremental verification, so for them the content is read into
+    /// memory before it's verified.
+    ///
+    /// For these algorithms, memory use grows with the content, and there's no limit other
+    /// than the available memory: a 4 GiB file needs 4 GiB, and a reader which never ends
+    /// never returns. ML-DSA could in principle absorb the message into μ (FIPS 204,
+    /// Algorithm 8) as it's read, but the ML-DSA implementation only verifies whole messages.
+    /// Content of unbounded or untrusted size should be signed with HashML-DSA instead, whose
+    /// digest the caller can compute in chunks, see `verify_prehashed`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader of the message to verify
+    /// * `signature` - The signature
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the OID is not a DSA key,
+    /// `QubitCryptError::FileReadError` if the reader fails
+    pub fn verify_reader(&self, reader: &mut impl Read, signature: &[u8]) -> Result<bool> {
+        // Check if this is a DSA key
+        if !is_dsa_oid(&self.oid) {
+            return Err(errors::QubitCryptError::UnsupportedOperation);
+        }
+
+        let dsa =
+            DsaManager::new_from_oid(&self.oid).map_err(|_| errors::QubitCryptError::InvalidOid)?;
+
+        match dsa.verify_reader(self.get_key(), reader, signature) {
+            Err(QubitCryptError::FileReadError) => Err(QubitCryptError::FileReadError),
+            result => Ok(result.unwrap_or(false)),
+        }
+    }
+
     /// Encapsulate to get a shared secret and a ciphertext based on this public key
     ///
     /// # Returns
     ///
-    /// A tuple containing the ciphertext and the shared secret (ct, ss)
+    /// A tuple containing the shared secret and the ciphertext (ss, ct)
     pub fn encap(&self) -> Result<(Vec<u8>, Vec<u8>)> {
         // Check if this is a KEM key
         if !is_kem_oid(&self.oid) {
@@ -274,9 +526,29 @@ impl PublicKey {
         let mut kem =
             KemManager::new_from_oid(&self.oid).map_err(|_| errors::QubitCryptError::InvalidOid)?;
 
-        let (ct, ss) = kem.encap(self.get_key())?;
+        let (ss, ct) = kem.encap(self.get_key())?;
+
+        Ok((ss, ct))
+    }
+
+    /// Encapsulate to get a shared secret and a ciphertext, returning the shared secret as a
+    /// `SharedSecret`
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and the ciphertext (ss, ct)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if this public key is not a KEM key
+    pub fn encap_typed(&self) -> Result<(SharedSecret, Vec<u8>)> {
+        if !is_kem_oid(&self.oid) {
+            return Err(errors::QubitCryptError::UnsupportedOperation);
+        }
 
-        Ok((ct, ss))
+        let mut kem =
+            KemManager::new_from_oid(&self.oid).map_err(|_| errors::QubitCryptError::InvalidOid)?;
+        kem.encap_typed(self.get_key())
     }
 }
 
@@ -294,8 +566,75 @@ impl EncodePublicKey for PublicKey {
 mod test {
     use crate::dsa::common::config::oids::Oid;
     use crate::dsa::common::dsa_type::DsaType;
+    use crate::dsa::dsa_manager::DsaManager;
 
     use super::*;
+    use crate::asn1::macros::test_asn1_roundtrip;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    #[test]
+    fn test_public_key_roundtrip() {
+        let (pk_dsa, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (pk_comp_dsa, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44EcdsaP256SHA256)
+            .generate()
+            .unwrap();
+        let (pk_kem, _) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let (pk_comp_kem, _) = KemKeyGenerator::new(KemAlgorithm::MlKem768X25519)
+            .generate()
+            .unwrap();
+        for pk in [pk_dsa, pk_comp_dsa, pk_kem, pk_comp_kem] {
+            test_asn1_roundtrip!(PublicKey, pk);
+        }
+        let pem_bytes = include_bytes!("../../test/data/mldsa44_ecdsa_p256_sha256_pk.pem");
+        let pk = PublicKey::from_pem(std::str::from_utf8(pem_bytes).unwrap()).unwrap();
+        test_asn1_roundtrip!(PublicKey, pk);
+    }
+
+    #[test]
+    fn test_pk_null_params() {
+        let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let der = pk.to_der().unwrap();
+        let der_null = pk.to_der_with_null_params().unwrap();
+        assert_eq!(der_null.len(), der.len() + 2);
+
+        let spki = SubjectPublicKeyInfoOwned::from_der(&der).unwrap();
+        assert!(spki.algorithm.parameters.is_none());
+        let spki_null = SubjectPublicKeyInfoOwned::from_der(&der_null).unwrap();
+        assert_eq!(spki_null.algorithm.oid, spki.algorithm.oid);
+        assert_eq!(spki_null.algorithm.parameters, Some(der::Any::null()));
+        assert_eq!(spki_null.subject_public_key, spki.subject_public_key);
+
+        // The conformant parser rejects the parameters
+        assert!(matches!(
+            PublicKey::from_der(&der_null),
+            Err(QubitCryptError::InvalidPublicKey)
+        ));
+    }
+
+    #[test]
+    fn test_pk_unused_bits() {
+        let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut pk_info = PublicKeyInfo::from_der(&pk.to_der().unwrap()).unwrap();
+
+        // A buggy encoder which marks the last bit of the key as unused
+        let mut key = pk.get_key().to_vec();
+        *key.last_mut().unwrap() &= 0xfe;
+        pk_info.public_key = BitString::new(1, key).unwrap();
+        let der = pk_info.to_der().unwrap();
+        assert!(matches!(
+            PublicKey::from_der(&der),
+            Err(errors::QubitCryptError::UnusedKeyBits(1))
+        ));
+    }
 
     #[test]
     fn test_composite_public_key() {
@@ -307,7 +646,7 @@ mod test {
         assert_eq!(pk.get_oid(), DsaType::MlDsa44EcdsaP256SHA256.get_oid());
 
         let key_bytes = pk.get_key();
-        let pk2 = CompositePublicKey::from_der(&pk.oid, &key_bytes).unwrap();
+        let pk2 = CompositePublicKey::from_der(&pk.oid, key_bytes).unwrap();
 
         assert_eq!(pk.oid, pk2.get_oid());
 
@@ -319,6 +658,56 @@ mod test {
         assert_eq!(pk.oid, oid);
     }
 
+    #[test]
+    fn test_pk_equals() {
+        let pem_bytes = include_bytes!("../../test/data/mldsa44_ecdsa_p256_sha256_pk.pem");
+        let pem = std::str::from_utf8(pem_bytes).unwrap().trim();
+        let pk = PublicKey::from_pem(pem).unwrap();
+
+        // Same key read through a different encoding
+        let pk2 = PublicKey::from_der(&pk.to_der().unwrap()).unwrap();
+        assert!(pk.equals(&pk2));
+
+        let c_pk = CompositePublicKey::from_der(pk.get_oid(), pk.get_key()).unwrap();
+        let pk3 = PublicKey::from_composite(&c_pk).unwrap();
+        assert!(pk.equals(&pk3));
+
+        // A different traditional component
+        let mut trad_pk = c_pk.get_trad_pk();
+        trad_pk[1] ^= 0x01;
+        let c_pk = CompositePublicKey::new(pk.get_oid(), &c_pk.get_pq_pk(), &trad_pk);
+        let pk4 = PublicKey::from_composite(&c_pk).unwrap();
+        assert!(!pk.equals(&pk4));
+
+        // Same key material with a different algorithm
+        let pk5 = PublicKey::new(&DsaType::MlDsa44Ed25519SHA512.get_oid(), pk.get_key()).unwrap();
+        assert!(!pk.equals(&pk5));
+    }
+
+    #[test]
+    fn test_pk_encoding_error_reason() {
+        let pk = PublicKey {
+            oid: "not-an-oid".to_string(),
+            key: vec![0u8; 32],
+            is_composite: false,
+        };
+
+        let err = pk.to_der().unwrap_err();
+        assert!(matches!(
+            err,
+            errors::QubitCryptError::PublicKeyEncodingFailed(ref reason) if reason.contains("invalid OID")
+        ));
+
+        let err = pk.to_spki().unwrap_err();
+        assert!(matches!(
+            err,
+            errors::QubitCryptError::PublicKeyEncodingFailed(_)
+        ));
+
+        // The spki trait can only report a generic error
+        assert!(pk.to_public_key_der().is_err());
+    }
+
     #[test]
     fn test_pk_no_headers() {
         let pem_bytes = include_bytes!("../../test/data/bad/no_headers.pem");
@@ -383,4 +772,59 @@ mod test {
             errors::QubitCryptError::InvalidPublicKey
         ));
     }
+
+    #[test]
+    fn test_security_category() {
+        use crate::dsa::common::dsa_info::DsaInfo;
+        use crate::kem::common::kem_info::KemInfo;
+        use crate::kem::common::kem_type::KemType;
+
+        assert_eq!(DsaInfo::new(DsaType::MlDsa44).security_category(), 2);
+        assert_eq!(
+            DsaInfo::new(DsaType::SlhDsaShake256f).security_category(),
+            5
+        );
+        assert_eq!(KemInfo::new(KemType::MlKem1024).security_category(), 5);
+        // A composite takes the category of its post-quantum component
+        assert_eq!(
+            DsaInfo::new(DsaType::MlDsa87EcdsaP384SHA512).security_category(),
+            5
+        );
+        assert_eq!(KemInfo::new(KemType::MlKem768X25519).security_category(), 3);
+        assert_eq!(KemInfo::new(KemType::XWing).security_category(), 3);
+        // Traditional algorithms have no post-quantum security
+        assert_eq!(DsaInfo::new(DsaType::Ed448SHA512).security_category(), 0);
+        assert_eq!(KemInfo::new(KemType::X448).security_category(), 0);
+
+        let (pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        assert_eq!(pk.security_category(), 2);
+        assert!(pk.require_security_category(2).is_ok());
+        assert!(matches!(
+            pk.require_security_category(3),
+            Err(QubitCryptError::InsufficientSecurityCategory)
+        ));
+
+        let (pk, _) = KemKeyGenerator::new(KemAlgorithm::MlKem768P384)
+            .generate()
+            .unwrap();
+        assert_eq!(pk.security_category(), 3);
+        assert!(pk.require_security_category(3).is_ok());
+
+        let mut dsa = DsaManager::new(DsaType::EcdsaP384SHA512).unwrap();
+        let (pk, _) = dsa.key_gen().unwrap();
+        // `PublicKey::new` only takes the OIDs of the exported algorithms, so the standalone
+        // ECDSA key is built directly
+        let pk = PublicKey {
+            oid: DsaType::EcdsaP384SHA512.get_oid(),
+            key: pk,
+            is_composite: false,
+        };
+        assert_eq!(pk.security_category(), 0);
+        assert!(matches!(
+            pk.require_security_category(1),
+            Err(QubitCryptError::InsufficientSecurityCategory)
+        ));
+    }
 }
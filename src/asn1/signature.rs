@@ -1,11 +1,101 @@
 use der::asn1::BitString;
+use der::{Decode, Encode};
 use pkcs8::spki::SignatureBitStringEncoding;
 
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
 /// Struct representing DSA signatures
+///
+/// The wrapped bytes are the raw signature as produced by the DSA. For composite
+/// DSAs this is the DER encoded CompositeSignatureValue.
+#[derive(Clone, Debug, PartialEq)]
 pub struct DsaSignature(pub Vec<u8>);
 
+impl DsaSignature {
+    /// Get the raw signature bytes
+    ///
+    /// # Returns
+    ///
+    /// The raw signature bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Encode the signature as a DER BIT STRING, as it appears in a certificate
+    ///
+    /// # Returns
+    ///
+    /// The DER encoded signature
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::SerializationFailed` if the signature cannot be encoded
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        self.to_bitstring()
+            .and_then(|bs| bs.to_der())
+            .map_err(|_| QubitCryptError::SerializationFailed)
+    }
+
+    /// Decode a signature from a DER BIT STRING
+    ///
+    /// # Arguments
+    ///
+    /// * `der` - The DER encoded signature
+    ///
+    /// # Returns
+    ///
+    /// The signature
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidSignature` if the bytes are not a DER BIT STRING,
+    /// or the BIT STRING has unused bits
+    pub fn from_der(der: &[u8]) -> Result<Self> {
+        let bs = BitString::from_der(der).map_err(|_| QubitCryptError::InvalidSignature)?;
+        let sig = bs.as_bytes().ok_or(QubitCryptError::InvalidSignature)?;
+        Ok(DsaSignature(sig.to_vec()))
+    }
+}
+
 impl SignatureBitStringEncoding for DsaSignature {
-    fn to_bitstring(&self) -> Result<BitString, der::Error> {
+    fn to_bitstring(&self) -> std::result::Result<BitString, der::Error> {
         BitString::from_bytes(&self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::Certificate;
+
+    #[test]
+    fn test_dsa_signature_der_roundtrip() {
+        let cert = Certificate::from_file(
+            "test/data/MlDsa44EcdsaP256SHA256-2.16.840.1.114027.80.8.1.4_ta.pem",
+        )
+        .unwrap();
+
+        let sig = cert.get_signature();
+        let der = sig.to_der().unwrap();
+        let sig2 = DsaSignature::from_der(&der).unwrap();
+        assert_eq!(sig, sig2);
+
+        // The signature can be verified independently of the certificate
+        let pem = std::fs::read_to_string(
+            "test/data/MlDsa44EcdsaP256SHA256-2.16.840.1.114027.80.8.1.4_ta.pem",
+        )
+        .unwrap();
+        let x509 = <x509_cert::Certificate as der::DecodePem>::from_pem(&pem).unwrap();
+        assert_eq!(der, x509.signature.to_der().unwrap());
+        let tbs = x509.tbs_certificate.to_der().unwrap();
+        let pk = cert.get_public_key().unwrap();
+        assert!(pk.verify(&tbs, sig2.as_bytes()).unwrap());
+
+        assert!(matches!(
+            DsaSignature::from_der(&[0x04, 0x01, 0x00]),
+            Err(QubitCryptError::InvalidSignature)
+        ));
+    }
+}
@@ -3,6 +3,7 @@ use crate::cea::common::cea_info::CeaInfo;
 use crate::cea::common::cea_trait::Cea;
 use crate::cea::common::cea_type::CeaType;
 use crate::cea::common::config::oids::Oid;
+use crate::utils::rng::rng_from_default_source;
 use crate::QubitCryptError;
 use cms::enveloped_data::EncryptedContentInfo;
 use const_oid::db::rfc5911::ID_DATA;
@@ -10,6 +11,7 @@ use der::asn1::{ObjectIdentifier, OctetString};
 use der::Decode;
 use der::Encode;
 use pkcs8::spki::AlgorithmIdentifierOwned;
+use rand_core::RngCore;
 
 use openssl::symm::{decrypt, decrypt_aead, encrypt, encrypt_aead, Cipher};
 
@@ -23,7 +25,20 @@ pub struct Aes {
 impl Aes {
     /// Get the OpenSSL cipher for the CEA type
     fn get_cipher(&self) -> Cipher {
-        match self.cea_type {
+        Self::cipher(&self.cea_type)
+    }
+
+    /// Get the OpenSSL cipher for a CEA type
+    ///
+    /// # Arguments
+    ///
+    /// * `cea_type` - The type of CEA
+    ///
+    /// # Returns
+    ///
+    /// The OpenSSL cipher
+    pub(crate) fn cipher(cea_type: &CeaType) -> Cipher {
+        match cea_type {
             CeaType::Aes128Gcm => Cipher::aes_128_gcm(),
             CeaType::Aes192Gcm => Cipher::aes_192_gcm(),
             CeaType::Aes256Gcm => Cipher::aes_256_gcm(),
@@ -47,12 +62,12 @@ impl Aes {
         let nonce_len = cipher.iv_len().unwrap_or(0);
         let nonce = if let Some(nonce) = nonce {
             if nonce.len() != nonce_len {
-                return Err(QubitCryptError::Unknown);
+                return Err(QubitCryptError::InvalidAesNonce);
             }
             nonce.to_vec()
         } else {
             let mut nonce = vec![0u8; nonce_len];
-            openssl::rand::rand_bytes(&mut nonce).map_err(|_| QubitCryptError::Unknown)?;
+            openssl::rand::rand_bytes(&mut nonce).map_err(|_| QubitCryptError::RngFailed)?;
             nonce
         };
         Ok(nonce)
@@ -74,24 +89,24 @@ impl Aes {
             .cea_type
             .get_oid()
             .parse()
-            .map_err(|_| QubitCryptError::Unknown)?;
+            .map_err(|_| QubitCryptError::InvalidOid)?;
 
         let parameters = match self.cea_type {
             CeaType::Aes128Gcm | CeaType::Aes192Gcm | CeaType::Aes256Gcm => {
-                AesParameters::new(nonce, 16)
-                    .map_err(|_| QubitCryptError::Unknown)?
+                AesParameters::new(nonce, 16)?
                     .to_der()
-                    .map_err(|_| QubitCryptError::Unknown)?
+                    .map_err(|_| QubitCryptError::SerializationFailed)?
             }
             CeaType::Aes128CbcPad | CeaType::Aes192CbcPad | CeaType::Aes256CbcPad => {
                 OctetString::new(nonce.to_vec())
-                    .map_err(|_| QubitCryptError::Unknown)?
+                    .map_err(|_| QubitCryptError::InvalidAesNonce)?
                     .to_der()
-                    .map_err(|_| QubitCryptError::Unknown)?
+                    .map_err(|_| QubitCryptError::SerializationFailed)?
             }
         };
 
-        let parameters = der::Any::from_der(&parameters).map_err(|_| QubitCryptError::Unknown)?;
+        let parameters =
+            der::Any::from_der(&parameters).map_err(|_| QubitCryptError::SerializationFailed)?;
         let enc_algorithm = AlgorithmIdentifierOwned {
             oid,
             parameters: Some(parameters),
@@ -102,14 +117,15 @@ impl Aes {
             ID_DATA
         };
 
-        let ct_oct_str = OctetString::new(ct.to_vec()).map_err(|_| QubitCryptError::Unknown)?;
+        let ct_oct_str =
+            OctetString::new(ct.to_vec()).map_err(|_| QubitCryptError::SerializationFailed)?;
         let enc = EncryptedContentInfo {
             content_type: cid,
             content_enc_alg: enc_algorithm,
             encrypted_content: Some(ct_oct_str),
         }
         .to_der()
-        .map_err(|_| QubitCryptError::Unknown)?;
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
         Ok(enc)
     }
 
@@ -170,6 +186,88 @@ impl Aes {
 
         Ok((alg, nonce, ct.as_bytes().to_vec()))
     }
+
+    /// Encrypt a message with AES-GCM and return the raw ciphertext and tag, without wrapping
+    /// them in an EncryptedContentInfo object
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use for encryption
+    /// * `nonce` - The nonce to use for encryption
+    /// * `plaintext` - The plaintext to encrypt
+    /// * `aad` - The additional authenticated data to use
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the ciphertext and the tag
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the CEA is not AES-GCM,
+    /// `QubitCryptError::InvalidContentEncryptionKey` if the key doesn't have the length of the
+    /// CEA, `QubitCryptError::InvalidAesNonce` if the nonce doesn't have the length of the CEA,
+    /// `QubitCryptError::CeaError` if the encryption fails
+    pub(crate) fn encrypt_detached(
+        &self,
+        key: &[u8],
+        nonce: &[u8],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let cipher = self.get_aead_cipher(key)?;
+        let nonce = self.get_nonce(&cipher, Some(nonce))?;
+        let mut tag = vec![0u8; 16];
+        let ct = encrypt_aead(cipher, key, Some(&nonce), aad, plaintext, &mut tag)
+            .map_err(|_| QubitCryptError::CeaError)?;
+        Ok((ct, tag))
+    }
+
+    /// Decrypt a raw ciphertext and tag created by `encrypt_detached`
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to use for decryption
+    /// * `nonce` - The nonce the message was encrypted with
+    /// * `ciphertext` - The ciphertext to decrypt
+    /// * `tag` - The tag of the ciphertext
+    /// * `aad` - The additional authenticated data to use
+    ///
+    /// # Returns
+    ///
+    /// The decrypted plaintext
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `encrypt_detached`, except that `QubitCryptError::InvalidCiphertext`
+    /// will be returned if the ciphertext fails to decrypt
+    pub(crate) fn decrypt_detached(
+        &self,
+        key: &[u8],
+        nonce: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        let cipher = self.get_aead_cipher(key)?;
+        let nonce = self.get_nonce(&cipher, Some(nonce))?;
+        decrypt_aead(cipher, key, Some(&nonce), aad, ciphertext, tag)
+            .map_err(|_| QubitCryptError::InvalidCiphertext)
+    }
+
+    /// Get the OpenSSL cipher for an AES-GCM CEA type and check the key length
+    fn get_aead_cipher(&self, key: &[u8]) -> Result<Cipher> {
+        if !matches!(
+            self.cea_type,
+            CeaType::Aes128Gcm | CeaType::Aes192Gcm | CeaType::Aes256Gcm
+        ) {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
+        let cipher = self.get_cipher();
+        if key.len() != cipher.key_len() {
+            return Err(QubitCryptError::InvalidContentEncryptionKey);
+        }
+        Ok(cipher)
+    }
 }
 
 impl Cea for Aes {
@@ -193,6 +291,9 @@ impl Cea for Aes {
         content_type_oid: Option<&str>,
     ) -> Result<(Vec<u8>, Vec<u8>)> {
         let cipher = self.get_cipher();
+        if key.len() != cipher.key_len() {
+            return Err(QubitCryptError::InvalidContentEncryptionKey);
+        }
         let nonce = self.get_nonce(&cipher, nonce)?;
 
         let mut tag = [0u8; 16];
@@ -201,12 +302,12 @@ impl Cea for Aes {
         let (ct, tag) = match self.cea_type {
             CeaType::Aes128Gcm | CeaType::Aes192Gcm | CeaType::Aes256Gcm => (
                 encrypt_aead(cipher, key, Some(&nonce), aad, plaintext, &mut tag)
-                    .map_err(|_| QubitCryptError::Unknown)?,
+                    .map_err(|_| QubitCryptError::CeaError)?,
                 tag.to_vec(),
             ),
             CeaType::Aes128CbcPad | CeaType::Aes192CbcPad | CeaType::Aes256CbcPad => (
                 encrypt(cipher, key, Some(&nonce), plaintext)
-                    .map_err(|_| QubitCryptError::Unknown)?,
+                    .map_err(|_| QubitCryptError::CeaError)?,
                 nonce.clone(),
             ),
         };
@@ -226,6 +327,9 @@ impl Cea for Aes {
             CeaType::Aes192CbcPad => Cipher::aes_192_cbc(),
             CeaType::Aes256CbcPad => Cipher::aes_256_cbc(),
         };
+        if key.len() != cipher.key_len() {
+            return Err(QubitCryptError::InvalidContentEncryptionKey);
+        }
 
         let aad = aad.unwrap_or(&[]);
 
@@ -245,7 +349,11 @@ impl Cea for Aes {
         let cipher = self.get_cipher();
         let key_len = cipher.key_len();
         let mut key = vec![0u8; key_len];
-        openssl::rand::rand_bytes(&mut key).map_err(|_| QubitCryptError::Unknown)?;
+        if let Some(mut rng) = rng_from_default_source()? {
+            rng.fill_bytes(&mut key);
+            return Ok(key);
+        }
+        openssl::rand::rand_bytes(&mut key).map_err(|_| QubitCryptError::RngFailed)?;
         Ok(key)
     }
 
@@ -278,4 +386,33 @@ mod tests {
         let mut cea = Aes::new(CeaType::Aes192Gcm).unwrap();
         test_cea!(cea);
     }
+
+    #[test]
+    fn test_errors() {
+        let mut cea = Aes::new(CeaType::Aes256Gcm).unwrap();
+        let key = cea.key_gen().unwrap();
+        let plaintext = b"Hello, world!";
+
+        // The failures point at the key and the nonce, not at the encryption
+        assert_eq!(
+            cea.encrypt(&key[..16], None, plaintext, None, None),
+            Err(QubitCryptError::InvalidContentEncryptionKey)
+        );
+        assert_eq!(
+            cea.encrypt(&key, Some(&[0u8; 16]), plaintext, None, None),
+            Err(QubitCryptError::InvalidAesNonce)
+        );
+
+        let (tag, ct) = cea.encrypt(&key, None, plaintext, None, None).unwrap();
+        assert_eq!(
+            Aes::decrypt(&key[..16], &tag, &ct, None),
+            Err(QubitCryptError::InvalidContentEncryptionKey)
+        );
+        let mut wrong_key = key.clone();
+        wrong_key[0] ^= 1;
+        assert_eq!(
+            Aes::decrypt(&wrong_key, &tag, &ct, None),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+    }
 }
@@ -24,6 +24,39 @@ pub enum CeaManager {
     Aes(Aes),
 }
 
+impl CeaManager {
+    /// Encrypt a message with an AEAD and return the raw ciphertext and tag
+    ///
+    /// See `Aes::encrypt_detached`
+    pub(crate) fn encrypt_detached(
+        &self,
+        key: &[u8],
+        nonce: &[u8],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        match self {
+            CeaManager::Aes(aes) => aes.encrypt_detached(key, nonce, plaintext, aad),
+        }
+    }
+
+    /// Decrypt a raw ciphertext and tag created by `encrypt_detached`
+    ///
+    /// See `Aes::decrypt_detached`
+    pub(crate) fn decrypt_detached(
+        &self,
+        key: &[u8],
+        nonce: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            CeaManager::Aes(aes) => aes.decrypt_detached(key, nonce, ciphertext, tag, aad),
+        }
+    }
+}
+
 impl Cea for CeaManager {
     fn new(cea_type: CeaType) -> Result<Self>
     where
@@ -84,6 +117,45 @@ mod tests {
         for cea_type in CEA_TYPES.iter() {
             let mut cea = CeaManager::new(cea_type.clone()).unwrap();
             test_cea!(cea);
+
+            let cea_info = cea.get_cea_info();
+            assert_eq!(cea.key_gen().unwrap().len(), cea_info.key_byte_len);
+            assert_eq!(cea.nonce_gen().unwrap().len(), cea_info.nonce_byte_len);
         }
     }
+
+    #[test]
+    fn test_detached() {
+        let mut cea = CeaManager::new(CeaType::Aes256Gcm).unwrap();
+        let key = cea.key_gen().unwrap();
+        let nonce = cea.nonce_gen().unwrap();
+        let (ct, tag) = cea
+            .encrypt_detached(&key, &nonce, b"Hello, world!", b"aad")
+            .unwrap();
+        assert_eq!(ct.len(), 13);
+        assert_eq!(
+            cea.decrypt_detached(&key, &nonce, &ct, &tag, b"aad")
+                .unwrap(),
+            b"Hello, world!"
+        );
+        assert_eq!(
+            cea.decrypt_detached(&key, &nonce, &ct, &tag, b"other"),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+        assert_eq!(
+            cea.encrypt_detached(&key[..16], &nonce, b"", b""),
+            Err(QubitCryptError::InvalidContentEncryptionKey)
+        );
+        assert_eq!(
+            cea.encrypt_detached(&key, &nonce[..8], b"", b""),
+            Err(QubitCryptError::InvalidAesNonce)
+        );
+
+        // CBC has no tag
+        let cea = CeaManager::new(CeaType::Aes256CbcPad).unwrap();
+        assert_eq!(
+            cea.encrypt_detached(&key, &nonce, b"", b""),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::cea::aes::Aes;
 use crate::cea::common::cea_type::CeaType;
 use crate::cea::common::config::oids::Oid;
 
@@ -11,6 +12,10 @@ pub struct CeaInfo {
     pub oid: String,
     /// Does it support AAD
     pub is_aad_supported: bool,
+    /// The length of the key in bytes
+    pub key_byte_len: usize,
+    /// The length of the IV or nonce in bytes
+    pub nonce_byte_len: usize,
 }
 
 impl CeaInfo {
@@ -30,10 +35,17 @@ impl CeaInfo {
             CeaType::Aes128Gcm | CeaType::Aes192Gcm | CeaType::Aes256Gcm
         );
 
+        // The lengths of the cipher which encrypts the content
+        let cipher = Aes::cipher(&cea_type);
+        let key_byte_len = cipher.key_len();
+        let nonce_byte_len = cipher.iv_len().unwrap_or(0);
+
         CeaInfo {
             cea_type,
             oid,
             is_aad_supported,
+            key_byte_len,
+            nonce_byte_len,
         }
     }
 }
@@ -67,6 +67,12 @@ pub trait Cea {
     /// # Returns
     ///
     /// A tuple containing the tag and the ciphertext (DER encoded bytes of a EncryptedContentInfo object)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContentEncryptionKey` will be returned if the key doesn't have
+    /// the length of the CEA, `QubitCryptError::InvalidAesNonce` if the nonce doesn't have
+    /// the length of the CEA, `QubitCryptError::CeaError` if the encryption fails
     fn encrypt(
         &self,
         key: &[u8],
@@ -89,5 +95,11 @@ pub trait Cea {
     /// # Returns
     ///
     /// The decrypted plaintext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContentEncryptionKey` will be returned if the key doesn't have
+    /// the length of the CEA, `QubitCryptError::InvalidCiphertext` if the ciphertext is
+    /// malformed or fails to decrypt, e.g. because the key or the tag is wrong
     fn decrypt(key: &[u8], tag: &[u8], ciphertext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>>;
 }
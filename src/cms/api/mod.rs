@@ -1,12 +1,20 @@
 pub use crate::cms::asn1::auth_enveloped_data_builder::ContentEncryptionAlgorithmAead;
 pub use crate::cms::asn1::auth_enveloped_data_content::AuthEnvelopedDataContent;
+pub use crate::cms::asn1::enveloped_content::EnvelopedContent;
 pub use crate::cms::asn1::enveloped_data_content::ContentEncryptionAlgorithm;
 pub use crate::cms::asn1::enveloped_data_content::EnvelopedDataContent;
+pub use crate::cms::asn1::kemri::KemRecipientInfo;
+pub use crate::cms::asn1::recipient_summary::RecipientSummary;
+pub use crate::cms::asn1::recipient_summary::RecipientType;
+pub use crate::cms::asn1::signed_data_builder::DigestAlgorithm;
+pub use crate::cms::asn1::signed_data_content::SignedDataContent;
 pub use crate::cms::cert_store_trait::CertificateStore;
+pub use crate::cms::cms_util::CmsUtil;
 pub use crate::cms::directory_cert_store::DirectoryCertificateStore;
 pub use crate::kdf::api::KdfType;
 pub use crate::wrap::api::WrapType;
 pub use cms::content_info::CmsVersion;
+pub use cms::enveloped_data::RecipientInfo;
 pub use cms::enveloped_data::UserKeyingMaterial;
 pub use der::asn1::SetOfVec;
 pub use der::Tag;
@@ -0,0 +1,120 @@
+//! The CMS algorithm protection attribute
+//! ([RFC 6211](https://datatracker.ietf.org/doc/html/rfc6211))
+
+use der::asn1::{ObjectIdentifier, SetOfVec};
+use der::Sequence;
+use spki::AlgorithmIdentifierOwned;
+use x509_cert::attr::{Attribute, AttributeValue, Attributes};
+
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The OID of the CMS algorithm protection attribute (`id-aa-CMSAlgorithmProtection`)
+pub const ID_AA_CMS_ALGORITHM_PROTECTION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.52");
+
+/// The `CMSAlgorithmProtection` type is defined in [RFC 6211 Section 2]
+/// ```text
+///   CMSAlgorithmProtection ::= SEQUENCE {
+///     digestAlgorithm         DigestAlgorithmIdentifier,
+///     signatureAlgorithm  [1] SignatureAlgorithmIdentifier OPTIONAL,
+///     macAlgorithm        [2] MessageAuthenticationCodeAlgorithm
+///                                      OPTIONAL
+///   }
+///   (WITH COMPONENTS { signatureAlgorithm PRESENT,
+///                      macAlgorithm ABSENT } |
+///    WITH COMPONENTS { signatureAlgorithm ABSENT,
+///                      macAlgorithm PRESENT })
+/// ```
+/// [RFC 6211 Section 2]: https://datatracker.ietf.org/doc/html/rfc6211#section-2
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+#[allow(missing_docs)]
+pub struct CmsAlgorithmProtection {
+    pub digest_algorithm: AlgorithmIdentifierOwned,
+    #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+    pub signature_algorithm: Option<AlgorithmIdentifierOwned>,
+    #[asn1(context_specific = "2", tag_mode = "IMPLICIT", optional = "true")]
+    pub mac_algorithm: Option<AlgorithmIdentifierOwned>,
+}
+
+/// Create the algorithm protection attribute of a signer
+///
+/// Pure ML-DSA and SLH-DSA have no separate digest step, but a signer with signed attributes
+/// still has a digest algorithm: the one of the message-digest attribute. That's the digest
+/// algorithm which is protected, while the signature algorithm is the one in the SignerInfo,
+/// e.g. `id-ml-dsa-65` with absent parameters.
+///
+/// # Arguments
+///
+/// * `digest_algorithm` - The digest algorithm of the SignerInfo
+/// * `signature_algorithm` - The signature algorithm of the SignerInfo
+///
+/// # Returns
+///
+/// The attribute, to be added to the signed attributes
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidAttribute` will be returned if the attribute can't be encoded
+pub(crate) fn create_algorithm_protection_attribute(
+    digest_algorithm: &AlgorithmIdentifierOwned,
+    signature_algorithm: &AlgorithmIdentifierOwned,
+) -> Result<Attribute> {
+    let protection = CmsAlgorithmProtection {
+        digest_algorithm: digest_algorithm.clone(),
+        signature_algorithm: Some(signature_algorithm.clone()),
+        mac_algorithm: None,
+    };
+    let value =
+        AttributeValue::encode_from(&protection).map_err(|_| QubitCryptError::InvalidAttribute)?;
+    let mut values = SetOfVec::new();
+    values
+        .insert(value)
+        .map_err(|_| QubitCryptError::InvalidAttribute)?;
+    Ok(Attribute {
+        oid: ID_AA_CMS_ALGORITHM_PROTECTION,
+        values,
+    })
+}
+
+/// Check the algorithm protection attribute of a signer, if there is one
+///
+/// The attribute is optional, but if it's present it must be present once, with a single
+/// value, and its algorithms must be the ones of the SignerInfo. A value with a MAC algorithm
+/// is for AuthenticatedData and doesn't match a signer.
+///
+/// # Arguments
+///
+/// * `signed_attrs` - The signed attributes
+/// * `digest_algorithm` - The digest algorithm of the SignerInfo
+/// * `signature_algorithm` - The signature algorithm of the SignerInfo
+///
+/// # Returns
+///
+/// True if the attribute is absent or matches, false otherwise
+pub(crate) fn check_algorithm_protection(
+    signed_attrs: &Attributes,
+    digest_algorithm: &AlgorithmIdentifierOwned,
+    signature_algorithm: &AlgorithmIdentifierOwned,
+) -> bool {
+    let mut attrs = signed_attrs
+        .iter()
+        .filter(|attr| attr.oid == ID_AA_CMS_ALGORITHM_PROTECTION);
+    let attr = match (attrs.next(), attrs.next()) {
+        (None, _) => return true,
+        (Some(attr), None) if attr.values.len() == 1 => attr,
+        _ => return false,
+    };
+
+    let Some(Ok(protection)) = attr
+        .values
+        .get(0)
+        .map(|value| value.decode_as::<CmsAlgorithmProtection>())
+    else {
+        return false;
+    };
+    protection.digest_algorithm == *digest_algorithm
+        && protection.signature_algorithm.as_ref() == Some(signature_algorithm)
+        && protection.mac_algorithm.is_none()
+}
@@ -11,9 +11,10 @@ use const_oid::db::rfc5911::{ID_AES_128_GCM, ID_AES_192_GCM, ID_AES_256_GCM};
 use der::{Decode, Encode};
 use spki::ObjectIdentifier;
 use x509_cert::attr::Attributes;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::cea::cea_manager::CeaManager;
+use crate::cea::common::cea_info::CeaInfo;
 use crate::cea::common::cea_type::CeaType;
 use crate::QubitCryptError;
 
@@ -21,6 +22,9 @@ use crate::cms::asn1::auth_env_data::AuthEnvelopedData;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
+/// The length of the GCM authentication tag
+const GCM_TAG_LEN: usize = 16;
+
 /// Content encryption algorithm for AuthEnvelopedData
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ContentEncryptionAlgorithmAead {
@@ -50,6 +54,8 @@ pub struct AuthEnvelopedDataBuilder<'c> {
     content_encryption_algorithm: ContentEncryptionAlgorithmAead,
     auth_attributes: Option<Attributes>,
     unauth_attributes: Option<Attributes>,
+    content_encryption_key: Option<Zeroizing<Vec<u8>>>,
+    nonce: Option<Vec<u8>>,
 }
 
 impl ContentEncryptionAlgorithmAead {
@@ -61,6 +67,93 @@ impl ContentEncryptionAlgorithmAead {
             ContentEncryptionAlgorithmAead::Aes256Gcm => ID_AES_256_GCM,
         }
     }
+
+    /// Get the length of the encrypted content for a plaintext of the given length
+    ///
+    /// GCM does not pad, so this is the length of the plaintext. The 16 byte
+    /// authentication tag is carried separately in the `mac` field of the
+    /// AuthEnvelopedData, and the nonce in the algorithm parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `plaintext_len` - The length of the plaintext in bytes
+    ///
+    /// # Returns
+    ///
+    /// The length of the encrypted content in bytes
+    pub fn ciphertext_len(&self, plaintext_len: usize) -> usize {
+        plaintext_len
+    }
+
+    /// Get the content encryption algorithm type of the algorithm
+    pub(crate) fn cea_type(&self) -> CeaType {
+        match self {
+            ContentEncryptionAlgorithmAead::Aes128Gcm => CeaType::Aes128Gcm,
+            ContentEncryptionAlgorithmAead::Aes192Gcm => CeaType::Aes192Gcm,
+            ContentEncryptionAlgorithmAead::Aes256Gcm => CeaType::Aes256Gcm,
+        }
+    }
+
+    /// Get the length of the key in bytes
+    pub fn key_len(&self) -> usize {
+        CeaInfo::new(self.cea_type()).key_byte_len
+    }
+
+    /// Get the length of the nonce in bytes, which is the recommended 96 bits for GCM
+    /// (NIST SP 800-38D)
+    pub fn nonce_len(&self) -> usize {
+        CeaInfo::new(self.cea_type()).nonce_byte_len
+    }
+
+    /// Get the length of the authentication tag in bytes
+    pub fn tag_len(&self) -> usize {
+        GCM_TAG_LEN
+    }
+
+    /// Get the maximum length of the plaintext which is encrypted into a frame of a stream
+    ///
+    /// Each frame holds its own nonce, the ciphertext and the authentication tag:
+    /// `nonce || ciphertext || tag`. With GCM the overhead is 28 bytes per frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_size` - The length of a frame in bytes
+    ///
+    /// # Returns
+    ///
+    /// The maximum length of the plaintext of a frame in bytes, which is 0 if the frame is too
+    /// small for the nonce and the tag
+    pub fn max_plaintext_per_frame(&self, frame_size: usize) -> usize {
+        frame_size.saturating_sub(self.nonce_len() + self.tag_len())
+    }
+
+    /// Split a plaintext into the chunks which are encrypted into frames of a stream
+    ///
+    /// Every chunk but the last one has the length `max_plaintext_per_frame(frame_size)`, so
+    /// its frame has exactly `frame_size` bytes. An empty plaintext has no chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `plaintext` - The plaintext
+    /// * `frame_size` - The length of a frame in bytes
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the chunks of the plaintext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContent` will be returned if the frame can't hold any plaintext
+    pub fn frame_chunks<'p>(
+        &self,
+        plaintext: &'p [u8],
+        frame_size: usize,
+    ) -> Result<std::slice::Chunks<'p, u8>> {
+        match self.max_plaintext_per_frame(frame_size) {
+            0 => Err(QubitCryptError::InvalidContent),
+            chunk_len => Ok(plaintext.chunks(chunk_len)),
+        }
+    }
 }
 
 impl<'c> AuthEnvelopedDataBuilder<'c> {
@@ -81,9 +174,31 @@ impl<'c> AuthEnvelopedDataBuilder<'c> {
             content_encryption_algorithm,
             auth_attributes,
             unauth_attributes,
+            content_encryption_key: None,
+            nonce: None,
         })
     }
 
+    /// Use the provided content encryption key instead of generating a random one
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContentEncryptionKey` if the length of the key does not match
+    /// the content encryption algorithm
+    pub fn content_encryption_key(&mut self, cek: &[u8]) -> Result<&mut Self> {
+        if cek.len() != self.content_encryption_algorithm.key_len() {
+            return Err(QubitCryptError::InvalidContentEncryptionKey);
+        }
+        self.content_encryption_key = Some(Zeroizing::new(cek.to_vec()));
+        Ok(self)
+    }
+
+    /// Use the provided nonce instead of generating a random one
+    pub fn nonce(&mut self, nonce: &[u8]) -> Result<&mut Self> {
+        self.nonce = Some(nonce.to_vec());
+        Ok(self)
+    }
+
     /// Add recipient info. A builder is used, which generates a `RecipientInfo` according to
     /// RFC 5652 § 6.2, when `AuthEnvelopedData` is built.
     pub fn add_recipient_info(
@@ -94,16 +209,20 @@ impl<'c> AuthEnvelopedDataBuilder<'c> {
         Ok(self)
     }
 
-    /// Generate an `AuthEnvelopedData` object according to RFC 5083 § 2.2 using a provided
-    /// random number generator.
-    pub fn build(&mut self) -> Result<AuthEnvelopedData> {
+    /// Generate an `AuthEnvelopedData` object according to RFC 5083 § 2.2. The builder is
+    /// consumed, as each build would encrypt the content with a new nonce (and key).
+    pub fn build(mut self) -> Result<AuthEnvelopedData> {
         // DER encode authenticated attributes, if any
         // Generate content encryption key
         // Encrypt content and capture authentication tag
         // Build recipient infos
         // Make sure, content encryption key is securely destroyed
         let aad = match &self.auth_attributes {
-            Some(attrs) => Some(attrs.to_der().map_err(|_| QubitCryptError::Unknown)?),
+            Some(attrs) => Some(
+                attrs
+                    .to_der()
+                    .map_err(|_| QubitCryptError::InvalidAttribute)?,
+            ),
             None => None,
         };
 
@@ -111,14 +230,20 @@ impl<'c> AuthEnvelopedDataBuilder<'c> {
         let cea_type = if let Some(oid) = CeaType::from_oid(&oid) {
             oid
         } else {
-            return Err(QubitCryptError::Unknown);
+            return Err(QubitCryptError::UnsupportedContentEncryptionAlgorithm);
         };
 
         // Create an instance of CEA
         let mut cea = CeaManager::new(cea_type)?;
-        // Generate a symmetric key
-        let mut cek = cea.key_gen()?;
-        let nonce = cea.nonce_gen()?;
+        // Generate a symmetric key, unless one was provided
+        let mut cek = match &self.content_encryption_key {
+            Some(cek) => cek.to_vec(),
+            None => cea.key_gen()?,
+        };
+        let nonce = match &self.nonce {
+            Some(nonce) => nonce.to_vec(),
+            None => cea.nonce_gen()?,
+        };
 
         // Convert content id to string
         let content_id = self.content_id.map(|oid| oid.to_string());
@@ -138,7 +263,7 @@ impl<'c> AuthEnvelopedDataBuilder<'c> {
             .iter_mut()
             .map(|ri| ri.build(&cek))
             .collect::<Result<Vec<RecipientInfo>>>()
-            .map_err(|_| QubitCryptError::Unknown)?;
+            .map_err(|_| QubitCryptError::InvalidRecipientInfo)?;
 
         cek.zeroize();
         let recip_infos =
@@ -146,7 +271,8 @@ impl<'c> AuthEnvelopedDataBuilder<'c> {
 
         let mac = MessageAuthenticationCode::new(tag).map_err(|_| QubitCryptError::Unknown)?;
 
-        let eci = EncryptedContentInfo::from_der(&eci).map_err(|_| QubitCryptError::Unknown)?;
+        let eci = EncryptedContentInfo::from_der(&eci)
+            .map_err(|_| QubitCryptError::SerializationFailed)?;
 
         Ok(AuthEnvelopedData {
             version: self.calculate_version(),
@@ -164,3 +290,72 @@ impl<'c> AuthEnvelopedDataBuilder<'c> {
         CmsVersion::V0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_encryption_key_length() {
+        for (cea, key_len) in [
+            (ContentEncryptionAlgorithmAead::Aes128Gcm, 16),
+            (ContentEncryptionAlgorithmAead::Aes192Gcm, 24),
+            (ContentEncryptionAlgorithmAead::Aes256Gcm, 32),
+        ] {
+            assert_eq!(cea.key_len(), key_len);
+            let mut builder =
+                AuthEnvelopedDataBuilder::new(None, None, b"content", cea, None, None).unwrap();
+            assert!(builder.content_encryption_key(&vec![1u8; key_len]).is_ok());
+            for len in [0, key_len - 8, key_len + 8] {
+                assert!(matches!(
+                    builder.content_encryption_key(&vec![1u8; len]),
+                    Err(QubitCryptError::InvalidContentEncryptionKey)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_chunks() {
+        use openssl::symm::{encrypt_aead, Cipher};
+
+        let alg = ContentEncryptionAlgorithmAead::Aes256Gcm;
+        assert_eq!(alg.max_plaintext_per_frame(1024), 996);
+        assert_eq!(alg.max_plaintext_per_frame(29), 1);
+        assert_eq!(alg.max_plaintext_per_frame(28), 0);
+        assert_eq!(alg.max_plaintext_per_frame(0), 0);
+
+        let plaintext = vec![7u8; 2500];
+        let chunks = alg
+            .frame_chunks(&plaintext, 1024)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(),
+            [996, 996, 508]
+        );
+        assert_eq!(chunks.concat(), plaintext);
+
+        // The frames of the full chunks are exactly the frame size
+        let key = [1u8; 32];
+        let nonce = [2u8; 12];
+        for chunk in &chunks[..2] {
+            let mut tag = vec![0u8; alg.tag_len()];
+            let ct = encrypt_aead(
+                Cipher::aes_256_gcm(),
+                &key,
+                Some(&nonce),
+                &[],
+                chunk,
+                &mut tag,
+            )
+            .unwrap();
+            assert_eq!(nonce.len() + ct.len() + tag.len(), 1024);
+        }
+
+        assert_eq!(alg.frame_chunks(&[], 1024).unwrap().count(), 0);
+        assert!(alg
+            .frame_chunks(&plaintext, 28)
+            .is_err_and(|err| err == QubitCryptError::InvalidContent));
+    }
+}
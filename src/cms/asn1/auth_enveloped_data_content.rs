@@ -1,6 +1,6 @@
-use crate::cea::common::cea_type::CeaType;
+use crate::cms::asn1::ber::decode_content_info;
 use cms::{
-    content_info::{CmsVersion, ContentInfo},
+    content_info::CmsVersion,
     enveloped_data::{OriginatorInfo, RecipientInfos},
 };
 use der::{Decode, Encode};
@@ -14,6 +14,7 @@ use crate::cms::cms_util::CmsUtil;
 use const_oid::db::rfc5911::ID_CT_AUTH_ENVELOPED_DATA;
 
 use crate::cms::asn1::auth_enveloped_data_builder::ContentEncryptionAlgorithmAead;
+use crate::cms::asn1::recipient_summary::RecipientSummary;
 use crate::cms::enveloped_data_builder::EnvelopedDataBuilder;
 
 use super::auth_env_data::AuthEnvelopedData;
@@ -145,19 +146,20 @@ impl AuthEnvelopedDataContent {
     /// # Returns
     ///
     /// The AuthEnvelopedDataContent object
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedRecipientType` will be returned if there are no KEM
+    /// recipients, `QubitCryptError::NoMatchingRecipient` if none of the KEM recipients is
+    /// identified by the certificate, `QubitCryptError::InvalidCiphertextLength` if the KEM
+    /// ciphertext of the recipient has the wrong length
     pub fn from_bytes_for_kem_recipient(
         data: &[u8],
         recipient_cert: &Certificate,
         recipient_private_key: &PrivateKey,
     ) -> Result<AuthEnvelopedDataContent> {
-        // First try to read it as a der encoded ContentInfo
-        let ci = if let Ok(content_info) = ContentInfo::from_der(data) {
-            content_info
-        } else {
-            // If that fails, try to read it as a pem encoded ContentInfo
-            let pem = pem::parse(data).map_err(|_| QubitCryptError::InvalidContent)?;
-            ContentInfo::from_der(pem.contents()).map_err(|_| QubitCryptError::InvalidContent)?
-        };
+        // The ContentInfo can be DER, BER with indefinite lengths or PEM encoded
+        let ci = decode_content_info(data)?;
 
         // Check if the cotent type is EnvelopedData
         if ci.content_type != ID_CT_AUTH_ENVELOPED_DATA {
@@ -173,7 +175,9 @@ impl AuthEnvelopedDataContent {
             .map_err(|_| QubitCryptError::InvalidContent)?;
 
         // try to decrypt the content
-        let pt = CmsUtil::decrypt_kemri(data, recipient_private_key, recipient_cert)?;
+        // The DER of the ContentInfo, as the data may be BER or PEM encoded
+        let der = ci.to_der().map_err(|_| QubitCryptError::InvalidContent)?;
+        let pt = CmsUtil::decrypt_kemri(&der, recipient_private_key, recipient_cert)?;
 
         Ok(AuthEnvelopedDataContent {
             version: ed.version,
@@ -215,6 +219,11 @@ impl AuthEnvelopedDataContent {
         self.recip_infos.clone()
     }
 
+    /// Get the number of recipients by type, without cloning the RecipientInfos
+    pub fn recipient_summary(&self) -> RecipientSummary {
+        RecipientSummary::new(&self.recip_infos)
+    }
+
     /// Get a builder for the AuthEnvelopedDataContent. This is used to create new AuthEnvelopedDataContent objects
     ///
     /// # Arguments
@@ -227,17 +236,13 @@ impl AuthEnvelopedDataContent {
     pub fn get_builder(
         content_encryption_alg: ContentEncryptionAlgorithmAead,
     ) -> Result<EnvelopedDataBuilder<'static>> {
-        let cea = match content_encryption_alg {
-            ContentEncryptionAlgorithmAead::Aes128Gcm => CeaType::Aes128Gcm,
-            ContentEncryptionAlgorithmAead::Aes192Gcm => CeaType::Aes192Gcm,
-            ContentEncryptionAlgorithmAead::Aes256Gcm => CeaType::Aes256Gcm,
-        };
-        EnvelopedDataBuilder::new(cea, true)
+        EnvelopedDataBuilder::new(content_encryption_alg.cea_type(), true)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use cms::content_info::ContentInfo;
     use der::{asn1::SetOfVec, Tag, Tagged};
     use spki::ObjectIdentifier;
     use x509_cert::attr::{Attribute, AttributeValue};
@@ -328,4 +333,35 @@ mod tests {
         // Check the recipient infos length
         assert_eq!(edc.get_recipient_infos().0.len(), 1);
     }
+
+    #[test]
+    fn test_ciphertext_len() {
+        let recipient_cert =
+            Certificate::from_file("test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der").unwrap();
+
+        for plaintext_len in [1, 16, 17, 100] {
+            let mut builder =
+                AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes128Gcm)
+                    .unwrap();
+            builder
+                .kem_recipient(
+                    &recipient_cert,
+                    &KdfType::HkdfWithSha256,
+                    &WrapType::Aes256,
+                    None,
+                )
+                .unwrap()
+                .content(&vec![0u8; plaintext_len])
+                .unwrap();
+            let content = builder.build().unwrap();
+
+            let ci = ContentInfo::from_der(&content).unwrap();
+            let ed = AuthEnvelopedData::from_der(&ci.content.to_der().unwrap()).unwrap();
+            let ct = ed.auth_encrypted_content.encrypted_content.unwrap();
+            assert_eq!(
+                ct.as_bytes().len(),
+                ContentEncryptionAlgorithmAead::Aes128Gcm.ciphertext_len(plaintext_len)
+            );
+        }
+    }
 }
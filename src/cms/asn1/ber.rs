@@ -0,0 +1,377 @@
+//! BER indefinite-length encoding of `EnvelopedData` and `AuthEnvelopedData`
+
+use cms::content_info::ContentInfo;
+use der::asn1::{AnyRef, OctetStringRef};
+use der::{Decode, Encode, Reader, SliceReader, Tag, TagNumber, Tagged};
+
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The size of the segments of the encrypted content, as required by CER (X.690 § 9.2)
+const SEGMENT_LEN: usize = 1000;
+
+/// The identifier and length octets of a SEQUENCE with an indefinite length
+const SEQUENCE_INDEFINITE: [u8; 2] = [0x30, 0x80];
+
+/// The identifier and length octets of a constructed [0] with an indefinite length
+const CONTEXT_0_INDEFINITE: [u8; 2] = [0xA0, 0x80];
+
+/// The end-of-contents octets which close an indefinite length
+const END_OF_CONTENTS: [u8; 2] = [0x00, 0x00];
+
+/// The maximum nesting of indefinite lengths accepted by `decode_indefinite_length`, which is
+/// 3 for the output of `encode_indefinite_length`
+const MAX_INDEFINITE_DEPTH: usize = 8;
+
+/// Re-encode a DER ContentInfo holding an EnvelopedData or AuthEnvelopedData with BER
+/// indefinite lengths
+///
+/// The ContentInfo, the EnvelopedData / AuthEnvelopedData and the EncryptedContentInfo get
+/// indefinite lengths, and the encrypted content is encoded as a constructed OCTET STRING of
+/// 1000 byte segments. Everything else, including the recipient infos and the attributes, is
+/// kept as DER. This is the layout used for streaming, where the headers are written before the
+/// length of the encrypted content is known. Here the whole DER encoding already exists, so
+/// only the encoding is changed.
+///
+/// # Arguments
+///
+/// * `content_info` - The DER encoded ContentInfo
+///
+/// # Returns
+///
+/// The BER encoded ContentInfo
+///
+/// # Errors
+///
+/// `QubitCryptError::SerializationFailed` will be returned if the ContentInfo can't be decoded
+pub(crate) fn encode_indefinite_length(content_info: &[u8]) -> Result<Vec<u8>> {
+    let content_info =
+        AnyRef::from_der(content_info).map_err(|_| QubitCryptError::SerializationFailed)?;
+    let mut reader =
+        SliceReader::new(content_info.value()).map_err(|_| QubitCryptError::SerializationFailed)?;
+    let content_type = reader
+        .tlv_bytes()
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
+    let content = AnyRef::decode(&mut reader).map_err(|_| QubitCryptError::SerializationFailed)?;
+    let enveloped_data =
+        AnyRef::from_der(content.value()).map_err(|_| QubitCryptError::SerializationFailed)?;
+
+    let mut out = Vec::with_capacity(content_info.value().len() + 64);
+    out.extend_from_slice(&SEQUENCE_INDEFINITE);
+    out.extend_from_slice(content_type);
+    out.extend_from_slice(&CONTEXT_0_INDEFINITE);
+    out.extend_from_slice(&SEQUENCE_INDEFINITE);
+
+    // The (authenticated) encrypted content info is the only SEQUENCE field.
+    let mut reader = SliceReader::new(enveloped_data.value())
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
+    while !reader.is_finished() {
+        let field =
+            AnyRef::decode(&mut reader).map_err(|_| QubitCryptError::SerializationFailed)?;
+        if field.tag() == Tag::Sequence {
+            encode_encrypted_content_info(&field, &mut out)?;
+        } else {
+            let field = field
+                .to_der()
+                .map_err(|_| QubitCryptError::SerializationFailed)?;
+            out.extend_from_slice(&field);
+        }
+    }
+
+    for _ in 0..3 {
+        out.extend_from_slice(&END_OF_CONTENTS);
+    }
+    Ok(out)
+}
+
+/// Encode an EncryptedContentInfo with an indefinite length and a segmented encrypted content
+///
+/// # Arguments
+///
+/// * `eci` - The DER decoded EncryptedContentInfo
+/// * `out` - The buffer to append the encoding to
+fn encode_encrypted_content_info(eci: &AnyRef, out: &mut Vec<u8>) -> Result<()> {
+    let mut reader =
+        SliceReader::new(eci.value()).map_err(|_| QubitCryptError::SerializationFailed)?;
+    let content_type = reader
+        .tlv_bytes()
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
+    let algorithm = reader
+        .tlv_bytes()
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
+
+    out.extend_from_slice(&SEQUENCE_INDEFINITE);
+    out.extend_from_slice(content_type);
+    out.extend_from_slice(algorithm);
+
+    if !reader.is_finished() {
+        // encryptedContent [0] IMPLICIT EncryptedContent OPTIONAL
+        let encrypted_content =
+            AnyRef::decode(&mut reader).map_err(|_| QubitCryptError::SerializationFailed)?;
+        let expected_tag = Tag::ContextSpecific {
+            constructed: false,
+            number: TagNumber::N0,
+        };
+        if encrypted_content.tag() != expected_tag || !reader.is_finished() {
+            return Err(QubitCryptError::SerializationFailed);
+        }
+
+        out.extend_from_slice(&CONTEXT_0_INDEFINITE);
+        for segment in encrypted_content.value().chunks(SEGMENT_LEN) {
+            let segment = OctetStringRef::new(segment)
+                .and_then(|segment| segment.to_der())
+                .map_err(|_| QubitCryptError::SerializationFailed)?;
+            out.extend_from_slice(&segment);
+        }
+        out.extend_from_slice(&END_OF_CONTENTS);
+    }
+
+    out.extend_from_slice(&END_OF_CONTENTS);
+    Ok(())
+}
+
+/// Re-encode a BER element with indefinite lengths as DER
+///
+/// This is the inverse of `encode_indefinite_length`, so that the envelopes it produces (and
+/// those of other tools with the same layout) can be parsed. Elements with a definite length are
+/// kept as they are, so they must already be DER. A constructed OCTET STRING, or a constructed
+/// [0] whose elements are all OCTET STRINGs, which is how the IMPLICIT encrypted content is
+/// segmented, becomes the primitive encoding of the concatenated segments.
+///
+/// # Arguments
+///
+/// * `ber` - The BER encoded element
+///
+/// # Returns
+///
+/// The DER encoded element
+///
+/// # Errors
+///
+/// `QubitCryptError::SerializationFailed` will be returned if the element is not valid BER,
+/// there are bytes after it, or the indefinite lengths are nested too deeply
+pub(crate) fn decode_indefinite_length(ber: &[u8]) -> Result<Vec<u8>> {
+    let (der, rest) = decode_element(ber, 0)?;
+    if !rest.is_empty() {
+        return Err(QubitCryptError::SerializationFailed);
+    }
+    Ok(der)
+}
+
+/// Decode a DER or BER encoded ContentInfo, which can also be PEM encoded
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidContent` will be returned if the data is not a ContentInfo
+pub(crate) fn decode_content_info(data: &[u8]) -> Result<ContentInfo> {
+    let decode = |data: &[u8]| {
+        ContentInfo::from_der(data).or_else(|_| {
+            let der = decode_indefinite_length(data)?;
+            ContentInfo::from_der(&der).map_err(|_| QubitCryptError::InvalidContent)
+        })
+    };
+    decode(data)
+        .or_else(|_| {
+            let pem = pem::parse(data).map_err(|_| QubitCryptError::InvalidContent)?;
+            decode(pem.contents())
+        })
+        .map_err(|_| QubitCryptError::InvalidContent)
+}
+
+/// Decode one BER element to DER
+///
+/// # Returns
+///
+/// The DER encoded element and the bytes after it
+fn decode_element(ber: &[u8], depth: usize) -> Result<(Vec<u8>, &[u8])> {
+    let &[tag, length, ..] = ber else {
+        return Err(QubitCryptError::SerializationFailed);
+    };
+    if length != 0x80 {
+        let len = SliceReader::new(ber)
+            .and_then(|mut reader| reader.tlv_bytes().map(|tlv| tlv.len()))
+            .map_err(|_| QubitCryptError::SerializationFailed)?;
+        return Ok((ber[..len].to_vec(), &ber[len..]));
+    }
+
+    // Only constructed elements with a single byte tag can have an indefinite length here
+    let is_constructed = tag & 0x20 != 0;
+    if !is_constructed || tag & 0x1f == 0x1f || depth == MAX_INDEFINITE_DEPTH {
+        return Err(QubitCryptError::SerializationFailed);
+    }
+
+    let mut children = Vec::new();
+    let mut rest = &ber[2..];
+    while !rest.starts_with(&END_OF_CONTENTS) {
+        let (child, remaining) = decode_element(rest, depth + 1)?;
+        children.push(child);
+        rest = remaining;
+    }
+    rest = &rest[END_OF_CONTENTS.len()..];
+
+    let is_string = (tag == 0x24 || tag == 0xA0)
+        && !children.is_empty()
+        && children.iter().all(|child| child[0] == 0x04);
+    let (tag, value) = if is_string {
+        let mut value = Vec::new();
+        for child in &children {
+            let segment = OctetStringRef::from_der(child)
+                .map_err(|_| QubitCryptError::SerializationFailed)?;
+            value.extend_from_slice(segment.as_bytes());
+        }
+        (tag & !0x20, value)
+    } else {
+        (tag, children.concat())
+    };
+
+    let length = der::Length::try_from(value.len())
+        .and_then(|length| length.to_der())
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
+    let mut der = Vec::with_capacity(1 + length.len() + value.len());
+    der.push(tag);
+    der.extend_from_slice(&length);
+    der.extend_from_slice(&value);
+    Ok((der, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::{Certificate, Pki};
+    use crate::cms::cms_util::CmsUtil;
+    use crate::content::{
+        AuthEnvelopedDataContent, ContentEncryptionAlgorithm, ContentEncryptionAlgorithmAead,
+        EnvelopedContent, EnvelopedDataContent,
+    };
+    use crate::dsas::DsaAlgorithm;
+    use crate::kdfs::KdfType;
+    use crate::kems::KemAlgorithm;
+    use crate::keys::PrivateKey;
+    use crate::wraps::WrapType;
+
+    #[test]
+    fn test_encode_indefinite_length() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 1).unwrap();
+        let (cert, sk) = &pki.leaves[0];
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+
+        for content_len in [1, 999, 2500] {
+            let content = vec![7u8; content_len];
+
+            let mut builder =
+                EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+            builder
+                .kem_recipient(cert, &kdf, &wrap, None)
+                .unwrap()
+                .content(&content)
+                .unwrap();
+            let der = builder.build().unwrap();
+            let ber = encode_indefinite_length(&der).unwrap();
+            assert_eq!(decode_indefinite_length(&ber).unwrap(), der);
+
+            // The segments are all 1000 bytes apart from the last
+            let num_segments = ber
+                .windows(4)
+                .filter(|w| w == &[0x04, 0x82, 0x03, 0xE8])
+                .count();
+            assert_eq!(num_segments, (content_len + 16) / SEGMENT_LEN);
+
+            let mut builder =
+                AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes256Gcm)
+                    .unwrap();
+            builder
+                .kem_recipient(cert, &kdf, &wrap, None)
+                .unwrap()
+                .content(&content)
+                .unwrap()
+                .indefinite_length(true)
+                .unwrap();
+            let ber = builder.build().unwrap();
+            assert!(ber.starts_with(&SEQUENCE_INDEFINITE));
+            assert!(ber.ends_with(&[0u8; 6]));
+            let der = decode_indefinite_length(&ber).unwrap();
+            assert_eq!(CmsUtil::decrypt_kemri(&der, sk, cert).unwrap(), content);
+        }
+    }
+
+    /// Check that the `CmsUtil` functions read a BER envelope with one KEM recipient
+    fn check_cms_util(ber: &[u8], cert: &Certificate, sk: &PrivateKey, content: &[u8]) {
+        assert_eq!(CmsUtil::decrypt_kemri(ber, sk, cert).unwrap(), content);
+        assert_eq!(CmsUtil::recipient_summary(ber).unwrap().total(), 1);
+        assert_eq!(CmsUtil::kem_recipient_infos(ber).unwrap().len(), 1);
+        let sk = PrivateKey::from_der(&sk.to_der().unwrap()).unwrap();
+        assert!(CmsUtil::verify_all_recipients(ber, &[(cert.clone(), sk)]).unwrap());
+    }
+
+    #[test]
+    fn test_parse_indefinite_length() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 1).unwrap();
+        let (cert, sk) = &pki.leaves[0];
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+        let content = vec![7u8; 2500];
+
+        // The parsers of the crate read the BER output of the builders
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes256Cbc).unwrap();
+        builder
+            .kem_recipient(cert, &kdf, &wrap, None)
+            .unwrap()
+            .content(&content)
+            .unwrap()
+            .indefinite_length(true)
+            .unwrap();
+        let ber = builder.build().unwrap();
+        let edc = EnvelopedDataContent::from_bytes_for_kem_recipient(&ber, cert, sk).unwrap();
+        assert_eq!(edc.get_content(), content);
+        let pem = pem::encode(&pem::Pem::new("CMS", ber.clone()));
+        let ec = EnvelopedContent::from_bytes_for_kem_recipient(pem.as_bytes(), cert, sk).unwrap();
+        assert_eq!(ec.get_content(), content);
+        check_cms_util(&ber, cert, sk, &content);
+
+        let mut builder =
+            AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes128Gcm)
+                .unwrap();
+        builder
+            .kem_recipient(cert, &kdf, &wrap, None)
+            .unwrap()
+            .content(&content)
+            .unwrap()
+            .indefinite_length(true)
+            .unwrap();
+        let ber = builder.build().unwrap();
+        let aedc = AuthEnvelopedDataContent::from_bytes_for_kem_recipient(&ber, cert, sk).unwrap();
+        assert_eq!(aedc.get_content(), content);
+        let ec = EnvelopedContent::from_bytes_for_kem_recipient(&ber, cert, sk).unwrap();
+        assert_eq!(ec.get_content(), content);
+        check_cms_util(&ber, cert, sk, &content);
+
+        // Truncated BER, a missing end-of-contents and a primitive with an indefinite length
+        for len in [1, 2, ber.len() / 2, ber.len() - 2] {
+            assert_eq!(
+                decode_indefinite_length(&ber[..len]).err(),
+                Some(QubitCryptError::SerializationFailed)
+            );
+            assert!(EnvelopedContent::from_bytes_for_kem_recipient(&ber[..len], cert, sk).is_err());
+        }
+        assert_eq!(
+            decode_indefinite_length(&[0x04, 0x80, 0x00, 0x00]).err(),
+            Some(QubitCryptError::SerializationFailed)
+        );
+        // Too deeply nested
+        let mut nested = [SEQUENCE_INDEFINITE; MAX_INDEFINITE_DEPTH + 1].concat();
+        nested.extend([END_OF_CONTENTS; MAX_INDEFINITE_DEPTH + 1].concat());
+        assert_eq!(
+            decode_indefinite_length(&nested).err(),
+            Some(QubitCryptError::SerializationFailed)
+        );
+        let mut expected = vec![0x30, 0x00];
+        for _ in 1..MAX_INDEFINITE_DEPTH {
+            expected = [vec![0x30, expected.len() as u8], expected].concat();
+        }
+        let nested = &nested[2..nested.len() - 2];
+        assert_eq!(decode_indefinite_length(nested).unwrap(), expected);
+    }
+}
@@ -0,0 +1,229 @@
+use const_oid::db::rfc5911::{ID_CT_AUTH_ENVELOPED_DATA, ID_ENVELOPED_DATA};
+use der::Encode;
+use x509_cert::attr::Attributes;
+
+use crate::cms::asn1::auth_enveloped_data_content::AuthEnvelopedDataContent;
+use crate::cms::asn1::ber::decode_content_info;
+use crate::cms::asn1::enveloped_data_content::EnvelopedDataContent;
+use crate::cms::asn1::recipient_summary::RecipientSummary;
+use crate::{certificates::Certificate, keys::PrivateKey, QubitCryptError};
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// Decrypted content of either an EnvelopedData or an AuthEnvelopedData
+///
+/// `EnvelopedDataContent::from_bytes_for_kem_recipient` and
+/// `AuthEnvelopedDataContent::from_bytes_for_kem_recipient` only accept their own content type.
+/// A consumer which can receive either, e.g. because the sender chooses between AES-CBC and
+/// AES-GCM, uses `EnvelopedContent::from_bytes_for_kem_recipient`, which dispatches on the
+/// content type of the ContentInfo.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::certificates::Pki;
+/// use qubitcrypt::content::{AuthEnvelopedDataContent, ContentEncryptionAlgorithmAead};
+/// use qubitcrypt::content::EnvelopedContent;
+/// use qubitcrypt::dsas::DsaAlgorithm;
+/// use qubitcrypt::kdfs::KdfType;
+/// use qubitcrypt::kems::KemAlgorithm;
+/// use qubitcrypt::wraps::WrapType;
+///
+/// let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem768, 1).unwrap();
+/// let (cert, sk) = &pki.leaves[0];
+///
+/// let mut builder =
+///     AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes256Gcm).unwrap();
+/// builder
+///     .kem_recipient(cert, &KdfType::HkdfWithSha256, &WrapType::Aes256, None)
+///     .unwrap()
+///     .content(b"abc")
+///     .unwrap();
+/// let data = builder.build().unwrap();
+///
+/// let content = EnvelopedContent::from_bytes_for_kem_recipient(&data, cert, sk).unwrap();
+/// assert!(content.is_authenticated());
+/// assert_eq!(content.get_content(), b"abc");
+/// ```
+pub enum EnvelopedContent {
+    /// An EnvelopedData (`id-envelopedData`)
+    Enveloped(EnvelopedDataContent),
+    /// An AuthEnvelopedData (`id-ct-authEnvelopedData`)
+    AuthEnveloped(AuthEnvelopedDataContent),
+}
+
+impl EnvelopedContent {
+    /// Load an EnvelopedData or AuthEnvelopedData from a file. The content is wrapped in a
+    /// ContentInfo object. The content is decrypted using the provided recipient certificate
+    /// and private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The file to read the content from
+    /// * `recipient_cert` - The recipient certificate
+    /// * `recipient_private_key` - The recipient private key
+    ///
+    /// # Returns
+    ///
+    /// The decrypted content
+    pub fn from_file_for_kem_recipient(
+        file: &str,
+        recipient_cert: &Certificate,
+        recipient_private_key: &PrivateKey,
+    ) -> Result<EnvelopedContent> {
+        let data = std::fs::read(file).map_err(|_| QubitCryptError::FileReadError)?;
+        EnvelopedContent::from_bytes_for_kem_recipient(&data, recipient_cert, recipient_private_key)
+    }
+
+    /// Load an EnvelopedData or AuthEnvelopedData from a byte array. The content is wrapped in
+    /// a DER, BER (with indefinite lengths) or PEM encoded ContentInfo object. The content is
+    /// decrypted using the provided recipient certificate and private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The byte array to read the content from
+    /// * `recipient_cert` - The recipient certificate
+    /// * `recipient_private_key` - The recipient private key
+    ///
+    /// # Returns
+    ///
+    /// The decrypted content
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContent` will be returned if the content type is neither
+    /// EnvelopedData nor AuthEnvelopedData, otherwise the same errors as the
+    /// `from_bytes_for_kem_recipient` of the content type
+    pub fn from_bytes_for_kem_recipient(
+        data: &[u8],
+        recipient_cert: &Certificate,
+        recipient_private_key: &PrivateKey,
+    ) -> Result<EnvelopedContent> {
+        // The ContentInfo can be DER, BER with indefinite lengths or PEM encoded
+        let ci = decode_content_info(data)?;
+        let der = ci.to_der().map_err(|_| QubitCryptError::InvalidContent)?;
+
+        if ci.content_type == ID_ENVELOPED_DATA {
+            let content = EnvelopedDataContent::from_bytes_for_kem_recipient(
+                &der,
+                recipient_cert,
+                recipient_private_key,
+            )?;
+            Ok(EnvelopedContent::Enveloped(content))
+        } else if ci.content_type == ID_CT_AUTH_ENVELOPED_DATA {
+            let content = AuthEnvelopedDataContent::from_bytes_for_kem_recipient(
+                &der,
+                recipient_cert,
+                recipient_private_key,
+            )?;
+            Ok(EnvelopedContent::AuthEnveloped(content))
+        } else {
+            Err(QubitCryptError::InvalidContent)
+        }
+    }
+
+    /// Check if the content was an AuthEnvelopedData, whose integrity is protected by the
+    /// content encryption algorithm
+    pub fn is_authenticated(&self) -> bool {
+        matches!(self, EnvelopedContent::AuthEnveloped(_))
+    }
+
+    /// Get the content
+    pub fn get_content(&self) -> Vec<u8> {
+        match self {
+            EnvelopedContent::Enveloped(content) => content.get_content(),
+            EnvelopedContent::AuthEnveloped(content) => content.get_content(),
+        }
+    }
+
+    /// Get the unprotected attributes, which are called unauthenticated attributes in an
+    /// AuthEnvelopedData
+    pub fn get_unprotected_attrs(&self) -> Option<Attributes> {
+        match self {
+            EnvelopedContent::Enveloped(content) => content.get_unprotected_attrs(),
+            EnvelopedContent::AuthEnveloped(content) => content.get_unprotected_attrs(),
+        }
+    }
+
+    /// Get the number of recipients by type
+    pub fn recipient_summary(&self) -> RecipientSummary {
+        match self {
+            EnvelopedContent::Enveloped(content) => content.recipient_summary(),
+            EnvelopedContent::AuthEnveloped(content) => content.recipient_summary(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::Pki;
+    use crate::content::{ContentEncryptionAlgorithm, ContentEncryptionAlgorithmAead};
+    use crate::dsas::DsaAlgorithm;
+    use crate::kdfs::KdfType;
+    use crate::kems::KemAlgorithm;
+    use crate::wraps::WrapType;
+    use cms::content_info::ContentInfo;
+
+    #[test]
+    fn test_enveloped_content() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 1).unwrap();
+        let (cert, sk) = &pki.leaves[0];
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .kem_recipient(cert, &kdf, &wrap, None)
+            .unwrap()
+            .content(b"cbc")
+            .unwrap();
+        let cbc = builder.build().unwrap();
+
+        let mut builder =
+            AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes128Gcm)
+                .unwrap();
+        builder
+            .kem_recipient(cert, &kdf, &wrap, None)
+            .unwrap()
+            .content(b"gcm")
+            .unwrap();
+        let gcm = builder.build().unwrap();
+
+        let content = EnvelopedContent::from_bytes_for_kem_recipient(&cbc, cert, sk).unwrap();
+        assert!(!content.is_authenticated());
+        assert_eq!(content.get_content(), b"cbc");
+        assert_eq!(content.recipient_summary().kem, 1);
+
+        let content = EnvelopedContent::from_bytes_for_kem_recipient(&gcm, cert, sk).unwrap();
+        assert!(content.is_authenticated());
+        assert_eq!(content.get_content(), b"gcm");
+        assert!(content.get_unprotected_attrs().is_none());
+
+        // PEM is decrypted as well
+        let pem = pem::encode(&pem::Pem::new("CMS", gcm.clone()));
+        let content =
+            EnvelopedContent::from_bytes_for_kem_recipient(pem.as_bytes(), cert, sk).unwrap();
+        assert_eq!(content.get_content(), b"gcm");
+
+        // The typed entry points still only accept their own content type
+        assert_eq!(
+            EnvelopedDataContent::from_bytes_for_kem_recipient(&gcm, cert, sk).err(),
+            Some(QubitCryptError::InvalidContent)
+        );
+        assert_eq!(
+            AuthEnvelopedDataContent::from_bytes_for_kem_recipient(&cbc, cert, sk).err(),
+            Some(QubitCryptError::InvalidContent)
+        );
+
+        let other = ContentInfo {
+            content_type: const_oid::db::rfc5911::ID_DATA,
+            content: der::Any::new(der::Tag::OctetString, b"abc".to_vec()).unwrap(),
+        };
+        assert_eq!(
+            EnvelopedContent::from_bytes_for_kem_recipient(&other.to_der().unwrap(), cert, sk)
+                .err(),
+            Some(QubitCryptError::InvalidContent)
+        );
+    }
+}
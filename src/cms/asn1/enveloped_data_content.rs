@@ -1,12 +1,20 @@
 use crate::cea::common::cea_type::CeaType;
+use crate::cms::asn1::ber::decode_content_info;
 use cms::{
     content_info::{CmsVersion, ContentInfo},
-    enveloped_data::{EnvelopedData, OriginatorInfo, RecipientInfos},
+    enveloped_data::{
+        EncryptedContentInfo, EnvelopedData, OriginatorInfo, RecipientInfo, RecipientInfos,
+    },
 };
 use der::{Decode, Encode};
-use x509_cert::attr::Attributes;
+use x509_cert::attr::{Attribute, Attributes};
 
+use crate::cms::asn1::auth_enveloped_data_builder::ContentEncryptionAlgorithmAead;
+use crate::cms::asn1::auth_enveloped_data_content::AuthEnvelopedDataContent;
+use crate::cms::asn1::kemri::KemRecipientInfo;
+use crate::cms::asn1::recipient_summary::RecipientSummary;
 use crate::{certificates::Certificate, keys::PrivateKey, QubitCryptError};
+use crate::{kdfs::KdfType, wraps::WrapType};
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
@@ -16,6 +24,7 @@ use const_oid::db::rfc5911::ID_ENVELOPED_DATA;
 use crate::cms::enveloped_data_builder::EnvelopedDataBuilder;
 
 /// The content encryption algorithm used to encrypt the content
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ContentEncryptionAlgorithm {
     /// AES 128 bit encryption in CBC mode
     Aes128Cbc,
@@ -25,6 +34,43 @@ pub enum ContentEncryptionAlgorithm {
     Aes256Cbc,
 }
 
+/// The block size of AES in bytes
+const AES_BLOCK_SIZE: usize = 16;
+
+impl ContentEncryptionAlgorithm {
+    /// Get the CEA type which implements this algorithm
+    pub(crate) fn get_cea_type(&self) -> CeaType {
+        match self {
+            ContentEncryptionAlgorithm::Aes128Cbc => CeaType::Aes128CbcPad,
+            ContentEncryptionAlgorithm::Aes192Cbc => CeaType::Aes192CbcPad,
+            ContentEncryptionAlgorithm::Aes256Cbc => CeaType::Aes256CbcPad,
+        }
+    }
+
+    /// Get the length of the encrypted content for a plaintext of the given length
+    ///
+    /// The plaintext is padded with PKCS #7 to the next block boundary, so a full block of
+    /// padding is added if the plaintext length is already a multiple of the block size.
+    /// The IV is carried in the algorithm parameters and is not part of the encrypted content.
+    ///
+    /// # Arguments
+    ///
+    /// * `plaintext_len` - The length of the plaintext in bytes
+    ///
+    /// # Returns
+    ///
+    /// The length of the encrypted content in bytes
+    pub fn ciphertext_len(&self, plaintext_len: usize) -> usize {
+        match self {
+            ContentEncryptionAlgorithm::Aes128Cbc
+            | ContentEncryptionAlgorithm::Aes192Cbc
+            | ContentEncryptionAlgorithm::Aes256Cbc => {
+                (plaintext_len / AES_BLOCK_SIZE + 1) * AES_BLOCK_SIZE
+            }
+        }
+    }
+}
+
 /// Main interaction point for the EnvelopedData content
 ///
 /// This struct is used to create, read and manipulate EnvelopedData content
@@ -109,6 +155,8 @@ pub struct EnvelopedDataContent {
     content: Vec<u8>,
     /// The unprotected attributes
     unprotected_attrs: Option<Attributes>,
+    /// The encrypted content, kept so that the EnvelopedData can be re-serialized
+    encrypted_content: EncryptedContentInfo,
 }
 
 impl EnvelopedDataContent {
@@ -151,19 +199,20 @@ impl EnvelopedDataContent {
     /// # Returns
     ///
     /// A new EnvelopedDataContent object
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedRecipientType` will be returned if there are no KEM
+    /// recipients, `QubitCryptError::NoMatchingRecipient` if none of the KEM recipients is
+    /// identified by the certificate, `QubitCryptError::InvalidCiphertextLength` if the KEM
+    /// ciphertext of the recipient has the wrong length
     pub fn from_bytes_for_kem_recipient(
         data: &[u8],
         recipient_cert: &Certificate,
         recipient_private_key: &PrivateKey,
     ) -> Result<EnvelopedDataContent> {
-        // First try to read it as a der encoded ContentInfo
-        let ci = if let Ok(content_info) = ContentInfo::from_der(data) {
-            content_info
-        } else {
-            // If that fails, try to read it as a pem encoded ContentInfo
-            let pem = pem::parse(data).map_err(|_| QubitCryptError::InvalidContent)?;
-            ContentInfo::from_der(pem.contents()).map_err(|_| QubitCryptError::InvalidContent)?
-        };
+        // The ContentInfo can be DER, BER with indefinite lengths or PEM encoded
+        let ci = decode_content_info(data)?;
 
         // Check if the cotent type is EnvelopedData
         if ci.content_type != ID_ENVELOPED_DATA {
@@ -179,7 +228,9 @@ impl EnvelopedDataContent {
             .map_err(|_| QubitCryptError::InvalidContent)?;
 
         // try to decrypt the content
-        let pt = CmsUtil::decrypt_kemri(data, recipient_private_key, recipient_cert)?;
+        // The DER of the ContentInfo, as the data may be BER or PEM encoded
+        let der = ci.to_der().map_err(|_| QubitCryptError::InvalidContent)?;
+        let pt = CmsUtil::decrypt_kemri(&der, recipient_private_key, recipient_cert)?;
 
         Ok(EnvelopedDataContent {
             version: ed.version,
@@ -187,6 +238,7 @@ impl EnvelopedDataContent {
             recip_infos: ed.recip_infos,
             content: pt,
             unprotected_attrs: ed.unprotected_attrs,
+            encrypted_content: ed.encrypted_content,
         })
     }
 
@@ -215,6 +267,149 @@ impl EnvelopedDataContent {
         self.recip_infos.clone()
     }
 
+    /// Get the number of recipients by type, without cloning the RecipientInfos
+    pub fn recipient_summary(&self) -> RecipientSummary {
+        RecipientSummary::new(&self.recip_infos)
+    }
+
+    /// Add an unprotected attribute. Unprotected attributes are not integrity protected,
+    /// so they can be added to an existing EnvelopedData without re-encrypting the content.
+    /// Use `to_der` to get the updated EnvelopedData.
+    ///
+    /// # Arguments
+    ///
+    /// * `attribute` - The attribute to add
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the EnvelopedDataContent
+    pub fn add_unprotected_attribute(&mut self, attribute: &Attribute) -> Result<&mut Self> {
+        let attributes = self.unprotected_attrs.get_or_insert_with(Attributes::new);
+        attributes
+            .insert(attribute.clone())
+            .map_err(|_| QubitCryptError::InvalidAttribute)?;
+
+        // Version 0 is only allowed if there are no unprotected attributes (RFC 5652 § 6.1)
+        if self.version == CmsVersion::V0 {
+            self.version = CmsVersion::V2;
+        }
+        Ok(self)
+    }
+
+    /// Serialize the EnvelopedData, wrapped in a ContentInfo, to DER bytes
+    ///
+    /// # Returns
+    ///
+    /// The DER encoded bytes of the ContentInfo
+    pub fn to_der(&self) -> Result<Vec<u8>> {
+        let ed = EnvelopedData {
+            version: self.version,
+            originator_info: self.originator_info.clone(),
+            recip_infos: self.recip_infos.clone(),
+            encrypted_content: self.encrypted_content.clone(),
+            unprotected_attrs: self.unprotected_attrs.clone(),
+        };
+
+        let content = der::Any::from_der(
+            &ed.to_der()
+                .map_err(|_| QubitCryptError::SerializationFailed)?,
+        )
+        .map_err(|_| QubitCryptError::SerializationFailed)?;
+
+        ContentInfo {
+            content_type: ID_ENVELOPED_DATA,
+            content,
+        }
+        .to_der()
+        .map_err(|_| QubitCryptError::SerializationFailed)
+    }
+
+    /// Re-encrypt an EnvelopedData under an AEAD content encryption algorithm
+    ///
+    /// The envelope is decrypted with the caller's certificate and private key, and the content
+    /// is encrypted again under a new content encryption key as an AuthEnvelopedData. This is
+    /// used to migrate envelopes from AES-CBC to AES-GCM.
+    ///
+    /// The recipient set is preserved where possible: every KEM recipient of the original
+    /// envelope whose certificate is `my_cert` or one of `recipients` is added to the new
+    /// envelope, with the same KDF, key wrap and user keying material. Recipients whose
+    /// certificate is not provided, and recipients which are not KEM recipients, can't be
+    /// re-wrapped for the new key and are dropped. The originator info and the unprotected
+    /// attributes are carried over, the latter as unauthenticated attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The DER encoded ContentInfo of the EnvelopedData
+    /// * `new_cea` - The content encryption algorithm of the new envelope
+    /// * `my_cert` - The certificate of the caller, who must be a recipient of the envelope
+    /// * `my_sk` - The private key of the caller
+    /// * `recipients` - The certificates of the other recipients
+    ///
+    /// # Returns
+    ///
+    /// The DER encoded ContentInfo of the new AuthEnvelopedData
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContent` will be returned if the data is not an EnvelopedData,
+    /// `QubitCryptError::InvalidEnvelopedData` if the caller is not a recipient and
+    /// `QubitCryptError::InvalidRecipientInfo` if a preserved recipient uses an unsupported KDF
+    /// or key wrap
+    pub fn reencrypt(
+        data: &[u8],
+        new_cea: ContentEncryptionAlgorithmAead,
+        my_cert: &Certificate,
+        my_sk: &PrivateKey,
+        recipients: &[Certificate],
+    ) -> Result<Vec<u8>> {
+        let edc = EnvelopedDataContent::from_bytes_for_kem_recipient(data, my_cert, my_sk)?;
+
+        let mut builder = AuthEnvelopedDataContent::get_builder(new_cea)?;
+        builder.content(&edc.content)?;
+        if let Some(originator_info) = edc.originator_info.clone() {
+            builder.originator_info(originator_info)?;
+        }
+        if let Some(attributes) = &edc.unprotected_attrs {
+            for attribute in attributes.iter() {
+                builder.unprotected_attribute(attribute)?;
+            }
+        }
+
+        for ri in edc.recip_infos.0.iter() {
+            let ori = match ri {
+                RecipientInfo::Ori(ori) => ori,
+                _ => continue,
+            };
+            let kemri = match ori
+                .ori_value
+                .to_der()
+                .ok()
+                .and_then(|value| KemRecipientInfo::from_der(&value).ok())
+            {
+                Some(kemri) => kemri,
+                None => continue,
+            };
+            let cert = match std::iter::once(my_cert)
+                .chain(recipients)
+                .find(|cert| cert.is_identified_by(&kemri.rid))
+            {
+                Some(cert) => cert,
+                None => continue,
+            };
+
+            let kdf = match &kemri.kdf {
+                Some(kdf) => KdfType::from_oid(&kdf.oid.to_string())
+                    .ok_or(QubitCryptError::InvalidRecipientInfo)?,
+                None => KdfType::Direct,
+            };
+            let wrap = WrapType::from_oid(&kemri.wrap.oid.to_string())
+                .ok_or(QubitCryptError::InvalidRecipientInfo)?;
+            builder.kem_recipient(cert, &kdf, &wrap, kemri.ukm)?;
+        }
+
+        builder.build()
+    }
+
     /// Get a new EnvelopedDataContentBuilder
     ///
     /// # Arguments
@@ -227,12 +422,7 @@ impl EnvelopedDataContent {
     pub fn get_builder(
         content_encryption_alg: ContentEncryptionAlgorithm,
     ) -> Result<EnvelopedDataBuilder<'static>> {
-        let cea = match content_encryption_alg {
-            ContentEncryptionAlgorithm::Aes128Cbc => CeaType::Aes128CbcPad,
-            ContentEncryptionAlgorithm::Aes192Cbc => CeaType::Aes192CbcPad,
-            ContentEncryptionAlgorithm::Aes256Cbc => CeaType::Aes256CbcPad,
-        };
-        EnvelopedDataBuilder::new(cea, false)
+        EnvelopedDataBuilder::new(content_encryption_alg.get_cea_type(), false)
     }
 }
 
@@ -316,4 +506,223 @@ mod tests {
         // Check the recipient infos length
         assert_eq!(edc.get_recipient_infos().0.len(), 1);
     }
+
+    #[test]
+    fn test_add_unprotected_attribute() {
+        let recipient_cert =
+            Certificate::from_file("test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der").unwrap();
+
+        let private_key =
+            PrivateKey::from_file("test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_priv.der")
+                .unwrap();
+
+        let data = b"abc";
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .kem_recipient(
+                &recipient_cert,
+                &KdfType::HkdfWithSha256,
+                &WrapType::Aes256,
+                None,
+            )
+            .unwrap()
+            .content(data)
+            .unwrap();
+        let content = builder.build().unwrap();
+
+        let mut edc = EnvelopedDataContent::from_bytes_for_kem_recipient(
+            &content,
+            &recipient_cert,
+            &private_key,
+        )
+        .unwrap();
+        assert!(edc.get_unprotected_attrs().is_none());
+
+        let mut attribute_vals: SetOfVec<AttributeValue> = SetOfVec::<AttributeValue>::new();
+        attribute_vals
+            .insert(AttributeValue::new(Tag::OctetString, b"relay".to_vec()).unwrap())
+            .unwrap();
+        let attribute = Attribute {
+            oid: ObjectIdentifier::new("1.3.6.1.4.1.22554.5.6").unwrap(),
+            values: attribute_vals,
+        };
+
+        edc.add_unprotected_attribute(&attribute).unwrap();
+        let annotated = edc.to_der().unwrap();
+
+        // The annotated envelope still decrypts and carries the attribute
+        let edc = EnvelopedDataContent::from_bytes_for_kem_recipient(
+            &annotated,
+            &recipient_cert,
+            &private_key,
+        )
+        .unwrap();
+        assert_eq!(edc.get_content(), data);
+        let attrs = edc.get_unprotected_attrs().unwrap();
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(attrs.get(0).unwrap(), &attribute);
+    }
+
+    #[test]
+    fn test_ciphertext_len() {
+        let recipient_cert =
+            Certificate::from_file("test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der").unwrap();
+
+        for plaintext_len in [1, 15, 16, 17, 32, 100] {
+            let mut builder =
+                EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes256Cbc).unwrap();
+            builder
+                .kem_recipient(
+                    &recipient_cert,
+                    &KdfType::HkdfWithSha256,
+                    &WrapType::Aes256,
+                    None,
+                )
+                .unwrap()
+                .content(&vec![0u8; plaintext_len])
+                .unwrap();
+            let content = builder.build().unwrap();
+
+            let ci = ContentInfo::from_der(&content).unwrap();
+            let ed = EnvelopedData::from_der(&ci.content.to_der().unwrap()).unwrap();
+            let ct = ed.encrypted_content.encrypted_content.unwrap();
+            assert_eq!(
+                ct.as_bytes().len(),
+                ContentEncryptionAlgorithm::Aes256Cbc.ciphertext_len(plaintext_len)
+            );
+        }
+
+        assert_eq!(ContentEncryptionAlgorithm::Aes128Cbc.ciphertext_len(0), 16);
+        assert_eq!(ContentEncryptionAlgorithm::Aes192Cbc.ciphertext_len(16), 32);
+    }
+
+    #[test]
+    fn test_reencrypt() {
+        use crate::certificates::{CertValidity, CertificateBuilder, Profile};
+        use crate::cms::asn1::auth_env_data::AuthEnvelopedData;
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+        use const_oid::db::rfc5911::ID_CT_AUTH_ENVELOPED_DATA;
+
+        let recipient_cert =
+            Certificate::from_file("test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der").unwrap();
+        let private_key =
+            PrivateKey::from_file("test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_priv.der")
+                .unwrap();
+
+        // Two more recipients, only one of which is provided when re-encrypting
+        let (_, ta_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let mut others = Vec::new();
+        for cn in ["CN=other.test.com", "CN=dropped.test.com"] {
+            let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem768)
+                .generate()
+                .unwrap();
+            let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+            let cert = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: recipient_cert.get_issuer(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: true,
+                },
+                None,
+                validity,
+                cn,
+                pk,
+                &ta_sk,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+            others.push((cert, sk));
+        }
+
+        let ukm = UserKeyingMaterial::new("test".as_bytes()).unwrap();
+        let data = b"abc";
+        let mut attribute_vals: SetOfVec<AttributeValue> = SetOfVec::<AttributeValue>::new();
+        attribute_vals
+            .insert(AttributeValue::new(Tag::OctetString, b"relay".to_vec()).unwrap())
+            .unwrap();
+        let attribute = Attribute {
+            oid: ObjectIdentifier::new("1.3.6.1.4.1.22554.5.6").unwrap(),
+            values: attribute_vals,
+        };
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .kem_recipient(
+                &recipient_cert,
+                &KdfType::HkdfWithSha256,
+                &WrapType::Aes256,
+                Some(ukm.clone()),
+            )
+            .unwrap()
+            .kem_recipient(&others[0].0, &KdfType::Kmac128, &WrapType::Aes128, None)
+            .unwrap()
+            .kem_recipient(
+                &others[1].0,
+                &KdfType::HkdfWithSha256,
+                &WrapType::Aes256,
+                None,
+            )
+            .unwrap()
+            .content(data)
+            .unwrap()
+            .unprotected_attribute(&attribute)
+            .unwrap();
+        let content = builder.build().unwrap();
+
+        let reencrypted = EnvelopedDataContent::reencrypt(
+            &content,
+            ContentEncryptionAlgorithmAead::Aes256Gcm,
+            &recipient_cert,
+            &private_key,
+            std::slice::from_ref(&others[0].0),
+        )
+        .unwrap();
+
+        let ci = ContentInfo::from_der(&reencrypted).unwrap();
+        assert_eq!(ci.content_type, ID_CT_AUTH_ENVELOPED_DATA);
+        let aed = AuthEnvelopedData::from_der(&ci.content.to_der().unwrap()).unwrap();
+        assert_eq!(aed.recip_infos.0.len(), 2);
+        assert_eq!(
+            aed.auth_encrypted_content.content_enc_alg.oid,
+            ContentEncryptionAlgorithmAead::Aes256Gcm.oid()
+        );
+
+        // The caller and the provided recipient can decrypt, the dropped recipient can't
+        for (cert, sk) in [
+            (&recipient_cert, &private_key),
+            (&others[0].0, &others[0].1),
+        ] {
+            let aedc =
+                AuthEnvelopedDataContent::from_bytes_for_kem_recipient(&reencrypted, cert, sk)
+                    .unwrap();
+            assert_eq!(aedc.get_content(), data);
+            let attrs = aedc.get_unprotected_attrs().unwrap();
+            assert_eq!(attrs.get(0).unwrap(), &attribute);
+        }
+        assert!(AuthEnvelopedDataContent::from_bytes_for_kem_recipient(
+            &reencrypted,
+            &others[1].0,
+            &others[1].1
+        )
+        .is_err());
+
+        // The caller must be a recipient of the original envelope
+        assert!(matches!(
+            EnvelopedDataContent::reencrypt(
+                &content,
+                ContentEncryptionAlgorithmAead::Aes256Gcm,
+                &recipient_cert,
+                &others[0].1,
+                &[],
+            ),
+            Err(QubitCryptError::InvalidEnvelopedData)
+        ));
+    }
 }
@@ -5,11 +5,16 @@
 
 use cms::{
     content_info::CmsVersion,
-    enveloped_data::{EncryptedKey, RecipientIdentifier, UserKeyingMaterial},
+    enveloped_data::{EncryptedKey, RecipientIdentifier, RecipientInfo, UserKeyingMaterial},
 };
-use der::{asn1::OctetString, Sequence};
+use der::{asn1::OctetString, Decode, Encode, Sequence};
 use spki::AlgorithmIdentifierOwned;
 
+use crate::cms::asn1::recipient_summary::RecipientType;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
 /// The `KEMRecipientInfo` type is defined in [draft-ietf-lamps-cms-kemri-07 Section 3]
 /// ```text
 ///   KEMRecipientInfo ::= SEQUENCE {
@@ -24,6 +29,9 @@ use spki::AlgorithmIdentifierOwned;
 ///     encryptedKey EncryptedKey }
 /// ```
 /// [draft-ietf-lamps-cms-kemri-07 Section 3]: https://datatracker.ietf.org/doc/html/draft-ietf-lamps-cms-kemri-07#section-3
+///
+/// The `kdf` is decoded as optional, for the envelopes of implementations which use the shared
+/// secret as the KEK (`KdfType::Direct`). It's unambiguous, as `kekLength` is an INTEGER.
 #[derive(Clone, Debug, Eq, PartialEq, Sequence)]
 #[allow(missing_docs)]
 pub struct KemRecipientInfo {
@@ -31,7 +39,7 @@ pub struct KemRecipientInfo {
     pub rid: RecipientIdentifier,
     pub kem: AlgorithmIdentifierOwned,
     pub kem_ct: OctetString,
-    pub kdf: AlgorithmIdentifierOwned,
+    pub kdf: Option<AlgorithmIdentifierOwned>,
     pub kek_length: u16,
     #[asn1(context_specific = "0", tag_mode = "EXPLICIT", optional = "true")]
     pub ukm: Option<UserKeyingMaterial>,
@@ -39,6 +47,58 @@ pub struct KemRecipientInfo {
     pub encrypted_key: EncryptedKey,
 }
 
+impl KemRecipientInfo {
+    /// Decode the KEMRecipientInfo of a RecipientInfo, e.g. one of
+    /// `EnvelopedDataContent::get_recipient_infos`
+    ///
+    /// # Arguments
+    ///
+    /// * `ri` - The RecipientInfo
+    ///
+    /// # Returns
+    ///
+    /// The KEMRecipientInfo
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidRecipientInfo` will be returned if the RecipientInfo is not a KEM
+    /// recipient or can't be decoded
+    pub fn from_recipient_info(ri: &RecipientInfo) -> Result<KemRecipientInfo> {
+        let RecipientInfo::Ori(ori) = ri else {
+            return Err(QubitCryptError::InvalidRecipientInfo);
+        };
+        if RecipientType::of(ri) != RecipientType::Kem {
+            return Err(QubitCryptError::InvalidRecipientInfo);
+        }
+        let ori_value = ori
+            .ori_value
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidRecipientInfo)?;
+        KemRecipientInfo::from_der(&ori_value).map_err(|_| QubitCryptError::InvalidRecipientInfo)
+    }
+
+    /// Get the OID of the KEM which encapsulated the shared secret
+    pub fn kem_oid(&self) -> String {
+        self.kem.oid.to_string()
+    }
+
+    /// Get the OID of the KDF which derived the key-encryption key from the shared secret, or
+    /// `None` if the KDF is absent and the shared secret is the key-encryption key
+    pub fn kdf_oid(&self) -> Option<String> {
+        self.kdf.as_ref().map(|kdf| kdf.oid.to_string())
+    }
+
+    /// Get the OID of the key wrap algorithm which wrapped the content encryption key
+    pub fn wrap_oid(&self) -> String {
+        self.wrap.oid.to_string()
+    }
+
+    /// Get the user keying material which was input to the KDF, if there is any
+    pub fn ukm(&self) -> Option<Vec<u8>> {
+        self.ukm.as_ref().map(|ukm| ukm.as_bytes().to_vec())
+    }
+}
+
 /// The `CMSORIforKEMOtherInfo` type is defined in [draft-ietf-lamps-cms-kemri-07 Section 5]
 /// ```text
 ///       CMSORIforKEMOtherInfo ::= SEQUENCE {
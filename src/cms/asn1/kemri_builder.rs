@@ -1,5 +1,7 @@
 // Adapted from Carl Wallace's code:
 // https://github.com/carl-wallace/kemri_toy/blob/main/src/asn1/kemri_builder.rs
+use crate::kdf::{api::KdfType, common::config::oids::Oid as _};
+use crate::QubitCryptError;
 use crate::{cms::cms_util::CmsUtil, wrap::api::WrapManager, wrap::common::wrap_trait::Wrap};
 use cms::{
     builder::{RecipientInfoBuilder, RecipientInfoType},
@@ -7,6 +9,7 @@ use cms::{
     enveloped_data::{OtherRecipientInfo, RecipientIdentifier, RecipientInfo, UserKeyingMaterial},
 };
 use der::{asn1::OctetString, Any, Decode, Encode};
+use rand_chacha::ChaCha20Rng;
 use spki::{AlgorithmIdentifier, ObjectIdentifier};
 
 use crate::{
@@ -14,7 +17,7 @@ use crate::{
     kem::{common::kem_trait::Kem, kem_manager::KemManager},
 };
 
-const ID_ORI_KEM: &str = "1.2.840.113549.1.9.16.13.3";
+pub(crate) const ID_ORI_KEM: &str = "1.2.840.113549.1.9.16.13.3";
 
 use super::kemri::KemRecipientInfo;
 
@@ -24,90 +27,88 @@ use cms::builder::Error;
 pub struct KemRecipientInfoBuilder {
     pub cert: Certificate,
     pub kem: KemManager,
-    pub kdf_oid: String,
+    pub kdf: KdfType,
     pub wrap_oid: String,
     pub ukm: Option<UserKeyingMaterial>,
+    /// The RNG of the encapsulation, if it's seeded for deterministic EnvelopedData
+    pub rng: Option<ChaCha20Rng>,
 }
 
 impl KemRecipientInfoBuilder {
     pub fn new(
         cert: &Certificate,
         kem: KemManager,
-        kdf_oid: String,
+        kdf: KdfType,
         wrap_oid: String,
         ukm: Option<UserKeyingMaterial>,
     ) -> Self {
         Self {
             cert: cert.clone(),
             kem,
-            kdf_oid,
+            kdf,
             wrap_oid,
             ukm,
+            rng: None,
         }
     }
-}
-
-impl RecipientInfoBuilder for KemRecipientInfoBuilder {
-    /// Returns the RecipientInfoType
-    fn recipient_info_type(&self) -> cms::builder::RecipientInfoType {
-        RecipientInfoType::Ori
-    }
-
-    /// Returns the `CMSVersion` for this `RecipientInfo`
-    fn recipient_info_version(&self) -> cms::content_info::CmsVersion {
-        CmsVersion::V3
-    }
 
-    fn build(&mut self, content_encryption_key: &[u8]) -> Result<RecipientInfo> {
-        let pk = self
-            .cert
-            .get_public_key()
-            .map_err(|_| Error::Builder("Error getting public key from cert".to_string()))?;
+    /// Build the RecipientInfo, containing a KEMRecipientInfo as an OtherRecipientInfo
+    ///
+    /// # Arguments
+    ///
+    /// * `content_encryption_key` - The CEK to wrap
+    ///
+    /// # Returns
+    ///
+    /// The RecipientInfo
+    ///
+    /// # Errors
+    ///
+    /// The error of the failing stage is passed through, e.g. the error of the KEM if the
+    /// encapsulation fails, of the KDF if the KEK can't be derived, or
+    /// `QubitCryptError::KeyWrapFailed` if the CEK can't be wrapped
+    pub(crate) fn build_kem_recipient_info(
+        &mut self,
+        content_encryption_key: &[u8],
+    ) -> std::result::Result<RecipientInfo, QubitCryptError> {
+        let pk = self.cert.get_public_key()?;
         let pk = pk.get_key();
-        let (ss, kem_ct) = self
-            .kem
-            .encap(pk)
-            .map_err(|_| Error::Builder("Error encapsulating key".to_string()))?;
-        let wrap_man = WrapManager::new_from_oid(&self.wrap_oid)
-            .map_err(|_| Error::Builder("Error creating wrap manager".to_string()))?;
+        let (ss, kem_ct) = match &mut self.rng {
+            Some(rng) => self.kem.encap_with_rng(pk, rng),
+            None => self.kem.encap(pk),
+        }?;
+        let wrap_man = WrapManager::new_from_oid(&self.wrap_oid)?;
 
         let kek_length = wrap_man.get_wrap_info().key_length;
 
-        let kek = CmsUtil::get_kek(
-            &ss,
-            &self.wrap_oid,
-            &self.kdf_oid,
-            kek_length,
-            self.ukm.clone(),
-        )
-        .map_err(|_| Error::Builder("Error getting KEK".to_string()))?;
+        let kdf_oid = self.kdf.get_oid();
+        let kek = match &kdf_oid {
+            // KdfType::Direct
+            None => CmsUtil::get_direct_kek(&ss, kek_length),
+            Some(kdf_oid) => {
+                CmsUtil::get_kek(&ss, &self.wrap_oid, kdf_oid, kek_length, self.ukm.clone())
+            }
+        }?;
 
-        let wrapped_key = wrap_man
-            .wrap(&kek, content_encryption_key)
-            .map_err(|_| Error::Builder("Error wrapping key".to_string()))?;
+        let wrapped_key = wrap_man.wrap(&kek, content_encryption_key)?;
 
-        let skid = self.cert.get_subject_key_identifier().map_err(|_| {
-            Error::Builder("Error getting subject key identifier from cert".to_string())
-        })?;
+        let skid = self.cert.get_subject_key_identifier()?;
         let rid = RecipientIdentifier::SubjectKeyIdentifier(skid);
 
         let kem_oid = self.kem.get_kem_info().oid;
-        let kem_oid: ObjectIdentifier = kem_oid
-            .parse()
-            .map_err(|_| Error::Builder("Error parsing KEM OID".to_string()))?;
+        let kem_oid: ObjectIdentifier = kem_oid.parse().map_err(|_| QubitCryptError::InvalidOid)?;
 
-        let kem_ct = OctetString::new(kem_ct)
-            .map_err(|_| Error::Builder("Error converting KEM CT to OctetString".to_string()))?;
+        let kem_ct = OctetString::new(kem_ct).map_err(|_| QubitCryptError::SerializationFailed)?;
 
-        let kdf_oid: ObjectIdentifier = self
-            .kdf_oid
-            .parse()
-            .map_err(|_| Error::Builder("Error parsing KDF OID".to_string()))?;
+        let kdf_oid: Option<ObjectIdentifier> = kdf_oid
+            .map(|oid| oid.parse())
+            .transpose()
+            .map_err(|_| QubitCryptError::InvalidOid)?;
 
         let wrap_oid: ObjectIdentifier = self
             .wrap_oid
             .parse()
-            .map_err(|_| Error::Builder("Error parsing wrap OID".to_string()))?;
+            .map_err(|_| QubitCryptError::InvalidOid)?;
 
         let kemri = KemRecipientInfo {
             version: CmsVersion::V0,
@@ -117,25 +118,28 @@ impl RecipientInfoBuilder for KemRecipientInfoBuilder {
                 parameters: None, // Params are absent for ML-KEM algorithms per draft-ietf-lamps-cms-kyber-01 section 10.2.1
             },
             kem_ct,
-            kdf: AlgorithmIdentifier {
-                oid: kdf_oid,
+            kdf: kdf_oid.map(|oid| AlgorithmIdentifier {
+                oid,
                 parameters: None, // Params are absent for AES key wrap algorithms per RFC 8619 section 3
-            },
+            }),
             kek_length,
             ukm: self.ukm.clone(),
             wrap: AlgorithmIdentifier {
                 oid: wrap_oid,
                 parameters: None,
             },
-            encrypted_key: OctetString::new(wrapped_key)?,
+            encrypted_key: OctetString::new(wrapped_key)
+                .map_err(|_| QubitCryptError::SerializationFailed)?,
         };
 
         let oid_kem: ObjectIdentifier = ID_ORI_KEM
             .parse()
-            .map_err(|_| Error::Builder("Error parsing KEM ORI OID".to_string()))?;
+            .map_err(|_| QubitCryptError::InvalidOid)?;
 
-        let der = kemri.to_der()?;
-        let ori_value = Any::from_der(&der)?;
+        let der = kemri
+            .to_der()
+            .map_err(|_| QubitCryptError::SerializationFailed)?;
+        let ori_value = Any::from_der(&der).map_err(|_| QubitCryptError::SerializationFailed)?;
         let ori = OtherRecipientInfo {
             ori_type: oid_kem,
             ori_value,
@@ -144,3 +148,22 @@ impl RecipientInfoBuilder for KemRecipientInfoBuilder {
         Ok(RecipientInfo::Ori(ori))
     }
 }
+
+impl RecipientInfoBuilder for KemRecipientInfoBuilder {
+    /// Returns the RecipientInfoType
+    fn recipient_info_type(&self) -> cms::builder::RecipientInfoType {
+        RecipientInfoType::Ori
+    }
+
+    /// Returns the `CMSVersion` for this `RecipientInfo`
+    fn recipient_info_version(&self) -> cms::content_info::CmsVersion {
+        CmsVersion::V3
+    }
+
+    /// Build the RecipientInfo. The builder error only carries the message of the
+    /// `QubitCryptError`, use `build_kem_recipient_info` to get the error itself.
+    fn build(&mut self, content_encryption_key: &[u8]) -> Result<RecipientInfo> {
+        self.build_kem_recipient_info(content_encryption_key)
+            .map_err(|e| Error::Builder(e.to_string()))
+    }
+}
@@ -1,6 +1,12 @@
+pub mod algorithm_protection;
 pub mod auth_env_data;
 pub mod auth_enveloped_data_builder;
 pub mod auth_enveloped_data_content;
+pub mod ber;
+pub mod enveloped_content;
 pub mod enveloped_data_content;
 pub mod kemri;
 pub mod kemri_builder;
+pub mod recipient_summary;
+pub mod signed_data_builder;
+pub mod signed_data_content;
@@ -0,0 +1,210 @@
+use cms::enveloped_data::{RecipientInfo, RecipientInfos};
+
+use crate::cms::asn1::kemri_builder::ID_ORI_KEM;
+
+/// The type of a RecipientInfo
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecipientType {
+    /// A KEMRecipientInfo (RFC 9629), which is an OtherRecipientInfo of type `id-ori-kem`
+    Kem,
+    /// A KeyAgreeRecipientInfo
+    KeyAgree,
+    /// A KeyTransRecipientInfo
+    KeyTrans,
+    /// A PasswordRecipientInfo
+    Password,
+    /// A KEKRecipientInfo
+    Kek,
+    /// An OtherRecipientInfo which is not a KEM recipient
+    Other,
+}
+
+impl RecipientType {
+    /// Get the type of a RecipientInfo
+    ///
+    /// # Arguments
+    ///
+    /// * `ri` - The RecipientInfo
+    ///
+    /// # Returns
+    ///
+    /// The type of the RecipientInfo
+    pub fn of(ri: &RecipientInfo) -> RecipientType {
+        match ri {
+            RecipientInfo::Ktri(_) => RecipientType::KeyTrans,
+            RecipientInfo::Kari(_) => RecipientType::KeyAgree,
+            RecipientInfo::Kekri(_) => RecipientType::Kek,
+            RecipientInfo::Pwri(_) => RecipientType::Password,
+            RecipientInfo::Ori(ori) if ori.ori_type.to_string() == ID_ORI_KEM => RecipientType::Kem,
+            RecipientInfo::Ori(_) => RecipientType::Other,
+        }
+    }
+
+    /// Get the ASN.1 name of the recipient type
+    ///
+    /// # Returns
+    ///
+    /// The name of the RecipientInfo choice, e.g. `KEKRecipientInfo`
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecipientType::Kem => "KEMRecipientInfo",
+            RecipientType::KeyAgree => "KeyAgreeRecipientInfo",
+            RecipientType::KeyTrans => "KeyTransRecipientInfo",
+            RecipientType::Password => "PasswordRecipientInfo",
+            RecipientType::Kek => "KEKRecipientInfo",
+            RecipientType::Other => "OtherRecipientInfo",
+        }
+    }
+}
+
+/// The number of recipients of an EnvelopedData or AuthEnvelopedData by type
+///
+/// This is a cheap way to check which kinds of recipients an envelope has, e.g. to reject an
+/// envelope without KEM recipients before trying to decrypt it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecipientSummary {
+    /// The number of KEMRecipientInfo recipients (RFC 9629)
+    pub kem: usize,
+    /// The number of KeyAgreeRecipientInfo recipients
+    pub key_agree: usize,
+    /// The number of KeyTransRecipientInfo recipients
+    pub key_trans: usize,
+    /// The number of PasswordRecipientInfo recipients
+    pub password: usize,
+    /// The number of KEKRecipientInfo recipients
+    pub kek: usize,
+    /// The number of OtherRecipientInfo recipients which are not KEM recipients
+    pub other: usize,
+}
+
+impl RecipientSummary {
+    /// Count the recipients by type
+    ///
+    /// # Arguments
+    ///
+    /// * `recip_infos` - The recipient infos
+    ///
+    /// # Returns
+    ///
+    /// The number of recipients of each type
+    pub(crate) fn new(recip_infos: &RecipientInfos) -> RecipientSummary {
+        let mut summary = RecipientSummary::default();
+        for ri in recip_infos.0.iter() {
+            match RecipientType::of(ri) {
+                RecipientType::Kem => summary.kem += 1,
+                RecipientType::KeyAgree => summary.key_agree += 1,
+                RecipientType::KeyTrans => summary.key_trans += 1,
+                RecipientType::Password => summary.password += 1,
+                RecipientType::Kek => summary.kek += 1,
+                RecipientType::Other => summary.other += 1,
+            }
+        }
+        summary
+    }
+
+    /// Get the types of recipients which are present
+    ///
+    /// # Returns
+    ///
+    /// The types with at least one recipient
+    pub fn types(&self) -> Vec<RecipientType> {
+        [
+            (RecipientType::Kem, self.kem),
+            (RecipientType::KeyAgree, self.key_agree),
+            (RecipientType::KeyTrans, self.key_trans),
+            (RecipientType::Password, self.password),
+            (RecipientType::Kek, self.kek),
+            (RecipientType::Other, self.other),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(recipient_type, _)| recipient_type)
+        .collect()
+    }
+
+    /// Get the total number of recipients
+    ///
+    /// # Returns
+    ///
+    /// The number of recipients of all types
+    pub fn total(&self) -> usize {
+        self.kem + self.key_agree + self.key_trans + self.password + self.kek + self.other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cms::enveloped_data::OtherRecipientInfo;
+    use der::Any;
+    use spki::ObjectIdentifier;
+
+    use super::*;
+    use crate::certificates::Pki;
+    use crate::content::{
+        AuthEnvelopedDataContent, CmsUtil, ContentEncryptionAlgorithm,
+        ContentEncryptionAlgorithmAead, EnvelopedDataContent,
+    };
+    use crate::dsas::DsaAlgorithm;
+    use crate::kdfs::KdfType;
+    use crate::kems::KemAlgorithm;
+    use crate::wraps::WrapType;
+    use crate::QubitCryptError;
+
+    #[test]
+    fn test_recipient_summary() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 2).unwrap();
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+        let other = RecipientInfo::Ori(OtherRecipientInfo {
+            ori_type: ObjectIdentifier::new("1.2.3.4").unwrap(),
+            ori_value: Any::new(der::Tag::Null, Vec::new()).unwrap(),
+        });
+        let expected = RecipientSummary {
+            kem: 2,
+            other: 1,
+            ..Default::default()
+        };
+        assert_eq!(expected.total(), 3);
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        for (cert, _) in pki.leaves.iter() {
+            builder.kem_recipient(cert, &kdf, &wrap, None).unwrap();
+        }
+        builder
+            .add_recipient_info(other.clone())
+            .unwrap()
+            .content_encryption_key(&[1u8; 16])
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        assert_eq!(CmsUtil::recipient_summary(&data).unwrap(), expected);
+        let (cert, sk) = &pki.leaves[1];
+        let edc = EnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk).unwrap();
+        assert_eq!(edc.recipient_summary(), expected);
+
+        let mut builder =
+            AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes128Gcm)
+                .unwrap();
+        for (cert, _) in pki.leaves.iter() {
+            builder.kem_recipient(cert, &kdf, &wrap, None).unwrap();
+        }
+        builder
+            .add_recipient_info(other)
+            .unwrap()
+            .content_encryption_key(&[1u8; 16])
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        assert_eq!(CmsUtil::recipient_summary(&data).unwrap(), expected);
+        let aedc = AuthEnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk).unwrap();
+        assert_eq!(aedc.recipient_summary(), expected);
+
+        assert!(matches!(
+            CmsUtil::recipient_summary(b"not cms"),
+            Err(QubitCryptError::InvalidEnvelopedData)
+        ));
+    }
+}
@@ -0,0 +1,374 @@
+use cms::cert::{CertificateChoices, IssuerAndSerialNumber};
+use cms::content_info::{CmsVersion, ContentInfo};
+use cms::signed_data::{
+    CertificateSet, EncapsulatedContentInfo, SignedAttributes, SignedData, SignerIdentifier,
+    SignerInfo, SignerInfos,
+};
+use const_oid::db::rfc5911::{ID_DATA, ID_SIGNED_DATA};
+use const_oid::db::rfc5912::{ID_SHA_256, ID_SHA_384, ID_SHA_512};
+use der::asn1::{ObjectIdentifier, OctetString, SetOfVec};
+use der::{Any, Encode};
+use sha2::Digest;
+use sha3::digest::ExtendableOutput;
+use spki::{AlgorithmIdentifierOwned, DynSignatureAlgorithmIdentifier};
+
+use crate::certificates::Certificate;
+use crate::cms::asn1::algorithm_protection::create_algorithm_protection_attribute;
+use crate::cms::cms_util::CmsUtil;
+use crate::keys::PrivateKey;
+use crate::utils::clock::{Clock, SystemClock};
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The OID of SHA3-256
+const ID_SHA3_256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.8");
+
+/// The OID of SHA3-384
+const ID_SHA3_384: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.9");
+
+/// The OID of SHA3-512
+const ID_SHA3_512: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.10");
+
+/// The OID of SHAKE256, which has a 512 bit output in CMS (RFC 8702)
+const ID_SHAKE256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.12");
+
+/// The digest algorithm of a signer, which is used for the message-digest attribute
+///
+/// ML-DSA signers should use SHA-512 (or SHAKE256), as the digest must have at least the
+/// security strength of the signature (RFC 9882).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    /// SHA-256
+    Sha256,
+    /// SHA-384
+    Sha384,
+    /// SHA-512
+    Sha512,
+    /// SHA3-256
+    Sha3_256,
+    /// SHA3-384
+    Sha3_384,
+    /// SHA3-512
+    Sha3_512,
+    /// SHAKE256 with a 512 bit output
+    Shake256,
+}
+
+impl DigestAlgorithm {
+    /// Get the OID of the digest algorithm
+    pub fn get_oid(&self) -> ObjectIdentifier {
+        match self {
+            DigestAlgorithm::Sha256 => ID_SHA_256,
+            DigestAlgorithm::Sha384 => ID_SHA_384,
+            DigestAlgorithm::Sha512 => ID_SHA_512,
+            DigestAlgorithm::Sha3_256 => ID_SHA3_256,
+            DigestAlgorithm::Sha3_384 => ID_SHA3_384,
+            DigestAlgorithm::Sha3_512 => ID_SHA3_512,
+            DigestAlgorithm::Shake256 => ID_SHAKE256,
+        }
+    }
+
+    /// Get the digest algorithm for an OID
+    ///
+    /// # Returns
+    ///
+    /// The digest algorithm, or `None` if it's not supported
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<DigestAlgorithm> {
+        [
+            DigestAlgorithm::Sha256,
+            DigestAlgorithm::Sha384,
+            DigestAlgorithm::Sha512,
+            DigestAlgorithm::Sha3_256,
+            DigestAlgorithm::Sha3_384,
+            DigestAlgorithm::Sha3_512,
+            DigestAlgorithm::Shake256,
+        ]
+        .into_iter()
+        .find(|alg| alg.get_oid() == *oid)
+    }
+
+    /// Compute the digest of the data
+    pub(crate) fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            DigestAlgorithm::Sha256 => sha2::Sha256::digest(data).to_vec(),
+            DigestAlgorithm::Sha384 => sha2::Sha384::digest(data).to_vec(),
+            DigestAlgorithm::Sha512 => sha2::Sha512::digest(data).to_vec(),
+            DigestAlgorithm::Sha3_256 => sha3::Sha3_256::digest(data).to_vec(),
+            DigestAlgorithm::Sha3_384 => sha3::Sha3_384::digest(data).to_vec(),
+            DigestAlgorithm::Sha3_512 => sha3::Sha3_512::digest(data).to_vec(),
+            DigestAlgorithm::Shake256 => {
+                let mut digest = vec![0u8; 64];
+                sha3::Shake256::digest_xof(data, &mut digest);
+                digest
+            }
+        }
+    }
+
+    /// Get the algorithm identifier, whose parameters are absent
+    fn algorithm_identifier(&self) -> AlgorithmIdentifierOwned {
+        AlgorithmIdentifierOwned {
+            oid: self.get_oid(),
+            parameters: None,
+        }
+    }
+}
+
+/// A builder for creating a SignedData
+///
+/// Each signer gets the content-type, message-digest, signing-time and CMS algorithm
+/// protection (RFC 6211) signed attributes, so the digest and signature algorithms of the
+/// SignerInfo are covered by the signature. The signing time is taken from the system clock,
+/// unless another clock is set with `clock`. The signers' certificates are embedded in the
+/// SignedData, along with any certificates added with `add_certificates`.
+pub struct SignedDataBuilder<'a> {
+    /// The digest algorithm of the signers
+    digest_algorithm: DigestAlgorithm,
+    /// The content
+    content: Vec<u8>,
+    /// Whether the content is left out of the SignedData
+    detached: bool,
+    /// The signers' certificates and private keys
+    signers: Vec<(&'a Certificate, &'a PrivateKey)>,
+    /// Further certificates to embed, e.g. the signers' intermediate CAs
+    certificates: Vec<&'a Certificate>,
+    /// The clock the signing time is taken from
+    clock: &'a dyn Clock,
+}
+
+impl<'a> SignedDataBuilder<'a> {
+    /// Create a new SignedDataBuilder
+    ///
+    /// # Arguments
+    ///
+    /// * `digest_algorithm` - The digest algorithm of the signers
+    ///
+    /// # Returns
+    ///
+    /// A new SignedDataBuilder
+    pub(crate) fn new(digest_algorithm: DigestAlgorithm) -> Result<Self> {
+        Ok(Self {
+            digest_algorithm,
+            content: Vec::new(),
+            detached: false,
+            signers: Vec::new(),
+            certificates: Vec::new(),
+            clock: &SystemClock,
+        })
+    }
+
+    /// Set the content to sign
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn content(&mut self, content: &[u8]) -> Result<&mut Self> {
+        self.content = content.to_vec();
+        Ok(self)
+    }
+
+    /// Set whether the content is detached, i.e. left out of the SignedData
+    ///
+    /// # Arguments
+    ///
+    /// * `detached` - Whether the content is detached
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn detached(&mut self, detached: bool) -> Result<&mut Self> {
+        self.detached = detached;
+        Ok(self)
+    }
+
+    /// Add a signer
+    ///
+    /// # Arguments
+    ///
+    /// * `cert` - The signer's certificate
+    /// * `private_key` - The signer's private key
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the private key is not of the
+    /// certificate's algorithm, `QubitCryptError::KeyUsageViolation` will be returned if the
+    /// certificate's key usage doesn't allow digital signatures
+    pub fn signer(
+        &mut self,
+        cert: &'a Certificate,
+        private_key: &'a PrivateKey,
+    ) -> Result<&mut Self> {
+        if private_key.get_oid() != cert.get_public_key_oid() {
+            return Err(QubitCryptError::InvalidPrivateKey);
+        }
+        if !cert.is_digital_signature_enabled() {
+            return Err(QubitCryptError::KeyUsageViolation);
+        }
+        self.signers.push((cert, private_key));
+        Ok(self)
+    }
+
+    /// Add certificates to embed in the SignedData, e.g. the signer's chain
+    ///
+    /// With the intermediate CAs (and optionally the root) embedded, a verifier which only
+    /// holds the root can build the path from the SignedData alone. Certificates which are
+    /// already embedded, such as the signers' own, are only embedded once.
+    ///
+    /// # Arguments
+    ///
+    /// * `certs` - The certificates
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn add_certificates(&mut self, certs: &'a [Certificate]) -> Result<&mut Self> {
+        self.certificates.extend(certs);
+        Ok(self)
+    }
+
+    /// Set the clock the signing time is taken from
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn clock(&mut self, clock: &'a dyn Clock) -> Result<&mut Self> {
+        self.clock = clock;
+        Ok(self)
+    }
+
+    /// Build the SignedData
+    ///
+    /// # Returns
+    ///
+    /// The DER bytes of the SignedData, wrapped in a ContentInfo
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::EmptyContent` if no content was set,
+    /// `QubitCryptError::SerializationFailed` if the SignedData can't be encoded
+    pub fn build(self) -> Result<Vec<u8>> {
+        if self.content.is_empty() {
+            return Err(QubitCryptError::EmptyContent);
+        }
+
+        let digest_algorithm = self.digest_algorithm.algorithm_identifier();
+        let mut digest_algorithms = SetOfVec::new();
+        digest_algorithms
+            .insert(digest_algorithm.clone())
+            .map_err(|_| QubitCryptError::SerializationFailed)?;
+
+        let econtent = if self.detached {
+            None
+        } else {
+            let econtent = OctetString::new(self.content.clone())
+                .map_err(|_| QubitCryptError::InvalidContent)?;
+            Some(Any::encode_from(&econtent).map_err(|_| QubitCryptError::InvalidContent)?)
+        };
+
+        let mut certificates = SetOfVec::new();
+        let signer_certs = self.signers.iter().map(|(cert, _)| *cert);
+        for cert in signer_certs.chain(self.certificates.iter().copied()) {
+            let choice = CertificateChoices::Certificate(cert.to_x509_cert());
+            // Signers may share a certificate, e.g. to sign with a composite key twice, and a
+            // chain may contain a signer's certificate
+            if !certificates.iter().any(|c| *c == choice) {
+                certificates
+                    .insert(choice)
+                    .map_err(|_| QubitCryptError::InvalidCertificate)?;
+            }
+        }
+
+        let mut signer_infos = SetOfVec::new();
+        for (cert, private_key) in &self.signers {
+            let signer_info = self.build_signer_info(cert, private_key, &digest_algorithm)?;
+            signer_infos
+                .insert(signer_info)
+                .map_err(|_| QubitCryptError::SignatureFailed)?;
+        }
+
+        let signed_data = SignedData {
+            version: CmsVersion::V1,
+            digest_algorithms,
+            encap_content_info: EncapsulatedContentInfo {
+                econtent_type: ID_DATA,
+                econtent,
+            },
+            certificates: (!certificates.is_empty()).then_some(CertificateSet(certificates)),
+            crls: None,
+            signer_infos: SignerInfos(signer_infos),
+        };
+
+        let content_info = ContentInfo {
+            content_type: ID_SIGNED_DATA,
+            content: Any::encode_from(&signed_data)
+                .map_err(|_| QubitCryptError::SerializationFailed)?,
+        };
+        content_info
+            .to_der()
+            .map_err(|_| QubitCryptError::SerializationFailed)
+    }
+
+    /// Build the SignedData and write it to a file
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path to write the file to
+    pub fn build_to_file(self, file_path: &str) -> Result<()> {
+        let data = self.build()?;
+        std::fs::write(file_path, data).map_err(|_| QubitCryptError::FileWriteError)
+    }
+
+    /// Build the SignerInfo of a signer, which signs the DER encoded signed attributes
+    fn build_signer_info(
+        &self,
+        cert: &Certificate,
+        private_key: &PrivateKey,
+        digest_algorithm: &AlgorithmIdentifierOwned,
+    ) -> Result<SignerInfo> {
+        let signature_algorithm = private_key
+            .signature_algorithm_identifier()
+            .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+
+        let digest = self.digest_algorithm.digest(&self.content);
+        let signing_time = self.clock.now();
+        let signed_attrs: SignedAttributes = [
+            CmsUtil::create_content_type_attribute(ID_DATA)?,
+            CmsUtil::create_message_digest_attribute(&digest)?,
+            CmsUtil::create_signing_time_attribute(&signing_time)?,
+            create_algorithm_protection_attribute(digest_algorithm, &signature_algorithm)?,
+        ]
+        .to_vec()
+        .try_into()
+        .map_err(|_| QubitCryptError::InvalidAttribute)?;
+
+        let msg = signed_attrs
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidAttribute)?;
+        let signature = private_key.sign(&msg)?;
+
+        Ok(SignerInfo {
+            version: CmsVersion::V1,
+            sid: SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+                issuer: cert.get_issuer(),
+                serial_number: cert.get_serial_number(),
+            }),
+            digest_alg: digest_algorithm.clone(),
+            signed_attrs: Some(signed_attrs),
+            signature_algorithm,
+            signature: OctetString::new(signature).map_err(|_| QubitCryptError::SignatureFailed)?,
+            unsigned_attrs: None,
+        })
+    }
+}
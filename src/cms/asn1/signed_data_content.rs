@@ -0,0 +1,890 @@
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::enveloped_data::RecipientIdentifier;
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
+use const_oid::db::rfc5911::{ID_CONTENT_TYPE, ID_MESSAGE_DIGEST, ID_SIGNED_DATA, ID_SIGNING_TIME};
+use der::asn1::{ObjectIdentifier, OctetStringRef};
+use der::{Decode, Encode};
+use x509_cert::attr::Attributes;
+
+use crate::certificates::Certificate;
+use crate::cms::asn1::algorithm_protection::check_algorithm_protection;
+use crate::cms::asn1::signed_data_builder::{DigestAlgorithm, SignedDataBuilder};
+use crate::cms::cms_util::CmsUtil;
+use crate::utils::clock::{Clock, SystemClock};
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// A SignedData content, as defined in
+/// [RFC 5652 § 5](https://datatracker.ietf.org/doc/html/rfc5652#section-5)
+///
+/// This is used to verify signatures made by other tools, e.g. a detached `.p7s` signature
+/// from OpenSSL, where the signer's certificate and any intermediate CA certificates are
+/// embedded in the SignedData. `SignedDataContent::get_builder` creates SignedData.
+///
+/// # Example
+/// ```no_run
+/// use qubitcrypt::certificates::Certificate;
+/// use qubitcrypt::content::SignedDataContent;
+///
+/// let data = std::fs::read("release.tar.gz").unwrap();
+/// let p7s = std::fs::read("release.tar.gz.p7s").unwrap();
+/// let ta = Certificate::from_file("root_ca.pem").unwrap();
+/// let valid = SignedDataContent::verify_detached_external(&data, &p7s, &[ta]).unwrap();
+/// ```
+pub struct SignedDataContent {
+    /// The SignedData
+    signed_data: SignedData,
+}
+
+impl SignedDataContent {
+    /// Create a new SignedDataContent object from bytes. The SignedData is wrapped in a
+    /// ContentInfo object, which can be DER or PEM encoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The bytes to read the SignedData content from
+    ///
+    /// # Returns
+    ///
+    /// A new SignedDataContent object
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContent` will be returned if the data is not a SignedData
+    pub fn from_bytes(data: &[u8]) -> Result<SignedDataContent> {
+        let ci = if let Ok(content_info) = ContentInfo::from_der(data) {
+            content_info
+        } else {
+            let pem = pem::parse(data).map_err(|_| QubitCryptError::InvalidContent)?;
+            ContentInfo::from_der(pem.contents()).map_err(|_| QubitCryptError::InvalidContent)?
+        };
+
+        if ci.content_type != ID_SIGNED_DATA {
+            return Err(QubitCryptError::InvalidContent);
+        }
+
+        let signed_data = ci
+            .content
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidContent)?;
+        let signed_data =
+            SignedData::from_der(&signed_data).map_err(|_| QubitCryptError::InvalidContent)?;
+
+        Ok(SignedDataContent { signed_data })
+    }
+
+    /// Get a new SignedDataBuilder
+    ///
+    /// # Arguments
+    ///
+    /// * `digest_algorithm` - The digest algorithm of the signers
+    ///
+    /// # Returns
+    ///
+    /// A new SignedDataBuilder which can be used to create a SignedData
+    ///
+    /// # Example
+    /// ```
+    /// use qubitcrypt::certificates::{CertValidity, CertificateBuilder, Pki, Profile};
+    /// use qubitcrypt::content::{DigestAlgorithm, SignedDataContent};
+    /// use qubitcrypt::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    /// use qubitcrypt::kems::KemAlgorithm;
+    ///
+    /// let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+    /// let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65).generate().unwrap();
+    /// let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+    /// let profile = Profile::Leaf {
+    ///     issuer: pki.intermediate_cert.get_subject(),
+    ///     enable_key_agreement: false,
+    ///     enable_key_encipherment: false,
+    /// };
+    /// let issuer_key = &pki.intermediate_key;
+    /// let cert = CertificateBuilder::new(profile, None, validity, "CN=Signer", pk, issuer_key)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut builder = SignedDataContent::get_builder(DigestAlgorithm::Sha512).unwrap();
+    /// builder
+    ///     .signer(&cert, &sk)
+    ///     .unwrap()
+    ///     .content(b"abc")
+    ///     .unwrap()
+    ///     .detached(true)
+    ///     .unwrap();
+    /// let p7s = builder.build().unwrap();
+    ///
+    /// let anchors = [pki.intermediate_cert.clone()];
+    /// let valid = SignedDataContent::verify_detached_external(b"abc", &p7s, &anchors).unwrap();
+    /// assert!(valid);
+    /// ```
+    pub fn get_builder<'a>(digest_algorithm: DigestAlgorithm) -> Result<SignedDataBuilder<'a>> {
+        SignedDataBuilder::new(digest_algorithm)
+    }
+
+    /// Get the certificates embedded in the SignedData
+    ///
+    /// Other certificate formats, such as attribute certificates, are skipped.
+    pub fn get_certificates(&self) -> Vec<Certificate> {
+        let Some(certificates) = self.signed_data.certificates.as_ref() else {
+            return Vec::new();
+        };
+        certificates
+            .0
+            .iter()
+            .filter_map(|choice| match choice {
+                CertificateChoices::Certificate(cert) => cert.to_der().ok(),
+                _ => None,
+            })
+            .filter_map(|der| Certificate::from_der(&der).ok())
+            .collect()
+    }
+
+    /// Get the signing times claimed by the signers in their signing-time attributes
+    ///
+    /// Signers without a signing-time attribute are skipped. The signing time is not covered
+    /// by a trusted third party, so it's only as trustworthy as the signer.
+    ///
+    /// # Returns
+    ///
+    /// The signing times, in the order of the signers
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidAttribute` will be returned if a signer has more than one
+    /// signing-time attribute or one with an invalid value
+    pub fn get_signing_times(&self) -> Result<Vec<chrono::DateTime<chrono::Utc>>> {
+        let mut signing_times = Vec::new();
+        for signer_info in self.signed_data.signer_infos.0.iter() {
+            let Some(signed_attrs) = signer_info.signed_attrs.as_ref() else {
+                continue;
+            };
+            if let Some(signing_time) = signing_time(signed_attrs)? {
+                signing_times.push(signing_time);
+            }
+        }
+        Ok(signing_times)
+    }
+
+    /// Check if the content is detached, i.e. the SignedData doesn't contain the signed content
+    pub fn is_detached(&self) -> bool {
+        self.signed_data.encap_content_info.econtent.is_none()
+    }
+
+    /// Verify a detached signature over the specified data
+    ///
+    /// Every signer must pass these checks:
+    ///
+    /// 1. The signer's certificate is found among the embedded certificates by the signer
+    ///    identifier.
+    /// 2. There is a chain from the signer's certificate to one of the trust anchors, through
+    ///    embedded CA certificates. The whole path must pass `Certificate::verify_chain`, so
+    ///    the name constraints of the trust anchor also apply to the signer's certificate.
+    ///    Certificates which are not embedded can't be used as intermediates, so they must be
+    ///    trust anchors.
+    /// 3. The key usage of the signer's certificate allows digital signatures.
+    /// 4. If there are signed attributes, the message-digest attribute matches the digest of
+    ///    the data and the content-type attribute matches the encapsulated content type. A
+    ///    signing-time attribute is optional, but there must be at most one and its value must be
+    ///    a valid time. The supported digest algorithms are SHA-2, SHA-3 and SHAKE256. If there
+    ///    is a CMS algorithm protection attribute (RFC 6211), its digest and signature
+    ///    algorithms match the ones of the SignerInfo.
+    /// 5. The signature algorithm is the one of the signer's public key and the signature is
+    ///    valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The signed data, which is not part of the SignedData
+    /// * `trust_anchors` - The trusted CA certificates
+    ///
+    /// # Returns
+    ///
+    /// True if all signers are trusted and all signatures are valid, false otherwise or if there
+    /// are no signers
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContent` will be returned if the content is not detached,
+    /// `QubitCryptError::KeyUsageViolation` will be returned if a trusted signer's key usage
+    /// doesn't allow digital signatures, `QubitCryptError::UnsupportedOperation` will be
+    /// returned if the digest algorithm is not supported
+    pub fn verify_detached(&self, data: &[u8], trust_anchors: &[Certificate]) -> Result<bool> {
+        self.verify_detached_with_clock(data, trust_anchors, &SystemClock)
+    }
+
+    /// Verify a detached signature like `verify_detached`, taking the current time from a clock
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The signed data, which is not part of the SignedData
+    /// * `trust_anchors` - The trusted CA certificates
+    /// * `clock` - The clock the validity periods of the chains are checked against
+    ///
+    /// # Returns
+    ///
+    /// True if all signers are trusted and all signatures are valid, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `verify_detached`
+    pub fn verify_detached_with_clock(
+        &self,
+        data: &[u8],
+        trust_anchors: &[Certificate],
+        clock: &dyn Clock,
+    ) -> Result<bool> {
+        if !self.is_detached() {
+            return Err(QubitCryptError::InvalidContent);
+        }
+
+        let certificates = self.get_certificates();
+        let signer_infos = &self.signed_data.signer_infos.0;
+        if signer_infos.is_empty() {
+            return Ok(false);
+        }
+        for signer_info in signer_infos.iter() {
+            if !self.verify_signer(signer_info, data, &certificates, trust_anchors, clock)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Verify a detached signature made by another tool
+    ///
+    /// This parses the SignedData and verifies it with `verify_detached`, using the
+    /// certificates embedded in the SignedData to find the signers and build their chains.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The signed data
+    /// * `p7s` - The DER or PEM encoded detached SignedData
+    /// * `trust_anchors` - The trusted CA certificates
+    ///
+    /// # Returns
+    ///
+    /// True if all signers are trusted and all signatures are valid, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `from_bytes` and `verify_detached`
+    pub fn verify_detached_external(
+        data: &[u8],
+        p7s: &[u8],
+        trust_anchors: &[Certificate],
+    ) -> Result<bool> {
+        SignedDataContent::from_bytes(p7s)?.verify_detached(data, trust_anchors)
+    }
+
+    /// Verify one signer of the SignedData
+    fn verify_signer(
+        &self,
+        signer_info: &SignerInfo,
+        data: &[u8],
+        certificates: &[Certificate],
+        trust_anchors: &[Certificate],
+        clock: &dyn Clock,
+    ) -> Result<bool> {
+        // 1. The signer's certificate
+        let rid = match &signer_info.sid {
+            SignerIdentifier::IssuerAndSerialNumber(issuer) => {
+                RecipientIdentifier::IssuerAndSerialNumber(issuer.clone())
+            }
+            SignerIdentifier::SubjectKeyIdentifier(skid) => {
+                RecipientIdentifier::SubjectKeyIdentifier(skid.clone())
+            }
+        };
+        let Some(signer_cert) = certificates.iter().find(|cert| cert.is_identified_by(&rid)) else {
+            return Ok(false);
+        };
+
+        // 2. The chain to a trust anchor
+        if !signer_cert.is_trusted_by(certificates, trust_anchors, clock) {
+            return Ok(false);
+        }
+
+        // 3. The key usage
+        if !signer_cert.is_digital_signature_enabled() {
+            return Err(QubitCryptError::KeyUsageViolation);
+        }
+
+        // 4. The signed attributes
+        let msg = match &signer_info.signed_attrs {
+            Some(signed_attrs) => {
+                let digest = digest(&signer_info.digest_alg.oid, data)?;
+                let econtent_type = self.signed_data.encap_content_info.econtent_type;
+                if !check_signed_attributes(signed_attrs, &digest, &econtent_type) {
+                    return Ok(false);
+                }
+                if !check_algorithm_protection(
+                    signed_attrs,
+                    &signer_info.digest_alg,
+                    &signer_info.signature_algorithm,
+                ) {
+                    return Ok(false);
+                }
+                signed_attrs
+                    .to_der()
+                    .map_err(|_| QubitCryptError::InvalidContent)?
+            }
+            None => data.to_vec(),
+        };
+
+        // 5. The signature
+        if signer_info.signature_algorithm.oid.to_string() != signer_cert.get_public_key_oid() {
+            return Ok(false);
+        }
+        let pk = signer_cert.get_public_key()?;
+        Ok(pk
+            .verify(&msg, signer_info.signature.as_bytes())
+            .unwrap_or(false))
+    }
+}
+
+/// Check the message-digest and content-type signed attributes
+///
+/// # Arguments
+///
+/// * `signed_attrs` - The signed attributes
+/// * `digest` - The digest of the content
+/// * `econtent_type` - The encapsulated content type
+///
+/// # Returns
+///
+/// True if both attributes are present once, with a single value which matches, and the
+/// signing-time attribute, if any, is valid
+fn check_signed_attributes(
+    signed_attrs: &Attributes,
+    digest: &[u8],
+    econtent_type: &ObjectIdentifier,
+) -> bool {
+    let single_value = |oid| {
+        let mut attrs = signed_attrs.iter().filter(|attr| attr.oid == oid);
+        match (attrs.next(), attrs.next()) {
+            (Some(attr), None) if attr.values.len() == 1 => attr.values.get(0),
+            _ => None,
+        }
+    };
+
+    let digest_matches = single_value(ID_MESSAGE_DIGEST)
+        .and_then(|value| value.decode_as::<OctetStringRef>().ok())
+        .map(|value| value.as_bytes() == digest)
+        .unwrap_or(false);
+    let content_type_matches = single_value(ID_CONTENT_TYPE)
+        .and_then(|value| value.decode_as::<ObjectIdentifier>().ok())
+        .map(|value| value == *econtent_type)
+        .unwrap_or(false);
+    digest_matches && content_type_matches && signing_time(signed_attrs).is_ok()
+}
+
+/// Get the signing time from the signing-time signed attribute
+///
+/// # Arguments
+///
+/// * `signed_attrs` - The signed attributes
+///
+/// # Returns
+///
+/// The signing time, or `None` if there is no signing-time attribute
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidAttribute` will be returned if there is more than one signing-time
+/// attribute or its value is not a single valid time
+fn signing_time(signed_attrs: &Attributes) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    let mut attrs = signed_attrs
+        .iter()
+        .filter(|attr| attr.oid == ID_SIGNING_TIME);
+    match (attrs.next(), attrs.next()) {
+        (None, _) => Ok(None),
+        (Some(attr), None) => CmsUtil::parse_signing_time_attribute(attr).map(Some),
+        _ => Err(QubitCryptError::InvalidAttribute),
+    }
+}
+
+/// Compute the digest of the data with the specified digest algorithm
+///
+/// # Errors
+///
+/// `QubitCryptError::UnsupportedOperation` will be returned if the digest algorithm is not
+/// supported
+fn digest(oid: &ObjectIdentifier, data: &[u8]) -> Result<Vec<u8>> {
+    let digest_algorithm =
+        DigestAlgorithm::from_oid(oid).ok_or(QubitCryptError::UnsupportedOperation)?;
+    Ok(digest_algorithm.digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::{CertValidity, CertificateBuilder, FixedClock, Pki, Profile};
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::KemAlgorithm;
+    use crate::keys::PrivateKey;
+    use cms::builder::{SignedDataBuilder, SignerInfoBuilder};
+    use cms::cert::IssuerAndSerialNumber;
+    use cms::signed_data::EncapsulatedContentInfo;
+    use const_oid::db::rfc5911::ID_DATA;
+    use const_oid::db::rfc5912::{ID_SHA_256, ID_SHA_384};
+    use sha2::Digest;
+    use spki::{AlgorithmIdentifierOwned, DynSignatureAlgorithmIdentifier};
+    use x509_cert::attr::Attribute;
+
+    use crate::asn1::signature::DsaSignature;
+    use crate::cms::asn1::algorithm_protection::{
+        create_algorithm_protection_attribute, CmsAlgorithmProtection,
+        ID_AA_CMS_ALGORITHM_PROTECTION,
+    };
+
+    /// Sign the data with the cms crate, as another tool would
+    fn sign_detached(
+        data: &[u8],
+        signer_cert: &Certificate,
+        signer_key: &PrivateKey,
+        embedded: &[&Certificate],
+        econtent: Option<&[u8]>,
+    ) -> Vec<u8> {
+        sign_detached_with_attributes(data, signer_cert, signer_key, embedded, econtent, &[])
+    }
+
+    /// Sign the data with the cms crate, adding more signed attributes
+    fn sign_detached_with_attributes(
+        data: &[u8],
+        signer_cert: &Certificate,
+        signer_key: &PrivateKey,
+        embedded: &[&Certificate],
+        econtent: Option<&[u8]>,
+        signed_attrs: &[Attribute],
+    ) -> Vec<u8> {
+        let digest = sha2::Sha256::digest(data).to_vec();
+        let eci = EncapsulatedContentInfo {
+            econtent_type: ID_DATA,
+            econtent: econtent
+                .map(|econtent| der::Any::new(der::Tag::OctetString, econtent.to_vec()).unwrap()),
+        };
+        let digest_alg = AlgorithmIdentifierOwned {
+            oid: ID_SHA_256,
+            parameters: None,
+        };
+        let sid = SignerIdentifier::IssuerAndSerialNumber(IssuerAndSerialNumber {
+            issuer: signer_cert.get_issuer(),
+            serial_number: signer_cert.get_serial_number(),
+        });
+        let external_digest = econtent.is_none().then_some(digest.as_slice());
+        let mut signer_info =
+            SignerInfoBuilder::new(signer_key, sid, digest_alg.clone(), &eci, external_digest)
+                .unwrap();
+        for attr in signed_attrs {
+            signer_info.add_signed_attribute(attr.clone()).unwrap();
+        }
+
+        let mut builder = SignedDataBuilder::new(&eci);
+        builder.add_digest_algorithm(digest_alg).unwrap();
+        for cert in embedded {
+            builder
+                .add_certificate(CertificateChoices::Certificate(cert.to_x509_cert()))
+                .unwrap();
+        }
+        builder
+            .add_signer_info::<PrivateKey, DsaSignature>(signer_info)
+            .unwrap();
+        builder.build().unwrap().to_der().unwrap()
+    }
+
+    /// Issue an ML-DSA-65 signing certificate from the intermediate CA
+    fn issue_signer(pki: &Pki) -> (Certificate, PrivateKey) {
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa65)
+            .generate()
+            .unwrap();
+        let signer_cert = CertificateBuilder::new(
+            Profile::Leaf {
+                issuer: pki.intermediate_cert.get_subject(),
+                enable_key_agreement: false,
+                enable_key_encipherment: false,
+            },
+            None,
+            validity,
+            "CN=Release Signer",
+            pk,
+            &pki.intermediate_key,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        (signer_cert, sk)
+    }
+
+    #[test]
+    fn test_verify_detached_external() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        let (signer_cert, sk) = issue_signer(&pki);
+
+        let data = b"release artifact";
+        let anchors = [pki.root_cert.clone()];
+        let p7s = sign_detached(
+            data,
+            &signer_cert,
+            &sk,
+            &[&signer_cert, &pki.intermediate_cert],
+            None,
+        );
+        let sdc = SignedDataContent::from_bytes(&p7s).unwrap();
+        assert!(sdc.is_detached());
+        assert_eq!(sdc.get_certificates().len(), 2);
+        assert!(SignedDataContent::verify_detached_external(data, &p7s, &anchors).unwrap());
+
+        // PEM is accepted as well
+        let pem = pem::encode(&pem::Pem::new("CMS", p7s.clone()));
+        assert!(
+            SignedDataContent::verify_detached_external(data, pem.as_bytes(), &anchors).unwrap()
+        );
+
+        // Other data, other trust anchors and a missing intermediate
+        assert!(!SignedDataContent::verify_detached_external(b"other", &p7s, &anchors).unwrap());
+        let other = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        assert!(
+            !SignedDataContent::verify_detached_external(data, &p7s, &[other.root_cert]).unwrap()
+        );
+        let p7s_without_chain = sign_detached(data, &signer_cert, &sk, &[&signer_cert], None);
+        assert!(
+            !SignedDataContent::verify_detached_external(data, &p7s_without_chain, &anchors)
+                .unwrap()
+        );
+        // The intermediate can also be a trust anchor itself
+        assert!(SignedDataContent::verify_detached_external(
+            data,
+            &p7s_without_chain,
+            &[pki.intermediate_cert.clone()]
+        )
+        .unwrap());
+
+        // The signer's certificate must be embedded
+        let p7s_without_signer = sign_detached(data, &signer_cert, &sk, &[], None);
+        assert!(
+            !SignedDataContent::verify_detached_external(data, &p7s_without_signer, &anchors)
+                .unwrap()
+        );
+
+        // Attached content and other content types are rejected
+        let p7s_attached = sign_detached(data, &signer_cert, &sk, &[&signer_cert], Some(data));
+        assert_eq!(
+            SignedDataContent::verify_detached_external(data, &p7s_attached, &anchors).err(),
+            Some(QubitCryptError::InvalidContent)
+        );
+        assert_eq!(
+            SignedDataContent::from_bytes(&pki.root_cert.to_der().unwrap()).err(),
+            Some(QubitCryptError::InvalidContent)
+        );
+    }
+
+    #[test]
+    fn test_embedded_chain() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        let (signer_cert, sk) = issue_signer(&pki);
+        let data = b"air-gapped release";
+        let anchors = [pki.root_cert.clone()];
+
+        // Without the intermediate CA there's no path to the root
+        let mut builder = SignedDataContent::get_builder(DigestAlgorithm::Sha512).unwrap();
+        builder
+            .signer(&signer_cert, &sk)
+            .unwrap()
+            .content(data)
+            .unwrap()
+            .detached(true)
+            .unwrap();
+        let p7s = builder.build().unwrap();
+        assert_eq!(
+            SignedDataContent::from_bytes(&p7s)
+                .unwrap()
+                .get_certificates()
+                .len(),
+            1
+        );
+        assert!(!SignedDataContent::verify_detached_external(data, &p7s, &anchors).unwrap());
+
+        // The whole chain, with the signer's certificate and the intermediate twice
+        let chain = [
+            signer_cert.clone(),
+            pki.intermediate_cert.clone(),
+            pki.root_cert.clone(),
+            pki.intermediate_cert.clone(),
+        ];
+        let mut builder = SignedDataContent::get_builder(DigestAlgorithm::Sha512).unwrap();
+        builder
+            .signer(&signer_cert, &sk)
+            .unwrap()
+            .add_certificates(&chain)
+            .unwrap()
+            .content(data)
+            .unwrap()
+            .detached(true)
+            .unwrap();
+        let p7s = builder.build().unwrap();
+
+        let sdc = SignedDataContent::from_bytes(&p7s).unwrap();
+        let certificates = sdc.get_certificates();
+        assert_eq!(certificates.len(), 3);
+        for cert in &chain[..3] {
+            assert!(certificates
+                .iter()
+                .any(|c| c.to_der().unwrap() == cert.to_der().unwrap()));
+        }
+        assert!(SignedDataContent::verify_detached_external(data, &p7s, &anchors).unwrap());
+    }
+
+    #[test]
+    fn test_root_name_constraints() {
+        use crate::certificates::NameConstraint;
+
+        let validity = || CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let keys = || {
+            DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+                .generate()
+                .unwrap()
+        };
+
+        // Only the root is constrained, the intermediate has no name constraints
+        let (root_pk, root_sk) = keys();
+        let mut builder = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity(),
+            "CN=Root CA,O=Example,C=US",
+            root_pk,
+            &root_sk,
+        )
+        .unwrap();
+        builder
+            .add_name_constraints(
+                &[NameConstraint::DirectoryName("O=Example,C=US".to_string())],
+                &[],
+            )
+            .unwrap();
+        let root = builder.build().unwrap();
+        let (int_pk, int_sk) = keys();
+        let intermediate = CertificateBuilder::new(
+            Profile::SubCA {
+                issuer: root.get_subject(),
+                path_len_constraint: Some(0),
+            },
+            None,
+            validity(),
+            "CN=Intermediate CA,O=Example,C=US",
+            int_pk,
+            &root_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let sign = |subject: &str| {
+            let (pk, sk) = keys();
+            let cert = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: intermediate.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: false,
+                },
+                None,
+                validity(),
+                subject,
+                pk,
+                &int_sk,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+            sign_detached(b"data", &cert, &sk, &[&cert, &intermediate], None)
+        };
+        let anchors = [root.clone()];
+
+        let p7s = sign("CN=Signer,O=Example,C=US");
+        assert!(SignedDataContent::verify_detached_external(b"data", &p7s, &anchors).unwrap());
+
+        // The intermediate alone permits any name, but the root's constraints apply to the
+        // whole path
+        let p7s = sign("CN=Signer,O=Other,C=US");
+        assert!(!SignedDataContent::verify_detached_external(b"data", &p7s, &anchors).unwrap());
+        assert!(SignedDataContent::verify_detached_external(
+            b"data",
+            &p7s,
+            &[intermediate.clone()]
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_signing_time() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        let (signer_cert, sk) = issue_signer(&pki);
+        let data = b"release artifact";
+        let anchors = [pki.root_cert.clone()];
+
+        // Dates before 2050 are UTCTime, later ones GeneralizedTime
+        for (time, tag) in [
+            ("2030-06-01T12:00:00Z", der::Tag::UtcTime),
+            ("2051-06-01T12:00:00Z", der::Tag::GeneralizedTime),
+        ] {
+            let time: chrono::DateTime<chrono::Utc> =
+                chrono::DateTime::parse_from_rfc3339(time).unwrap().into();
+            let clock = FixedClock(time);
+            let mut builder = SignedDataContent::get_builder(DigestAlgorithm::Sha512).unwrap();
+            builder
+                .signer(&signer_cert, &sk)
+                .unwrap()
+                .add_certificates(std::slice::from_ref(&pki.intermediate_cert))
+                .unwrap()
+                .clock(&clock)
+                .unwrap()
+                .content(data)
+                .unwrap()
+                .detached(true)
+                .unwrap();
+            let p7s = builder.build().unwrap();
+
+            let sdc = SignedDataContent::from_bytes(&p7s).unwrap();
+            assert_eq!(sdc.get_signing_times().unwrap(), vec![time]);
+            let signer_info = sdc.signed_data.signer_infos.0.get(0).unwrap();
+            let attr = signer_info
+                .signed_attrs
+                .as_ref()
+                .unwrap()
+                .iter()
+                .find(|attr| attr.oid == ID_SIGNING_TIME)
+                .unwrap();
+            assert_eq!(der::Tagged::tag(attr.values.get(0).unwrap()), tag);
+            // The signing time doesn't affect the validity checks of the chain
+            assert!(sdc.verify_detached(data, &anchors).unwrap());
+        }
+
+        // A single signing-time attribute from another tool is accepted, a second one rejected
+        let time = chrono::DateTime::parse_from_rfc3339("2030-06-01T12:00:00Z").unwrap();
+        let attr = CmsUtil::create_signing_time_attribute(&time.into()).unwrap();
+        let embedded = [&signer_cert, &pki.intermediate_cert];
+        let p7s = sign_detached_with_attributes(
+            data,
+            &signer_cert,
+            &sk,
+            &embedded,
+            None,
+            &[attr.clone()],
+        );
+        let sdc = SignedDataContent::from_bytes(&p7s).unwrap();
+        assert_eq!(sdc.get_signing_times().unwrap(), vec![time]);
+        assert!(sdc.verify_detached(data, &anchors).unwrap());
+        let later =
+            CmsUtil::create_signing_time_attribute(&(time + chrono::Duration::hours(1)).into())
+                .unwrap();
+        let p7s =
+            sign_detached_with_attributes(data, &signer_cert, &sk, &embedded, None, &[attr, later]);
+        let sdc = SignedDataContent::from_bytes(&p7s).unwrap();
+        assert_eq!(
+            sdc.get_signing_times().err(),
+            Some(QubitCryptError::InvalidAttribute)
+        );
+        assert!(!sdc.verify_detached(data, &anchors).unwrap());
+    }
+
+    #[test]
+    fn test_algorithm_protection() {
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 0).unwrap();
+        let (signer_cert, sk) = issue_signer(&pki);
+        let data = b"release artifact";
+        let anchors = [pki.root_cert.clone()];
+
+        for digest_algorithm in [DigestAlgorithm::Sha512, DigestAlgorithm::Shake256] {
+            let mut builder = SignedDataContent::get_builder(digest_algorithm).unwrap();
+            builder
+                .signer(&signer_cert, &sk)
+                .unwrap()
+                .content(data)
+                .unwrap()
+                .detached(true)
+                .unwrap();
+            let p7s = builder.build().unwrap();
+            assert!(SignedDataContent::verify_detached_external(
+                data,
+                &p7s,
+                &[pki.intermediate_cert.clone()]
+            )
+            .unwrap());
+
+            // The attribute binds the digest algorithm and ML-DSA with absent parameters
+            let sdc = SignedDataContent::from_bytes(&p7s).unwrap();
+            let signer_info = sdc.signed_data.signer_infos.0.get(0).unwrap();
+            let signed_attrs = signer_info.signed_attrs.as_ref().unwrap();
+            let attr = signed_attrs
+                .iter()
+                .find(|attr| attr.oid == ID_AA_CMS_ALGORITHM_PROTECTION)
+                .unwrap();
+            let protection: CmsAlgorithmProtection =
+                attr.values.get(0).unwrap().decode_as().unwrap();
+            assert_eq!(protection.digest_algorithm.oid, digest_algorithm.get_oid());
+            assert_eq!(
+                protection.signature_algorithm.unwrap().oid.to_string(),
+                signer_cert.get_public_key_oid()
+            );
+            assert!(protection.mac_algorithm.is_none());
+        }
+
+        // A validly signed attribute with other algorithms than the SignerInfo
+        let sha256 = AlgorithmIdentifierOwned {
+            oid: ID_SHA_256,
+            parameters: None,
+        };
+        let ml_dsa_65 = sk.signature_algorithm_identifier().unwrap();
+        let (_, ml_dsa_44) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let ml_dsa_44 = ml_dsa_44.signature_algorithm_identifier().unwrap();
+        let sha384 = AlgorithmIdentifierOwned {
+            oid: ID_SHA_384,
+            parameters: None,
+        };
+        for (digest_alg, signature_alg, expected) in [
+            (&sha256, &ml_dsa_65, true),
+            (&sha256, &ml_dsa_44, false),
+            (&sha384, &ml_dsa_65, false),
+        ] {
+            let attr = create_algorithm_protection_attribute(digest_alg, signature_alg).unwrap();
+            let p7s = sign_detached_with_attributes(
+                data,
+                &signer_cert,
+                &sk,
+                &[&signer_cert, &pki.intermediate_cert],
+                None,
+                &[attr],
+            );
+            assert_eq!(
+                SignedDataContent::verify_detached_external(data, &p7s, &anchors).unwrap(),
+                expected
+            );
+        }
+
+        // Swapping the signature algorithm of the SignerInfo
+        let mut builder = SignedDataContent::get_builder(DigestAlgorithm::Sha512).unwrap();
+        builder
+            .signer(&signer_cert, &sk)
+            .unwrap()
+            .content(data)
+            .unwrap()
+            .detached(true)
+            .unwrap();
+        let mut sdc = SignedDataContent::from_bytes(&builder.build().unwrap()).unwrap();
+        let mut signer_info = sdc.signed_data.signer_infos.0.get(0).unwrap().clone();
+        signer_info.signature_algorithm = ml_dsa_44;
+        sdc.signed_data.signer_infos.0 = [signer_info].to_vec().try_into().unwrap();
+        assert!(!sdc.verify_detached(data, &anchors).unwrap());
+
+        // The signer's key must be the certificate's key
+        let mut builder = SignedDataContent::get_builder(DigestAlgorithm::Sha512).unwrap();
+        assert_eq!(
+            builder.signer(&signer_cert, &pki.root_key).err(),
+            Some(QubitCryptError::InvalidPrivateKey)
+        );
+    }
+}
@@ -5,19 +5,33 @@
 // https://datatracker.ietf.org/doc/rfc9629/
 
 use crate::cea::common::cea_trait::Cea;
+use crate::cea::common::config::oids::Oid as _;
 use crate::certificates::Certificate;
+use crate::cms::asn1::ber::decode_content_info;
+use crate::cms::asn1::enveloped_data_content::ContentEncryptionAlgorithm;
+use crate::cms::asn1::kemri_builder::KemRecipientInfoBuilder;
+use crate::cms::asn1::recipient_summary::{RecipientSummary, RecipientType};
+use crate::kdf::api::KdfType;
+use crate::kem::common::kem_trait::Kem;
+use crate::kem::kem_manager::KemManager;
+use crate::wrap::api::WrapType;
+use crate::wrap::common::config::key_length::KeyLength as _;
+use crate::wrap::common::config::oids::Oid as _;
 use crate::{
     cms::asn1::kemri::KemRecipientInfo, kdf::api::KdfManager, kdf::common::kdf_trait::Kdf,
     keys::PrivateKey, wrap::api::WrapManager, wrap::common::wrap_trait::Wrap, QubitCryptError,
 };
 use cms::content_info::ContentInfo;
-use cms::enveloped_data::{EnvelopedData, OtherRecipientInfo, RecipientInfo, UserKeyingMaterial};
+use cms::enveloped_data::{
+    EnvelopedData, OtherRecipientInfo, RecipientInfo, RecipientInfos, UserKeyingMaterial,
+};
 use const_oid::db::rfc5911::{ID_CT_AUTH_ENVELOPED_DATA, ID_ENVELOPED_DATA};
 use der::asn1::{OctetStringRef, SetOfVec};
 use der::Tag;
 use der::{asn1::OctetString, Decode, Encode};
 use spki::{AlgorithmIdentifierOwned, ObjectIdentifier};
 use x509_cert::attr::{Attribute, AttributeValue};
+use x509_cert::time::Time;
 
 use crate::cea::cea_manager::CeaManager;
 use crate::cms::asn1::kemri::CmsOriForKemOtherInfo;
@@ -27,7 +41,7 @@ use crate::cms::asn1::auth_env_data::AuthEnvelopedData;
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
 /// A utility class for CMS operations
-pub(crate) struct CmsUtil {}
+pub struct CmsUtil {}
 
 impl CmsUtil {
     /// Get the key encryption key (KEK) for a shared secret
@@ -71,6 +85,147 @@ impl CmsUtil {
         Ok(kek)
     }
 
+    /// Get the key encryption key (KEK) of a KEM recipient without a KDF, which is the shared
+    /// secret itself (`KdfType::Direct`)
+    ///
+    /// # Arguments
+    ///
+    /// * `ss` - The shared secret
+    /// * `kek_length` - The length of the KEK
+    ///
+    /// # Returns
+    ///
+    /// The KEK as bytes
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidSharedSecretLength` will be returned if the shared secret
+    /// doesn't have the length of the KEK
+    pub(crate) fn get_direct_kek(ss: &[u8], kek_length: u16) -> Result<Vec<u8>> {
+        if ss.len() != kek_length as usize {
+            return Err(QubitCryptError::InvalidSharedSecretLength {
+                expected: kek_length as usize,
+                actual: ss.len(),
+            });
+        }
+        Ok(ss.to_vec())
+    }
+
+    /// Check that a KEM, KDF and key wrap can be combined for a KEM recipient
+    ///
+    /// Without a KDF (`KdfType::Direct`), the shared secret of the KEM must have the key length
+    /// of the key wrap. Any KEM can be combined with a KDF.
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidSharedSecretLength` will be returned if the shared secret can't
+    /// be used as the KEK
+    pub(crate) fn check_kem_parameters(
+        kem: &KemManager,
+        kdf: &KdfType,
+        wrap_type: &WrapType,
+    ) -> Result<()> {
+        if *kdf != KdfType::Direct {
+            return Ok(());
+        }
+        let expected = wrap_type.get_key_length() as usize;
+        let actual = kem.get_kem_info().ss_byte_len;
+        if actual != expected {
+            return Err(QubitCryptError::InvalidSharedSecretLength { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Choose the strongest content encryption algorithm which all recipients support
+    ///
+    /// An EnvelopedData is encrypted with a single algorithm, so it has to be one that every
+    /// recipient can decrypt. The candidates are tried from strongest to weakest: AES-256-CBC,
+    /// AES-192-CBC, AES-128-CBC.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipient_capabilities` - For each recipient, the OIDs of the content encryption
+    ///   algorithms it supports (e.g. taken from its S/MIME capabilities)
+    ///
+    /// # Returns
+    ///
+    /// The strongest algorithm supported by all recipients. If there are no recipients, this is
+    /// AES-256-CBC. If the recipients have no algorithm in common, this is AES-128-CBC, which
+    /// all CMS implementations are required to support (RFC 8551, Section 2.7).
+    pub fn choose_cea(recipient_capabilities: &[Vec<String>]) -> ContentEncryptionAlgorithm {
+        const PREFERENCE: [ContentEncryptionAlgorithm; 3] = [
+            ContentEncryptionAlgorithm::Aes256Cbc,
+            ContentEncryptionAlgorithm::Aes192Cbc,
+            ContentEncryptionAlgorithm::Aes128Cbc,
+        ];
+
+        PREFERENCE
+            .into_iter()
+            .find(|alg| {
+                let oid = alg.get_cea_type().get_oid();
+                recipient_capabilities
+                    .iter()
+                    .all(|capabilities| capabilities.contains(&oid))
+            })
+            .unwrap_or(ContentEncryptionAlgorithm::Aes128Cbc)
+    }
+
+    /// Build a KEM RecipientInfo for a content encryption key (CEK), independently of
+    /// the EnvelopedData / AuthEnvelopedData it will be part of.
+    ///
+    /// This allows the CEK to be wrapped for each recipient separately, e.g. by different
+    /// services, with the resulting RecipientInfos assembled centrally using
+    /// `EnvelopedDataBuilder::add_recipient_info` and the same CEK.
+    ///
+    /// # Arguments
+    ///
+    /// * `cek` - The content encryption key to wrap
+    /// * `recipient_cert` - The certificate of the recipient
+    /// * `kdf` - The key derivation function to use
+    /// * `wrap_type` - The key wrap type to use
+    /// * `ukm` - The user keying material to use
+    ///
+    /// # Returns
+    ///
+    /// The RecipientInfo, containing a KEMRecipientInfo as an OtherRecipientInfo
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` if the certificate doesn't hold a KEM key
+    ///
+    /// `QubitCryptError::InvalidCertificate` if the certificate is not enabled for key encipherment
+    ///
+    /// `QubitCryptError::InvalidSharedSecretLength` if the KDF is `KdfType::Direct` and the
+    /// shared secret of the KEM doesn't have the key length of the key wrap
+    ///
+    /// The error of the KEM, the KDF or the key wrap if the CEK could not be encapsulated or
+    /// wrapped, e.g. `QubitCryptError::KeyWrapFailed`
+    pub fn build_kem_recipient_info(
+        cek: &[u8],
+        recipient_cert: &Certificate,
+        kdf: &KdfType,
+        wrap_type: &WrapType,
+        ukm: Option<UserKeyingMaterial>,
+    ) -> Result<RecipientInfo> {
+        if !recipient_cert.is_kem() {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
+        if !recipient_cert.is_key_encipherment_enabled() {
+            return Err(QubitCryptError::InvalidCertificate);
+        }
+
+        let kem_manager = KemManager::new_from_oid(&recipient_cert.get_public_key_oid())?;
+        Self::check_kem_parameters(&kem_manager, kdf, wrap_type)?;
+        let mut kemri_builder = KemRecipientInfoBuilder::new(
+            recipient_cert,
+            kem_manager,
+            kdf.clone(),
+            wrap_type.get_oid(),
+            ukm,
+        );
+        kemri_builder.build_kem_recipient_info(cek)
+    }
+
     /// Get the content encryption key (CEK) for a recipient
     ///
     /// # Arguments
@@ -86,6 +241,7 @@ impl CmsUtil {
         ori: &OtherRecipientInfo,
         private_key: &PrivateKey,
         cert: &Certificate,
+        combiner_label: Option<&[u8]>,
     ) -> Result<Vec<u8>> {
         let ori_value = ori
             .ori_value
@@ -95,19 +251,42 @@ impl CmsUtil {
             .map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
 
         if !cert.is_identified_by(&kemri.rid) {
-            return Err(QubitCryptError::InvalidCertificate);
+            return Err(QubitCryptError::NoMatchingRecipient);
         }
 
+        // Catch truncated or padded ciphertexts before they reach the KEM. A key of another KEM
+        // is just the wrong key, which fails to decapsulate.
         let kem_ct = kemri.kem_ct.as_bytes();
-        let ss = private_key.decap(kem_ct)?;
+        let mut kem = KemManager::new_from_oid(private_key.get_oid())?;
+        let kem_info = kem.get_kem_info();
+        let is_same_kem = kemri.kem.oid.to_string() == kem_info.oid;
+        if let Some(expected) = kem_info.ct_byte_len.filter(|_| is_same_kem) {
+            if kem_ct.len() != expected {
+                return Err(QubitCryptError::InvalidCiphertextLength {
+                    expected,
+                    actual: kem_ct.len(),
+                });
+            }
+        }
+        let ss = match combiner_label {
+            Some(label) => {
+                kem.set_combiner_label(label)?;
+                kem.decap(private_key.get_key(), kem_ct)?
+            }
+            None => private_key.decap(kem_ct)?,
+        };
 
-        let kek = Self::get_kek(
-            &ss,
-            &kemri.wrap.oid.to_string(),
-            &kemri.kdf.oid.to_string(),
-            kemri.kek_length,
-            kemri.ukm,
-        )?;
+        // A KEMRecipientInfo without a KDF uses the shared secret as the KEK
+        let kek = match &kemri.kdf {
+            Some(kdf) => Self::get_kek(
+                &ss,
+                &kemri.wrap.oid.to_string(),
+                &kdf.oid.to_string(),
+                kemri.kek_length,
+                kemri.ukm,
+            )?,
+            None => Self::get_direct_kek(&ss, kemri.kek_length)?,
+        };
 
         let wrapped_cek = kemri.encrypted_key.as_bytes();
         let wrap = WrapManager::new_from_oid(&kemri.wrap.oid.to_string())?;
@@ -119,6 +298,7 @@ impl CmsUtil {
         auth_enveloped_data_der: &[u8],
         private_key: &PrivateKey,
         cert: &Certificate,
+        combiner_label: Option<&[u8]>,
     ) -> Result<Vec<u8>> {
         let ed = AuthEnvelopedData::from_der(auth_enveloped_data_der)
             .map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
@@ -137,28 +317,15 @@ impl CmsUtil {
 
         let mac = ed.mac.as_bytes();
 
-        for ri in ed.recip_infos.0.iter() {
-            match ri {
-                RecipientInfo::Ori(ori) => {
-                    if let Ok(key) = Self::get_cek(ori, private_key, cert) {
-                        let result = CeaManager::decrypt(&key, mac, &ct, Some(&aad))?;
-                        return Ok(result);
-                    } else {
-                        continue;
-                    }
-                }
-                _ => {
-                    continue;
-                }
-            };
-        }
-        Err(QubitCryptError::InvalidEnvelopedData)
+        let key = Self::get_recipient_cek(&ed.recip_infos, private_key, cert, combiner_label)?;
+        CeaManager::decrypt(&key, mac, &ct, Some(&aad))
     }
 
     fn decrypt_enveloped_kemri(
         enveloped_data_der: &[u8],
         private_key: &PrivateKey,
         cert: &Certificate,
+        combiner_label: Option<&[u8]>,
     ) -> Result<Vec<u8>> {
         let ed = EnvelopedData::from_der(enveloped_data_der)
             .map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
@@ -182,30 +349,222 @@ impl CmsUtil {
             .map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
         let iv: &[u8] = os_iv.as_bytes();
 
-        for ri in ed.recip_infos.0.iter() {
-            match ri {
-                RecipientInfo::Ori(ori) => {
-                    if let Ok(key) = Self::get_cek(ori, private_key, cert) {
-                        let result = CeaManager::decrypt(&key, iv, &ct, None)?;
-                        return Ok(result);
-                    } else {
-                        continue;
-                    }
+        let key = Self::get_recipient_cek(&ed.recip_infos, private_key, cert, combiner_label)?;
+        CeaManager::decrypt(&key, iv, &ct, None)
+    }
+
+    /// Get the content encryption key (CEK) from the KEM recipient identified by the certificate
+    ///
+    /// # Arguments
+    ///
+    /// * `recip_infos` - The recipient infos of the EnvelopedData or AuthEnvelopedData
+    /// * `private_key` - The private key of the recipient
+    /// * `cert` - The certificate of the recipient
+    ///
+    /// # Returns
+    ///
+    /// The CEK as bytes
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedRecipientType` if there are no KEM recipients, with the names
+    /// of the recipient types which are present, e.g. `KEKRecipientInfo`
+    ///
+    /// `QubitCryptError::NoMatchingRecipient` if none of the KEM recipients is identified by the
+    /// certificate
+    ///
+    /// `QubitCryptError::InvalidCiphertextLength` if the KEM ciphertext of the recipient
+    /// identified by the certificate doesn't have the ciphertext length of the private key's KEM
+    ///
+    /// `QubitCryptError::InvalidSharedSecretLength` if the recipient has no KDF and the shared
+    /// secret doesn't have the length of the KEK
+    ///
+    /// `QubitCryptError::InvalidEnvelopedData` if the CEK of the recipient identified by the
+    /// certificate can't be recovered with the private key
+    fn get_recipient_cek(
+        recip_infos: &RecipientInfos,
+        private_key: &PrivateKey,
+        cert: &Certificate,
+        combiner_label: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let summary = RecipientSummary::new(recip_infos);
+        if summary.kem == 0 {
+            let types = summary.types().iter().map(RecipientType::name).collect();
+            return Err(QubitCryptError::UnsupportedRecipientType(types));
+        }
+
+        let mut error = None;
+        for ri in recip_infos.0.iter() {
+            let RecipientInfo::Ori(ori) = ri else {
+                continue;
+            };
+            if RecipientType::of(ri) != RecipientType::Kem {
+                continue;
+            }
+            match Self::get_cek(ori, private_key, cert, combiner_label) {
+                Ok(key) => return Ok(key),
+                Err(QubitCryptError::NoMatchingRecipient) => {}
+                // Keep looking, the certificate may identify more than one recipient
+                Err(
+                    e @ (QubitCryptError::InvalidCiphertextLength { .. }
+                    | QubitCryptError::InvalidSharedSecretLength { .. }),
+                ) => error = Some(e),
+                Err(_) => {
+                    error.get_or_insert(QubitCryptError::InvalidEnvelopedData);
                 }
-                _ => continue,
             }
         }
 
-        Err(QubitCryptError::InvalidEnvelopedData)
+        Err(error.unwrap_or(QubitCryptError::NoMatchingRecipient))
     }
 
+    /// Decrypt an EnvelopedData or AuthEnvelopedData for a KEMRecipientInfo recipient
+    ///
+    /// The KEM is selected by the OID of the private key, so single-algorithm ML-KEM keys and
+    /// composite ML-KEM keys (decapsulated with both component KEMs and the composite combiner)
+    /// are handled the same way.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The DER, BER or PEM encoded ContentInfo
+    /// * `private_key` - The private key of the recipient
+    /// * `cert` - The certificate of the recipient
+    ///
+    /// # Returns
+    ///
+    /// The decrypted content
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedRecipientType` if there are no KEM recipients
+    ///
+    /// `QubitCryptError::NoMatchingRecipient` if none of the KEM recipients is identified by the
+    /// certificate
+    ///
+    /// `QubitCryptError::InvalidCiphertextLength` if the KEM ciphertext of the recipient doesn't
+    /// have the length of the private key's KEM, e.g. because the envelope was truncated
+    ///
+    /// `QubitCryptError::InvalidEnvelopedData` if the content can't be decoded or if the recipient
+    /// info of the certificate can't be decrypted with the private key
     pub fn decrypt_kemri(
         data: &[u8],
         private_key: &PrivateKey,
         cert: &Certificate,
     ) -> Result<Vec<u8>> {
-        let content_info: ContentInfo =
-            ContentInfo::from_der(data).map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
+        Self::decrypt_kemri_for(data, private_key, cert, None)
+    }
+
+    /// Decrypt an EnvelopedData or AuthEnvelopedData for a KEMRecipientInfo recipient whose
+    /// composite KEM uses an additional combiner label
+    ///
+    /// This is the counterpart of `EnvelopedDataBuilder::kem_recipient_with_label`, the label
+    /// must be the one the content was encrypted with.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The DER, BER or PEM encoded ContentInfo
+    /// * `private_key` - The private key of the recipient
+    /// * `cert` - The certificate of the recipient
+    /// * `combiner_label` - The domain separation label
+    ///
+    /// # Returns
+    ///
+    /// The decrypted content
+    ///
+    /// # Errors
+    ///
+    /// The errors of `decrypt_kemri`, and `QubitCryptError::UnsupportedOperation` if the private
+    /// key is not a composite KEM key
+    pub fn decrypt_kemri_with_label(
+        data: &[u8],
+        private_key: &PrivateKey,
+        cert: &Certificate,
+        combiner_label: &[u8],
+    ) -> Result<Vec<u8>> {
+        KemManager::new_from_oid(private_key.get_oid())?.set_combiner_label(combiner_label)?;
+        Self::decrypt_kemri_for(data, private_key, cert, Some(combiner_label))
+    }
+
+    /// Decrypt an EnvelopedData or AuthEnvelopedData, with an optional combiner label for a
+    /// composite KEM
+    fn decrypt_kemri_for(
+        data: &[u8],
+        private_key: &PrivateKey,
+        cert: &Certificate,
+        combiner_label: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let content_info = Self::decode_envelope(data)?;
+        let oid = content_info.content_type;
+        let enveloped_data = content_info
+            .content
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
+
+        if oid == ID_ENVELOPED_DATA {
+            Self::decrypt_enveloped_kemri(&enveloped_data, private_key, cert, combiner_label)
+        } else if oid == ID_CT_AUTH_ENVELOPED_DATA {
+            Self::decrypt_auth_enveloped_kemri(&enveloped_data, private_key, cert, combiner_label)
+        } else {
+            Err(QubitCryptError::InvalidEnvelopedData)
+        }
+    }
+
+    /// Count the recipients of an EnvelopedData or AuthEnvelopedData by type, without
+    /// decrypting it
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The DER, BER or PEM encoded ContentInfo
+    ///
+    /// # Returns
+    ///
+    /// The number of recipients of each type
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidEnvelopedData` if the data is not an EnvelopedData or
+    /// AuthEnvelopedData
+    pub fn recipient_summary(data: &[u8]) -> Result<RecipientSummary> {
+        Ok(RecipientSummary::new(&Self::get_recipient_infos(data)?))
+    }
+
+    /// Get the KEM recipients of an EnvelopedData or AuthEnvelopedData, without decrypting it
+    ///
+    /// The KEM, KDF and key wrap algorithms of each recipient can be compared with the ones a
+    /// recipient's key and this build support, e.g. when decryption fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The DER, BER or PEM encoded ContentInfo
+    ///
+    /// # Returns
+    ///
+    /// The KEMRecipientInfo of each KEM recipient, in the order of the recipient infos
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidEnvelopedData` if the data is not an EnvelopedData or
+    /// AuthEnvelopedData
+    ///
+    /// `QubitCryptError::InvalidRecipientInfo` if a KEM recipient can't be decoded
+    pub fn kem_recipient_infos(data: &[u8]) -> Result<Vec<KemRecipientInfo>> {
+        Self::get_recipient_infos(data)?
+            .0
+            .iter()
+            .filter(|ri| RecipientType::of(ri) == RecipientType::Kem)
+            .map(KemRecipientInfo::from_recipient_info)
+            .collect()
+    }
+
+    /// Decode the ContentInfo of an EnvelopedData or AuthEnvelopedData, which can be DER, BER
+    /// with indefinite lengths (see `EnvelopedDataBuilder::indefinite_length`) or PEM encoded
+    fn decode_envelope(data: &[u8]) -> Result<ContentInfo> {
+        decode_content_info(data).map_err(|_| QubitCryptError::InvalidEnvelopedData)
+    }
+
+    /// Get the recipient infos of an EnvelopedData or AuthEnvelopedData
+    fn get_recipient_infos(data: &[u8]) -> Result<RecipientInfos> {
+        let content_info = Self::decode_envelope(data)?;
         let oid = content_info.content_type;
         let enveloped_data = content_info
             .content
@@ -213,17 +572,67 @@ impl CmsUtil {
             .map_err(|_| QubitCryptError::InvalidEnvelopedData)?;
 
         if oid == ID_ENVELOPED_DATA {
-            Self::decrypt_enveloped_kemri(&enveloped_data, private_key, cert)
+            Ok(EnvelopedData::from_der(&enveloped_data)
+                .map_err(|_| QubitCryptError::InvalidEnvelopedData)?
+                .recip_infos)
         } else if oid == ID_CT_AUTH_ENVELOPED_DATA {
-            Self::decrypt_auth_enveloped_kemri(&enveloped_data, private_key, cert)
+            Ok(AuthEnvelopedData::from_der(&enveloped_data)
+                .map_err(|_| QubitCryptError::InvalidEnvelopedData)?
+                .recip_infos)
         } else {
             Err(QubitCryptError::InvalidEnvelopedData)
         }
     }
 
+    /// Check that every recipient of an EnvelopedData or AuthEnvelopedData can decrypt it, and
+    /// that they all get the same content
+    ///
+    /// Each recipient decrypts independently with `decrypt_kemri`, so a malformed recipient info
+    /// (e.g. a content encryption key wrapped with the wrong key) is caught even if the other
+    /// recipients can decrypt.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The DER, BER or PEM encoded ContentInfo
+    /// * `recipients` - The certificates and private keys of the intended recipients
+    ///
+    /// # Returns
+    ///
+    /// True if every recipient decrypted the same content, false if a recipient can't decrypt,
+    /// the contents differ or there are no recipients
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidEnvelopedData` if the data is not an EnvelopedData or
+    /// AuthEnvelopedData
+    pub fn verify_all_recipients(
+        data: &[u8],
+        recipients: &[(Certificate, PrivateKey)],
+    ) -> Result<bool> {
+        let content_info = Self::decode_envelope(data)?;
+        if content_info.content_type != ID_ENVELOPED_DATA
+            && content_info.content_type != ID_CT_AUTH_ENVELOPED_DATA
+        {
+            return Err(QubitCryptError::InvalidEnvelopedData);
+        }
+
+        let mut expected: Option<Vec<u8>> = None;
+        for (cert, private_key) in recipients {
+            let pt = match Self::decrypt_kemri(data, private_key, cert) {
+                Ok(pt) => pt,
+                Err(_) => return Ok(false),
+            };
+            match &expected {
+                Some(expected) if *expected != pt => return Ok(false),
+                Some(_) => {}
+                None => expected = Some(pt),
+            }
+        }
+        Ok(expected.is_some())
+    }
+
     /// Create a content-type attribute according to
     /// [RFC 5652 § 11.1](https://datatracker.ietf.org/doc/html/rfc5652#section-11.1)
-    #[allow(dead_code)]
     pub(crate) fn create_content_type_attribute(
         content_type: ObjectIdentifier,
     ) -> Result<Attribute> {
@@ -245,7 +654,6 @@ impl CmsUtil {
 
     /// Create a message digest attribute according to
     /// [RFC 5652 § 11.2](https://datatracker.ietf.org/doc/html/rfc5652#section-11.2)
-    #[allow(dead_code)]
     pub(crate) fn create_message_digest_attribute(message_digest: &[u8]) -> Result<Attribute> {
         let message_digest_der =
             OctetStringRef::new(message_digest).map_err(|_| QubitCryptError::InvalidAttribute)?;
@@ -263,10 +671,96 @@ impl CmsUtil {
         };
         Ok(attribute)
     }
+
+    /// Create a signing-time attribute according to
+    /// [RFC 5652 § 11.3](https://datatracker.ietf.org/doc/html/rfc5652#section-11.3)
+    ///
+    /// Dates from 1950 through 2049 are encoded as UTCTime, all other dates as
+    /// GeneralizedTime, as required by the RFC. Fractional seconds are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `signing_time` - The time at which the signer claims to have signed the content
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidAttribute` will be returned if the time can't be encoded
+    pub(crate) fn create_signing_time_attribute(
+        signing_time: &chrono::DateTime<chrono::Utc>,
+    ) -> Result<Attribute> {
+        use chrono::{Datelike, Timelike};
+        let dt = der::DateTime::new(
+            signing_time.year() as u16,
+            signing_time.month() as u8,
+            signing_time.day() as u8,
+            signing_time.hour() as u8,
+            signing_time.minute() as u8,
+            signing_time.second() as u8,
+        )
+        .map_err(|_| QubitCryptError::InvalidAttribute)?;
+        let time = if (1950..2050).contains(&dt.year()) {
+            Time::UtcTime(
+                der::asn1::UtcTime::from_date_time(dt)
+                    .map_err(|_| QubitCryptError::InvalidAttribute)?,
+            )
+        } else {
+            Time::GeneralTime(der::asn1::GeneralizedTime::from_date_time(dt))
+        };
+
+        let signing_time_attribute_value =
+            AttributeValue::encode_from(&time).map_err(|_| QubitCryptError::InvalidAttribute)?;
+        let mut values = SetOfVec::new();
+        values
+            .insert(signing_time_attribute_value)
+            .map_err(|_| QubitCryptError::InvalidAttribute)?;
+        let attribute = Attribute {
+            oid: const_oid::db::rfc5911::ID_SIGNING_TIME,
+            values,
+        };
+        Ok(attribute)
+    }
+
+    /// Parse a signing-time attribute according to
+    /// [RFC 5652 § 11.3](https://datatracker.ietf.org/doc/html/rfc5652#section-11.3)
+    ///
+    /// Both the UTCTime and the GeneralizedTime encodings are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `attribute` - The signing-time attribute
+    ///
+    /// # Returns
+    ///
+    /// The signing time
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidAttribute` will be returned if the attribute is not a
+    /// signing-time attribute with exactly one valid time value
+    pub(crate) fn parse_signing_time_attribute(
+        attribute: &Attribute,
+    ) -> Result<chrono::DateTime<chrono::Utc>> {
+        if attribute.oid != const_oid::db::rfc5911::ID_SIGNING_TIME || attribute.values.len() != 1 {
+            return Err(QubitCryptError::InvalidAttribute);
+        }
+        let value = attribute
+            .values
+            .get(0)
+            .ok_or(QubitCryptError::InvalidAttribute)?
+            .to_der()
+            .map_err(|_| QubitCryptError::InvalidAttribute)?;
+        let time = Time::from_der(&value).map_err(|_| QubitCryptError::InvalidAttribute)?;
+        let since_epoch = time.to_unix_duration();
+        chrono::DateTime::from_timestamp(since_epoch.as_secs() as i64, 0)
+            .ok_or(QubitCryptError::InvalidAttribute)
+    }
 }
 #[cfg(test)]
 mod tests {
     use crate::certificates::Certificate;
+    use crate::kdf::common::config::oids::Oid as _;
+    use crate::kem::common::kem_type::KemType;
+    use cms::builder::RecipientInfoBuilder;
 
     use super::*;
 
@@ -280,7 +774,7 @@ mod tests {
 
         let ee = Certificate::from_der(ee_bytes).unwrap();
         let result = ta.verify_child(&ee).unwrap();
-        assert_eq!(result, true);
+        assert!(result);
 
         let enveloped = include_bytes!("../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_kemri_id-alg-hkdf-with-sha256_ukm.der");
 
@@ -318,4 +812,544 @@ mod tests {
         let expected = b"abc";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_verify_all_recipients() {
+        use crate::certificates::Pki;
+        use crate::content::EnvelopedDataContent;
+        use crate::dsas::DsaAlgorithm;
+        use crate::kems::KemAlgorithm;
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 3).unwrap();
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+        let cek = [5u8; 16];
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .content(b"abc")
+            .unwrap()
+            .content_encryption_key(&cek)
+            .unwrap();
+        for (cert, _) in pki.leaves.iter() {
+            builder.kem_recipient(cert, &kdf, &wrap, None).unwrap();
+        }
+        let data = builder.build().unwrap();
+        assert!(CmsUtil::verify_all_recipients(&data, &pki.leaves).unwrap());
+        assert!(CmsUtil::verify_all_recipients(&data, &pki.leaves[1..]).unwrap());
+        assert!(!CmsUtil::verify_all_recipients(&data, &[]).unwrap());
+
+        // One recipient info wraps the wrong key
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .content(b"abc")
+            .unwrap()
+            .content_encryption_key(&cek)
+            .unwrap();
+        for (cert, _) in pki.leaves[..2].iter() {
+            builder.kem_recipient(cert, &kdf, &wrap, None).unwrap();
+        }
+        let ri = CmsUtil::build_kem_recipient_info(&[6u8; 16], &pki.leaves[2].0, &kdf, &wrap, None)
+            .unwrap();
+        builder.add_recipient_info(ri).unwrap();
+        let data = builder.build().unwrap();
+        assert!(CmsUtil::verify_all_recipients(&data, &pki.leaves[..2]).unwrap());
+        assert!(!CmsUtil::verify_all_recipients(&data, &pki.leaves).unwrap());
+
+        // A key which is not a recipient
+        let other = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 1).unwrap();
+        assert!(!CmsUtil::verify_all_recipients(&data, &other.leaves).unwrap());
+
+        assert!(matches!(
+            CmsUtil::verify_all_recipients(b"not cms", &pki.leaves),
+            Err(QubitCryptError::InvalidEnvelopedData)
+        ));
+    }
+
+    #[test]
+    fn test_recipient_errors() {
+        use cms::enveloped_data::{KekIdentifier, KekRecipientInfo};
+        use der::Any;
+
+        use crate::certificates::Pki;
+        use crate::content::{
+            AuthEnvelopedDataContent, ContentEncryptionAlgorithmAead, EnvelopedDataContent,
+        };
+        use crate::dsas::DsaAlgorithm;
+        use crate::kems::KemAlgorithm;
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 2).unwrap();
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+        let kek = RecipientInfo::Kekri(KekRecipientInfo {
+            version: cms::content_info::CmsVersion::V4,
+            kek_id: KekIdentifier {
+                kek_identifier: OctetString::new(vec![1u8; 8]).unwrap(),
+                date: None,
+                other: None,
+            },
+            key_enc_alg: AlgorithmIdentifierOwned {
+                oid: WrapType::Aes128.get_oid().parse().unwrap(),
+                parameters: None,
+            },
+            encrypted_key: OctetString::new(vec![2u8; 24]).unwrap(),
+        });
+        let other = RecipientInfo::Ori(OtherRecipientInfo {
+            ori_type: ObjectIdentifier::new("1.2.3.4").unwrap(),
+            ori_value: Any::new(der::Tag::Null, Vec::new()).unwrap(),
+        });
+        let (cert, sk) = &pki.leaves[0];
+
+        // Only recipients which are not KEM recipients
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .add_recipient_info(kek.clone())
+            .unwrap()
+            .add_recipient_info(other.clone())
+            .unwrap()
+            .content_encryption_key(&[1u8; 16])
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        let expected = vec![RecipientType::Kek.name(), RecipientType::Other.name()];
+        assert_eq!(
+            CmsUtil::decrypt_kemri(&data, sk, cert),
+            Err(QubitCryptError::UnsupportedRecipientType(expected.clone()))
+        );
+        assert!(
+            EnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk)
+                .is_err_and(|err| err == QubitCryptError::UnsupportedRecipientType(expected))
+        );
+
+        let mut builder =
+            AuthEnvelopedDataContent::get_builder(ContentEncryptionAlgorithmAead::Aes128Gcm)
+                .unwrap();
+        builder
+            .add_recipient_info(other.clone())
+            .unwrap()
+            .content_encryption_key(&[1u8; 16])
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        assert!(
+            AuthEnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk)
+                .is_err_and(|err| err
+                    == QubitCryptError::UnsupportedRecipientType(vec!["OtherRecipientInfo"]))
+        );
+
+        // KEM recipients, but not the certificate
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .kem_recipient(&pki.leaves[1].0, &kdf, &wrap, None)
+            .unwrap()
+            .add_recipient_info(kek)
+            .unwrap()
+            .content_encryption_key(&[1u8; 16])
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        assert_eq!(
+            CmsUtil::decrypt_kemri(&data, sk, cert),
+            Err(QubitCryptError::NoMatchingRecipient)
+        );
+
+        // The certificate is a recipient, but the private key is not its key
+        assert_eq!(
+            CmsUtil::decrypt_kemri(&data, sk, &pki.leaves[1].0),
+            Err(QubitCryptError::InvalidEnvelopedData)
+        );
+        assert_eq!(
+            CmsUtil::decrypt_kemri(&data, &pki.leaves[1].1, &pki.leaves[1].0).unwrap(),
+            b"abc"
+        );
+    }
+
+    #[test]
+    fn test_invalid_ciphertext_length() {
+        use der::Any;
+
+        use crate::certificates::Pki;
+        use crate::cms::asn1::kemri_builder::ID_ORI_KEM;
+        use crate::content::EnvelopedDataContent;
+        use crate::dsas::DsaAlgorithm;
+        use crate::kems::KemAlgorithm;
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem512, 1).unwrap();
+        let (cert, sk) = &pki.leaves[0];
+        let cek = [1u8; 16];
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .kem_recipient(cert, &KdfType::HkdfWithSha256, &WrapType::Aes128, None)
+            .unwrap()
+            .content_encryption_key(&cek)
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        let kemri = CmsUtil::kem_recipient_infos(&data).unwrap().remove(0);
+
+        // The same recipient, with the last byte of the KEM ciphertext cut off
+        let ct = kemri.kem_ct.as_bytes();
+        let mut truncated = kemri.clone();
+        truncated.kem_ct = OctetString::new(&ct[..ct.len() - 1]).unwrap();
+        let ori = RecipientInfo::Ori(OtherRecipientInfo {
+            ori_type: ObjectIdentifier::new(ID_ORI_KEM).unwrap(),
+            ori_value: Any::from_der(&truncated.to_der().unwrap()).unwrap(),
+        });
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .add_recipient_info(ori)
+            .unwrap()
+            .content_encryption_key(&cek)
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+
+        let error = || QubitCryptError::InvalidCiphertextLength {
+            expected: 768,
+            actual: 767,
+        };
+        assert_eq!(CmsUtil::decrypt_kemri(&data, sk, cert), Err(error()));
+        assert!(
+            EnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk)
+                .is_err_and(|err| err == error())
+        );
+    }
+
+    #[test]
+    fn test_direct_kek() {
+        use der::Any;
+
+        use crate::certificates::Pki;
+        use crate::cms::asn1::kemri_builder::ID_ORI_KEM;
+        use crate::content::EnvelopedDataContent;
+        use crate::dsas::DsaAlgorithm;
+        use crate::kems::KemAlgorithm;
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem768, 1).unwrap();
+        let (cert, sk) = &pki.leaves[0];
+        let cek = [1u8; 32];
+
+        // The 32 byte ML-KEM shared secret is the AES-256 KEK
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes256Cbc).unwrap();
+        builder
+            .kem_recipient(cert, &KdfType::Direct, &WrapType::Aes256, None)
+            .unwrap()
+            .content_encryption_key(&cek)
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        let kemri = CmsUtil::kem_recipient_infos(&data).unwrap().remove(0);
+        assert_eq!(kemri.kdf, None);
+        assert_eq!(kemri.kdf_oid(), None);
+        assert_eq!(kemri.kek_length, 32);
+        assert_eq!(CmsUtil::decrypt_kemri(&data, sk, cert).unwrap(), b"abc");
+        let edc = EnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk).unwrap();
+        assert_eq!(edc.get_content(), b"abc");
+
+        // An envelope of a peer which wraps with AES-128, but has no KDF to shorten the secret
+        let mut short = kemri.clone();
+        short.wrap.oid = ObjectIdentifier::new(&WrapType::Aes128.get_oid()).unwrap();
+        short.kek_length = 16;
+        let ori = RecipientInfo::Ori(OtherRecipientInfo {
+            ori_type: ObjectIdentifier::new(ID_ORI_KEM).unwrap(),
+            ori_value: Any::from_der(&short.to_der().unwrap()).unwrap(),
+        });
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes256Cbc).unwrap();
+        builder
+            .add_recipient_info(ori)
+            .unwrap()
+            .content_encryption_key(&cek)
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        let error = || QubitCryptError::InvalidSharedSecretLength {
+            expected: 16,
+            actual: 32,
+        };
+        assert_eq!(CmsUtil::decrypt_kemri(&data, sk, cert), Err(error()));
+
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes256Cbc).unwrap();
+        assert!(builder
+            .kem_recipient(cert, &KdfType::Direct, &WrapType::Aes128, None)
+            .is_err_and(|err| err == error()));
+        assert_eq!(
+            CmsUtil::build_kem_recipient_info(
+                &cek,
+                cert,
+                &KdfType::Direct,
+                &WrapType::Aes128,
+                None
+            ),
+            Err(error())
+        );
+    }
+
+    #[test]
+    fn test_kem_recipient_infos() {
+        use crate::certificates::Pki;
+        use crate::content::EnvelopedDataContent;
+        use crate::dsas::DsaAlgorithm;
+        use crate::kems::KemAlgorithm;
+
+        let ee = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
+        ))
+        .unwrap();
+        let data = include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_kemri_id-kmac128_ukm.der"
+        );
+        let kemris = CmsUtil::kem_recipient_infos(data).unwrap();
+        assert_eq!(kemris.len(), 1);
+        assert!(ee.is_identified_by(&kemris[0].rid));
+        assert_eq!(kemris[0].kem_oid(), ee.get_public_key_oid());
+        assert_eq!(kemris[0].kdf_oid(), KdfType::Kmac128.get_oid());
+        assert!(kemris[0].ukm().is_some());
+
+        let data = include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_kemri_id-alg-hkdf-with-sha256.der"
+        );
+        let kemris = CmsUtil::kem_recipient_infos(data).unwrap();
+        assert_eq!(kemris[0].kdf_oid(), KdfType::HkdfWithSha256.get_oid());
+        assert_eq!(kemris[0].ukm(), None);
+
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem768, 2).unwrap();
+        let ukm = UserKeyingMaterial::new(b"ukm".to_vec()).unwrap();
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes256Cbc).unwrap();
+        builder
+            .kem_recipient(
+                &pki.leaves[0].0,
+                &KdfType::HkdfWithSha512,
+                &WrapType::Aes256,
+                Some(ukm),
+            )
+            .unwrap()
+            .kem_recipient(&pki.leaves[1].0, &KdfType::Kmac256, &WrapType::Aes128, None)
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        let kemris = CmsUtil::kem_recipient_infos(&data).unwrap();
+        assert_eq!(kemris.len(), 2);
+        // The recipient infos are a SET, so they are sorted by their encoding
+        let find = |cert: &Certificate| {
+            kemris
+                .iter()
+                .find(|kemri| cert.is_identified_by(&kemri.rid))
+                .unwrap()
+        };
+        let kemri = find(&pki.leaves[0].0);
+        assert_eq!(kemri.kem_oid(), pki.leaves[0].0.get_public_key_oid());
+        assert_eq!(kemri.kdf_oid(), KdfType::HkdfWithSha512.get_oid());
+        assert_eq!(kemri.wrap_oid(), WrapType::Aes256.get_oid());
+        assert_eq!(kemri.ukm(), Some(b"ukm".to_vec()));
+        let kemri = find(&pki.leaves[1].0);
+        assert_eq!(kemri.kdf_oid(), KdfType::Kmac256.get_oid());
+        assert_eq!(kemri.wrap_oid(), WrapType::Aes128.get_oid());
+        assert_eq!(kemri.ukm(), None);
+
+        // The recipient infos of a decrypted envelope
+        let (cert, sk) = &pki.leaves[1];
+        let edc = EnvelopedDataContent::from_bytes_for_kem_recipient(&data, cert, sk).unwrap();
+        let decoded = edc
+            .get_recipient_infos()
+            .0
+            .iter()
+            .map(|ri| KemRecipientInfo::from_recipient_info(ri).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, kemris);
+
+        let other = RecipientInfo::Ori(OtherRecipientInfo {
+            ori_type: ObjectIdentifier::new("1.2.3.4").unwrap(),
+            ori_value: der::Any::new(der::Tag::Null, Vec::new()).unwrap(),
+        });
+        assert_eq!(
+            KemRecipientInfo::from_recipient_info(&other),
+            Err(QubitCryptError::InvalidRecipientInfo)
+        );
+        assert_eq!(
+            CmsUtil::kem_recipient_infos(b"not cms"),
+            Err(QubitCryptError::InvalidEnvelopedData)
+        );
+    }
+
+    #[test]
+    fn test_build_kem_recipient_info() {
+        let ta = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.3.17_MlDsa44_ta.der"
+        ))
+        .unwrap();
+        let ee = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
+        ))
+        .unwrap();
+        let sk = PrivateKey::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_priv.der"
+        ))
+        .unwrap();
+
+        let cek = [1u8; 16];
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes128;
+
+        let result = CmsUtil::build_kem_recipient_info(&cek, &ta, &kdf, &wrap, None);
+        assert!(matches!(result, Err(QubitCryptError::UnsupportedOperation)));
+
+        let ri = CmsUtil::build_kem_recipient_info(&cek, &ee, &kdf, &wrap, None).unwrap();
+        let ori = match ri {
+            RecipientInfo::Ori(ori) => ori,
+            _ => panic!("Expected an OtherRecipientInfo"),
+        };
+        assert_eq!(CmsUtil::get_cek(&ori, &sk, &ee, None).unwrap(), cek);
+    }
+
+    #[test]
+    fn test_kem_recipient_info_errors() {
+        let ee = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
+        ))
+        .unwrap();
+        let cek = [1u8; 16];
+        let new_builder = |kem_type: KemType, kdf: KdfType| {
+            KemRecipientInfoBuilder::new(
+                &ee,
+                KemManager::new(kem_type).unwrap(),
+                kdf,
+                WrapType::Aes128.get_oid(),
+                None,
+            )
+        };
+
+        // The KDF stage: ML-KEM's shared secret is too long for an AES-128 KEK
+        let mut builder = new_builder(KemType::MlKem512, KdfType::Direct);
+        let expected = || QubitCryptError::InvalidSharedSecretLength {
+            expected: 16,
+            actual: 32,
+        };
+        assert_eq!(
+            builder.build_kem_recipient_info(&cek).err(),
+            Some(expected())
+        );
+        // The builder error of the cms crate keeps the message
+        match RecipientInfoBuilder::build(&mut builder, &cek) {
+            Err(cms::builder::Error::Builder(message)) => {
+                assert_eq!(message, expected().to_string())
+            }
+            _ => panic!("Expected a builder error"),
+        }
+
+        // The wrap stage: AES key wrap needs a multiple of 64 bits
+        let kdf = KdfType::HkdfWithSha256;
+        let mut builder = new_builder(KemType::MlKem512, kdf.clone());
+        assert_eq!(
+            builder.build_kem_recipient_info(&cek[..15]).err(),
+            Some(QubitCryptError::KeyWrapFailed)
+        );
+        assert_eq!(
+            CmsUtil::build_kem_recipient_info(&cek[..15], &ee, &kdf, &WrapType::Aes128, None).err(),
+            Some(QubitCryptError::KeyWrapFailed)
+        );
+
+        // The encapsulation stage: the error of X-Wing for an ML-KEM-512 key
+        let mut builder = new_builder(KemType::XWing, kdf);
+        assert_eq!(
+            builder.build_kem_recipient_info(&cek).err(),
+            Some(QubitCryptError::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn test_choose_cea() {
+        let aes128 = "2.16.840.1.101.3.4.1.2".to_string();
+        let aes192 = "2.16.840.1.101.3.4.1.22".to_string();
+        let aes256 = "2.16.840.1.101.3.4.1.42".to_string();
+        let gcm = "2.16.840.1.101.3.4.1.46".to_string();
+
+        assert_eq!(
+            CmsUtil::choose_cea(&[]),
+            ContentEncryptionAlgorithm::Aes256Cbc
+        );
+        assert_eq!(
+            CmsUtil::choose_cea(&[vec![aes128.clone(), aes256.clone()]]),
+            ContentEncryptionAlgorithm::Aes256Cbc
+        );
+        assert_eq!(
+            CmsUtil::choose_cea(&[
+                vec![aes128.clone(), aes192.clone(), aes256.clone()],
+                vec![aes192.clone(), aes128.clone()],
+                vec![gcm.clone(), aes192.clone(), aes256.clone()],
+            ]),
+            ContentEncryptionAlgorithm::Aes192Cbc
+        );
+        assert_eq!(
+            CmsUtil::choose_cea(&[vec![aes256.clone()], vec![aes128.clone()]]),
+            ContentEncryptionAlgorithm::Aes128Cbc
+        );
+        assert_eq!(
+            CmsUtil::choose_cea(&[vec![gcm], vec![]]),
+            ContentEncryptionAlgorithm::Aes128Cbc
+        );
+    }
+
+    #[test]
+    fn test_signing_time_attribute() {
+        use chrono::TimeZone;
+        use der::Tagged;
+
+        // The year selects the encoding, a far future signing time needs GeneralizedTime
+        for (time, tag) in [
+            (
+                chrono::Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0),
+                Tag::UtcTime,
+            ),
+            (
+                chrono::Utc.with_ymd_and_hms(2049, 12, 31, 23, 59, 59),
+                Tag::UtcTime,
+            ),
+            (
+                chrono::Utc.with_ymd_and_hms(2050, 1, 1, 0, 0, 0),
+                Tag::GeneralizedTime,
+            ),
+            (
+                chrono::Utc.with_ymd_and_hms(2100, 7, 4, 8, 0, 0),
+                Tag::GeneralizedTime,
+            ),
+        ] {
+            let time = time.unwrap();
+            let attribute = CmsUtil::create_signing_time_attribute(&time).unwrap();
+            assert_eq!(attribute.oid, const_oid::db::rfc5911::ID_SIGNING_TIME);
+            assert_eq!(attribute.values.get(0).unwrap().tag(), tag);
+
+            let attribute = Attribute::from_der(&attribute.to_der().unwrap()).unwrap();
+            assert_eq!(
+                CmsUtil::parse_signing_time_attribute(&attribute).unwrap(),
+                time
+            );
+        }
+
+        // Not a signing-time attribute
+        let attribute =
+            CmsUtil::create_content_type_attribute(const_oid::db::rfc5911::ID_DATA).unwrap();
+        assert!(matches!(
+            CmsUtil::parse_signing_time_attribute(&attribute),
+            Err(QubitCryptError::InvalidAttribute)
+        ));
+    }
 }
@@ -1,25 +1,33 @@
+use crate::cea::cea_manager::CeaManager;
+use crate::cea::common::cea_info::CeaInfo;
+use crate::cea::common::cea_trait::Cea;
 use crate::kdf::api::KdfType;
-use crate::kdf::common::config::oids::Oid as _;
 use crate::kem::common::kem_trait::Kem;
 use crate::wrap::api::WrapType;
 use crate::wrap::common::config::oids::Oid as _;
 use cms::builder::{
     ContentEncryptionAlgorithm, KekRecipientInfoBuilder, KeyAgreeRecipientInfoBuilder,
     KeyTransRecipientInfoBuilder, OtherRecipientInfoBuilder, PasswordRecipientInfoBuilder,
+    RecipientInfoBuilder, RecipientInfoType,
+};
+use cms::content_info::{CmsVersion, ContentInfo};
+use cms::enveloped_data::{
+    EncryptedContentInfo, OriginatorInfo, RecipientInfo, UserKeyingMaterial,
 };
-use cms::content_info::ContentInfo;
-use cms::enveloped_data::{OriginatorInfo, UserKeyingMaterial};
 use const_oid::db::rfc5911::{ID_CT_AUTH_ENVELOPED_DATA, ID_ENVELOPED_DATA};
 use der::{Decode, Encode};
-use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
 use x509_cert::attr::{Attribute, Attributes};
+use zeroize::Zeroizing;
 
 use crate::{
     cea::common::cea_type::CeaType, certificates::Certificate, kem::kem_manager, QubitCryptError,
 };
 
+use crate::cms::asn1::ber::encode_indefinite_length;
 use crate::cms::asn1::kemri_builder::KemRecipientInfoBuilder;
+use crate::cms::cms_util::CmsUtil;
 
 use super::asn1::auth_enveloped_data_builder::{
     AuthEnvelopedDataBuilder, ContentEncryptionAlgorithmAead,
@@ -60,8 +68,20 @@ pub struct EnvelopedDataBuilder<'a> {
     pwri_builders: Vec<PasswordRecipientInfoBuilder>,
     /// The Other recipient info builders (other than Kem Recipient Info, and other types)
     ori_builders: Vec<OtherRecipientInfoBuilder>,
+    /// Recipient infos which were built elsewhere for the content encryption key
+    recipient_infos: Vec<PrebuiltRecipientInfo>,
+    /// The content encryption key, if it is provided by the caller
+    content_encryption_key: Option<Zeroizing<Vec<u8>>>,
     /// Whether this is an AuthEnvelopedData
     is_auth_enveloped: bool,
+    /// Whether to encode with BER indefinite lengths instead of DER
+    indefinite_length: bool,
+    /// The version of the EnvelopedData, if it's not calculated from the contents
+    version: Option<CmsVersion>,
+    /// The seed of the deterministic mode, see `deterministic`
+    seed: Option<[u8; 32]>,
+    /// The IV or nonce of the content encryption, which is only set in the deterministic mode
+    nonce: Option<Vec<u8>>,
 }
 
 impl<'a> EnvelopedDataBuilder<'a> {
@@ -96,7 +116,13 @@ impl<'a> EnvelopedDataBuilder<'a> {
             kari_builders: Vec::new(),
             pwri_builders: Vec::new(),
             ori_builders: Vec::new(),
+            recipient_infos: Vec::new(),
+            content_encryption_key: None,
             is_auth_enveloped,
+            indefinite_length: false,
+            version: None,
+            seed: None,
+            nonce: None,
         })
     }
 
@@ -174,6 +200,13 @@ impl<'a> EnvelopedDataBuilder<'a> {
 
     /// Add a KEM recipient
     ///
+    /// The certificate can hold any KEM key, including composite ML-KEM keys. For a composite
+    /// key, the output of the composite combiner is used as the shared secret for the KDF.
+    ///
+    /// The KDF, key wrap and UKM apply to this recipient only, so recipients with different
+    /// capabilities can be mixed: the CEK is wrapped separately for each recipient with its own
+    /// KEK, and each KEMRecipientInfo records the parameters needed to unwrap it.
+    ///
     /// # Arguments
     ///
     /// * `cert` - The certificate of the recipient
@@ -181,9 +214,22 @@ impl<'a> EnvelopedDataBuilder<'a> {
     /// * `wrap_type` - The key wrap type to use
     /// * `ukm` - The user keying material to use
     ///
+    /// `Certificate::suitable_kem_parameters` lists the KDF and key wrap combinations which
+    /// match the security category of the recipient's KEM.
+    ///
     /// # Returns
     ///
     /// A mutable reference to the builder
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` if the certificate doesn't hold a KEM key, e.g.
+    /// a signing certificate
+    ///
+    /// `QubitCryptError::InvalidCertificate` if the certificate is not enabled for key encipherment
+    ///
+    /// `QubitCryptError::InvalidSharedSecretLength` if the KDF is `KdfType::Direct` and the
+    /// shared secret of the KEM doesn't have the key length of the key wrap
     pub fn kem_recipient(
         &mut self,
         cert: &Certificate,
@@ -191,15 +237,67 @@ impl<'a> EnvelopedDataBuilder<'a> {
         wrap_type: &WrapType,
         ukm: Option<UserKeyingMaterial>,
     ) -> Result<&mut Self> {
+        self.add_kem_recipient(cert, kdf, wrap_type, ukm, None)
+    }
+
+    /// Add a KEM recipient whose composite KEM uses an additional combiner label
+    ///
+    /// The label is mixed into the combiner of the composite KEM, see
+    /// `dyn_composite_kem_with_label`. It is not recorded in the KEMRecipientInfo, so the
+    /// recipient must decrypt with the same label using `CmsUtil::decrypt_kemri_with_label`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cert` - The certificate of the recipient
+    /// * `kdf` - The key derivation function to use
+    /// * `wrap_type` - The key wrap type to use
+    /// * `ukm` - The user keying material to use
+    /// * `combiner_label` - The domain separation label
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    ///
+    /// # Errors
+    ///
+    /// The errors of `kem_recipient`, and `QubitCryptError::UnsupportedOperation` if the
+    /// certificate doesn't hold a composite KEM key
+    pub fn kem_recipient_with_label(
+        &mut self,
+        cert: &Certificate,
+        kdf: &KdfType,
+        wrap_type: &WrapType,
+        ukm: Option<UserKeyingMaterial>,
+        combiner_label: &[u8],
+    ) -> Result<&mut Self> {
+        self.add_kem_recipient(cert, kdf, wrap_type, ukm, Some(combiner_label))
+    }
+
+    /// Add a KEM recipient, with an optional combiner label for a composite KEM
+    fn add_kem_recipient(
+        &mut self,
+        cert: &Certificate,
+        kdf: &KdfType,
+        wrap_type: &WrapType,
+        ukm: Option<UserKeyingMaterial>,
+        combiner_label: Option<&[u8]>,
+    ) -> Result<&mut Self> {
+        if !cert.is_kem() {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
         if !cert.is_key_encipherment_enabled() {
             return Err(QubitCryptError::InvalidCertificate);
         }
 
-        let kdf_oid = kdf.get_oid();
         let wrap_oid = wrap_type.get_oid();
 
-        let kem_manager = kem_manager::KemManager::new_from_oid(&cert.get_public_key_oid())?;
-        let kemri_builder = KemRecipientInfoBuilder::new(cert, kem_manager, kdf_oid, wrap_oid, ukm);
+        let mut kem_manager = kem_manager::KemManager::new_from_oid(&cert.get_public_key_oid())?;
+        if let Some(label) = combiner_label {
+            kem_manager.set_combiner_label(label)?;
+        }
+        CmsUtil::check_kem_parameters(&kem_manager, kdf, wrap_type)?;
+        let kemri_builder =
+            KemRecipientInfoBuilder::new(cert, kem_manager, kdf.clone(), wrap_oid, ukm);
         self.kemri_builders.push(kemri_builder);
         Ok(self)
     }
@@ -277,6 +375,49 @@ impl<'a> EnvelopedDataBuilder<'a> {
         Ok(self)
     }
 
+    /// Add a RecipientInfo which was already built for the content encryption key, for
+    /// example with `CmsUtil::build_kem_recipient_info`. This allows the content encryption
+    /// key to be wrapped for each recipient independently and the results to be assembled here.
+    ///
+    /// The content encryption key must be set with `content_encryption_key`, since the
+    /// RecipientInfo can only be decrypted to the key which was wrapped in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipient_info` - The RecipientInfo to add
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn add_recipient_info(&mut self, recipient_info: RecipientInfo) -> Result<&mut Self> {
+        self.recipient_infos
+            .push(PrebuiltRecipientInfo(recipient_info));
+        Ok(self)
+    }
+
+    /// Set the content encryption key (CEK). If it is not set, a random CEK is generated
+    /// when the EnvelopedData / AuthEnvelopedData is built.
+    ///
+    /// # Arguments
+    ///
+    /// * `cek` - The content encryption key
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidContentEncryptionKey` if the length of the key does not match
+    /// the content encryption algorithm
+    pub fn content_encryption_key(&mut self, cek: &[u8]) -> Result<&mut Self> {
+        if cek.len() != CeaInfo::new(self.cea_type.clone()).key_byte_len {
+            return Err(QubitCryptError::InvalidContentEncryptionKey);
+        }
+        self.content_encryption_key = Some(Zeroizing::new(cek.to_vec()));
+        Ok(self)
+    }
+
     /// Set the originator info
     ///
     /// # Arguments
@@ -291,65 +432,195 @@ impl<'a> EnvelopedDataBuilder<'a> {
         Ok(self)
     }
 
+    /// Encode the EnvelopedData / AuthEnvelopedData with BER indefinite lengths instead of DER
+    ///
+    /// The ContentInfo, the EnvelopedData / AuthEnvelopedData and the EncryptedContentInfo
+    /// get indefinite lengths and the encrypted content becomes a constructed OCTET STRING of
+    /// 1000 byte segments, the layout other tools use for streaming large payloads. The
+    /// recipient infos and attributes stay DER. This only changes the encoding: the envelope is
+    /// still built in memory as DER and re-encoded at the end, so memory use doesn't change.
+    /// Only enable this if the consumer accepts BER: RFC 5652 allows it, but decoders which only
+    /// accept DER reject it. All parsers of the crate, including `CmsUtil::decrypt_kemri`,
+    /// accept it. The default is DER.
+    ///
+    /// # Arguments
+    ///
+    /// * `indefinite_length` - Whether to use indefinite lengths
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn indefinite_length(&mut self, indefinite_length: bool) -> Result<&mut Self> {
+        self.indefinite_length = indefinite_length;
+        Ok(self)
+    }
+
+    /// Set the version of the EnvelopedData, instead of calculating it according to
+    /// RFC 5652 § 6.1
+    ///
+    /// This is for peers which expect a specific version. The version can be higher than the
+    /// calculated one, but not lower, e.g. KEM recipients require at least `CmsVersion::V3`.
+    /// It's checked against the recipients when the EnvelopedData is built. The version of an
+    /// AuthEnvelopedData is always `CmsVersion::V0` (RFC 5083).
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    pub fn with_version(&mut self, version: CmsVersion) -> Result<&mut Self> {
+        self.version = Some(version);
+        Ok(self)
+    }
+
+    /// Make the output reproducible, for golden-file tests
+    ///
+    /// The randomness of the build is taken from a ChaCha20 stream seeded with `seed`: the
+    /// content encryption key (unless one is set with `content_encryption_key`), the IV or nonce
+    /// and the encapsulation of each KEM recipient. Building the same contents with the same
+    /// seed gives the same bytes. Other recipients are included as they are built, so they must
+    /// be deterministic themselves, e.g. KEK recipients, or added with `add_recipient_info`.
+    ///
+    /// **Warning:** the output is only as secret as the seed, so this must not be used for
+    /// real data. It's only available with the `test-utils` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed of the randomness
+    ///
+    /// # Returns
+    ///
+    /// A mutable reference to the builder
+    #[cfg(feature = "test-utils")]
+    pub fn deterministic(&mut self, seed: [u8; 32]) -> Result<&mut Self> {
+        self.seed = Some(seed);
+        Ok(self)
+    }
+
+    /// Derive the content encryption key, the IV or nonce and the RNGs of the KEM recipients
+    /// from the seed of the deterministic mode, in this order
+    fn apply_seed(&mut self, seed: [u8; 32]) {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let cea_info = CeaInfo::new(self.cea_type.clone());
+
+        let mut cek = Zeroizing::new(vec![0u8; cea_info.key_byte_len]);
+        rng.fill_bytes(&mut cek);
+        if self.content_encryption_key.is_none() {
+            self.content_encryption_key = Some(cek);
+        }
+
+        let mut nonce = vec![0u8; cea_info.nonce_byte_len];
+        rng.fill_bytes(&mut nonce);
+        self.nonce = Some(nonce);
+
+        for kemri_builder in &mut self.kemri_builders {
+            let mut kem_seed = [0u8; 32];
+            rng.fill_bytes(&mut kem_seed);
+            kemri_builder.rng = Some(ChaCha20Rng::from_seed(kem_seed));
+        }
+    }
+
     /// Build the EnvelopedData
-    fn build_enveloped(self) -> Result<Vec<u8>> {
+    ///
+    /// Without a content encryption key from the caller, a random one is generated here rather
+    /// than by the builder of the cms crate, so that the errors of the KEM recipients are passed
+    /// through instead of being reduced to a message.
+    fn build_enveloped(mut self) -> Result<Vec<u8>> {
+        if self.content_encryption_key.is_none() {
+            if !ALLOWED_CEA_TYPES_ENVELOPED.contains(&self.cea_type) {
+                return Err(QubitCryptError::UnsupportedOperation);
+            }
+            let mut cea = CeaManager::new(self.cea_type.clone())?;
+            self.content_encryption_key = Some(Zeroizing::new(cea.key_gen()?));
+        }
+        self.build_enveloped_with_cek()
+    }
+
+    /// Build the EnvelopedData using the content encryption key provided by the caller
+    fn build_enveloped_with_cek(mut self) -> Result<Vec<u8>> {
+        let cek = self
+            .content_encryption_key
+            .take()
+            .ok_or(QubitCryptError::InvalidContentEncryptionKey)?;
+
+        let mut cea = CeaManager::new(self.cea_type.clone())?;
+        let nonce = match self.nonce.take() {
+            Some(nonce) => nonce,
+            None => cea.nonce_gen()?,
+        };
+        let (_, eci) = cea.encrypt(&cek, Some(&nonce), &self.plaintext, None, None)?;
+        let encrypted_content = EncryptedContentInfo::from_der(&eci)
+            .map_err(|_| QubitCryptError::SerializationFailed)?;
+
+        // The builder of the cms crate builds the RecipientInfos and calculates the version
+        // (RFC 5652 § 6.1). It encrypts the content with a CEK of its own, so it is given no
+        // content and each recipient is built with the CEK of this builder instead.
         let cea = match self.cea_type {
             CeaType::Aes128CbcPad => ContentEncryptionAlgorithm::Aes128Cbc,
             CeaType::Aes192CbcPad => ContentEncryptionAlgorithm::Aes192Cbc,
             CeaType::Aes256CbcPad => ContentEncryptionAlgorithm::Aes256Cbc,
             _ => return Err(QubitCryptError::UnsupportedOperation),
         };
-
         let mut builder = cms::builder::EnvelopedDataBuilder::new(
             self.originator_info.clone(),
-            &self.plaintext,
+            &[],
             cea,
             self.unprotected_attributes.clone(),
         )
         .map_err(|_| QubitCryptError::Unknown)?;
 
-        for kemri_builder in self.kemri_builders {
-            let kemri = kemri_builder;
+        // The KEM recipients are built here to pass their errors through
+        for mut kemri_builder in self.kemri_builders {
+            let recipient_info = kemri_builder.build_kem_recipient_info(&cek)?;
             builder
-                .add_recipient_info(kemri)
+                .add_recipient_info(PrebuiltRecipientInfo(recipient_info))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
         for kek_builder in self.kek_builders {
             builder
-                .add_recipient_info(kek_builder)
+                .add_recipient_info(WithCek(kek_builder, &cek))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
         for ktri_builder in self.ktri_builders {
             builder
-                .add_recipient_info(ktri_builder)
+                .add_recipient_info(WithCek(ktri_builder, &cek))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
         for kari_builder in self.kari_builders {
             builder
-                .add_recipient_info(kari_builder)
+                .add_recipient_info(WithCek(kari_builder, &cek))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
         for pwri_builder in self.pwri_builders {
             builder
-                .add_recipient_info(pwri_builder)
+                .add_recipient_info(WithCek(pwri_builder, &cek))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
         for ori_builder in self.ori_builders {
             builder
-                .add_recipient_info(ori_builder)
+                .add_recipient_info(WithCek(ori_builder, &cek))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
-        let mut rng = ChaCha20Rng::from_entropy();
+        for recipient_info in self.recipient_infos {
+            builder
+                .add_recipient_info(recipient_info)
+                .map_err(|_| QubitCryptError::Unknown)?;
+        }
 
-        let enveloped_data = builder
+        let mut rng = ChaCha20Rng::from_entropy();
+        let mut enveloped_data = builder
             .build_with_rng(&mut rng)
-            .map_err(|_| QubitCryptError::Unknown)?;
+            .map_err(|_| QubitCryptError::InvalidRecipientInfo)?;
+        enveloped_data.version = check_version(self.version, enveloped_data.version, false)?;
+        enveloped_data.encrypted_content = encrypted_content;
 
         enveloped_data
             .to_der()
@@ -357,7 +628,13 @@ impl<'a> EnvelopedDataBuilder<'a> {
     }
 
     /// Build the AuthEnvelopedData
-    pub fn build_auth_enveloped(self) -> Result<Vec<u8>> {
+    ///
+    /// As for EnvelopedData, a random content encryption key is generated here if the caller
+    /// didn't provide one, so that the KEM recipients can be built with it before the
+    /// AuthEnvelopedData and their errors are passed through.
+    fn build_auth_enveloped(mut self) -> Result<Vec<u8>> {
+        check_version(self.version, CmsVersion::V0, true)?;
+
         let cea = match self.cea_type {
             CeaType::Aes128Gcm => ContentEncryptionAlgorithmAead::Aes128Gcm,
             CeaType::Aes192Gcm => ContentEncryptionAlgorithmAead::Aes192Gcm,
@@ -374,10 +651,19 @@ impl<'a> EnvelopedDataBuilder<'a> {
             self.unprotected_attributes.clone(),
         )?;
 
-        for kemri_builder in self.kemri_builders {
-            let kemri = kemri_builder;
+        let cek = match self.content_encryption_key.take() {
+            Some(cek) => cek,
+            None => Zeroizing::new(CeaManager::new(self.cea_type.clone())?.key_gen()?),
+        };
+        builder.content_encryption_key(&cek)?;
+        if let Some(nonce) = &self.nonce {
+            builder.nonce(nonce)?;
+        }
+
+        for mut kemri_builder in self.kemri_builders {
+            let recipient_info = kemri_builder.build_kem_recipient_info(&cek)?;
             builder
-                .add_recipient_info(kemri)
+                .add_recipient_info(PrebuiltRecipientInfo(recipient_info))
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
@@ -411,6 +697,12 @@ impl<'a> EnvelopedDataBuilder<'a> {
                 .map_err(|_| QubitCryptError::Unknown)?;
         }
 
+        for recipient_info in self.recipient_infos {
+            builder
+                .add_recipient_info(recipient_info)
+                .map_err(|_| QubitCryptError::Unknown)?;
+        }
+
         let enveloped_data = builder.build()?;
 
         enveloped_data
@@ -420,16 +712,48 @@ impl<'a> EnvelopedDataBuilder<'a> {
 
     /// Build the EnvelopedData or AuthEnvelopedData and returns the DER bytes
     ///
+    /// The builder is consumed, so it can't be built a second time, e.g. with another random
+    /// content encryption key, or changed after it was built:
+    /// ```compile_fail,E0382
+    /// use qubitcrypt::content::{ContentEncryptionAlgorithm, EnvelopedDataContent};
+    ///
+    /// let mut builder =
+    ///     EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+    /// builder.content(b"abc").unwrap();
+    /// let first = builder.build();
+    /// let second = builder.build();
+    /// ```
+    ///
     /// # Returns
     ///
-    /// The DER bytes of the EnvelopedData or AuthEnvelopedData
-    pub fn build(self) -> Result<Vec<u8>> {
+    /// The DER bytes of the EnvelopedData or AuthEnvelopedData, or the BER bytes if
+    /// `indefinite_length` is enabled
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::EmptyContent` if no content was set
+    ///
+    /// `QubitCryptError::InvalidContentEncryptionKey` if pre-built RecipientInfos were added but
+    /// no content encryption key was set
+    ///
+    /// `QubitCryptError::InvalidEnvelopedData` if the version set with `with_version` is not
+    /// compatible with the contents
+    pub fn build(mut self) -> Result<Vec<u8>> {
         let is_auth_enveloped = self.is_auth_enveloped;
+        let indefinite_length = self.indefinite_length;
 
         if self.plaintext.is_empty() {
             return Err(QubitCryptError::EmptyContent);
         }
 
+        if !self.recipient_infos.is_empty() && self.content_encryption_key.is_none() {
+            return Err(QubitCryptError::InvalidContentEncryptionKey);
+        }
+
+        if let Some(seed) = self.seed.take() {
+            self.apply_seed(seed);
+        }
+
         let data = if !self.is_auth_enveloped {
             self.build_enveloped()?
         } else {
@@ -451,6 +775,10 @@ impl<'a> EnvelopedDataBuilder<'a> {
             .to_der()
             .map_err(|_| QubitCryptError::Unknown)?;
 
+        if indefinite_length {
+            return encode_indefinite_length(&ci_der);
+        }
+
         Ok(ci_der)
     }
 
@@ -465,12 +793,107 @@ impl<'a> EnvelopedDataBuilder<'a> {
     }
 }
 
+/// Check that the version set by the caller is compatible with the contents
+///
+/// # Arguments
+///
+/// * `version` - The version set by the caller, if any
+/// * `calculated` - The version calculated from the contents
+/// * `is_auth_enveloped` - Whether this is an AuthEnvelopedData
+///
+/// # Returns
+///
+/// The version to encode
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidEnvelopedData` if the version is not a version of the content type
+/// or lower than the calculated version
+fn check_version(
+    version: Option<CmsVersion>,
+    calculated: CmsVersion,
+    is_auth_enveloped: bool,
+) -> Result<CmsVersion> {
+    let Some(version) = version else {
+        return Ok(calculated);
+    };
+
+    let compatible = if is_auth_enveloped {
+        version == CmsVersion::V0
+    } else {
+        // EnvelopedData has no version 1 and versions above 4 are not defined
+        !matches!(version, CmsVersion::V1 | CmsVersion::V5) && version >= calculated
+    };
+
+    if !compatible {
+        return Err(QubitCryptError::InvalidEnvelopedData);
+    }
+    Ok(version)
+}
+
+/// A recipient info builder which is given the content encryption key of the
+/// `EnvelopedDataBuilder` instead of the one generated by the builder of the cms crate
+struct WithCek<'k, B>(B, &'k [u8]);
+
+impl<B: RecipientInfoBuilder> RecipientInfoBuilder for WithCek<'_, B> {
+    fn recipient_info_type(&self) -> RecipientInfoType {
+        self.0.recipient_info_type()
+    }
+
+    fn recipient_info_version(&self) -> CmsVersion {
+        self.0.recipient_info_version()
+    }
+
+    fn build(
+        &mut self,
+        _content_encryption_key: &[u8],
+    ) -> std::result::Result<RecipientInfo, cms::builder::Error> {
+        self.0.build(self.1)
+    }
+}
+
+/// A RecipientInfo which was built elsewhere, added to the builder as is
+struct PrebuiltRecipientInfo(RecipientInfo);
+
+impl RecipientInfoBuilder for PrebuiltRecipientInfo {
+    fn recipient_info_type(&self) -> RecipientInfoType {
+        match &self.0 {
+            RecipientInfo::Ktri(_) => RecipientInfoType::Ktri,
+            RecipientInfo::Kari(_) => RecipientInfoType::Kari,
+            RecipientInfo::Kekri(_) => RecipientInfoType::Kekri,
+            RecipientInfo::Pwri(_) => RecipientInfoType::Pwri,
+            RecipientInfo::Ori(_) => RecipientInfoType::Ori,
+        }
+    }
+
+    fn recipient_info_version(&self) -> CmsVersion {
+        match &self.0 {
+            RecipientInfo::Ktri(ri) => ri.version,
+            RecipientInfo::Kari(ri) => ri.version,
+            RecipientInfo::Kekri(ri) => ri.version,
+            RecipientInfo::Pwri(ri) => ri.version,
+            // Same as the KemRecipientInfoBuilder
+            RecipientInfo::Ori(_) => CmsVersion::V3,
+        }
+    }
+
+    fn build(
+        &mut self,
+        _content_encryption_key: &[u8],
+    ) -> std::result::Result<RecipientInfo, cms::builder::Error> {
+        Ok(self.0.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use cms::enveloped_data::EnvelopedData;
+
     use crate::dsa::common::config::oids::Oid;
     use crate::dsa::common::dsa_trait::Dsa;
     use crate::dsa::common::dsa_type::DsaType;
     use crate::dsa::dsa_manager::DsaManager;
+    use crate::kdf::common::config::oids::Oid as _;
     use crate::kem::common::config::oids::Oid as _;
     use crate::kem::common::kem_type::KemType;
     use crate::{
@@ -503,7 +926,7 @@ mod tests {
 
         let result = builder.kem_recipient(&cert_ta_1, &kdf, &wrap, ukm.clone());
         assert!(result.is_err());
-        assert!(matches!(result, Err(QubitCryptError::InvalidCertificate)));
+        assert!(matches!(result, Err(QubitCryptError::UnsupportedOperation)));
 
         let cert_ee_1: Certificate = Certificate::from_der(include_bytes!(
             "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
@@ -587,6 +1010,250 @@ mod tests {
         assert_eq!(pt, plaintext);
     }
 
+    #[test]
+    fn test_composite_kem_recipient() {
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        let plaintext = b"Hello, World!".to_vec();
+        let (ta_pk, ta_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+        let ta_cert = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity,
+            "CN=ta.test.com",
+            ta_pk,
+            &ta_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        for (alg, auth) in [
+            (KemAlgorithm::MlKem768X25519, false),
+            (KemAlgorithm::MlKem768P256, true),
+            (KemAlgorithm::MlKem768P384, true),
+            (KemAlgorithm::MlKem1024P384, false),
+        ] {
+            let (ee_pk, ee_sk) = KemKeyGenerator::new(alg).generate().unwrap();
+            assert!(ee_sk.is_composite());
+            let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+            let ee_cert = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: ta_cert.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: true,
+                },
+                None,
+                validity,
+                "CN=ee.test.com",
+                ee_pk,
+                &ta_sk,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+
+            let cea_type = if auth {
+                CeaType::Aes256Gcm
+            } else {
+                CeaType::Aes256CbcPad
+            };
+            let mut builder = EnvelopedDataBuilder::new(cea_type, auth).unwrap();
+            builder.content(&plaintext).unwrap();
+            builder
+                .kem_recipient(&ee_cert, &KdfType::HkdfWithSha256, &WrapType::Aes256, None)
+                .unwrap();
+            let result = builder.build().unwrap();
+
+            let pt =
+                crate::cms::cms_util::CmsUtil::decrypt_kemri(&result, &ee_sk, &ee_cert).unwrap();
+            assert_eq!(pt, plaintext);
+
+            // A composite key of the same type which is not the recipient's
+            let (_, other_sk) = KemKeyGenerator::new(alg).generate().unwrap();
+            assert!(
+                crate::cms::cms_util::CmsUtil::decrypt_kemri(&result, &other_sk, &ee_cert).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_composite_kem_recipient_with_label() {
+        use crate::cms::cms_util::CmsUtil;
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        let plaintext = b"Hello, World!".to_vec();
+        let (ta_pk, ta_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+        let ta_cert =
+            CertificateBuilder::new(Profile::Root, None, validity, "CN=ta", ta_pk, &ta_sk)
+                .unwrap()
+                .build()
+                .unwrap();
+        let issue = |alg: KemAlgorithm| {
+            let (ee_pk, ee_sk) = KemKeyGenerator::new(alg).generate().unwrap();
+            let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+            let ee_cert = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: ta_cert.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: true,
+                },
+                None,
+                validity,
+                "CN=ee",
+                ee_pk,
+                &ta_sk,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+            (ee_cert, ee_sk)
+        };
+
+        let (ee_cert, ee_sk) = issue(KemAlgorithm::MlKem768X25519);
+        let mut builder = EnvelopedDataBuilder::new(CeaType::Aes256CbcPad, false).unwrap();
+        builder.content(&plaintext).unwrap();
+        builder
+            .kem_recipient_with_label(
+                &ee_cert,
+                &KdfType::HkdfWithSha256,
+                &WrapType::Aes256,
+                None,
+                b"protocol A",
+            )
+            .unwrap();
+        let result = builder.build().unwrap();
+
+        let pt =
+            CmsUtil::decrypt_kemri_with_label(&result, &ee_sk, &ee_cert, b"protocol A").unwrap();
+        assert_eq!(pt, plaintext);
+
+        // The shared secret depends on the label
+        assert!(CmsUtil::decrypt_kemri(&result, &ee_sk, &ee_cert).is_err());
+        assert!(
+            CmsUtil::decrypt_kemri_with_label(&result, &ee_sk, &ee_cert, b"protocol B").is_err()
+        );
+
+        // Only composite KEMs have a combiner label
+        let (ee_cert, ee_sk) = issue(KemAlgorithm::MlKem768);
+        let mut builder = EnvelopedDataBuilder::new(CeaType::Aes256CbcPad, false).unwrap();
+        assert!(matches!(
+            builder.kem_recipient_with_label(
+                &ee_cert,
+                &KdfType::HkdfWithSha256,
+                &WrapType::Aes256,
+                None,
+                b"protocol A",
+            ),
+            Err(QubitCryptError::UnsupportedOperation)
+        ));
+        assert_eq!(
+            CmsUtil::decrypt_kemri_with_label(&result, &ee_sk, &ee_cert, b"protocol A"),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+    }
+
+    #[test]
+    fn test_per_recipient_kdf_and_wrap() {
+        use crate::cms::asn1::kemri::KemRecipientInfo;
+        use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+        use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+        let plaintext = b"Hello, World!".to_vec();
+        let (ta_pk, ta_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+        let ta_cert =
+            CertificateBuilder::new(Profile::Root, None, validity, "CN=ta", ta_pk, &ta_sk)
+                .unwrap()
+                .build()
+                .unwrap();
+
+        let params = [
+            (
+                KemAlgorithm::MlKem512,
+                KdfType::HkdfWithSha256,
+                WrapType::Aes128,
+                None,
+            ),
+            (
+                KemAlgorithm::MlKem768,
+                KdfType::Kmac256,
+                WrapType::Aes256,
+                None,
+            ),
+            (
+                KemAlgorithm::MlKem768X25519,
+                KdfType::Shake256,
+                WrapType::Aes128,
+                Some(UserKeyingMaterial::new(b"ukm".to_vec()).unwrap()),
+            ),
+        ];
+
+        let mut builder = EnvelopedDataBuilder::new(CeaType::Aes256CbcPad, false).unwrap();
+        builder.content(&plaintext).unwrap();
+        let mut recipients = Vec::new();
+        for (i, (alg, kdf, wrap, ukm)) in params.iter().enumerate() {
+            let (ee_pk, ee_sk) = KemKeyGenerator::new(*alg).generate().unwrap();
+            let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+            let ee_cert = CertificateBuilder::new(
+                Profile::Leaf {
+                    issuer: ta_cert.get_subject(),
+                    enable_key_agreement: false,
+                    enable_key_encipherment: true,
+                },
+                None,
+                validity,
+                format!("CN=ee{}", i),
+                ee_pk,
+                &ta_sk,
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+            builder
+                .kem_recipient(&ee_cert, kdf, wrap, ukm.clone())
+                .unwrap();
+            recipients.push((ee_cert, ee_sk));
+        }
+        let result = builder.build().unwrap();
+
+        // Each KEMRecipientInfo carries its own parameters
+        let ci = ContentInfo::from_der(&result).unwrap();
+        let ed = EnvelopedData::from_der(&ci.content.to_der().unwrap()).unwrap();
+        assert_eq!(ed.recip_infos.0.len(), params.len());
+        for (ri, (_, kdf, wrap, ukm)) in ed.recip_infos.0.iter().zip(params.iter()) {
+            let ori = match ri {
+                RecipientInfo::Ori(ori) => ori,
+                _ => panic!("Expected an OtherRecipientInfo"),
+            };
+            let kemri = KemRecipientInfo::from_der(&ori.ori_value.to_der().unwrap()).unwrap();
+            assert_eq!(kemri.kdf_oid(), kdf.get_oid());
+            assert_eq!(kemri.wrap.oid.to_string(), wrap.get_oid());
+            let kek_length = match wrap {
+                WrapType::Aes128 => 16,
+                WrapType::Aes256 => 32,
+            };
+            assert_eq!(kemri.kek_length, kek_length);
+            assert_eq!(&kemri.ukm, ukm);
+        }
+
+        // And every recipient can decrypt with them
+        for (ee_cert, ee_sk) in recipients.iter() {
+            let pt = crate::cms::cms_util::CmsUtil::decrypt_kemri(&result, ee_sk, ee_cert).unwrap();
+            assert_eq!(pt, plaintext);
+        }
+    }
+
     #[test]
     fn test_auth_enveloped_data_kemri() {
         let plaintext = b"Hello, World!".to_vec();
@@ -607,7 +1274,7 @@ mod tests {
 
         let result = builder.kem_recipient(&cert_ta_1, &kdf, &wrap, ukm.clone());
         assert!(result.is_err());
-        assert!(matches!(result, Err(QubitCryptError::InvalidCertificate)));
+        assert!(matches!(result, Err(QubitCryptError::UnsupportedOperation)));
 
         let cert_ee_1: Certificate = Certificate::from_der(include_bytes!(
             "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
@@ -691,6 +1358,194 @@ mod tests {
         assert_eq!(pt, plaintext);
     }
 
+    #[test]
+    fn test_enveloped_data_prebuilt_recipient_info() {
+        let plaintext = b"Hello, World!".to_vec();
+        let cert_ee_1: Certificate = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
+        ))
+        .expect("Failed to create Certificate");
+        let sk_ee_1 = PrivateKey::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_priv.der"
+        ))
+        .expect("Failed to create PrivateKey");
+
+        for (cea_type, is_auth_enveloped) in
+            [(CeaType::Aes256CbcPad, false), (CeaType::Aes256Gcm, true)]
+        {
+            let cek = [7u8; 32];
+
+            // Wrap the CEK for the recipient independently of the builder
+            let ri = crate::cms::cms_util::CmsUtil::build_kem_recipient_info(
+                &cek,
+                &cert_ee_1,
+                &KdfType::HkdfWithSha256,
+                &WrapType::Aes256,
+                None,
+            )
+            .unwrap();
+
+            // A pre-built RecipientInfo without the CEK it wraps can't be used
+            let mut builder = EnvelopedDataBuilder::new(cea_type.clone(), is_auth_enveloped)
+                .expect("Failed to create EnvelopedDataBuilder");
+            builder.content(&plaintext).unwrap();
+            builder.add_recipient_info(ri.clone()).unwrap();
+            assert!(matches!(
+                builder.build(),
+                Err(QubitCryptError::InvalidContentEncryptionKey)
+            ));
+
+            let mut builder = EnvelopedDataBuilder::new(cea_type, is_auth_enveloped)
+                .expect("Failed to create EnvelopedDataBuilder");
+            assert!(matches!(
+                builder.content_encryption_key(&[7u8; 16]),
+                Err(QubitCryptError::InvalidContentEncryptionKey)
+            ));
+            builder
+                .content(&plaintext)
+                .unwrap()
+                .content_encryption_key(&cek)
+                .unwrap()
+                .add_recipient_info(ri)
+                .unwrap();
+
+            let result = builder.build().expect("Failed to build enveloped data");
+
+            let pt = crate::cms::cms_util::CmsUtil::decrypt_kemri(&result, &sk_ee_1, &cert_ee_1)
+                .expect("Failed to decrypt enveloped data");
+            assert_eq!(pt, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_with_version() {
+        use crate::cms::asn1::enveloped_data_content::EnvelopedDataContent;
+
+        let plaintext = b"Hello, World!".to_vec();
+        let cert_ee_1: Certificate = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
+        ))
+        .expect("Failed to create Certificate");
+        let sk_ee_1 = PrivateKey::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_priv.der"
+        ))
+        .expect("Failed to create PrivateKey");
+        let kdf = KdfType::HkdfWithSha256;
+        let wrap = WrapType::Aes256;
+
+        let build = |version: Option<CmsVersion>, cek: Option<&[u8]>, is_auth: bool| {
+            let cea_type = if is_auth {
+                CeaType::Aes256Gcm
+            } else {
+                CeaType::Aes256CbcPad
+            };
+            let mut builder = EnvelopedDataBuilder::new(cea_type, is_auth).unwrap();
+            builder
+                .content(&plaintext)
+                .unwrap()
+                .kem_recipient(&cert_ee_1, &kdf, &wrap, None)
+                .unwrap();
+            if let Some(version) = version {
+                builder.with_version(version).unwrap();
+            }
+            if let Some(cek) = cek {
+                builder.content_encryption_key(cek).unwrap();
+            }
+            builder.build()
+        };
+
+        // KEM recipients require at least V3, both with and without a caller provided CEK
+        for cek in [None, Some([7u8; 32].as_slice())] {
+            let result = build(None, cek, false).unwrap();
+            let edc =
+                EnvelopedDataContent::from_bytes_for_kem_recipient(&result, &cert_ee_1, &sk_ee_1)
+                    .unwrap();
+            assert_eq!(edc.get_version(), CmsVersion::V3);
+
+            let result = build(Some(CmsVersion::V4), cek, false).unwrap();
+            let edc =
+                EnvelopedDataContent::from_bytes_for_kem_recipient(&result, &cert_ee_1, &sk_ee_1)
+                    .unwrap();
+            assert_eq!(edc.get_version(), CmsVersion::V4);
+            assert_eq!(edc.get_content(), plaintext);
+
+            for version in [
+                CmsVersion::V0,
+                CmsVersion::V1,
+                CmsVersion::V2,
+                CmsVersion::V5,
+            ] {
+                assert!(matches!(
+                    build(Some(version), cek, false),
+                    Err(QubitCryptError::InvalidEnvelopedData)
+                ));
+            }
+        }
+
+        // AuthEnvelopedData is always V0
+        assert!(build(Some(CmsVersion::V0), None, true).is_ok());
+        assert!(matches!(
+            build(Some(CmsVersion::V3), None, true),
+            Err(QubitCryptError::InvalidEnvelopedData)
+        ));
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_deterministic() {
+        use crate::asn1::pki::Pki;
+        use crate::dsa::api::algorithm::DsaAlgorithm;
+        use crate::kem::api::algorithm::KemAlgorithm;
+
+        let plaintext = b"Hello, World!".to_vec();
+        let cert_ee_1: Certificate = Certificate::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_ee.der"
+        ))
+        .expect("Failed to create Certificate");
+        let sk_ee_1 = PrivateKey::from_der(include_bytes!(
+            "../../test/data/cms/2.16.840.1.101.3.4.4.1_MlKem512_priv.der"
+        ))
+        .expect("Failed to create PrivateKey");
+        // A composite recipient, whose encapsulation includes an ephemeral EC key
+        let pki = Pki::generate(DsaAlgorithm::MlDsa44, KemAlgorithm::MlKem768P384, 1).unwrap();
+        let (cert_ee_2, sk_ee_2) = &pki.leaves[0];
+
+        let build = |cea_type: CeaType, seed: Option<[u8; 32]>| {
+            let is_auth = cea_type == CeaType::Aes256Gcm;
+            let mut builder = EnvelopedDataBuilder::new(cea_type, is_auth).unwrap();
+            builder
+                .content(&plaintext)
+                .unwrap()
+                .kem_recipient(
+                    &cert_ee_1,
+                    &KdfType::HkdfWithSha256,
+                    &WrapType::Aes256,
+                    None,
+                )
+                .unwrap()
+                .kem_recipient(cert_ee_2, &KdfType::Kmac256, &WrapType::Aes128, None)
+                .unwrap();
+            if let Some(seed) = seed {
+                builder.deterministic(seed).unwrap();
+            }
+            builder.build().unwrap()
+        };
+
+        for cea_type in [CeaType::Aes256CbcPad, CeaType::Aes256Gcm] {
+            let first = build(cea_type.clone(), Some([1u8; 32]));
+            assert_eq!(first, build(cea_type.clone(), Some([1u8; 32])));
+            assert_ne!(first, build(cea_type.clone(), Some([2u8; 32])));
+            assert_ne!(build(cea_type.clone(), None), build(cea_type.clone(), None));
+
+            let pt =
+                crate::cms::cms_util::CmsUtil::decrypt_kemri(&first, &sk_ee_1, &cert_ee_1).unwrap();
+            assert_eq!(pt, plaintext);
+            let pt =
+                crate::cms::cms_util::CmsUtil::decrypt_kemri(&first, sk_ee_2, cert_ee_2).unwrap();
+            assert_eq!(pt, plaintext);
+        }
+    }
+
     // #[test]
     // fn gen_cms_test_data() {
     //     // Generate a TA key pair
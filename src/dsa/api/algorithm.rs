@@ -1,4 +1,5 @@
 use crate::dsa::common::{config::oids::Oid, dsa_type::DsaType};
+use crate::utils::oid_map::OidMap;
 
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
@@ -96,10 +97,7 @@ impl DsaAlgorithm {
     ///
     /// True if the algorithm is a composite algorithm, false otherwise
     pub fn is_composite(&self) -> bool {
-        !matches!(
-            self,
-            DsaAlgorithm::MlDsa44 | DsaAlgorithm::MlDsa65 | DsaAlgorithm::MlDsa87
-        )
+        self.get_dsa_type().is_composite()
     }
 
     /// Get the OID for the algorithm
@@ -121,9 +119,7 @@ impl DsaAlgorithm {
     ///
     /// The DSA algorithm or None if the OID is not found
     pub fn from_oid(oid: &str) -> Option<DsaAlgorithm> {
-        DsaAlgorithm::all()
-            .iter()
-            .find(|x| x.get_oid() == oid)
-            .cloned()
+        static OIDS: OidMap<DsaAlgorithm> = OidMap::new();
+        OIDS.get(oid, DsaAlgorithm::all, DsaAlgorithm::get_oid)
     }
 }
@@ -3,6 +3,8 @@ use crate::asn1::public_key::PublicKey;
 use crate::dsa::common::config::oids::Oid;
 use crate::dsa::{api::algorithm::DsaAlgorithm, common::dsa_trait::Dsa, dsa_manager::DsaManager};
 use crate::errors;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
 type Result<T> = std::result::Result<T, errors::QubitCryptError>;
 
@@ -54,6 +56,60 @@ impl DsaKeyGenerator {
         let sk = PrivateKey::new(&oid, &sk)?;
         Ok((pk, sk))
     }
+
+    /// Generate `n` keypairs using the default RNG
+    ///
+    /// The keypairs share one manager, instead of creating one per call of `generate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn generate_batch(&mut self, n: usize) -> Result<Vec<(PublicKey, PrivateKey)>> {
+        let mut dsa_manager = self.new_manager()?;
+        let keys = dsa_manager.key_gen_batch(n)?;
+        self.to_key_pairs(keys)
+    }
+
+    /// Generate `n` keypairs from a seed
+    ///
+    /// The same seed always gives the same keypairs in the same order, e.g. for reproducible
+    /// test fixtures. The seed must be kept as secret as the private keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    /// * `seed` - The seed of the RNG
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn generate_batch_from_seed(
+        &mut self,
+        n: usize,
+        seed: [u8; 32],
+    ) -> Result<Vec<(PublicKey, PrivateKey)>> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let mut dsa_manager = self.new_manager()?;
+        let keys = dsa_manager.key_gen_batch_with_rng(n, &mut rng)?;
+        self.to_key_pairs(keys)
+    }
+
+    /// Create the manager of the algorithm
+    fn new_manager(&self) -> Result<DsaManager> {
+        DsaManager::new(self.algorithm.get_dsa_type())
+    }
+
+    /// Wrap raw keypairs in the public and private key types
+    fn to_key_pairs(&self, keys: Vec<(Vec<u8>, Vec<u8>)>) -> Result<Vec<(PublicKey, PrivateKey)>> {
+        let oid = self.algorithm.get_dsa_type().get_oid();
+        keys.iter()
+            .map(|(pk, sk)| Ok((PublicKey::new(&oid, pk)?, PrivateKey::new(&oid, sk)?)))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -132,4 +188,35 @@ mod test {
         let sig = sk.sign(msg).unwrap();
         assert!(pk.verify(msg, &sig).unwrap());
     }
+
+    #[test]
+    fn test_generate_batch() {
+        let mut key_generator = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44);
+        let keys = key_generator.generate_batch(3).unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_ne!(keys[0].0.get_key(), keys[1].0.get_key());
+        let msg = b"Hello, world!";
+        for (pk, sk) in &keys {
+            assert_eq!(pk.get_oid(), DsaAlgorithm::MlDsa44.get_oid());
+            assert!(pk.verify(msg, &sk.sign(msg).unwrap()).unwrap());
+        }
+        assert!(key_generator.generate_batch(0).unwrap().is_empty());
+
+        // A seed gives the same keys in the same order
+        let first = key_generator
+            .generate_batch_from_seed(3, [1u8; 32])
+            .unwrap();
+        let second = key_generator
+            .generate_batch_from_seed(2, [1u8; 32])
+            .unwrap();
+        for ((pk_1, sk_1), (pk_2, sk_2)) in first.iter().zip(&second) {
+            assert_eq!(pk_1.get_key(), pk_2.get_key());
+            assert_eq!(sk_1.get_key(), sk_2.get_key());
+        }
+        assert_ne!(first[0].0.get_key(), first[1].0.get_key());
+        let other = key_generator
+            .generate_batch_from_seed(1, [2u8; 32])
+            .unwrap();
+        assert_ne!(other[0].0.get_key(), first[0].0.get_key());
+    }
 }
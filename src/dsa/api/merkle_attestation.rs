@@ -0,0 +1,257 @@
+use sha2::{Digest, Sha256};
+
+use crate::asn1::private_key::PrivateKey;
+use crate::asn1::public_key::PublicKey;
+use crate::errors::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The prefix of the message which is signed, binding the signature to this construction
+const ROOT_SIG_LABEL: &[u8] = b"qubitcrypt merkle attestation v1";
+
+/// The domain separation prefix of a leaf hash, as in RFC 6962
+const LEAF_PREFIX: u8 = 0x00;
+
+/// The domain separation prefix of an interior node hash, as in RFC 6962
+const NODE_PREFIX: u8 = 0x01;
+
+/// A proof that a document hash is included in a signed Merkle tree
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleProof {
+    /// The index of the document hash in the list the tree was built from
+    pub leaf_index: usize,
+    /// The number of document hashes the tree was built from
+    pub leaf_count: usize,
+    /// The sibling hashes from the leaf up to the root
+    pub path: Vec<[u8; 32]>,
+}
+
+/// Attest to many documents with a single signature
+///
+/// A Merkle tree is built over the document hashes and only its root is signed, so the cost of
+/// the signature is shared by all the documents. Each document gets an inclusion proof, which
+/// together with the root signature is enough to verify that document on its own.
+///
+/// The tree hashes leaves and interior nodes with SHA-256 and distinct prefixes as in RFC 6962,
+/// and the signature covers the number of leaves as well as the root.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::dsas::{DsaAlgorithm, DsaKeyGenerator, MerkleAttestation};
+///
+/// let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44).generate().unwrap();
+/// let hashes = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+///
+/// let (root_sig, proofs) = MerkleAttestation::build(&hashes, &sk).unwrap();
+/// assert!(MerkleAttestation::verify(&hashes[1], &proofs[1], &root_sig, &pk).unwrap());
+/// assert!(!MerkleAttestation::verify(&hashes[0], &proofs[1], &root_sig, &pk).unwrap());
+/// ```
+pub struct MerkleAttestation;
+
+impl MerkleAttestation {
+    /// Build a Merkle tree over the document hashes and sign its root
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - The document hashes
+    /// * `signer_sk` - The private key used to sign the root
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the signature of the root and an inclusion proof for each document
+    /// hash, in the same order as the hashes (root_sig, proofs)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::EmptyContent` will be returned if there are no hashes,
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the key is not a DSA key
+    pub fn build<H: AsRef<[u8]>>(
+        hashes: &[H],
+        signer_sk: &PrivateKey,
+    ) -> Result<(Vec<u8>, Vec<MerkleProof>)> {
+        if hashes.is_empty() {
+            return Err(QubitCryptError::EmptyContent);
+        }
+
+        let leaf_count = hashes.len();
+        let mut proofs: Vec<MerkleProof> = (0..leaf_count)
+            .map(|leaf_index| MerkleProof {
+                leaf_index,
+                leaf_count,
+                path: Vec::new(),
+            })
+            .collect();
+
+        // The position of each leaf's ancestor in the current level
+        let mut positions: Vec<usize> = (0..leaf_count).collect();
+        let mut level: Vec<[u8; 32]> = hashes.iter().map(|h| leaf_hash(h.as_ref())).collect();
+        while level.len() > 1 {
+            for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+                let sibling = *position ^ 1;
+                if sibling < level.len() {
+                    proof.path.push(level[sibling]);
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    // An odd node out is promoted to the next level unchanged
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        let root_sig = signer_sk.sign(&root_message(leaf_count, &level[0]))?;
+        Ok((root_sig, proofs))
+    }
+
+    /// Verify that a document hash is included in a Merkle tree with a valid root signature
+    ///
+    /// # Arguments
+    ///
+    /// * `doc_hash` - The document hash
+    /// * `proof` - The inclusion proof of the document hash
+    /// * `root_sig` - The signature of the root
+    /// * `signer_pk` - The public key of the signer
+    ///
+    /// # Returns
+    ///
+    /// True if the root computed from the document hash and the proof has a valid signature,
+    /// false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the key is not a DSA key
+    pub fn verify(
+        doc_hash: &[u8],
+        proof: &MerkleProof,
+        root_sig: &[u8],
+        signer_pk: &PublicKey,
+    ) -> Result<bool> {
+        if proof.leaf_index >= proof.leaf_count {
+            return Ok(false);
+        }
+
+        let mut path = proof.path.iter();
+        let mut node = leaf_hash(doc_hash);
+        let mut position = proof.leaf_index;
+        let mut level_len = proof.leaf_count;
+        while level_len > 1 {
+            let has_sibling = (position ^ 1) < level_len;
+            if has_sibling {
+                let sibling = match path.next() {
+                    Some(sibling) => sibling,
+                    None => return Ok(false),
+                };
+                node = if position % 2 == 0 {
+                    node_hash(&node, sibling)
+                } else {
+                    node_hash(sibling, &node)
+                };
+            }
+            position /= 2;
+            level_len = level_len.div_ceil(2);
+        }
+        if path.next().is_some() {
+            return Ok(false);
+        }
+
+        signer_pk.verify(&root_message(proof.leaf_count, &node), root_sig)
+    }
+}
+
+/// Hash a document hash into a leaf of the tree
+fn leaf_hash(doc_hash: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(doc_hash);
+    hasher.finalize().into()
+}
+
+/// Hash two child nodes into their parent
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Get the message which is signed for a tree
+fn root_message(leaf_count: usize, root: &[u8; 32]) -> Vec<u8> {
+    let mut msg = ROOT_SIG_LABEL.to_vec();
+    msg.extend_from_slice(&(leaf_count as u64).to_be_bytes());
+    msg.extend_from_slice(root);
+    msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    #[test]
+    fn test_merkle_attestation() {
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let (other_pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+
+        // Power of two, odd and single leaf trees
+        for count in [1usize, 2, 5, 8, 13] {
+            let hashes: Vec<[u8; 32]> = (0..count)
+                .map(|i| Sha256::digest(i.to_be_bytes()).into())
+                .collect();
+            let (root_sig, proofs) = MerkleAttestation::build(&hashes, &sk).unwrap();
+            assert_eq!(proofs.len(), count);
+
+            for (i, proof) in proofs.iter().enumerate() {
+                assert!(MerkleAttestation::verify(&hashes[i], proof, &root_sig, &pk).unwrap());
+                assert!(
+                    !MerkleAttestation::verify(&hashes[i], proof, &root_sig, &other_pk).unwrap()
+                );
+                assert!(!MerkleAttestation::verify(b"not a leaf", proof, &root_sig, &pk).unwrap());
+
+                // The proof of a different leaf
+                if count > 1 {
+                    let other = &proofs[(i + 1) % count];
+                    assert!(!MerkleAttestation::verify(&hashes[i], other, &root_sig, &pk).unwrap());
+                }
+
+                // A tampered proof
+                let mut bad = proof.clone();
+                bad.leaf_count += 1;
+                assert!(!MerkleAttestation::verify(&hashes[i], &bad, &root_sig, &pk).unwrap());
+                let mut bad = proof.clone();
+                bad.path.push([0u8; 32]);
+                assert!(!MerkleAttestation::verify(&hashes[i], &bad, &root_sig, &pk).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_attestation_errors() {
+        let (_, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let empty: Vec<[u8; 32]> = Vec::new();
+        assert!(matches!(
+            MerkleAttestation::build(&empty, &sk),
+            Err(QubitCryptError::EmptyContent)
+        ));
+
+        let (_, sk_kem) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        assert!(matches!(
+            MerkleAttestation::build(&[[0u8; 32]], &sk_kem),
+            Err(QubitCryptError::UnsupportedOperation)
+        ));
+    }
+}
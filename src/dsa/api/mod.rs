@@ -1,2 +1,3 @@
 pub mod algorithm;
 pub mod key_generator;
+pub mod merkle_attestation;
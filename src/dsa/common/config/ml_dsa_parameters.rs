@@ -0,0 +1,91 @@
+use crate::dsa::common::dsa_type::DsaType;
+
+/// The parameters of an ML-DSA parameter set, as listed in FIPS 204, Table 1
+///
+/// The modulus q = 8380417 and the number of dropped bits d = 13 are the same for all
+/// parameter sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MlDsaParameters {
+    /// The number of rows of the matrix A
+    pub k: usize,
+    /// The number of columns of the matrix A
+    pub l: usize,
+    /// The bound of the coefficients of the private key vectors s1 and s2
+    pub eta: u32,
+    /// The number of ±1 coefficients of the challenge polynomial
+    pub tau: u32,
+    /// The collision strength of the commitment hash c̃ in bits
+    pub lambda: u32,
+    /// The coefficient range of the masking vector y
+    pub gamma1: u32,
+    /// The low-order rounding range
+    pub gamma2: u32,
+    /// The bound τ · η on the coefficients of c · s1 and c · s2
+    pub beta: u32,
+    /// The maximum number of ones in the hint
+    pub omega: u32,
+    /// The NIST security category claimed for the parameter set
+    pub security_category: u8,
+}
+
+/// A trait to get the ML-DSA parameters of a DSA
+pub trait MlDsaParams {
+    /// Get the ML-DSA parameters
+    ///
+    /// # Returns
+    ///
+    /// The parameters, or `None` if the DSA is not ML-DSA
+    fn get_ml_dsa_parameters(&self) -> Option<MlDsaParameters>;
+}
+
+impl MlDsaParams for DsaType {
+    /// Get the ML-DSA parameters
+    ///
+    /// Composite DSAs return `None`, the parameters of their ML-DSA component can be looked up
+    /// with the ML-DSA type.
+    ///
+    /// # Returns
+    ///
+    /// The parameters, or `None` if the DSA is not ML-DSA
+    fn get_ml_dsa_parameters(&self) -> Option<MlDsaParameters> {
+        match self {
+            DsaType::MlDsa44 => Some(MlDsaParameters {
+                k: 4,
+                l: 4,
+                eta: 2,
+                tau: 39,
+                lambda: 128,
+                gamma1: 1 << 17,
+                gamma2: (8380417 - 1) / 88,
+                beta: 78,
+                omega: 80,
+                security_category: 2,
+            }),
+            DsaType::MlDsa65 => Some(MlDsaParameters {
+                k: 6,
+                l: 5,
+                eta: 4,
+                tau: 49,
+                lambda: 192,
+                gamma1: 1 << 19,
+                gamma2: (8380417 - 1) / 32,
+                beta: 196,
+                omega: 55,
+                security_category: 3,
+            }),
+            DsaType::MlDsa87 => Some(MlDsaParameters {
+                k: 8,
+                l: 7,
+                eta: 2,
+                tau: 60,
+                lambda: 256,
+                gamma1: 1 << 19,
+                gamma2: (8380417 - 1) / 32,
+                beta: 120,
+                omega: 75,
+                security_category: 5,
+            }),
+            _ => None,
+        }
+    }
+}
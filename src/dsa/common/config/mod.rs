@@ -1,4 +1,6 @@
+pub mod ml_dsa_parameters;
 pub mod oids;
 pub mod pk_len;
+pub mod security_category;
 pub mod sig_len;
 pub mod sk_len;
@@ -0,0 +1,75 @@
+use crate::dsa::common::dsa_type::DsaType;
+
+/// A trait to get the NIST security category of a DSA
+pub trait SecurityCategory {
+    /// Get the NIST security category
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    fn get_security_category(&self) -> u8;
+}
+
+impl SecurityCategory for DsaType {
+    /// Get the NIST security category
+    ///
+    /// NIST categories describe the security against a quantum adversary, so traditional
+    /// algorithms are given 0 whatever their classical strength. Composite algorithms are given
+    /// the category of their post-quantum component.
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    fn get_security_category(&self) -> u8 {
+        match self {
+            // No security against a quantum adversary
+            DsaType::Rsa2048PssSHA256 => 0,
+            DsaType::Rsa2048Pkcs15SHA256 => 0,
+            DsaType::Rsa3072PssSHA512 => 0,
+            DsaType::Rsa3072Pkcs15SHA512 => 0,
+
+            DsaType::EcdsaP256SHA256 => 0,
+            DsaType::EcdsaP256SHA512 => 0,
+            DsaType::EcdsaP384SHA512 => 0,
+            DsaType::EcdsaBrainpoolP256r1SHA512 => 0,
+            DsaType::EcdsaBrainpoolP256r1SHA256 => 0,
+            DsaType::EcdsaBrainpoolP384r1SHA512 => 0,
+            DsaType::Ed25519SHA512 => 0,
+            DsaType::Ed448SHA512 => 0,
+
+            DsaType::MlDsa44 => 2,
+            DsaType::MlDsa65 => 3,
+            DsaType::MlDsa87 => 5,
+
+            // A composite has the category of its post-quantum component
+            DsaType::MlDsa44Rsa2048PssSha256
+            | DsaType::MlDsa44Rsa2048Pkcs15Sha256
+            | DsaType::MlDsa44Ed25519SHA512
+            | DsaType::MlDsa44EcdsaP256SHA256
+            | DsaType::MlDsa44EcdsaBrainpoolP256r1SHA256
+            | DsaType::MlDsa65Rsa3072PssSHA512
+            | DsaType::MlDsa65Rsa3072Pkcs15SHA512
+            | DsaType::MlDsa65EcdsaP256SHA512
+            | DsaType::MlDsa65EcdsaBrainpoolP256r1SHA512
+            | DsaType::MlDsa65Ed25519SHA512
+            | DsaType::MlDsa87EcdsaP384SHA512
+            | DsaType::MlDsa87EcdsaBrainpoolP384r1SHA512
+            | DsaType::MlDsa87Ed448SHA512 => self
+                .get_pq_type()
+                .map_or(0, |pq_type| pq_type.get_security_category()),
+
+            DsaType::SlhDsaSha2_128s => 1,
+            DsaType::SlhDsaSha2_128f => 1,
+            DsaType::SlhDsaSha2_192s => 3,
+            DsaType::SlhDsaSha2_192f => 3,
+            DsaType::SlhDsaSha2_256s => 5,
+            DsaType::SlhDsaSha2_256f => 5,
+            DsaType::SlhDsaShake128s => 1,
+            DsaType::SlhDsaShake128f => 1,
+            DsaType::SlhDsaShake192s => 3,
+            DsaType::SlhDsaShake192f => 3,
+            DsaType::SlhDsaShake256s => 5,
+            DsaType::SlhDsaShake256f => 5,
+        }
+    }
+}
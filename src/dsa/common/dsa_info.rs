@@ -1,5 +1,8 @@
+use crate::dsa::api::algorithm::DsaAlgorithm;
+use crate::dsa::common::config::ml_dsa_parameters::{MlDsaParameters, MlDsaParams};
 use crate::dsa::common::config::oids::Oid;
 use crate::dsa::common::config::pk_len::PKLen;
+use crate::dsa::common::config::security_category::SecurityCategory;
 use crate::dsa::common::config::sig_len::SigLen;
 use crate::dsa::common::config::sk_len::SKLen;
 use crate::dsa::common::dsa_type::DsaType;
@@ -20,6 +23,8 @@ pub struct DsaInfo {
     pub sig_byte_len: Option<usize>,
     /// The OID of the DSA
     pub oid: String,
+    /// The parameters of the parameter set (only for ML-DSA, otherwise `None`)
+    ml_dsa_parameters: Option<MlDsaParameters>,
 }
 
 impl DsaInfo {
@@ -37,12 +42,59 @@ impl DsaInfo {
         let sk_byte_len = dsa_type.get_sk_len();
         let sig_byte_len = dsa_type.get_sig_len();
         let oid = dsa_type.get_oid();
+        let ml_dsa_parameters = dsa_type.get_ml_dsa_parameters();
         DsaInfo {
             dsa_type,
             pk_byte_len,
             sk_byte_len,
             sig_byte_len,
             oid,
+            ml_dsa_parameters,
         }
     }
+
+    /// Create the metadata of a custom DSA registered with `register_dsa`
+    ///
+    /// The metadata is that of a built-in DSA with the OID of the custom DSA, so a variant of a
+    /// built-in DSA only needs to change the fields which differ. The DSA type, and so the
+    /// security category, stays that of the built-in DSA.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The built-in DSA the custom DSA is based on
+    /// * `oid` - The OID of the custom DSA
+    ///
+    /// # Returns
+    ///
+    /// The metadata of the custom DSA
+    pub fn new_custom(base: DsaAlgorithm, oid: &str) -> DsaInfo {
+        DsaInfo {
+            oid: oid.to_string(),
+            ..DsaInfo::new(base.get_dsa_type())
+        }
+    }
+
+    /// Get the NIST security category of the DSA
+    ///
+    /// Traditional DSAs have no post-quantum security and are given 0, composite DSAs the
+    /// category of their ML-DSA component.
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    pub fn security_category(&self) -> u8 {
+        self.dsa_type.get_security_category()
+    }
+
+    /// Get the parameters of an ML-DSA parameter set
+    ///
+    /// These are fixed per parameter set (FIPS 204, Table 1) and are informational, e.g. for an
+    /// inventory of the algorithms in use.
+    ///
+    /// # Returns
+    ///
+    /// The parameters, or `None` if the DSA is not ML-DSA
+    pub fn parameters(&self) -> Option<MlDsaParameters> {
+        self.ml_dsa_parameters
+    }
 }
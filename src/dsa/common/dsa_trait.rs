@@ -88,6 +88,56 @@ pub trait Dsa {
     /// A boolean indicating if the signature is valid
     fn verify(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool>;
 
+    /// Sign a message with an application context string (FIPS 204, Section 5.2)
+    ///
+    /// The context binds the signature to an application or protocol, so a signature made
+    /// with one context doesn't verify with another. `sign` signs with the empty context.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the message
+    /// * `msg` - The message to sign
+    /// * `ctx` - The context string, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// The signature of the message
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the DSA has no context
+    /// strings, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    fn sign_with_context(&self, _sk: &[u8], _msg: &[u8], _ctx: &[u8]) -> Result<Vec<u8>> {
+        Err(QubitCryptError::UnsupportedOperation)
+    }
+
+    /// Verify a signature made with an application context string, see `sign_with_context`
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `msg` - The message to verify
+    /// * `signature` - The signature to verify
+    /// * `ctx` - The context string the message was signed with, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the DSA has no context
+    /// strings, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    fn verify_with_context(
+        &self,
+        _pk: &[u8],
+        _msg: &[u8],
+        _signature: &[u8],
+        _ctx: &[u8],
+    ) -> Result<bool> {
+        Err(QubitCryptError::UnsupportedOperation)
+    }
+
     /// Get DSA metadata information such as the key lengths,
     /// size of signature, etc.
     ///
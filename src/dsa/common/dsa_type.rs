@@ -2,6 +2,7 @@ use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use super::config::oids::Oid;
+use crate::utils::oid_map::OidMap;
 
 #[derive(Clone, Debug, PartialEq, EnumIter)]
 pub enum DsaType {
@@ -62,13 +63,50 @@ impl DsaType {
     }
 
     pub fn is_composite(&self) -> bool {
-        !matches!(self, DsaType::MlDsa44 | DsaType::MlDsa65 | DsaType::MlDsa87)
+        matches!(
+            self,
+            DsaType::MlDsa44Rsa2048PssSha256
+                | DsaType::MlDsa44Rsa2048Pkcs15Sha256
+                | DsaType::MlDsa44Ed25519SHA512
+                | DsaType::MlDsa44EcdsaP256SHA256
+                | DsaType::MlDsa44EcdsaBrainpoolP256r1SHA256
+                | DsaType::MlDsa65Rsa3072PssSHA512
+                | DsaType::MlDsa65Rsa3072Pkcs15SHA512
+                | DsaType::MlDsa65EcdsaP256SHA512
+                | DsaType::MlDsa65EcdsaBrainpoolP256r1SHA512
+                | DsaType::MlDsa65Ed25519SHA512
+                | DsaType::MlDsa87EcdsaP384SHA512
+                | DsaType::MlDsa87EcdsaBrainpoolP384r1SHA512
+                | DsaType::MlDsa87Ed448SHA512
+        )
+    }
+
+    /// Get the post-quantum component of a composite DSA
+    ///
+    /// # Returns
+    ///
+    /// The ML-DSA type of the composite, or None if the type is not a composite
+    pub fn get_pq_type(&self) -> Option<DsaType> {
+        match self {
+            DsaType::MlDsa44Rsa2048PssSha256
+            | DsaType::MlDsa44Rsa2048Pkcs15Sha256
+            | DsaType::MlDsa44Ed25519SHA512
+            | DsaType::MlDsa44EcdsaP256SHA256
+            | DsaType::MlDsa44EcdsaBrainpoolP256r1SHA256 => Some(DsaType::MlDsa44),
+            DsaType::MlDsa65Rsa3072PssSHA512
+            | DsaType::MlDsa65Rsa3072Pkcs15SHA512
+            | DsaType::MlDsa65EcdsaP256SHA512
+            | DsaType::MlDsa65EcdsaBrainpoolP256r1SHA512
+            | DsaType::MlDsa65Ed25519SHA512 => Some(DsaType::MlDsa65),
+            DsaType::MlDsa87EcdsaP384SHA512
+            | DsaType::MlDsa87EcdsaBrainpoolP384r1SHA512
+            | DsaType::MlDsa87Ed448SHA512 => Some(DsaType::MlDsa87),
+            _ => None,
+        }
     }
 
     pub fn from_oid(oid: &str) -> Option<DsaType> {
-        let all_dsa_types = DsaType::all();
-        all_dsa_types
-            .into_iter()
-            .find(|dsa_type| dsa_type.get_oid() == oid)
+        static OIDS: OidMap<DsaType> = OidMap::new();
+        OIDS.get(oid, DsaType::all, DsaType::get_oid)
     }
 }
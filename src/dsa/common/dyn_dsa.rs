@@ -0,0 +1,333 @@
+use rand_core::CryptoRngCore;
+
+use crate::dsa::common::dsa_info::DsaInfo;
+use crate::dsa::common::dsa_trait::Dsa;
+use crate::dsa::common::sig_encoding::SigEncoding;
+use crate::dsa::dsa_manager::DsaManager;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// An object-safe version of the DSA trait.
+///
+/// The `Dsa` trait has constructors which require `Self: Sized` and a generic RNG
+/// parameter, so it cannot be used as a trait object. This trait exposes the same
+/// operations without the constructors, so that an algorithm selected at runtime
+/// can be held as a `Box<dyn DynDsa>`. Use `dyn_dsa_from_oid` to create one.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::dsas::{dyn_dsa_from_oid, DsaAlgorithm, DynDsa};
+///
+/// let mut dsa: Box<dyn DynDsa> = dyn_dsa_from_oid(&DsaAlgorithm::MlDsa44.get_oid()).unwrap();
+/// let (pk, sk) = dsa.key_gen().unwrap();
+/// let sig = dsa.sign(&sk, b"message").unwrap();
+/// assert!(dsa.verify(&pk, b"message", &sig).unwrap());
+/// ```
+pub trait DynDsa {
+    /// Generate a keypair using the default RNG
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the public and secret keys (pk, sk).
+    fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)>;
+
+    /// Generate a keypair using the specified RNG
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - A random number generator
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the public and secret keys (pk, sk).
+    fn key_gen_with_rng(&mut self, rng: &mut dyn CryptoRngCore) -> Result<(Vec<u8>, Vec<u8>)>;
+
+    /// Sign a message
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the message
+    /// * `msg` - The message to sign
+    ///
+    /// # Returns
+    ///
+    /// The signature of the message
+    fn sign(&self, sk: &[u8], msg: &[u8]) -> Result<Vec<u8>>;
+
+    /// Verify a signature
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `msg` - The message to verify
+    /// * `signature` - The signature to verify
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    fn verify(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool>;
+
+    /// Get DSA metadata information such as the key lengths,
+    /// size of signature, etc.
+    ///
+    /// # Returns
+    ///
+    /// A structure containing metadata about the DSA
+    fn get_dsa_info(&self) -> DsaInfo;
+
+    /// Get the public key from a secret key
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key
+    ///
+    /// # Returns
+    ///
+    /// The public key
+    fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>>;
+
+    /// Sign a message and encode the signature
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the message
+    /// * `msg` - The message to sign
+    /// * `encoding` - The encoding of the signature
+    ///
+    /// # Returns
+    ///
+    /// The encoded signature of the message
+    fn sign_wrapped(&self, sk: &[u8], msg: &[u8], encoding: SigEncoding) -> Result<Vec<u8>> {
+        encoding.encode(self.sign(sk, msg)?)
+    }
+
+    /// Verify a signature which is either raw or wrapped in an OCTET STRING
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `msg` - The message to verify
+    /// * `signature` - The signature to verify, in either encoding
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    fn verify_wrapped(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
+        let sig = SigEncoding::unwrap_signature(signature, self.get_dsa_info().sig_byte_len);
+        self.verify(pk, msg, sig)
+    }
+
+    /// Sign the digest of a message with the prehash variant of the DSA, e.g. HashML-DSA or
+    /// Ed25519ph
+    ///
+    /// The default is for a DSA without a prehash variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the digest
+    /// * `digest` - The digest of the message
+    /// * `hash_oid` - The OID of the hash function the digest was computed with
+    /// * `ctx` - The context string, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// The signature of the message
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the DSA has no prehash variant
+    fn sign_prehashed(
+        &self,
+        _sk: &[u8],
+        _digest: &[u8],
+        _hash_oid: &str,
+        _ctx: &[u8],
+    ) -> Result<Vec<u8>> {
+        Err(QubitCryptError::UnsupportedOperation)
+    }
+
+    /// Verify a signature made with the prehash variant of the DSA
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `digest` - The digest of the message
+    /// * `hash_oid` - The OID of the hash function the digest was computed with
+    /// * `signature` - The signature to verify
+    /// * `ctx` - The context string the digest was signed with
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the DSA has no prehash variant
+    fn verify_prehashed(
+        &self,
+        _pk: &[u8],
+        _digest: &[u8],
+        _hash_oid: &str,
+        _signature: &[u8],
+        _ctx: &[u8],
+    ) -> Result<bool> {
+        Err(QubitCryptError::UnsupportedOperation)
+    }
+}
+
+impl DynDsa for DsaManager {
+    fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+        Dsa::key_gen(self)
+    }
+
+    fn key_gen_with_rng(&mut self, mut rng: &mut dyn CryptoRngCore) -> Result<(Vec<u8>, Vec<u8>)> {
+        Dsa::key_gen_with_rng(self, &mut rng)
+    }
+
+    fn sign(&self, sk: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
+        Dsa::sign(self, sk, msg)
+    }
+
+    fn verify(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
+        Dsa::verify(self, pk, msg, signature)
+    }
+
+    fn get_dsa_info(&self) -> DsaInfo {
+        Dsa::get_dsa_info(self)
+    }
+
+    fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        Dsa::get_public_key(self, sk)
+    }
+
+    fn sign_wrapped(&self, sk: &[u8], msg: &[u8], encoding: SigEncoding) -> Result<Vec<u8>> {
+        DsaManager::sign_wrapped(self, sk, msg, encoding)
+    }
+
+    fn verify_wrapped(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
+        DsaManager::verify_wrapped(self, pk, msg, signature)
+    }
+
+    fn sign_prehashed(
+        &self,
+        sk: &[u8],
+        digest: &[u8],
+        hash_oid: &str,
+        ctx: &[u8],
+    ) -> Result<Vec<u8>> {
+        DsaManager::sign_prehashed(self, sk, digest, hash_oid, ctx)
+    }
+
+    fn verify_prehashed(
+        &self,
+        pk: &[u8],
+        digest: &[u8],
+        hash_oid: &str,
+        signature: &[u8],
+        ctx: &[u8],
+    ) -> Result<bool> {
+        DsaManager::verify_prehashed(self, pk, digest, hash_oid, signature, ctx)
+    }
+}
+
+/// Create a DSA trait object from an OID
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the DSA
+///
+/// # Returns
+///
+/// A boxed DSA instance
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidOid` will be returned if the OID is not a known DSA OID
+pub fn dyn_dsa_from_oid(oid: &str) -> Result<Box<dyn DynDsa>> {
+    let dsa = DsaManager::new_from_oid(oid)?;
+    Ok(Box::new(dsa))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::dsa::common::config::oids::Oid;
+    use crate::dsa::common::dsa_type::DsaType;
+
+    #[test]
+    fn test_dyn_dsa() {
+        let dsa_types = [
+            DsaType::MlDsa44,
+            DsaType::EcdsaP256SHA256,
+            DsaType::Ed25519SHA512,
+            DsaType::MlDsa44EcdsaP256SHA256,
+            DsaType::SlhDsaSha2_128f,
+        ];
+
+        let mut dsas: Vec<Box<dyn DynDsa>> = dsa_types
+            .iter()
+            .map(|dsa_type| dyn_dsa_from_oid(&dsa_type.get_oid()).unwrap())
+            .collect();
+
+        let msg = b"Hello, world!";
+        for (dsa, dsa_type) in dsas.iter_mut().zip(dsa_types.iter()) {
+            assert_eq!(dsa.get_dsa_info().dsa_type, *dsa_type);
+
+            let (pk, sk) = dsa.key_gen().unwrap();
+            assert_eq!(dsa.get_public_key(&sk).unwrap(), pk);
+
+            let sig = dsa.sign(&sk, msg).unwrap();
+            assert!(dsa.verify(&pk, msg, &sig).unwrap());
+            assert!(!dsa.verify(&pk, b"Goodbye, world!", &sig).unwrap_or(false));
+
+            let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+            let (pk, sk) = dsa.key_gen_with_rng(&mut rng).unwrap();
+            let sig = dsa.sign(&sk, msg).unwrap();
+            assert!(dsa.verify(&pk, msg, &sig).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_dyn_dsa_invalid_oid() {
+        let result = dyn_dsa_from_oid("1.2.3.4");
+        assert!(matches!(result, Err(QubitCryptError::InvalidOid)));
+    }
+
+    #[test]
+    fn test_dyn_dsa_prehashed() {
+        use sha2::{Digest, Sha512};
+
+        const SHA512: &str = "2.16.840.1.101.3.4.2.3";
+        let digest = Sha512::digest(b"large message");
+        let other = Sha512::digest(b"other message");
+
+        // Ed25519ph and HashML-DSA are reached through the same methods
+        for dsa_type in [DsaType::Ed25519SHA512, DsaType::MlDsa65] {
+            let mut dsa = dyn_dsa_from_oid(&dsa_type.get_oid()).unwrap();
+            let (pk, sk) = dsa.key_gen().unwrap();
+            let sig = dsa.sign_prehashed(&sk, &digest, SHA512, b"").unwrap();
+            assert!(dsa
+                .verify_prehashed(&pk, &digest, SHA512, &sig, b"")
+                .unwrap());
+            assert!(!dsa
+                .verify_prehashed(&pk, &other, SHA512, &sig, b"")
+                .unwrap());
+            assert!(!dsa.verify(&pk, b"large message", &sig).unwrap_or(false));
+        }
+
+        let mut dsa = dyn_dsa_from_oid(&DsaType::MlDsa44Ed25519SHA512.get_oid()).unwrap();
+        let (pk, sk) = dsa.key_gen().unwrap();
+        assert!(matches!(
+            dsa.sign_prehashed(&sk, &digest, SHA512, b""),
+            Err(QubitCryptError::UnsupportedOperation)
+        ));
+        assert!(matches!(
+            dsa.verify_prehashed(&pk, &digest, SHA512, &[0u8; 64], b""),
+            Err(QubitCryptError::UnsupportedOperation)
+        ));
+    }
+}
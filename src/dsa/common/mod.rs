@@ -2,4 +2,6 @@ pub mod config;
 pub mod dsa_info;
 pub mod dsa_trait;
 pub mod dsa_type;
+pub mod dyn_dsa;
 pub mod macros;
+pub mod sig_encoding;
@@ -0,0 +1,64 @@
+use der::asn1::OctetStringRef;
+use der::{Decode, Encode};
+
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The encoding of a signature produced by `sign_wrapped`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigEncoding {
+    /// The raw signature, as returned by `sign`
+    Raw,
+    /// The raw signature wrapped in a DER OCTET STRING
+    OctetString,
+}
+
+impl SigEncoding {
+    /// Encode a raw signature
+    ///
+    /// # Arguments
+    ///
+    /// * `sig` - The raw signature
+    ///
+    /// # Returns
+    ///
+    /// The encoded signature
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::SignatureFailed` will be returned if the signature can't be encoded
+    pub(crate) fn encode(&self, sig: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            SigEncoding::Raw => Ok(sig),
+            SigEncoding::OctetString => OctetStringRef::new(&sig)
+                .and_then(|os| os.to_der())
+                .map_err(|_| QubitCryptError::SignatureFailed),
+        }
+    }
+
+    /// Get the raw signature from a signature in either encoding
+    ///
+    /// A signature of exactly the fixed signature length of the DSA is raw. Otherwise a
+    /// signature which is a complete DER OCTET STRING, holding a signature of the fixed length
+    /// if there is one, is unwrapped. Raw signatures of the DSAs which have no fixed length are
+    /// DER SEQUENCEs, so they can't be mistaken for an OCTET STRING.
+    ///
+    /// # Arguments
+    ///
+    /// * `sig` - The signature
+    /// * `sig_byte_len` - The fixed length of the raw signature, if any
+    ///
+    /// # Returns
+    ///
+    /// The raw signature
+    pub(crate) fn unwrap_signature(sig: &[u8], sig_byte_len: Option<usize>) -> &[u8] {
+        if sig_byte_len == Some(sig.len()) {
+            return sig;
+        }
+        match OctetStringRef::from_der(sig) {
+            Ok(os) if sig_byte_len.map_or(true, |len| len == os.as_bytes().len()) => os.as_bytes(),
+            _ => sig,
+        }
+    }
+}
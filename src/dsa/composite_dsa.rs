@@ -1,6 +1,6 @@
 use crate::asn1::asn_util::oid_to_der;
 use crate::asn1::composite_private_key::CompositePrivateKey;
-use crate::asn1::composite_public_key::CompositePublicKey;
+use crate::asn1::composite_public_key::{CompositeComponent, CompositePublicKey};
 use crate::dsa::asn1::composite_dsa_primitives::CompositeSignatureValue;
 use crate::dsa::common::dsa_info::DsaInfo;
 use crate::dsa::dsa_manager::DsaManager;
@@ -123,6 +123,122 @@ impl CompositeDsaManager {
     }
 }
 
+impl CompositeDsaManager {
+    /// Check that the components of a composite private key belong together
+    ///
+    /// The OID and the length of each component is checked against the component algorithms
+    /// and the public key is derived from each component. The derived public keys are compared
+    /// with the public keys embedded in the components, if any, and with `c_pk`, if provided.
+    ///
+    /// # Arguments
+    ///
+    /// * `c_sk` - The composite private key
+    /// * `c_pk` - The composite public key which should belong to the private key, if any
+    ///
+    /// # Returns
+    ///
+    /// True if the composite private key is consistent, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if a component can't be decoded
+    pub(crate) fn validate_private_key(
+        &self,
+        c_sk: &CompositePrivateKey,
+        c_pk: Option<&CompositePublicKey>,
+    ) -> Result<bool> {
+        if c_sk.get_oid() != self.dsa_info.oid
+            || c_pk.is_some_and(|c_pk| c_pk.get_oid() != self.dsa_info.oid)
+        {
+            return Ok(false);
+        }
+
+        let pq_sk = c_sk.get_pq_sk()?;
+        let trad_sk = c_sk.get_trad_sk()?;
+        let expected_pq_pk = c_pk.map(|c_pk| c_pk.get_pq_pk());
+        let expected_trad_pk = c_pk.map(|c_pk| c_pk.get_trad_pk());
+
+        for (dsa, sk, expected_pk) in [
+            (&self.pq_dsa, &pq_sk, expected_pq_pk),
+            (&self.trad_dsa, &trad_sk, expected_trad_pk),
+        ] {
+            let info = dsa.get_dsa_info();
+            if sk.algorithm.oid.to_string() != info.oid
+                || info
+                    .sk_byte_len
+                    .is_some_and(|len| len != sk.private_key.len())
+            {
+                return Ok(false);
+            }
+
+            let pk = match dsa.get_public_key(sk.private_key) {
+                Ok(pk) => pk,
+                Err(_) => return Ok(false),
+            };
+            if sk
+                .public_key
+                .is_some_and(|embedded| embedded != pk.as_slice())
+                || expected_pk.is_some_and(|expected| expected != pk)
+            {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Verify one component of a composite signature
+    ///
+    /// # Security
+    ///
+    /// This only checks half of the composite signature, so it gives none of the protection
+    /// of the composite: a forgery of the verified component is accepted, whatever the other
+    /// component is. Verifying only the traditional component is not quantum-safe, and
+    /// verifying only the post-quantum component relies on ML-DSA alone. This is meant for
+    /// relying parties which have only one of the component keys during a migration, and
+    /// accept that risk. `verify` checks both components and should be used otherwise.
+    ///
+    /// The component is verified over the same message as in `verify`: the DER encoded OID of
+    /// the composite algorithm followed by the pre-hash of the message, so a component
+    /// signature which was not made as part of this composite doesn't verify.
+    ///
+    /// # Arguments
+    ///
+    /// * `which` - The component to verify
+    /// * `pk_component` - The public key of the component
+    /// * `msg` - The message
+    /// * `signature` - The composite signature
+    ///
+    /// # Returns
+    ///
+    /// True if the component signature is valid, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidSignature` will be returned if the composite signature can't be
+    /// decoded, and the errors of the component DSA, e.g. for an invalid public key
+    pub fn verify_component(
+        &self,
+        which: CompositeComponent,
+        pk_component: &[u8],
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        let msg = self.pre_hash(msg)?;
+
+        let c_sig = CompositeSignatureValue::from_der(signature)
+            .map_err(|_| QubitCryptError::InvalidSignature)?;
+
+        match which {
+            CompositeComponent::Pq => self.pq_dsa.verify(pk_component, &msg, &c_sig.get_pq_sig()),
+            CompositeComponent::Trad => {
+                self.trad_dsa
+                    .verify(pk_component, &msg, &c_sig.get_trad_sig())
+            }
+        }
+    }
+}
+
 impl Dsa for CompositeDsaManager {
     fn new(dsa_type: super::common::dsa_type::DsaType) -> Result<Self>
     where
@@ -293,6 +409,57 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_verify_component() {
+        for dsa_type in [
+            DsaType::MlDsa44EcdsaP256SHA256,
+            DsaType::MlDsa65Ed25519SHA512,
+            DsaType::MlDsa44Rsa2048PssSha256,
+        ] {
+            let mut dsa = CompositeDsaManager::new(dsa_type.clone()).unwrap();
+            let (pk, sk) = dsa.key_gen().unwrap();
+            let msg = b"Hello, world!";
+            let sig = dsa.sign(&sk, msg).unwrap();
+
+            let c_pk = CompositePublicKey::from_der(&dsa.get_dsa_info().oid, &pk).unwrap();
+            let pq_pk = c_pk.get_pq_pk();
+            let trad_pk = c_pk.get_trad_pk();
+            for (which, pk) in [
+                (CompositeComponent::Pq, &pq_pk),
+                (CompositeComponent::Trad, &trad_pk),
+            ] {
+                assert!(dsa.verify_component(which, pk, msg, &sig).unwrap());
+                assert!(!dsa
+                    .verify_component(which, pk, b"Hello, World!", &sig)
+                    .unwrap());
+            }
+
+            // A component verifies even if the other half of the signature is broken
+            let c_sig = CompositeSignatureValue::from_der(&sig).unwrap();
+            let mut trad_sig = c_sig.get_trad_sig();
+            let last = trad_sig.len() - 1;
+            trad_sig[last] ^= 1;
+            let broken = CompositeSignatureValue::new(&c_sig.get_pq_sig(), &trad_sig)
+                .to_der()
+                .unwrap();
+            assert!(!dsa.verify(&pk, msg, &broken).unwrap_or(false));
+            assert!(dsa
+                .verify_component(CompositeComponent::Pq, &pq_pk, msg, &broken)
+                .unwrap());
+
+            // The component signs the domain separated message, not the message itself
+            let pq_dsa = DsaManager::new(DsaType::MlDsa44).unwrap();
+            if dsa_type == DsaType::MlDsa44EcdsaP256SHA256 {
+                assert!(!pq_dsa.verify(&pq_pk, msg, &c_sig.get_pq_sig()).unwrap());
+            }
+
+            assert_eq!(
+                dsa.verify_component(CompositeComponent::Pq, &pq_pk, msg, b"not der"),
+                Err(QubitCryptError::InvalidSignature)
+            );
+        }
+    }
+
     #[test]
     fn test_mldsa_44_rsa_2048_pss_sha256() {
         let dsa = CompositeDsaManager::new(DsaType::MlDsa44Rsa2048PssSha256);
@@ -1,11 +1,15 @@
+use std::io::Read;
+
 use rand_core::CryptoRngCore;
 
 use crate::dsa::common::dsa_trait::Dsa;
 use crate::dsa::common::dsa_type::DsaType;
+use crate::dsa::common::sig_encoding::SigEncoding;
 use crate::dsa::composite_dsa::CompositeDsaManager;
 use crate::dsa::ec_dsa::EcDsaManager;
 use crate::dsa::ml_dsa::MlDsaManager;
 use crate::dsa::rsa_dsa::RsaDsaManager;
+use crate::utils::registry::{self, CustomDsa};
 use crate::QubitCryptError;
 
 use crate::dsa::slh_dsa::SlhDsaManager;
@@ -77,6 +81,193 @@ pub enum DsaManager {
     Composite(CompositeDsaManager),
     /// SLH DSA manager
     Slh(SlhDsaManager),
+    /// A custom DSA registered with `register_dsa`
+    Custom(CustomDsa),
+}
+
+impl DsaManager {
+    /// Sign a message and encode the signature
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the message
+    /// * `msg` - The message to sign
+    /// * `encoding` - The encoding of the signature
+    ///
+    /// # Returns
+    ///
+    /// The encoded signature of the message
+    pub fn sign_wrapped(&self, sk: &[u8], msg: &[u8], encoding: SigEncoding) -> Result<Vec<u8>> {
+        encoding.encode(self.sign(sk, msg)?)
+    }
+
+    /// Verify a signature which is either raw or wrapped in an OCTET STRING
+    ///
+    /// The encoding is detected from the signature, see `SigEncoding::unwrap_signature`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `msg` - The message to verify
+    /// * `signature` - The signature to verify, in either encoding
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    pub fn verify_wrapped(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
+        let sig = SigEncoding::unwrap_signature(signature, self.get_dsa_info().sig_byte_len);
+        self.verify(pk, msg, sig)
+    }
+
+    /// Sign the digest of a message with the prehash variant of the DSA
+    ///
+    /// The message is hashed by the caller, e.g. while it's streamed from a file. ML-DSA signs
+    /// the digest with HashML-DSA, see `MlDsaManager::sign_prehashed`, and Ed25519 with
+    /// Ed25519ph, see `EcDsaManager::sign_prehashed`. The other DSAs have no supported prehash
+    /// variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the digest
+    /// * `digest` - The digest of the message
+    /// * `hash_oid` - The OID of the hash function, e.g. "2.16.840.1.101.3.4.2.3" for SHA-512
+    /// * `ctx` - The context string, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// The signature of the message
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the DSA has no prehash
+    /// variant, `QubitCryptError::InvalidOid` if the hash function can't be used with it,
+    /// `QubitCryptError::InvalidContent` if the digest doesn't have the length of the hash
+    /// function, `QubitCryptError::InvalidContext` if the context is longer than 255 bytes
+    pub fn sign_prehashed(
+        &self,
+        sk: &[u8],
+        digest: &[u8],
+        hash_oid: &str,
+        ctx: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            DsaManager::Ml(dsa) => dsa.sign_prehashed(sk, digest, hash_oid, ctx),
+            DsaManager::Ec(dsa) => dsa.sign_prehashed(sk, digest, hash_oid, ctx),
+            DsaManager::Custom(custom) => custom.get().sign_prehashed(sk, digest, hash_oid, ctx),
+            _ => Err(QubitCryptError::UnsupportedOperation),
+        }
+    }
+
+    /// Verify a signature made with the prehash variant of the DSA, see `sign_prehashed`
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `digest` - The digest of the message
+    /// * `hash_oid` - The OID of the hash function the digest was computed with
+    /// * `signature` - The signature to verify
+    /// * `ctx` - The context string the digest was signed with, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// The errors of `sign_prehashed`, `QubitCryptError::InvalidPublicKey` if the public key is
+    /// invalid
+    pub fn verify_prehashed(
+        &self,
+        pk: &[u8],
+        digest: &[u8],
+        hash_oid: &str,
+        signature: &[u8],
+        ctx: &[u8],
+    ) -> Result<bool> {
+        match self {
+            DsaManager::Ml(dsa) => dsa.verify_prehashed(pk, digest, hash_oid, signature, ctx),
+            DsaManager::Ec(dsa) => dsa.verify_prehashed(pk, digest, hash_oid, signature, ctx),
+            DsaManager::Custom(custom) => custom
+                .get()
+                .verify_prehashed(pk, digest, hash_oid, signature, ctx),
+            _ => Err(QubitCryptError::UnsupportedOperation),
+        }
+    }
+
+    /// Verify a signature over the content of a reader
+    ///
+    /// ECDSA and RSA hash the content in chunks as it's read. ML-DSA, SLH-DSA, EdDSA and
+    /// the composites need the whole message, as their implementations have no incremental
+    /// verification, so the content of the reader is read into memory first. Memory use grows
+    /// with the content without a limit, see `PublicKey::verify_reader`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `reader` - The reader of the message to verify
+    /// * `signature` - The signature to verify
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::FileReadError` will be returned if the reader fails
+    pub fn verify_reader(
+        &self,
+        pk: &[u8],
+        reader: &mut impl Read,
+        signature: &[u8],
+    ) -> Result<bool> {
+        match self {
+            DsaManager::Rsa(rsa) => rsa.verify_reader(pk, reader, signature),
+            DsaManager::Ec(ec) => ec.verify_reader(pk, reader, signature),
+            _ => {
+                let mut msg = Vec::new();
+                reader
+                    .read_to_end(&mut msg)
+                    .map_err(|_| QubitCryptError::FileReadError)?;
+                self.verify(pk, &msg, signature)
+            }
+        }
+    }
+
+    /// Generate `n` keypairs with the same manager, using the default RNG
+    ///
+    /// The manager is set up once for the whole batch, e.g. when provisioning many identities.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn key_gen_batch(&mut self, n: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        (0..n).map(|_| self.key_gen()).collect()
+    }
+
+    /// Generate `n` keypairs with the same manager, using a specified RNG
+    ///
+    /// The keypairs are generated one after the other from the RNG, so a seeded RNG always
+    /// gives the same keypairs in the same order.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    /// * `rng` - The random number generator to use
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn key_gen_batch_with_rng(
+        &mut self,
+        n: usize,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        (0..n).map(|_| self.key_gen_with_rng(rng)).collect()
+    }
 }
 
 impl Dsa for DsaManager {
@@ -110,6 +301,7 @@ impl Dsa for DsaManager {
             DsaManager::Ec(ec) => ec.key_gen(),
             DsaManager::Slh(slh) => slh.key_gen(),
             DsaManager::Composite(composite) => composite.key_gen(),
+            DsaManager::Custom(custom) => custom.get_mut().key_gen(),
         }
     }
 
@@ -120,6 +312,7 @@ impl Dsa for DsaManager {
             DsaManager::Ec(ec) => ec.key_gen_with_rng(rng),
             DsaManager::Slh(slh) => slh.key_gen_with_rng(rng),
             DsaManager::Composite(composite) => composite.key_gen_with_rng(rng),
+            DsaManager::Custom(custom) => custom.get_mut().key_gen_with_rng(rng),
         }
     }
 
@@ -130,6 +323,7 @@ impl Dsa for DsaManager {
             DsaManager::Ec(ec) => ec.sign(sk, msg),
             DsaManager::Slh(slh) => slh.sign(sk, msg),
             DsaManager::Composite(composite) => composite.sign(sk, msg),
+            DsaManager::Custom(custom) => custom.get().sign(sk, msg),
         }
     }
 
@@ -140,6 +334,21 @@ impl Dsa for DsaManager {
             DsaManager::Ec(ec) => ec.verify(pk, msg, sig),
             DsaManager::Slh(slh) => slh.verify(pk, msg, sig),
             DsaManager::Composite(composite) => composite.verify(pk, msg, sig),
+            DsaManager::Custom(custom) => custom.get().verify(pk, msg, sig),
+        }
+    }
+
+    fn sign_with_context(&self, sk: &[u8], msg: &[u8], ctx: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            DsaManager::Ml(ml) => ml.sign_with_context(sk, msg, ctx),
+            _ => Err(QubitCryptError::UnsupportedOperation),
+        }
+    }
+
+    fn verify_with_context(&self, pk: &[u8], msg: &[u8], sig: &[u8], ctx: &[u8]) -> Result<bool> {
+        match self {
+            DsaManager::Ml(ml) => ml.verify_with_context(pk, msg, sig, ctx),
+            _ => Err(QubitCryptError::UnsupportedOperation),
         }
     }
 
@@ -150,6 +359,7 @@ impl Dsa for DsaManager {
             DsaManager::Ec(ec) => ec.get_dsa_info(),
             DsaManager::Slh(slh) => slh.get_dsa_info(),
             DsaManager::Composite(composite) => composite.get_dsa_info(),
+            DsaManager::Custom(custom) => custom.get().get_dsa_info(),
         }
     }
 
@@ -160,12 +370,42 @@ impl Dsa for DsaManager {
             DsaManager::Ec(ec) => ec.get_public_key(sk),
             DsaManager::Slh(slh) => slh.get_public_key(sk),
             DsaManager::Composite(composite) => composite.get_public_key(sk),
+            DsaManager::Custom(custom) => custom.get().get_public_key(sk),
+        }
+    }
+
+    /// Create a new DSA manager from an OID
+    ///
+    /// The built-in DSAs take precedence, then the custom DSAs registered with `register_dsa`
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the DSA
+    ///
+    /// # Returns
+    ///
+    /// A new DSA manager
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidOid` will be returned if the OID is not a known DSA OID
+    fn new_from_oid(oid: &str) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        if let Some(dsa_type) = DsaType::from_oid(oid) {
+            return Self::new(dsa_type);
         }
+        registry::new_custom_dsa(oid)
+            .map(DsaManager::Custom)
+            .ok_or(QubitCryptError::InvalidOid)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use der::Decode;
+
     use super::*;
     use crate::dsa::common::dsa_type::DsaType;
 
@@ -184,4 +424,89 @@ mod tests {
             assert_eq!(dsa.get_dsa_info().dsa_type, dsa_type);
         }
     }
+
+    #[test]
+    fn test_sign_wrapped() {
+        let msg = b"Hello, world!";
+        // Fixed length raw signatures and DER encoded ones
+        for dsa_type in [
+            DsaType::MlDsa44,
+            DsaType::MlDsa87,
+            DsaType::EcdsaP256SHA256,
+            DsaType::MlDsa44EcdsaP256SHA256,
+        ] {
+            let mut dsa = DsaManager::new(dsa_type).unwrap();
+            let (pk, sk) = dsa.key_gen().unwrap();
+
+            let raw = dsa.sign_wrapped(&sk, msg, SigEncoding::Raw).unwrap();
+            assert!(dsa.verify(&pk, msg, &raw).unwrap());
+            assert!(dsa.verify_wrapped(&pk, msg, &raw).unwrap());
+
+            let wrapped = dsa
+                .sign_wrapped(&sk, msg, SigEncoding::OctetString)
+                .unwrap();
+            assert_eq!(wrapped[0], 0x04);
+            let inner = der::asn1::OctetString::from_der(&wrapped).unwrap();
+            assert!(dsa.verify(&pk, msg, inner.as_bytes()).unwrap());
+            assert!(dsa.verify_wrapped(&pk, msg, &wrapped).unwrap());
+            assert!(!dsa
+                .verify_wrapped(&pk, b"Goodbye, world!", &wrapped)
+                .unwrap_or(false));
+        }
+    }
+
+    /// A reader which returns its content a few bytes at a time
+    struct ChunkedReader<'a>(&'a [u8]);
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(7);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_verify_reader() {
+        let msg = vec![0x5a; 100_000];
+        for dsa_type in [
+            DsaType::MlDsa44,
+            DsaType::EcdsaP256SHA256,
+            DsaType::EcdsaP384SHA512,
+            DsaType::Ed25519SHA512,
+            DsaType::Rsa2048PssSHA256,
+            DsaType::MlDsa44EcdsaP256SHA256,
+        ] {
+            let mut dsa = DsaManager::new(dsa_type).unwrap();
+            let (pk, sk) = dsa.key_gen().unwrap();
+            let sig = dsa.sign(&sk, &msg).unwrap();
+
+            assert!(dsa
+                .verify_reader(&pk, &mut ChunkedReader(&msg), &sig)
+                .unwrap());
+            assert!(!dsa
+                .verify_reader(&pk, &mut ChunkedReader(&msg[1..]), &sig)
+                .unwrap_or(false));
+        }
+
+        // A failing reader is an error, not an invalid signature
+        let mut dsa = DsaManager::new(DsaType::EcdsaP256SHA256).unwrap();
+        let (pk, sk) = dsa.key_gen().unwrap();
+        let sig = dsa.sign(&sk, &msg).unwrap();
+        let mut reader = std::io::Read::chain(&msg[..10], FailingReader);
+        assert_eq!(
+            dsa.verify_reader(&pk, &mut reader, &sig),
+            Err(QubitCryptError::FileReadError)
+        );
+    }
+
+    /// A reader which always fails
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("read failed"))
+        }
+    }
 }
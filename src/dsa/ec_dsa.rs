@@ -1,14 +1,21 @@
-use ed25519_dalek::SigningKey;
+use std::io::Read;
+
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
 use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
-use openssl::pkey::Id;
+use openssl::pkey::{Id, PKey};
+use openssl::sign::Verifier;
 use rand_core::CryptoRngCore;
+use sha2::digest::consts::U64;
+use sha2::digest::{FixedOutput, HashMarker, Output, OutputSizeUser, Update};
 
 use crate::dsa::common::dsa_info::DsaInfo;
 use crate::dsa::common::dsa_trait::Dsa;
 use crate::dsa::common::dsa_type::DsaType;
+use crate::utils::openssl_utils::get_ec_key_from_pk;
 use crate::utils::openssl_utils::get_pk_from_sk_ec_based;
 use crate::utils::openssl_utils::get_pk_from_sk_pkey_based;
+use crate::utils::openssl_utils::is_sk_in_range_ec_based;
 use crate::utils::openssl_utils::sign_ec_based;
 use crate::utils::openssl_utils::sign_pkey_based;
 use crate::utils::openssl_utils::verify_ec_based;
@@ -16,10 +23,39 @@ use crate::utils::openssl_utils::verify_pkey_based;
 use crate::utils::openssl_utils::{
     get_key_pair_ec_based, get_key_pair_ec_based_with_rng, get_key_pair_pkey_based,
 };
+use crate::utils::rng::rng_from_default_source;
 use crate::QubitCryptError;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
+/// The OID of SHA-512, the hash function of Ed25519ph
+const SHA512_OID: &str = "2.16.840.1.101.3.4.2.3";
+
+/// A SHA-512 digest computed by the caller
+///
+/// `ed25519_dalek` takes the prehashed message as a hash state and finalizes it, so this hash
+/// "state" holds the digest and outputs it unchanged.
+#[derive(Clone, Default)]
+struct Sha512Digest(Vec<u8>);
+
+impl Update for Sha512Digest {
+    fn update(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(data);
+    }
+}
+
+impl OutputSizeUser for Sha512Digest {
+    type OutputSize = U64;
+}
+
+impl FixedOutput for Sha512Digest {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.0);
+    }
+}
+
+impl HashMarker for Sha512Digest {}
+
 #[derive(Clone)]
 pub struct EcDsaManager {
     pub dsa_info: DsaInfo,
@@ -28,6 +64,170 @@ pub struct EcDsaManager {
     digest: MessageDigest,
 }
 
+impl EcDsaManager {
+    /// Check that a secret key of a NIST or Brainpool curve is in the range [1, n-1]
+    ///
+    /// Ed25519 and Ed448 secret keys are hashed and clamped as defined in RFC 8032, so every
+    /// 32 / 57 byte string is a valid secret key and there is nothing to check.
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the secret key is out of range
+    fn check_private_key(&self, sk: &[u8]) -> Result<()> {
+        if let Some(nid) = self.ec_based_nid {
+            if !is_sk_in_range_ec_based(sk, nid).map_err(|_| QubitCryptError::InvalidPrivateKey)? {
+                return Err(QubitCryptError::InvalidPrivateKey);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sign the SHA-512 digest of a message with Ed25519ph, the prehash variant of Ed25519
+    /// (RFC 8032, Section 5.1)
+    ///
+    /// The message is hashed by the caller, e.g. while it's streamed from a file, and the digest
+    /// is signed with the prehash flag set, so the signature is not a valid Ed25519 signature of
+    /// the message. RFC 8410 assigns no OID to Ed25519ph, so it is selected by calling this
+    /// method on an Ed25519 manager rather than by a `DsaType`.
+    ///
+    /// Ed448ph is not supported: the OpenSSL bindings don't expose the prehash instance of
+    /// Ed448, and `ed448_rust` can't decode an Ed448 public key for verification.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to sign the digest
+    /// * `digest` - The SHA-512 digest of the message
+    /// * `hash_oid` - The OID of the hash function, which must be SHA-512
+    /// * `ctx` - The context string, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// The signature of the message
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the DSA is not Ed25519,
+    /// `QubitCryptError::InvalidOid` if the hash function is not SHA-512,
+    /// `QubitCryptError::InvalidContent` if the digest is not 64 bytes long,
+    /// `QubitCryptError::InvalidContext` if the context is longer than 255 bytes,
+    /// `QubitCryptError::InvalidPrivateKey` if the secret key has the wrong length
+    pub fn sign_prehashed(
+        &self,
+        sk: &[u8],
+        digest: &[u8],
+        hash_oid: &str,
+        ctx: &[u8],
+    ) -> Result<Vec<u8>> {
+        let digest = self.ed25519ph_digest(digest, hash_oid, ctx)?;
+        let sk: &[u8; 32] = sk
+            .try_into()
+            .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+        let sig = SigningKey::from_bytes(sk)
+            .sign_prehashed(digest, Some(ctx))
+            .map_err(|_| QubitCryptError::SignatureFailed)?;
+        Ok(sig.to_bytes().to_vec())
+    }
+
+    /// Verify an Ed25519ph signature over the SHA-512 digest of a message, see
+    /// `sign_prehashed`
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `digest` - The SHA-512 digest of the message
+    /// * `hash_oid` - The OID of the hash function, which must be SHA-512
+    /// * `signature` - The signature to verify
+    /// * `ctx` - The context string the digest was signed with, at most 255 bytes
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// The errors of `sign_prehashed` and `QubitCryptError::InvalidPublicKey` if the public key
+    /// is invalid
+    pub fn verify_prehashed(
+        &self,
+        pk: &[u8],
+        digest: &[u8],
+        hash_oid: &str,
+        signature: &[u8],
+        ctx: &[u8],
+    ) -> Result<bool> {
+        let digest = self.ed25519ph_digest(digest, hash_oid, ctx)?;
+        let pk: &[u8; 32] = pk
+            .try_into()
+            .map_err(|_| QubitCryptError::InvalidPublicKey)?;
+        let pk = VerifyingKey::from_bytes(pk).map_err(|_| QubitCryptError::InvalidPublicKey)?;
+        let sig = match Signature::from_slice(signature) {
+            Ok(sig) => sig,
+            Err(_) => return Ok(false),
+        };
+        Ok(pk.verify_prehashed(digest, Some(ctx), &sig).is_ok())
+    }
+
+    /// Check the inputs of Ed25519ph and wrap the digest for `ed25519_dalek`
+    fn ed25519ph_digest(&self, digest: &[u8], hash_oid: &str, ctx: &[u8]) -> Result<Sha512Digest> {
+        if self.pk_based_id != Some(Id::ED25519) {
+            return Err(QubitCryptError::UnsupportedOperation);
+        }
+        if hash_oid != SHA512_OID {
+            return Err(QubitCryptError::InvalidOid);
+        }
+        if digest.len() != 64 {
+            return Err(QubitCryptError::InvalidContent);
+        }
+        if ctx.len() > 255 {
+            return Err(QubitCryptError::InvalidContext);
+        }
+        Ok(Sha512Digest(digest.to_vec()))
+    }
+
+    /// Verify a signature over the content of a reader
+    ///
+    /// ECDSA hashes the content in chunks as it's read. Ed25519 and Ed448 hash the message
+    /// twice (RFC 8032), so the content of the reader is read into memory first.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `reader` - The reader of the message to verify
+    /// * `signature` - The signature to verify
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::FileReadError` will be returned if the reader fails
+    pub fn verify_reader(
+        &self,
+        pk: &[u8],
+        reader: &mut impl Read,
+        signature: &[u8],
+    ) -> Result<bool> {
+        let Some(nid) = self.ec_based_nid else {
+            let mut msg = Vec::new();
+            reader
+                .read_to_end(&mut msg)
+                .map_err(|_| QubitCryptError::FileReadError)?;
+            return self.verify(pk, &msg, signature);
+        };
+
+        let pkey = get_ec_key_from_pk(nid, pk)
+            .and_then(|ec_key| Ok(PKey::from_ec_key(ec_key)?))
+            .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
+        let mut verifier = Verifier::new(self.digest, &pkey)
+            .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
+        std::io::copy(reader, &mut verifier).map_err(|_| QubitCryptError::FileReadError)?;
+        verifier
+            .verify(signature)
+            .map_err(|_| QubitCryptError::SignatureVerificationFailed)
+    }
+}
+
 impl Dsa for EcDsaManager {
     fn new(dsa_type: DsaType) -> Result<Self>
     where
@@ -67,6 +267,10 @@ impl Dsa for EcDsaManager {
     }
 
     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+        if let Some(mut rng) = rng_from_default_source()? {
+            return self.key_gen_with_rng(&mut rng);
+        }
+
         let result = if let Some(nid) = self.ec_based_nid {
             get_key_pair_ec_based(nid)
         } else if let Some(id) = self.pk_based_id {
@@ -108,6 +312,7 @@ impl Dsa for EcDsaManager {
     }
 
     fn sign(&self, sk: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
+        self.check_private_key(sk)?;
         let result = if let Some(nid) = self.ec_based_nid {
             sign_ec_based(nid, sk, msg, self.digest)
         } else if let Some(id) = self.pk_based_id {
@@ -136,6 +341,7 @@ impl Dsa for EcDsaManager {
     }
 
     fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        self.check_private_key(sk)?;
         if let Some(nid) = self.ec_based_nid {
             get_pk_from_sk_ec_based(sk, nid).map_err(|_| QubitCryptError::InvalidPrivateKey)
         } else if let Some(id) = self.pk_based_id {
@@ -198,4 +404,102 @@ mod tests {
         let dsa = EcDsaManager::new(DsaType::Ed448SHA512);
         test_dsa!(dsa);
     }
+
+    #[test]
+    fn test_ed25519ph() {
+        // RFC 8032, Section 7.3
+        let sk = hex::decode("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42")
+            .unwrap();
+        let pk = hex::decode("ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf")
+            .unwrap();
+        let sig = hex::decode(
+            "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae41\
+             31f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406",
+        )
+        .unwrap();
+
+        let abc = <sha2::Sha512 as sha2::Digest>::digest(b"abc");
+        let abd = <sha2::Sha512 as sha2::Digest>::digest(b"abd");
+
+        let dsa = EcDsaManager::new(DsaType::Ed25519SHA512).unwrap();
+        assert_eq!(dsa.get_public_key(&sk).unwrap(), pk);
+        assert_eq!(dsa.sign_prehashed(&sk, &abc, SHA512_OID, b"").unwrap(), sig);
+        assert!(dsa
+            .verify_prehashed(&pk, &abc, SHA512_OID, &sig, b"")
+            .unwrap());
+        assert!(!dsa
+            .verify_prehashed(&pk, &abd, SHA512_OID, &sig, b"")
+            .unwrap());
+        assert!(!dsa
+            .verify_prehashed(&pk, &abc, SHA512_OID, &sig[1..], b"")
+            .unwrap());
+        assert!(!dsa
+            .verify_prehashed(&pk, &abc, SHA512_OID, &sig, b"ctx")
+            .unwrap());
+
+        // A prehash signature is not a signature of the message, and vice versa
+        assert!(!dsa.verify(&pk, b"abc", &sig).unwrap_or(false));
+        let pure_sig = dsa.sign(&sk, b"abc").unwrap();
+        assert!(!dsa
+            .verify_prehashed(&pk, &abc, SHA512_OID, &pure_sig, b"")
+            .unwrap());
+
+        // The context is part of the signed data
+        let ctx_sig = dsa.sign_prehashed(&sk, &abc, SHA512_OID, b"ctx").unwrap();
+        assert!(dsa
+            .verify_prehashed(&pk, &abc, SHA512_OID, &ctx_sig, b"ctx")
+            .unwrap());
+        assert!(!dsa
+            .verify_prehashed(&pk, &abc, SHA512_OID, &ctx_sig, b"")
+            .unwrap());
+
+        assert!(matches!(
+            dsa.sign_prehashed(&sk, &abc, "2.16.840.1.101.3.4.2.10", b""),
+            Err(QubitCryptError::InvalidOid)
+        ));
+        assert!(matches!(
+            dsa.sign_prehashed(&sk, &abc[..32], SHA512_OID, b""),
+            Err(QubitCryptError::InvalidContent)
+        ));
+        assert!(matches!(
+            dsa.sign_prehashed(&sk, &abc, SHA512_OID, &[0u8; 256]),
+            Err(QubitCryptError::InvalidContext)
+        ));
+
+        for dsa_type in [DsaType::Ed448SHA512, DsaType::EcdsaP256SHA256] {
+            let mut dsa = EcDsaManager::new(dsa_type).unwrap();
+            let (pk, sk) = dsa.key_gen().unwrap();
+            assert!(matches!(
+                dsa.sign_prehashed(&sk, &abc, SHA512_OID, b""),
+                Err(QubitCryptError::UnsupportedOperation)
+            ));
+            assert!(matches!(
+                dsa.verify_prehashed(&pk, &abc, SHA512_OID, &sig, b""),
+                Err(QubitCryptError::UnsupportedOperation)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_rejects_out_of_range_sk() {
+        for dsa_type in [
+            DsaType::EcdsaP256SHA256,
+            DsaType::EcdsaP384SHA512,
+            DsaType::EcdsaBrainpoolP256r1SHA256,
+            DsaType::EcdsaBrainpoolP384r1SHA512,
+        ] {
+            let mut dsa = EcDsaManager::new(dsa_type).unwrap();
+            let (_, sk) = dsa.key_gen().unwrap();
+            let all_ff = vec![0xFFu8; sk.len()];
+            assert!(matches!(
+                dsa.sign(&all_ff, b"message"),
+                Err(QubitCryptError::InvalidPrivateKey)
+            ));
+            assert!(matches!(
+                dsa.get_public_key(&all_ff),
+                Err(QubitCryptError::InvalidPrivateKey)
+            ));
+            assert!(dsa.sign(&sk, b"message").is_ok());
+        }
+    }
 }
@@ -1,12 +1,14 @@
-use openssl::sign::RsaPssSaltlen;
-use rand::SeedableRng;
-use rand_chacha::ChaCha20Rng;
+use std::io::Read;
+
+use openssl::pkey::{PKey, Public};
+use openssl::sign::{RsaPssSaltlen, Verifier};
 use rsa::pkcs1::{EncodeRsaPrivateKey, EncodeRsaPublicKey};
 use rsa::RsaPrivateKey;
 
 use crate::dsa::common::dsa_info::DsaInfo;
 use crate::dsa::common::dsa_trait::Dsa;
 use crate::dsa::common::dsa_type::DsaType;
+use crate::utils::rng::default_rng;
 use crate::QubitCryptError;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
@@ -16,6 +18,88 @@ pub struct RsaDsaManager {
     pub dsa_info: DsaInfo,
 }
 
+impl RsaDsaManager {
+    /// Parse a PKCS#1 public key
+    fn public_pkey(pk: &[u8]) -> Result<PKey<Public>> {
+        let rsa_pk = openssl::rsa::Rsa::public_key_from_der_pkcs1(pk)
+            .map_err(|_| QubitCryptError::SerializationFailed)?;
+        PKey::from_rsa(rsa_pk).map_err(|_| QubitCryptError::SignatureVerificationFailed)
+    }
+
+    /// Create a verifier with the hash and padding of the DSA type
+    fn verifier<'a>(&self, pkey: &'a PKey<Public>) -> Result<Verifier<'a>> {
+        let (hash, padding) = match self.dsa_info.dsa_type {
+            DsaType::Rsa2048Pkcs15SHA256 => (
+                openssl::hash::MessageDigest::sha256(),
+                openssl::rsa::Padding::PKCS1,
+            ),
+            DsaType::Rsa2048PssSHA256 => (
+                openssl::hash::MessageDigest::sha256(),
+                openssl::rsa::Padding::PKCS1_PSS,
+            ),
+            DsaType::Rsa3072Pkcs15SHA512 => (
+                openssl::hash::MessageDigest::sha512(),
+                openssl::rsa::Padding::PKCS1,
+            ),
+            DsaType::Rsa3072PssSHA512 => (
+                openssl::hash::MessageDigest::sha512(),
+                openssl::rsa::Padding::PKCS1_PSS,
+            ),
+            _ => {
+                panic!("Not implemented");
+            }
+        };
+
+        let mut verifier =
+            Verifier::new(hash, pkey).map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
+        verifier
+            .set_rsa_padding(padding)
+            .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
+
+        if padding == openssl::rsa::Padding::PKCS1_PSS {
+            verifier
+                .set_rsa_mgf1_md(hash)
+                .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
+            verifier
+                .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+                .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
+        }
+
+        Ok(verifier)
+    }
+
+    /// Verify a signature over the content of a reader
+    ///
+    /// The content is hashed in chunks as it's read, so it's never held in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to verify the signature
+    /// * `reader` - The reader of the message to verify
+    /// * `signature` - The signature to verify
+    ///
+    /// # Returns
+    ///
+    /// A boolean indicating if the signature is valid
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::FileReadError` will be returned if the reader fails
+    pub fn verify_reader(
+        &self,
+        pk: &[u8],
+        reader: &mut impl Read,
+        signature: &[u8],
+    ) -> Result<bool> {
+        let pkey = Self::public_pkey(pk)?;
+        let mut verifier = self.verifier(&pkey)?;
+        std::io::copy(reader, &mut verifier).map_err(|_| QubitCryptError::FileReadError)?;
+        verifier
+            .verify(signature)
+            .map_err(|_| QubitCryptError::SignatureVerificationFailed)
+    }
+}
+
 impl Dsa for RsaDsaManager {
     /// Create a new DSA instance
     ///
@@ -40,7 +124,7 @@ impl Dsa for RsaDsaManager {
     ///
     /// A tuple containing the public and secret keys (pk, sk)
     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
-        let mut rng = ChaCha20Rng::from_entropy();
+        let mut rng = default_rng()?;
         self.key_gen_with_rng(&mut rng)
     }
 
@@ -163,50 +247,8 @@ impl Dsa for RsaDsaManager {
     ///
     /// A boolean indicating if the signature is valid
     fn verify(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
-        let rsa_pk = openssl::rsa::Rsa::public_key_from_der_pkcs1(pk)
-            .map_err(|_| QubitCryptError::SerializationFailed)?;
-        let pkey = openssl::pkey::PKey::from_rsa(rsa_pk)
-            .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
-
-        let (hash, padding) = match self.dsa_info.dsa_type {
-            DsaType::Rsa2048Pkcs15SHA256 => (
-                openssl::hash::MessageDigest::sha256(),
-                openssl::rsa::Padding::PKCS1,
-            ),
-            DsaType::Rsa2048PssSHA256 => (
-                openssl::hash::MessageDigest::sha256(),
-                openssl::rsa::Padding::PKCS1_PSS,
-            ),
-            DsaType::Rsa3072Pkcs15SHA512 => (
-                openssl::hash::MessageDigest::sha512(),
-                openssl::rsa::Padding::PKCS1,
-            ),
-            DsaType::Rsa3072PssSHA512 => (
-                openssl::hash::MessageDigest::sha512(),
-                openssl::rsa::Padding::PKCS1_PSS,
-            ),
-            _ => {
-                panic!("Not implemented");
-            }
-        };
-
-        // Create a verifier
-        let mut verifier = openssl::sign::Verifier::new(hash, &pkey)
-            .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
-        verifier
-            .set_rsa_padding(padding)
-            .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
-
-        if padding == openssl::rsa::Padding::PKCS1_PSS {
-            verifier
-                .set_rsa_mgf1_md(hash)
-                .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
-            verifier
-                .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
-                .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
-        }
-
-        // Verify the signature
+        let pkey = Self::public_pkey(pk)?;
+        let mut verifier = self.verifier(&pkey)?;
         verifier
             .update(msg)
             .map_err(|_| QubitCryptError::SignatureVerificationFailed)?;
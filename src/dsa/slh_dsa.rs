@@ -1,10 +1,9 @@
 use crate::dsa::common::dsa_info::DsaInfo;
 use crate::dsa::common::dsa_trait::Dsa;
 use crate::dsa::common::dsa_type::DsaType;
+use crate::utils::rng::default_rng;
 use crate::QubitCryptError;
 
-use rand_core::SeedableRng;
-
 // When IPD feature is not enabled
 use fips205::slh_dsa_sha2_128f;
 use fips205::slh_dsa_sha2_128s;
@@ -97,6 +96,18 @@ macro_rules! get_public_key {
     }};
 }
 
+/// A DSA manager for the stateless hash-based signatures of SLH-DSA (FIPS 205)
+///
+/// The security of SLH-DSA only rests on its hash function (SHA-2 or SHAKE), so it's an
+/// alternative to ML-DSA where lattice assumptions aren't trusted, e.g. to sign firmware.
+/// The sizes of the parameter sets are in the `DsaInfo` of the manager: the public keys are
+/// 32 to 64 bytes, the signatures 7856 to 49856 bytes.
+///
+/// The small (`s`) parameter sets have signatures about half the size of the fast (`f`) ones,
+/// but signing is more than an order of magnitude slower, so they suit signatures which are
+/// made rarely and verified often. Key generation of the `s` sets is slow as well, while
+/// verification is fast for all parameter sets. The messages are signed with the pure variant
+/// and an empty context.
 #[derive(Clone)]
 pub struct SlhDsaManager {
     pub dsa_info: DsaInfo,
@@ -149,7 +160,7 @@ impl Dsa for SlhDsaManager {
     ///
     /// A tuple containing the public and secret keys (pk, sk)
     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
-        let mut rng = rand_chacha::ChaCha20Rng::from_entropy();
+        let mut rng = default_rng()?;
         self.key_gen_with_rng(&mut rng)
     }
 
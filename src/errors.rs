@@ -39,6 +39,8 @@ pub enum QubitCryptError {
     SignatureFailed,
     #[error("Invalid signature")]
     InvalidSignature,
+    #[error("The context string is longer than 255 bytes")]
+    InvalidContext,
     #[error("Key pair generation failed")]
     KeyPairGenerationFailed,
     #[error("Missing not_after")]
@@ -87,4 +89,46 @@ pub enum QubitCryptError {
     InvalidContent,
     #[error("Unsupported Content Encryption Algorithm")]
     UnsupportedContentEncryptionAlgorithm,
+    #[error("Error while encrypting the content")]
+    CeaError,
+    #[error("ML-KEM encapsulation key failed the FIPS 203 modulus check")]
+    InvalidEncapsulationKey,
+    #[error("Public key encoding failed: {0}")]
+    PublicKeyEncodingFailed(String),
+    #[error(
+        "The BIT STRING of the key has {0} unused bits, but keys are a whole number of octets"
+    )]
+    UnusedKeyBits(u8),
+    #[error("Missing or invalid content encryption key")]
+    InvalidContentEncryptionKey,
+    #[error("Key usage violation. KEM keys can't be used in CA certificates, only DSA keys can sign certificates, and signing requires the digital signature usage")]
+    KeyUsageViolation,
+    #[error("The security category of the key is below the required category")]
+    InsufficientSecurityCategory,
+    #[error("Random number generation failed")]
+    RngFailed,
+    #[error("The envelope has no KEM recipients, only recipients of the types {}", .0.join(", "))]
+    UnsupportedRecipientType(Vec<&'static str>),
+    #[error("None of the KEM recipients of the envelope is identified by the certificate")]
+    NoMatchingRecipient,
+    #[error("The KEM ciphertext has {actual} bytes, but the KEM of the private key expects {expected} bytes")]
+    InvalidCiphertextLength { expected: usize, actual: usize },
+    #[error("The certificate is expired or not yet valid")]
+    CertificateExpired,
+    #[error("The issuer name or authority key identifier of the certificate doesn't match the certificate above it")]
+    IssuerMismatch,
+    #[error("The issuing certificate is not a CA, or its path length constraint is exceeded")]
+    BasicConstraintsViolation,
+    #[error("The names of the certificate are not permitted by a CA above it")]
+    NameConstraintsViolation,
+    #[error("The certification path is invalid, failures by the position in the path: {0:?}")]
+    InvalidChain(Vec<(usize, QubitCryptError)>),
+    #[error(
+        "The shared secret has {actual} bytes, but the key wrap expects a KEK of {expected} bytes"
+    )]
+    InvalidSharedSecretLength { expected: usize, actual: usize },
+    #[error("KdfType::Direct is not a KDF, the KEM shared secret is used as the KEK")]
+    NotAKdf,
+    #[error("The name constraints have a subtree which can't be checked, only DNS names, mailboxes, directory names, IP address ranges and URI hosts are supported")]
+    UnsupportedNameConstraint,
 }
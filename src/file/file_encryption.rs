@@ -0,0 +1,557 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use zeroize::Zeroizing;
+
+use crate::cea::cea_manager::CeaManager;
+use crate::cea::common::cea_trait::Cea;
+use crate::cea::common::cea_type::CeaType;
+use crate::kdf::common::kdf_trait::Kdf;
+use crate::kdf::common::kdf_type::KdfType;
+use crate::kdf::hkdf::Hkdf;
+use crate::kem::common::kem_sizes::kem_ciphertext_len;
+use crate::keys::{PrivateKey, PublicKey};
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+// A file encrypted to a KEM public key has the following layout, all integers are big endian:
+//
+// magic (4) | version (1) | OID length (2) | KEM OID | ct length (4) | KEM ciphertext | chunks
+//
+// The content encryption key is derived from the KEM shared secret with HKDF-SHA256. The
+// plaintext is split into chunks of CHUNK_SIZE bytes, each encrypted with AES-256-GCM using the
+// whole header as AAD. The nonce of a chunk is its index, with the last byte set to 1 for the
+// final chunk, so chunks can't be reordered, dropped or truncated without detection. The final
+// chunk is always shorter than CHUNK_SIZE, and may be empty.
+
+/// The magic bytes at the start of an encrypted file
+const MAGIC: &[u8; 4] = b"QCFE";
+
+/// The version of the file format
+const VERSION: u8 = 1;
+
+/// The size of a plaintext chunk in bytes
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The size of the AES-GCM tag appended to each chunk in bytes
+const TAG_SIZE: usize = 16;
+
+/// The size of the AES-GCM nonce in bytes
+const NONCE_SIZE: usize = 12;
+
+/// The size of the content encryption key in bytes
+const KEY_SIZE: usize = 32;
+
+/// The upper bound for the KEM ciphertext length when it can't be determined from the OID
+const MAX_CT_SIZE: usize = 16 * 1024;
+
+/// The HKDF info used to derive the content encryption key
+const KDF_INFO: &[u8] = b"qubitcrypt file encryption v1";
+
+/// Encrypt a file to the specified KEM public key
+///
+/// This is a lightweight format intended for encrypting files between users of this library. It
+/// is not interoperable with other implementations, use `EnvelopedDataContent` or
+/// `AuthEnvelopedDataContent` if interoperability is required. The file is processed in chunks,
+/// so memory usage doesn't depend on the size of the file.
+///
+/// # Arguments
+///
+/// * `input` - The path to the file to encrypt
+/// * `output` - The path to write the encrypted file to
+/// * `recipient_pk` - The KEM public key of the recipient
+///
+/// # Errors
+///
+/// `QubitCryptError::UnsupportedOperation` will be returned if the public key is not a KEM key.
+/// `QubitCryptError::FileReadError` or `QubitCryptError::FileWriteError` will be returned if the
+/// files can't be read or written. The encrypted file is written to a temporary file in the
+/// directory of `output`, which replaces `output` only once encryption succeeded, so an existing
+/// file at `output` is kept if it fails.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::files::{decrypt_file, encrypt_file};
+/// use qubitcrypt::kems::{KemAlgorithm, KemKeyGenerator};
+///
+/// let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem768).generate().unwrap();
+///
+/// let dir = std::env::temp_dir().join(format!("qubitcrypt_doc_{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let plaintext = dir.join("plain.txt");
+/// let encrypted = dir.join("plain.txt.qcfe");
+/// let decrypted = dir.join("decrypted.txt");
+/// std::fs::write(&plaintext, b"Hello, world!").unwrap();
+///
+/// encrypt_file(plaintext.to_str().unwrap(), encrypted.to_str().unwrap(), &pk).unwrap();
+/// decrypt_file(encrypted.to_str().unwrap(), decrypted.to_str().unwrap(), &sk).unwrap();
+/// assert_eq!(std::fs::read(&decrypted).unwrap(), b"Hello, world!");
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn encrypt_file(input: &str, output: &str, recipient_pk: &PublicKey) -> Result<()> {
+    let reader = File::open(input).map_err(|_| QubitCryptError::FileReadError)?;
+    write_replacing(output, |writer| {
+        encrypt(&mut BufReader::new(reader), writer, recipient_pk)
+    })
+}
+
+/// Decrypt a file created by `encrypt_file`
+///
+/// # Arguments
+///
+/// * `input` - The path to the encrypted file
+/// * `output` - The path to write the decrypted file to
+/// * `sk` - The KEM private key of the recipient
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidContent` will be returned if the file is not in the expected format.
+/// `QubitCryptError::InvalidPrivateKey` will be returned if the file was encrypted for a
+/// different KEM algorithm. `QubitCryptError::InvalidCiphertext` will be returned if the file was
+/// encrypted for a different key, or has been modified or truncated. The plaintext is written to
+/// a temporary file in the directory of `output`, which replaces `output` only once the whole
+/// file is decrypted and authenticated, so an existing file at `output` is kept if decryption
+/// fails.
+pub fn decrypt_file(input: &str, output: &str, sk: &PrivateKey) -> Result<()> {
+    let reader = File::open(input).map_err(|_| QubitCryptError::FileReadError)?;
+    write_replacing(output, |writer| {
+        decrypt(&mut BufReader::new(reader), writer, sk)
+    })
+}
+
+/// Write a file through a temporary file, which is renamed to the path only if writing succeeds
+///
+/// The temporary file is created in the same directory, so the rename doesn't cross file
+/// systems and replaces an existing file at the path at once. It is removed if writing fails.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to write
+/// * `write` - Writes the contents of the file
+fn write_replacing(
+    path: &str,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<()>,
+) -> Result<()> {
+    let path = Path::new(path);
+    let temp_path = temp_path_for(path)?;
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .map_err(|_| QubitCryptError::FileWriteError)?;
+    let mut writer = BufWriter::new(file);
+    let result = write(&mut writer);
+    drop(writer);
+    let result = result.and_then(|_| {
+        std::fs::rename(&temp_path, path).map_err(|_| QubitCryptError::FileWriteError)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Get a path for a temporary file next to a file, which no other call uses
+fn temp_path_for(path: &Path) -> Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let file_name = path.file_name().ok_or(QubitCryptError::FileWriteError)?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    Ok(path.with_file_name(temp_name))
+}
+
+/// Encrypt everything from a reader to a KEM public key and write the result to a writer
+fn encrypt<R: Read, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    recipient_pk: &PublicKey,
+) -> Result<()> {
+    let (ss, ct) = recipient_pk.encap()?;
+    let ss = Zeroizing::new(ss);
+
+    let oid = recipient_pk.get_oid().as_bytes();
+    let oid_len = u16::try_from(oid.len()).map_err(|_| QubitCryptError::InvalidOid)?;
+    let ct_len = u32::try_from(ct.len()).map_err(|_| QubitCryptError::EncapFailed)?;
+
+    let mut header = Vec::with_capacity(MAGIC.len() + 7 + oid.len() + ct.len());
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    header.extend_from_slice(&oid_len.to_be_bytes());
+    header.extend_from_slice(oid);
+    header.extend_from_slice(&ct_len.to_be_bytes());
+    header.extend_from_slice(&ct);
+    writer
+        .write_all(&header)
+        .map_err(|_| QubitCryptError::FileWriteError)?;
+
+    let key = derive_key(&ss)?;
+    let cea = CeaManager::new(CeaType::Aes256Gcm)?;
+    let mut chunk = Zeroizing::new(vec![0u8; CHUNK_SIZE]);
+    let mut counter: u64 = 0;
+    loop {
+        let n = read_full(reader, &mut chunk)?;
+        let last = n < CHUNK_SIZE;
+        let nonce = chunk_nonce(counter, last);
+        let (ct, tag) = cea.encrypt_detached(&key, &nonce, &chunk[..n], &header)?;
+        writer
+            .write_all(&ct)
+            .and_then(|_| writer.write_all(&tag))
+            .map_err(|_| QubitCryptError::FileWriteError)?;
+        if last {
+            break;
+        }
+        counter = counter
+            .checked_add(1)
+            .ok_or(QubitCryptError::InvalidContent)?;
+    }
+
+    writer
+        .flush()
+        .map_err(|_| QubitCryptError::FileWriteError)?;
+    Ok(())
+}
+
+/// Decrypt everything from a reader with a KEM private key and write the plaintext to a writer
+fn decrypt<R: Read, W: Write>(reader: &mut R, writer: &mut W, sk: &PrivateKey) -> Result<()> {
+    let mut header = vec![0u8; MAGIC.len() + 3];
+    read_exact(reader, &mut header)?;
+    if &header[..MAGIC.len()] != MAGIC || header[MAGIC.len()] != VERSION {
+        return Err(QubitCryptError::InvalidContent);
+    }
+
+    let oid_len = u16::from_be_bytes([header[MAGIC.len() + 1], header[MAGIC.len() + 2]]) as usize;
+    let oid_start = header.len();
+    header.resize(oid_start + oid_len + 4, 0);
+    read_exact(reader, &mut header[oid_start..])?;
+    let oid = std::str::from_utf8(&header[oid_start..oid_start + oid_len])
+        .map_err(|_| QubitCryptError::InvalidContent)?
+        .to_string();
+    if oid != sk.get_oid() {
+        return Err(QubitCryptError::InvalidPrivateKey);
+    }
+
+    let ct_len_start = oid_start + oid_len;
+    let ct_len = u32::from_be_bytes([
+        header[ct_len_start],
+        header[ct_len_start + 1],
+        header[ct_len_start + 2],
+        header[ct_len_start + 3],
+    ]) as usize;
+    match kem_ciphertext_len(&oid) {
+        Some(expected) if expected != ct_len => return Err(QubitCryptError::InvalidContent),
+        None if ct_len > MAX_CT_SIZE => return Err(QubitCryptError::InvalidContent),
+        _ => {}
+    }
+    let ct_start = header.len();
+    header.resize(ct_start + ct_len, 0);
+    read_exact(reader, &mut header[ct_start..])?;
+
+    let ss = Zeroizing::new(sk.decap(&header[ct_start..])?);
+    let key = derive_key(&ss)?;
+    let cea = CeaManager::new(CeaType::Aes256Gcm)?;
+    let mut chunk = vec![0u8; CHUNK_SIZE + TAG_SIZE];
+    let mut counter: u64 = 0;
+    loop {
+        let n = read_full(reader, &mut chunk)?;
+        if n < TAG_SIZE {
+            return Err(QubitCryptError::InvalidCiphertext);
+        }
+        let last = n < chunk.len();
+        let nonce = chunk_nonce(counter, last);
+        let (ct, tag) = chunk[..n].split_at(n - TAG_SIZE);
+        let pt = Zeroizing::new(cea.decrypt_detached(&key, &nonce, ct, tag, &header)?);
+        writer
+            .write_all(&pt)
+            .map_err(|_| QubitCryptError::FileWriteError)?;
+        if last {
+            break;
+        }
+        counter = counter
+            .checked_add(1)
+            .ok_or(QubitCryptError::InvalidContent)?;
+    }
+
+    writer
+        .flush()
+        .map_err(|_| QubitCryptError::FileWriteError)?;
+    Ok(())
+}
+
+/// Derive the content encryption key from the KEM shared secret
+fn derive_key(ss: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let kdf = Hkdf::new(KdfType::HkdfWithSha256)?;
+    Ok(Zeroizing::new(kdf.derive(ss, KDF_INFO, KEY_SIZE, None)?))
+}
+
+/// Get the nonce for a chunk from its index and whether it is the final chunk
+fn chunk_nonce(counter: u64, last: bool) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+/// Read from the reader until the buffer is full or the end of the input is reached
+///
+/// # Returns
+///
+/// The number of bytes read, which is less than the buffer length only at the end of the input
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(read) => n += read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => return Err(QubitCryptError::FileReadError),
+        }
+    }
+    Ok(n)
+}
+
+/// Read exactly enough bytes to fill the buffer, a short read means the header is truncated
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+    if read_full(reader, buf)? != buf.len() {
+        return Err(QubitCryptError::InvalidContent);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    fn encrypt_bytes(data: &[u8], pk: &PublicKey) -> Vec<u8> {
+        let mut out = Vec::new();
+        encrypt(&mut Cursor::new(data), &mut out, pk).unwrap();
+        out
+    }
+
+    fn decrypt_bytes(data: &[u8], sk: &PrivateKey) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        decrypt(&mut Cursor::new(data), &mut out, sk)?;
+        Ok(out)
+    }
+
+    /// Create a directory for the files of one test, which no other test or test run uses
+    fn unique_temp_dir(name: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "qubitcrypt_{}_{}_{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_encryption_roundtrip() {
+        let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem768)
+            .generate()
+            .unwrap();
+
+        // Empty, short, exactly one chunk and several chunks
+        for len in [0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, 2 * CHUNK_SIZE + 17] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let enc = encrypt_bytes(&data, &pk);
+            assert_eq!(decrypt_bytes(&enc, &sk).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_file_encryption_composite() {
+        let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem768X25519)
+            .generate()
+            .unwrap();
+        let enc = encrypt_bytes(b"Hello, world!", &pk);
+        assert_eq!(decrypt_bytes(&enc, &sk).unwrap(), b"Hello, world!");
+    }
+
+    #[test]
+    fn test_file_encryption_tampering() {
+        let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let data = vec![7u8; CHUNK_SIZE + 100];
+        let enc = encrypt_bytes(&data, &pk);
+
+        // Flipping a bit in the content
+        let mut tampered = enc.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert_eq!(
+            decrypt_bytes(&tampered, &sk),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+
+        // Truncating at a chunk boundary
+        let chunks_len = CHUNK_SIZE + 100 + 2 * TAG_SIZE;
+        let header_len = enc.len() - chunks_len;
+        let truncated = &enc[..header_len + CHUNK_SIZE + TAG_SIZE];
+        assert_eq!(
+            decrypt_bytes(truncated, &sk),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+
+        // Truncated header
+        assert_eq!(
+            decrypt_bytes(&enc[..header_len - 1], &sk),
+            Err(QubitCryptError::InvalidContent)
+        );
+
+        // Bad magic
+        let mut bad_magic = enc.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            decrypt_bytes(&bad_magic, &sk),
+            Err(QubitCryptError::InvalidContent)
+        );
+
+        // A different key of the same algorithm
+        let (_, sk2) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            decrypt_bytes(&enc, &sk2),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+
+        // A key of a different algorithm
+        let (_, sk3) = KemKeyGenerator::new(KemAlgorithm::MlKem768)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            decrypt_bytes(&enc, &sk3),
+            Err(QubitCryptError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_file_encryption_files() {
+        let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem1024)
+            .generate()
+            .unwrap();
+        let dir = unique_temp_dir("file_encryption");
+        let plaintext = dir.join("plain.bin");
+        let encrypted = dir.join("plain.bin.qcfe");
+        let decrypted = dir.join("decrypted.bin");
+        let data = vec![42u8; 3 * CHUNK_SIZE / 2];
+        std::fs::write(&plaintext, &data).unwrap();
+
+        encrypt_file(
+            plaintext.to_str().unwrap(),
+            encrypted.to_str().unwrap(),
+            &pk,
+        )
+        .unwrap();
+        decrypt_file(
+            encrypted.to_str().unwrap(),
+            decrypted.to_str().unwrap(),
+            &sk,
+        )
+        .unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), data);
+
+        // Only KEM keys can be used, and the encrypted file of before is kept
+        let (pk_dsa, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let before = std::fs::read(&encrypted).unwrap();
+        assert_eq!(
+            encrypt_file(
+                plaintext.to_str().unwrap(),
+                encrypted.to_str().unwrap(),
+                &pk_dsa,
+            ),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+        assert_eq!(std::fs::read(&encrypted).unwrap(), before);
+
+        assert_eq!(
+            encrypt_file("does/not/exist", encrypted.to_str().unwrap(), &pk),
+            Err(QubitCryptError::FileReadError)
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_encryption_keeps_output() {
+        let (pk, _) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let (_, other_sk) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        let dir = unique_temp_dir("keeps_output");
+        let plaintext = dir.join("plain.bin");
+        let encrypted = dir.join("plain.bin.qcfe");
+        let existing = dir.join("existing.bin");
+        std::fs::write(&plaintext, b"secret").unwrap();
+        std::fs::write(&existing, b"keep me").unwrap();
+        encrypt_file(
+            plaintext.to_str().unwrap(),
+            encrypted.to_str().unwrap(),
+            &pk,
+        )
+        .unwrap();
+
+        // Decrypting with the wrong key or a DSA key fails without touching the existing file
+        assert_eq!(
+            decrypt_file(
+                encrypted.to_str().unwrap(),
+                existing.to_str().unwrap(),
+                &other_sk,
+            ),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+        let (pk_dsa, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            encrypt_file(
+                plaintext.to_str().unwrap(),
+                existing.to_str().unwrap(),
+                &pk_dsa,
+            ),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+        assert_eq!(std::fs::read(&existing).unwrap(), b"keep me");
+
+        // No temporary files are left behind
+        let mut names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["existing.bin", "plain.bin", "plain.bin.qcfe"]);
+
+        // A successful decryption replaces the file
+        let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem512)
+            .generate()
+            .unwrap();
+        encrypt_file(
+            plaintext.to_str().unwrap(),
+            encrypted.to_str().unwrap(),
+            &pk,
+        )
+        .unwrap();
+        decrypt_file(encrypted.to_str().unwrap(), existing.to_str().unwrap(), &sk).unwrap();
+        assert_eq!(std::fs::read(&existing).unwrap(), b"secret");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,13 +1,13 @@
 use crate::kdf::common::kdf_type::KdfType;
 
-/// A trait to get the OID of a DSA
+/// A trait to get the OID of a KDF
 pub trait Oid {
-    /// Get the OID for the DSA
+    /// Get the OID for the KDF
     ///
     /// # Returns
     ///
-    /// The OID for the DSA as a string
-    fn get_oid(&self) -> String;
+    /// The OID for the KDF as a string, or `None` if it has no OID
+    fn get_oid(&self) -> Option<String>;
 }
 
 impl Oid for KdfType {
@@ -15,9 +15,10 @@ impl Oid for KdfType {
     ///
     /// # Returns
     ///
-    /// The OID for the kdF
-    fn get_oid(&self) -> String {
-        match self {
+    /// The OID for the KDF, or `None` for `KdfType::Direct`, which is not a KDF. Its
+    /// KEMRecipientInfo has no `kdf` field.
+    fn get_oid(&self) -> Option<String> {
+        let oid = match self {
             KdfType::HkdfWithSha256 => "1.2.840.113549.1.9.16.3.28",
             KdfType::HkdfWithSha384 => "1.2.840.113549.1.9.16.3.29",
             KdfType::HkdfWithSha512 => "1.2.840.113549.1.9.16.3.30",
@@ -25,7 +26,8 @@ impl Oid for KdfType {
             KdfType::Kmac256 => "2.16.840.1.101.3.4.2.22",
             KdfType::Shake128 => "2.16.840.1.101.3.4.2.11",
             KdfType::Shake256 => "2.16.840.1.101.3.4.2.12",
-        }
-        .to_string()
+            KdfType::Direct => return None,
+        };
+        Some(oid.to_string())
     }
 }
@@ -7,8 +7,8 @@ use crate::kdf::common::kdf_type::KdfType;
 pub struct KdfInfo {
     /// The type of KDF
     pub kdf_type: KdfType,
-    /// The OID of the KDF
-    pub oid: String,
+    /// The OID of the KDF, which is `None` for `KdfType::Direct`
+    pub oid: Option<String>,
 }
 
 impl KdfInfo {
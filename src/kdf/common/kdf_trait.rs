@@ -21,7 +21,7 @@ pub trait Kdf {
     {
         let all_kdf_types = KdfType::all();
         for kdf_type in all_kdf_types {
-            if kdf_type.get_oid() == oid {
+            if kdf_type.get_oid().as_deref() == Some(oid) {
                 let kdf = Self::new(kdf_type)?;
                 return Ok(kdf);
             }
@@ -19,6 +19,12 @@ pub enum KdfType {
     Shake128,
     /// Shake 256
     Shake256,
+    /// No KDF, the KEM shared secret is used as the key-encryption key
+    ///
+    /// The `kdf` of the KEMRecipientInfo is absent, as in the envelopes of implementations
+    /// without a KDF layer. RFC 9629 requires a KDF, so this is for interoperability only. The
+    /// shared secret must have the key length of the key wrap, and the UKM is not used.
+    Direct,
 }
 
 impl KdfType {
@@ -27,11 +33,26 @@ impl KdfType {
         KdfType::iter().collect()
     }
 
-    /// Get the OID of the KDF
+    /// Get the KDF for an OID
+    ///
+    /// `KdfType::Direct` has no OID, so it's never returned. An absent `kdf` field of a
+    /// KEMRecipientInfo has to be mapped to it by the caller.
     pub fn from_oid(oid: &str) -> Option<KdfType> {
         let all_kdf_types = KdfType::all();
         all_kdf_types
             .into_iter()
-            .find(|kdf_type| kdf_type.get_oid() == oid)
+            .find(|kdf_type| kdf_type.get_oid().as_deref() == Some(oid))
+    }
+
+    /// Get the security strength of the KDF in bits, which is the strength of its hash or PRF
+    pub(crate) fn security_strength(&self) -> u16 {
+        match self {
+            KdfType::HkdfWithSha256 | KdfType::Kmac256 | KdfType::Shake256 => 256,
+            KdfType::HkdfWithSha384 => 384,
+            KdfType::HkdfWithSha512 => 512,
+            KdfType::Kmac128 | KdfType::Shake128 => 128,
+            // The strength is the one of the shared secret, which is never suggested
+            KdfType::Direct => 0,
+        }
     }
 }
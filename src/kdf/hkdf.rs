@@ -12,6 +12,75 @@ pub struct Hkdf {
     kdf_type: KdfType,
 }
 
+/// Run an HKDF step with the hash function of the KDF type
+macro_rules! with_hash {
+    ($kdf_type:expr, $hash:ident => $body:expr) => {
+        match $kdf_type {
+            KdfType::HkdfWithSha256 => {
+                type $hash = sha2::Sha256;
+                $body
+            }
+            KdfType::HkdfWithSha384 => {
+                type $hash = sha2::Sha384;
+                $body
+            }
+            KdfType::HkdfWithSha512 => {
+                type $hash = sha2::Sha512;
+                $body
+            }
+            _ => Err(QubitCryptError::NotImplemented),
+        }
+    };
+}
+
+impl Hkdf {
+    /// HKDF-Extract, as defined in
+    /// [RFC 5869 § 2.2](https://datatracker.ietf.org/doc/html/rfc5869#section-2.2)
+    ///
+    /// # Arguments
+    ///
+    /// * `salt` - Optional salt. A missing salt is a string of zeros of the hash length.
+    /// * `ikm` - The input keying material
+    ///
+    /// # Returns
+    ///
+    /// The pseudorandom key (PRK), which has the hash length
+    pub fn extract(&self, salt: Option<&[u8]>, ikm: &[u8]) -> Result<Vec<u8>> {
+        with_hash!(self.kdf_type, H => {
+            let (prk, _) = hkdf::Hkdf::<H>::extract(salt, ikm);
+            Ok(prk.to_vec())
+        })
+    }
+
+    /// HKDF-Expand, as defined in
+    /// [RFC 5869 § 2.3](https://datatracker.ietf.org/doc/html/rfc5869#section-2.3)
+    ///
+    /// # Arguments
+    ///
+    /// * `prk` - The pseudorandom key, usually the output of `extract`
+    /// * `info` - The context and application specific information
+    /// * `length` - The length of the output keying material
+    ///
+    /// # Returns
+    ///
+    /// The output keying material (OKM)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::KdfError` will be returned if the PRK is shorter than the hash length,
+    /// `QubitCryptError::InvalidHkdfLength` will be returned if the length is more than 255
+    /// times the hash length
+    pub fn expand(&self, prk: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>> {
+        with_hash!(self.kdf_type, H => {
+            let hkdf = hkdf::Hkdf::<H>::from_prk(prk).map_err(|_| QubitCryptError::KdfError)?;
+            let mut okm: Vec<u8> = vec![0; length];
+            hkdf.expand(info, &mut okm)
+                .map_err(|_| QubitCryptError::InvalidHkdfLength)?;
+            Ok(okm)
+        })
+    }
+}
+
 impl Kdf for Hkdf {
     fn new(kdf_type: KdfType) -> Result<Hkdf> {
         match kdf_type {
@@ -22,6 +91,8 @@ impl Kdf for Hkdf {
         }
     }
 
+    /// HKDF-Extract-then-Expand, as defined in
+    /// [RFC 5869 § 2](https://datatracker.ietf.org/doc/html/rfc5869#section-2)
     fn derive(
         &self,
         ikm: &[u8],
@@ -29,30 +100,8 @@ impl Kdf for Hkdf {
         length: usize,
         salt: Option<&[u8]>,
     ) -> Result<Vec<u8>> {
-        match self.kdf_type {
-            KdfType::HkdfWithSha256 => {
-                let prk = hkdf::Hkdf::<sha2::Sha256>::new(salt, ikm);
-                let mut okm: Vec<u8> = vec![0; length];
-                prk.expand(info, &mut okm)
-                    .map_err(|_| QubitCryptError::InvalidHkdfLength)?;
-                Ok(okm)
-            }
-            KdfType::HkdfWithSha512 => {
-                let prk = hkdf::Hkdf::<sha2::Sha512>::new(salt, ikm);
-                let mut okm: Vec<u8> = vec![0; length];
-                prk.expand(info, &mut okm)
-                    .map_err(|_| QubitCryptError::InvalidHkdfLength)?;
-                Ok(okm)
-            }
-            KdfType::HkdfWithSha384 => {
-                let prk = hkdf::Hkdf::<sha2::Sha384>::new(salt, ikm);
-                let mut okm: Vec<u8> = vec![0; length];
-                prk.expand(info, &mut okm)
-                    .map_err(|_| QubitCryptError::InvalidHkdfLength)?;
-                Ok(okm)
-            }
-            _ => Err(QubitCryptError::NotImplemented),
-        }
+        let prk = self.extract(salt, ikm)?;
+        self.expand(&prk, info, length)
     }
 
     fn get_kdf_info(&self) -> super::common::kdf_info::KdfInfo {
@@ -100,4 +149,65 @@ mod tests {
         assert!(okm.is_err());
         assert_eq!(okm.unwrap_err(), QubitCryptError::InvalidHkdfLength);
     }
+
+    #[test]
+    fn test_rfc5869_vectors() {
+        // Test cases 1 to 3 of RFC 5869 Appendix A, the other cases are for SHA-1
+        let range = |start: u8, end: u8| (start..=end).collect::<Vec<u8>>();
+        let vectors = [
+            (
+                vec![0x0b; 22],
+                Some(range(0x00, 0x0c)),
+                range(0xf0, 0xf9),
+                "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf\
+                 34007208d5b887185865",
+            ),
+            (
+                range(0x00, 0x4f),
+                Some(range(0x60, 0xaf)),
+                range(0xb0, 0xff),
+                "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+                "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c\
+                 59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71\
+                 cc30c58179ec3e87c14c01d5c1f3434f1d87",
+            ),
+            (
+                vec![0x0b; 22],
+                None,
+                Vec::new(),
+                "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+                 9d201395faa4b61a96c8",
+            ),
+        ];
+
+        let hkdf = Hkdf::new(KdfType::HkdfWithSha256).unwrap();
+        for (ikm, salt, info, prk, okm) in vectors {
+            let prk = hex::decode(prk).unwrap();
+            let okm = hex::decode(okm).unwrap();
+            assert_eq!(hkdf.extract(salt.as_deref(), &ikm).unwrap(), prk);
+            assert_eq!(hkdf.expand(&prk, &info, okm.len()).unwrap(), okm);
+            assert_eq!(
+                hkdf.derive(&ikm, &info, okm.len(), salt.as_deref())
+                    .unwrap(),
+                okm
+            );
+            // A missing salt is the same as a salt of zeros
+            if salt.is_none() {
+                assert_eq!(hkdf.extract(Some(&[0u8; 32]), &ikm).unwrap(), prk);
+            }
+        }
+
+        // Salt and info are separate inputs
+        let ikm = vec![0x0b; 22];
+        let okm = hkdf.derive(&ikm, b"ab", 32, Some(b"cd")).unwrap();
+        assert_ne!(hkdf.derive(&ikm, b"cd", 32, Some(b"ab")).unwrap(), okm);
+        assert_ne!(hkdf.derive(&ikm, b"abcd", 32, None).unwrap(), okm);
+
+        assert_eq!(
+            hkdf.expand(&[0u8; 31], b"", 32).unwrap_err(),
+            QubitCryptError::KdfError
+        );
+    }
 }
@@ -37,6 +37,7 @@ impl Kdf for KdfManager {
             _ if HKDF_TYPES.contains(&kdf_type) => KdfManager::Hkdf(Hkdf::new(kdf_type)?),
             _ if KMAC_TYPES.contains(&kdf_type) => KdfManager::Kmac(Kmac::new(kdf_type)?),
             _ if SHA3_TYPES.contains(&kdf_type) => KdfManager::Sha3(Sha3Kdf::new(kdf_type)?),
+            KdfType::Direct => return Err(QubitCryptError::NotAKdf),
             _ => {
                 return Err(QubitCryptError::NotImplemented);
             }
@@ -115,4 +116,28 @@ mod tests {
         let result = kmac.derive(ikm, info, length, Some(salt)).unwrap();
         assert_eq!(result.len(), length);
     }
+
+    #[test]
+    fn test_direct() {
+        use crate::kdf::common::config::oids::Oid;
+
+        // Direct has no OID and no KDF
+        assert_eq!(KdfType::Direct.get_oid(), None);
+        assert!(matches!(
+            KdfManager::new(KdfType::Direct),
+            Err(QubitCryptError::NotAKdf)
+        ));
+        assert_eq!(KdfType::from_oid(""), None);
+        assert!(matches!(
+            KdfManager::new_from_oid(""),
+            Err(QubitCryptError::InvalidOid)
+        ));
+
+        // The other KDFs round-trip through their OIDs
+        for kdf_type in KdfType::all() {
+            if let Some(oid) = kdf_type.get_oid() {
+                assert_eq!(KdfType::from_oid(&oid), Some(kdf_type));
+            }
+        }
+    }
 }
@@ -121,4 +121,20 @@ mod tests {
         let okm = shake.derive(ikm, info, length, None).unwrap();
         assert_eq!(okm.len(), length);
     }
+
+    #[test]
+    fn test_shake_256_vector() {
+        // SHAKE256 of the empty string (FIPS 202 / NIST CAVP)
+        let shake = Sha3Kdf::new(KdfType::Shake256).unwrap();
+        let okm = shake.derive(b"", b"", 32, None).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f"
+        );
+
+        // Unlike HKDF, SHAKE has no extract step: the salt, the input keying material and the
+        // info are absorbed in this order, as X-Wing expects for its empty info and salt
+        let okm = shake.derive(b"ikm", b"info", 64, Some(b"salt")).unwrap();
+        assert_eq!(okm, shake.derive(b"saltikminfo", b"", 64, None).unwrap());
+    }
 }
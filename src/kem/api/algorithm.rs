@@ -1,4 +1,5 @@
 use crate::kem::common::{config::oids::Oid, kem_type::KemType};
+use crate::utils::oid_map::OidMap;
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
 
@@ -29,6 +30,15 @@ pub enum KemAlgorithm {
     MlKem1024P384,
     MlKem1024BrainpoolP384r1,
     MlKem1024X448,
+
+    /// Pure traditional KEMs (ECDH-KEM)
+    X25519,
+    X448,
+
+    /// The X25519MLKEM768 hybrid of TLS 1.3. Its OID is experimental, see `KemType`
+    X25519MlKem768,
+    /// X-Wing (draft-connolly-cfrg-xwing-kem)
+    XWing,
 }
 
 impl KemAlgorithm {
@@ -64,6 +74,13 @@ impl KemAlgorithm {
             KemAlgorithm::MlKem1024P384 => KemType::MlKem1024P384,
             KemAlgorithm::MlKem1024BrainpoolP384r1 => KemType::MlKem1024BrainpoolP384r1,
             KemAlgorithm::MlKem1024X448 => KemType::MlKem1024X448,
+
+            // Pure traditional KEMs
+            KemAlgorithm::X25519 => KemType::X25519,
+            KemAlgorithm::X448 => KemType::X448,
+
+            KemAlgorithm::X25519MlKem768 => KemType::X25519MlKem768,
+            KemAlgorithm::XWing => KemType::XWing,
         }
     }
 
@@ -75,7 +92,13 @@ impl KemAlgorithm {
     pub fn is_composite(&self) -> bool {
         !matches!(
             self,
-            KemAlgorithm::MlKem512 | KemAlgorithm::MlKem768 | KemAlgorithm::MlKem1024
+            KemAlgorithm::MlKem512
+                | KemAlgorithm::MlKem768
+                | KemAlgorithm::MlKem1024
+                | KemAlgorithm::X25519
+                | KemAlgorithm::X448
+                | KemAlgorithm::X25519MlKem768
+                | KemAlgorithm::XWing
         )
     }
 
@@ -98,9 +121,7 @@ impl KemAlgorithm {
     ///
     /// The KEM algorithm corresponding to the OID, or None if the OID is not recognized
     pub fn from_oid(oid: &str) -> Option<KemAlgorithm> {
-        KemAlgorithm::all()
-            .iter()
-            .find(|x| x.get_oid() == oid)
-            .cloned()
+        static OIDS: OidMap<KemAlgorithm> = OidMap::new();
+        OIDS.get(oid, KemAlgorithm::all, KemAlgorithm::get_oid)
     }
 }
@@ -0,0 +1,194 @@
+use zeroize::Zeroizing;
+
+use crate::kem::api::algorithm::KemAlgorithm;
+use crate::kem::common::kem_info::KemInfo;
+use crate::kem::common::kem_trait::Kem;
+use crate::kem::kem_manager::KemManager;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// A KEM with reusable output buffers, for encapsulating and decapsulating in a loop
+///
+/// `KemManager::encap` and `KemManager::decap` return new vectors for every call. A
+/// `KemContext` allocates its buffers once, sized from the `KemInfo` of the algorithm, and
+/// `encap_reuse` and `decap_reuse` return slices into them. For ML-KEM the outputs are written
+/// straight into the buffers. The other KEMs still allocate internally and their outputs are
+/// copied, so the buffers are only reused, not the internal allocations. KEMs whose
+/// ciphertext length depends on the key (RSA) size the ciphertext buffer on the first call.
+///
+/// **Buffer lifetime:** the returned slices borrow the context mutably, so they are valid until
+/// the next call on the context and can't be held across it. The next call overwrites them,
+/// so copy a shared secret or ciphertext that must outlive it. `encap_reuse` and `decap_reuse`
+/// share the shared secret buffer. The shared secret buffer is zeroized when the context is
+/// dropped.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::kems::{dyn_kem_from_oid, KemAlgorithm, KemContext};
+///
+/// let (pk, sk) = dyn_kem_from_oid(&KemAlgorithm::MlKem768.get_oid()).unwrap().key_gen().unwrap();
+/// let mut ctx = KemContext::new(KemAlgorithm::MlKem768).unwrap();
+/// for _ in 0..3 {
+///     let (ss, ct) = ctx.encap_reuse(&pk).unwrap();
+///     let (ss, ct) = (ss.to_vec(), ct.to_vec());
+///     assert_eq!(ctx.decap_reuse(&sk, &ct).unwrap(), ss.as_slice());
+/// }
+/// ```
+pub struct KemContext {
+    kem: KemManager,
+    ss: Zeroizing<Vec<u8>>,
+    ct: Vec<u8>,
+}
+
+impl KemContext {
+    /// Create a new KEM context with buffers for the specified algorithm
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The KEM algorithm
+    ///
+    /// # Returns
+    ///
+    /// The new KEM context
+    pub fn new(algorithm: KemAlgorithm) -> Result<KemContext> {
+        let kem = KemManager::new(algorithm.get_kem_type())?;
+        let kem_info = kem.get_kem_info();
+        Ok(KemContext {
+            kem,
+            ss: Zeroizing::new(vec![0u8; kem_info.ss_byte_len]),
+            ct: vec![0u8; kem_info.ct_byte_len.unwrap_or(0)],
+        })
+    }
+
+    /// Get KEM metadata information such as the key lengths,
+    /// size of ciphertext, etc.
+    ///
+    /// # Returns
+    ///
+    /// A structure containing metadata about the KEM
+    pub fn get_kem_info(&self) -> KemInfo {
+        self.kem.get_kem_info()
+    }
+
+    /// Encapsulate a public key into the buffers of the context
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct), valid until the next call
+    /// on the context
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `encap`
+    pub fn encap_reuse(&mut self, pk: &[u8]) -> Result<(&[u8], &[u8])> {
+        match &self.kem {
+            KemManager::Ml(kem) => kem.encap_into(pk, &mut self.ss, &mut self.ct)?,
+            _ => {
+                let (ss, ct) = self.kem.encap(pk)?;
+                let ss = Zeroizing::new(ss);
+                self.ss.clear();
+                self.ss.extend_from_slice(&ss);
+                self.ct.clear();
+                self.ct.extend_from_slice(&ct);
+            }
+        }
+        Ok((&self.ss, &self.ct))
+    }
+
+    /// Decapsulate a ciphertext into the shared secret buffer of the context
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    ///
+    /// # Returns
+    ///
+    /// The shared secret, valid until the next call on the context
+    ///
+    /// # Errors
+    ///
+    /// The same errors as `decap`
+    pub fn decap_reuse(&mut self, sk: &[u8], ct: &[u8]) -> Result<&[u8]> {
+        match &self.kem {
+            KemManager::Ml(kem) => kem.decap_into(sk, ct, &mut self.ss)?,
+            _ => {
+                let ss = Zeroizing::new(self.kem.decap(sk, ct)?);
+                self.ss.clear();
+                self.ss.extend_from_slice(&ss);
+            }
+        }
+        Ok(&self.ss)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kems::KemKeyGenerator;
+
+    #[test]
+    fn test_kem_context() {
+        for algorithm in [
+            KemAlgorithm::MlKem512,
+            KemAlgorithm::MlKem1024,
+            KemAlgorithm::MlKem768X25519,
+            KemAlgorithm::MlKem768Rsa2048,
+        ] {
+            let (pk, sk) = KemKeyGenerator::new(algorithm).generate().unwrap();
+            let mut ctx = KemContext::new(algorithm).unwrap();
+            let kem_info = ctx.get_kem_info();
+            let mut kem = KemManager::new(algorithm.get_kem_type()).unwrap();
+
+            let mut last_ct = Vec::new();
+            for _ in 0..3 {
+                let (ss, ct) = ctx.encap_reuse(pk.get_key()).unwrap();
+                assert_eq!(ss.len(), kem_info.ss_byte_len);
+                if let Some(ct_len) = kem_info.ct_byte_len {
+                    assert_eq!(ct.len(), ct_len);
+                }
+                assert_ne!(ct, last_ct.as_slice());
+                let (ss, ct) = (ss.to_vec(), ct.to_vec());
+                assert_eq!(kem.decap(sk.get_key(), &ct).unwrap(), ss);
+                assert_eq!(ctx.decap_reuse(sk.get_key(), &ct).unwrap(), ss.as_slice());
+                last_ct = ct;
+            }
+
+            // The allocating API and the context agree
+            let (ss, ct) = kem.encap(pk.get_key()).unwrap();
+            assert_eq!(ctx.decap_reuse(sk.get_key(), &ct).unwrap(), ss.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_kem_context_reuses_buffers() {
+        let (pk, sk) = KemKeyGenerator::new(KemAlgorithm::MlKem768)
+            .generate()
+            .unwrap();
+        let mut ctx = KemContext::new(KemAlgorithm::MlKem768).unwrap();
+        let ss_ptr = ctx.ss.as_ptr();
+        let ct_ptr = ctx.ct.as_ptr();
+        for _ in 0..3 {
+            let ct = ctx.encap_reuse(pk.get_key()).unwrap().1.to_vec();
+            ctx.decap_reuse(sk.get_key(), &ct).unwrap();
+            assert_eq!(ctx.ss.as_ptr(), ss_ptr);
+            assert_eq!(ctx.ct.as_ptr(), ct_ptr);
+        }
+
+        // Errors leave the context usable
+        assert_eq!(
+            ctx.encap_reuse(&[0u8; 10]).err(),
+            Some(QubitCryptError::InvalidPublicKey)
+        );
+        assert_eq!(
+            ctx.decap_reuse(sk.get_key(), &[0u8; 10]).err(),
+            Some(QubitCryptError::InvalidCiphertext)
+        );
+        assert!(ctx.encap_reuse(pk.get_key()).is_ok());
+    }
+}
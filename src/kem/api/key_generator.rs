@@ -3,6 +3,8 @@ use crate::asn1::public_key::PublicKey;
 use crate::errors;
 use crate::kem::common::config::oids::Oid;
 use crate::kem::{api::algorithm::KemAlgorithm, common::kem_trait::Kem, kem_manager::KemManager};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
 // Change the alias to use `Box<dyn error::Error>`.
 type Result<T> = std::result::Result<T, errors::QubitCryptError>;
@@ -54,4 +56,68 @@ impl KemKeyGenerator {
             .map_err(|_| errors::QubitCryptError::KeyPairGenerationFailed)?;
         Ok((pk, sk))
     }
+
+    /// Generate `n` keypairs using the default RNG
+    ///
+    /// The keypairs share one manager, instead of creating one per call of `generate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn generate_batch(&mut self, n: usize) -> Result<Vec<(PublicKey, PrivateKey)>> {
+        let mut kem_manager = self.new_manager()?;
+        let keys = kem_manager
+            .key_gen_batch(n)
+            .map_err(|_| errors::QubitCryptError::KeyPairGenerationFailed)?;
+        self.to_key_pairs(keys)
+    }
+
+    /// Generate `n` keypairs from a seed
+    ///
+    /// The same seed always gives the same keypairs in the same order, e.g. for reproducible
+    /// test fixtures. The seed must be kept as secret as the private keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    /// * `seed` - The seed of the RNG
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn generate_batch_from_seed(
+        &mut self,
+        n: usize,
+        seed: [u8; 32],
+    ) -> Result<Vec<(PublicKey, PrivateKey)>> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        let mut kem_manager = self.new_manager()?;
+        let keys = kem_manager
+            .key_gen_batch_with_rng(n, &mut rng)
+            .map_err(|_| errors::QubitCryptError::KeyPairGenerationFailed)?;
+        self.to_key_pairs(keys)
+    }
+
+    /// Create the manager of the algorithm
+    fn new_manager(&self) -> Result<KemManager> {
+        KemManager::new(self.algorithm.get_kem_type())
+    }
+
+    /// Wrap raw keypairs in the public and private key types
+    fn to_key_pairs(&self, keys: Vec<(Vec<u8>, Vec<u8>)>) -> Result<Vec<(PublicKey, PrivateKey)>> {
+        let oid = self.algorithm.get_kem_type().get_oid();
+        keys.iter()
+            .map(|(pk, sk)| {
+                let pk = PublicKey::new(&oid, pk)
+                    .map_err(|_| errors::QubitCryptError::KeyPairGenerationFailed)?;
+                let sk = PrivateKey::new(&oid, sk)
+                    .map_err(|_| errors::QubitCryptError::KeyPairGenerationFailed)?;
+                Ok((pk, sk))
+            })
+            .collect()
+    }
 }
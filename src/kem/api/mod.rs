@@ -1,2 +1,3 @@
 pub mod algorithm;
+pub mod kem_context;
 pub mod key_generator;
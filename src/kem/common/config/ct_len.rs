@@ -53,6 +53,7 @@ impl CTLen for KemType {
             // KemType::MlKem1024BrainpoolP384r1 => Some(1568 + 97 + 10),
             // KemType::MlKem1024X448 => Some(1568 + 56 + 10),
             KemType::XWing => Some(1120),
+            KemType::X25519MlKem768 => Some(1088 + 32),
         }
     }
 }
@@ -1,5 +1,6 @@
 pub mod ct_len;
 pub mod oids;
 pub mod pk_len;
+pub mod security_category;
 pub mod sk_len;
 pub mod ss_len;
@@ -59,9 +59,15 @@ impl Oid for KemType {
             KemType::MlKem1024 => "2.16.840.1.101.3.4.4.3",
 
             // XWing
-            // TODO: XWing doesn't have an OID yet
-            // this should be updated when an OID is assigned
-            KemType::XWing => "2.16.840.1.101.3.4.4.2.0",
+            // id-XWing of draft-connolly-cfrg-xwing-kem
+            KemType::XWing => "1.3.6.1.4.1.62253.25722",
+
+            // X25519MLKEM768
+            // X25519MLKEM768 is a TLS group (0x11EC) without an assigned OID. This is an
+            // experimental OID under the UUID arc (ITU-T X.667), derived from the UUID
+            // cbb33eb9-920f-4546-b4bb-df997414b194. It is private to this crate and not
+            // interoperable, it should be replaced when an OID is assigned.
+            KemType::X25519MlKem768 => "2.25.270763976493824420356241782849320300948",
         }
         .to_string()
     }
@@ -54,6 +54,7 @@ impl PKLen for KemType {
             // KemType::MlKem1024BrainpoolP384r1 => Some(1568 + 97 + 12),
             // KemType::MlKem1024X448 => Some(1568 + 56 + 12),
             KemType::XWing => Some(1216),
+            KemType::X25519MlKem768 => Some(1184 + 32),
         }
     }
 }
@@ -0,0 +1,62 @@
+use crate::kem::common::kem_type::KemType;
+
+/// A trait to get the NIST security category of a KEM
+pub trait SecurityCategory {
+    /// Get the NIST security category
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    fn get_security_category(&self) -> u8;
+}
+
+impl SecurityCategory for KemType {
+    /// Get the NIST security category
+    ///
+    /// NIST categories describe the security against a quantum adversary, so traditional
+    /// algorithms are given 0 whatever their classical strength. Composite algorithms are given
+    /// the category of their post-quantum component.
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    fn get_security_category(&self) -> u8 {
+        match self {
+            // No security against a quantum adversary
+            KemType::P256 => 0,
+            KemType::P384 => 0,
+            KemType::X25519 => 0,
+            KemType::BrainpoolP256r1 => 0,
+            KemType::BrainpoolP384r1 => 0,
+            KemType::X448 => 0,
+            KemType::RsaOAEP2048 => 0,
+            KemType::RsaOAEP3072 => 0,
+            KemType::RsaOAEP4096 => 0,
+            KemType::MlKem512 => 1,
+            KemType::MlKem768 => 3,
+            KemType::MlKem1024 => 5,
+
+            // A composite has the category of its post-quantum component
+            KemType::MlKem512P256
+            | KemType::MlKem512BrainpoolP256r1
+            | KemType::MlKem512X25519
+            | KemType::MlKem512Rsa2048
+            | KemType::MlKem512Rsa3072
+            | KemType::MlKem768P256
+            | KemType::MlKem768Rsa2048
+            | KemType::MlKem768Rsa3072
+            | KemType::MlKem768Rsa4096
+            | KemType::MlKem768X25519
+            | KemType::MlKem768P384
+            | KemType::MlKem768BrainpoolP256r1
+            | KemType::MlKem1024P384
+            | KemType::MlKem1024BrainpoolP384r1
+            | KemType::MlKem1024X448 => self
+                .get_pq_type()
+                .map_or(0, |pq_type| pq_type.get_security_category()),
+            // ML-KEM-768 and X25519
+            KemType::XWing => 3,
+            KemType::X25519MlKem768 => 3,
+        }
+    }
+}
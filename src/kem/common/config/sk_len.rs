@@ -61,6 +61,8 @@ impl SKLen for KemType {
             // KemType::MlKem1024BrainpoolP384r1 => Some(3168 + 48 + 151),
             // KemType::MlKem1024X448 => Some(3168 + 56 + 110),
             KemType::XWing => Some(32),
+            // The ML-KEM-768 decapsulation key and the X25519 private key
+            KemType::X25519MlKem768 => Some(2400 + 32),
         }
     }
 }
@@ -64,6 +64,8 @@ impl SSLen for KemType {
             // KemType::MlKem1024BrainpoolP384r1 => 64,
             // KemType::MlKem1024X448 => 64,
             KemType::XWing => 32,
+            // The shared secrets are concatenated, not combined
+            KemType::X25519MlKem768 => 32 + 32,
         }
     }
 }
@@ -0,0 +1,294 @@
+use rand_core::CryptoRngCore;
+
+use crate::kem::common::kem_info::KemInfo;
+use crate::kem::common::kem_trait::Kem;
+use crate::kem::common::shared_secret::SharedSecret;
+use crate::kem::kem_manager::KemManager;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// An object-safe version of the KEM trait.
+///
+/// The `Kem` trait has constructors which require `Self: Sized` and a generic RNG
+/// parameter, so it cannot be used as a trait object. This trait exposes the same
+/// operations without the constructors, so that a KEM negotiated at runtime can be
+/// held as a `Box<dyn DynKem>`. Use `dyn_kem_from_oid` to create one.
+///
+/// # Example
+/// ```
+/// use qubitcrypt::kems::{dyn_kem_from_oid, DynKem, KemAlgorithm};
+///
+/// let mut kem: Box<dyn DynKem> = dyn_kem_from_oid(&KemAlgorithm::MlKem768.get_oid()).unwrap();
+/// let (pk, sk) = kem.key_gen().unwrap();
+/// let (ss, ct) = kem.encap(&pk).unwrap();
+/// assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+/// ```
+pub trait DynKem {
+    /// Generate a keypair using the default random number generator
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the public and secret keys (pk, sk)
+    fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)>;
+
+    /// Generate a keypair with a specified random number generator
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to use
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the public and secret keys (pk, sk)
+    fn key_gen_with_rng(&mut self, rng: &mut dyn CryptoRngCore) -> Result<(Vec<u8>, Vec<u8>)>;
+
+    /// Encapsulate a public key
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap(&mut self, pk: &[u8]) -> Result<(Vec<u8>, Vec<u8>)>;
+
+    /// Encapsulate a public key with caller-supplied coins instead of fresh randomness, for
+    /// known answer tests
+    ///
+    /// The coins are the 32 byte message `m` for ML-KEM and the ephemeral private key for
+    /// the EC based KEMs. A KEM which doesn't support it returns
+    /// `QubitCryptError::NotImplemented`, which is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `coins` - The randomness of the encapsulation
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap_deterministic(&mut self, _pk: &[u8], _coins: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        Err(QubitCryptError::NotImplemented)
+    }
+
+    /// Decapsulate a ciphertext
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    ///
+    /// # Returns
+    ///
+    /// The shared secret
+    fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>>;
+
+    /// Encapsulate a public key, returning the shared secret as a `SharedSecret`
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap_typed(&mut self, pk: &[u8]) -> Result<(SharedSecret, Vec<u8>)> {
+        let (ss, ct) = self.encap(pk)?;
+        Ok((SharedSecret::new(ss), ct))
+    }
+
+    /// Decapsulate a ciphertext, returning the shared secret as a `SharedSecret`
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    ///
+    /// # Returns
+    ///
+    /// The shared secret
+    fn decap_typed(&self, sk: &[u8], ct: &[u8]) -> Result<SharedSecret> {
+        Ok(SharedSecret::new(self.decap(sk, ct)?))
+    }
+
+    /// Check that a ciphertext is well-formed for a public key, before decapsulating it
+    ///
+    /// The built-in KEMs check what can be checked without the private key, see
+    /// `KemManager::validate_ciphertext`. For a KEM whose ciphertexts are opaque this is a
+    /// no-op, which is the default.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key the ciphertext was produced for
+    /// * `ct` - The ciphertext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` will be returned if the public key is invalid,
+    /// `QubitCryptError::InvalidCiphertext` if the ciphertext is invalid
+    fn validate_ciphertext(&self, _pk: &[u8], _ct: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Get KEM metadata information such as the key lengths,
+    /// size of ciphertext, etc.
+    ///
+    /// # Returns
+    ///
+    /// A structure containing metadata about the KEM
+    fn get_kem_info(&self) -> KemInfo;
+}
+
+impl DynKem for KemManager {
+    fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+        Kem::key_gen(self)
+    }
+
+    fn key_gen_with_rng(&mut self, mut rng: &mut dyn CryptoRngCore) -> Result<(Vec<u8>, Vec<u8>)> {
+        Kem::key_gen_with_rng(self, &mut rng)
+    }
+
+    fn encap(&mut self, pk: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        Kem::encap(self, pk)
+    }
+
+    fn encap_deterministic(&mut self, pk: &[u8], coins: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        Kem::encap_deterministic(self, pk, coins)
+    }
+
+    fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+        Kem::decap(self, sk, ct)
+    }
+
+    fn get_kem_info(&self) -> KemInfo {
+        Kem::get_kem_info(self)
+    }
+
+    fn validate_ciphertext(&self, pk: &[u8], ct: &[u8]) -> Result<()> {
+        KemManager::validate_ciphertext(self, pk, ct)
+    }
+}
+
+/// Create a KEM trait object from an OID
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM
+///
+/// # Returns
+///
+/// A boxed KEM instance
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidOid` will be returned if the OID is not a known KEM OID
+pub fn dyn_kem_from_oid(oid: &str) -> Result<Box<dyn DynKem>> {
+    let kem = KemManager::new_from_oid(oid)?;
+    Ok(Box::new(kem))
+}
+
+/// Create a composite KEM trait object with an additional domain separation label
+///
+/// The label is mixed into the combiner of the composite KEM, so that secrets derived by
+/// different protocols using the same composite KEM can't collide. Encapsulation and
+/// decapsulation must use the same label. X-Wing is not supported, its label is fixed by
+/// the X-Wing specification.
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the composite KEM
+/// * `combiner_label` - The domain separation label
+///
+/// # Returns
+///
+/// A boxed KEM instance
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidOid` will be returned if the OID is not a known KEM OID,
+/// `QubitCryptError::UnsupportedOperation` will be returned if the KEM is not a composite KEM
+pub fn dyn_composite_kem_with_label(oid: &str, combiner_label: &[u8]) -> Result<Box<dyn DynKem>> {
+    let mut kem = KemManager::new_from_oid(oid)?;
+    kem.set_combiner_label(combiner_label)?;
+    Ok(Box::new(kem))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    use super::*;
+    use crate::kem::common::config::oids::Oid;
+    use crate::kem::common::kem_type::KemType;
+
+    #[test]
+    fn test_dyn_kem() {
+        let kem_types = [
+            KemType::MlKem512,
+            KemType::X25519,
+            KemType::P256,
+            KemType::MlKem768X25519,
+            KemType::MlKem768Rsa2048,
+        ];
+
+        let mut kems: Vec<Box<dyn DynKem>> = kem_types
+            .iter()
+            .map(|kem_type| dyn_kem_from_oid(&kem_type.get_oid()).unwrap())
+            .collect();
+
+        for (kem, kem_type) in kems.iter_mut().zip(kem_types.iter()) {
+            assert_eq!(kem.get_kem_info().kem_type, *kem_type);
+
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (ss, ct) = kem.encap(&pk).unwrap();
+            assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+
+            let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+            let (pk, sk) = kem.key_gen_with_rng(&mut rng).unwrap();
+            let (ss, ct) = kem.encap(&pk).unwrap();
+            assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+        }
+    }
+
+    #[test]
+    fn test_dyn_kem_typed() {
+        let mut kem = dyn_kem_from_oid(&KemType::MlKem768X25519.get_oid()).unwrap();
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (ss, ct) = kem.encap_typed(&pk).unwrap();
+        assert_eq!(kem.decap_typed(&sk, &ct).unwrap().expose(), ss.expose());
+        assert_eq!(kem.decap(&sk, &ct).unwrap(), ss.expose());
+    }
+
+    #[test]
+    fn test_dyn_composite_kem_with_label() {
+        let oid = KemType::MlKem768X25519.get_oid();
+        let mut kem = dyn_kem_from_oid(&oid).unwrap();
+        let mut kem_a = dyn_composite_kem_with_label(&oid, b"protocol A").unwrap();
+        let kem_b = dyn_composite_kem_with_label(&oid, b"protocol B").unwrap();
+
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (ss, ct) = kem.encap(&pk).unwrap();
+        let ss_a = kem_a.decap(&sk, &ct).unwrap();
+        let ss_b = kem_b.decap(&sk, &ct).unwrap();
+        assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+        assert_ne!(ss_a, ss);
+        assert_ne!(ss_b, ss);
+        assert_ne!(ss_a, ss_b);
+
+        let (ss, ct) = kem_a.encap(&pk).unwrap();
+        assert_eq!(kem_a.decap(&sk, &ct).unwrap(), ss);
+        assert_ne!(kem_b.decap(&sk, &ct).unwrap(), ss);
+
+        let result = dyn_composite_kem_with_label(&KemType::MlKem768.get_oid(), b"label");
+        assert!(matches!(result, Err(QubitCryptError::UnsupportedOperation)));
+    }
+
+    #[test]
+    fn test_dyn_kem_invalid_oid() {
+        let result = dyn_kem_from_oid("1.2.3.4");
+        assert!(matches!(result, Err(QubitCryptError::InvalidOid)));
+    }
+}
@@ -1,6 +1,8 @@
+use crate::kem::api::algorithm::KemAlgorithm;
 use crate::kem::common::config::ct_len::CTLen;
 use crate::kem::common::config::oids::Oid;
 use crate::kem::common::config::pk_len::PKLen;
+use crate::kem::common::config::security_category::SecurityCategory;
 use crate::kem::common::config::sk_len::SKLen;
 use crate::kem::common::config::ss_len::SSLen;
 use crate::kem::common::kem_type::KemType;
@@ -41,4 +43,37 @@ impl KemInfo {
             oid: kem_type.get_oid(),
         }
     }
+
+    /// Create the metadata of a custom KEM registered with `register_kem`
+    ///
+    /// The metadata is that of a built-in KEM with the OID of the custom KEM, so a variant of a
+    /// built-in KEM only needs to change the fields which differ. The KEM type, and so the
+    /// security category, stays that of the built-in KEM.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The built-in KEM the custom KEM is based on
+    /// * `oid` - The OID of the custom KEM
+    ///
+    /// # Returns
+    ///
+    /// The metadata of the custom KEM
+    pub fn new_custom(base: KemAlgorithm, oid: &str) -> KemInfo {
+        KemInfo {
+            oid: oid.to_string(),
+            ..KemInfo::new(base.get_kem_type())
+        }
+    }
+
+    /// Get the NIST security category of the KEM
+    ///
+    /// Traditional KEMs have no post-quantum security and are given 0, composite KEMs the
+    /// category of their ML-KEM component.
+    ///
+    /// # Returns
+    ///
+    /// The NIST security category (1 to 5), or 0 if it doesn't reach category 1
+    pub fn security_category(&self) -> u8 {
+        self.kem_type.get_security_category()
+    }
 }
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::kem::common::config::ct_len::CTLen;
+use crate::kem::common::config::oids::Oid;
+use crate::kem::common::config::ss_len::SSLen;
+use crate::kem::common::kem_type::KemType;
+
+/// The sizes of a KEM which are needed during negotiation
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct KemSizes {
+    /// The length of the shared secret in bytes
+    ss_byte_len: Option<usize>,
+    /// The length of the ciphertext in bytes
+    ct_byte_len: Option<usize>,
+}
+
+/// Get the table of KEM sizes keyed by OID
+///
+/// The table is built once from the same `KemType` configuration that backs `KemInfo`,
+/// so it can't drift from the values reported by a `KemManager`. Some KEM types share an
+/// OID (e.g. all RSA OAEP key sizes), in which case a size that differs between them is
+/// recorded as unknown.
+fn kem_sizes() -> &'static HashMap<String, KemSizes> {
+    static KEM_SIZES: OnceLock<HashMap<String, KemSizes>> = OnceLock::new();
+    KEM_SIZES.get_or_init(|| {
+        let mut table: HashMap<String, KemSizes> = HashMap::new();
+        for kem_type in KemType::all() {
+            let sizes = KemSizes {
+                ss_byte_len: Some(kem_type.get_ss_len()),
+                ct_byte_len: kem_type.get_ct_len(),
+            };
+            table
+                .entry(kem_type.get_oid())
+                .and_modify(|existing| {
+                    if existing.ss_byte_len != sizes.ss_byte_len {
+                        existing.ss_byte_len = None;
+                    }
+                    if existing.ct_byte_len != sizes.ct_byte_len {
+                        existing.ct_byte_len = None;
+                    }
+                })
+                .or_insert(sizes);
+        }
+        table
+    })
+}
+
+/// Get the length of the shared secret of a KEM without creating a KEM manager
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM
+///
+/// # Returns
+///
+/// The length of the shared secret in bytes, or `None` if the OID is not a known KEM OID
+pub fn kem_shared_secret_len(oid: &str) -> Option<usize> {
+    kem_sizes().get(oid).and_then(|sizes| sizes.ss_byte_len)
+}
+
+/// Get the length of the ciphertext of a KEM without creating a KEM manager
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM
+///
+/// # Returns
+///
+/// The length of the ciphertext in bytes, or `None` if the OID is not a known KEM OID or
+/// the length is not determined by the OID alone (e.g. RSA OAEP, where it depends on the
+/// key size)
+pub fn kem_ciphertext_len(oid: &str) -> Option<usize> {
+    kem_sizes().get(oid).and_then(|sizes| sizes.ct_byte_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kem::common::kem_info::KemInfo;
+
+    #[test]
+    fn test_kem_sizes_match_kem_info() {
+        for kem_type in KemType::all() {
+            let oid = kem_type.get_oid();
+            let shares_oid = KemType::all()
+                .iter()
+                .filter(|other| other.get_oid() == oid)
+                .count()
+                > 1;
+            if shares_oid {
+                continue;
+            }
+
+            let kem_info = KemInfo::new(kem_type.clone());
+            assert_eq!(kem_shared_secret_len(&oid), Some(kem_info.ss_byte_len));
+            assert_eq!(kem_ciphertext_len(&oid), kem_info.ct_byte_len);
+        }
+    }
+
+    #[test]
+    fn test_kem_sizes_shared_oid() {
+        // All RSA OAEP key sizes share an OID, the ciphertext length depends on the key
+        let oid = KemType::RsaOAEP2048.get_oid();
+        assert_eq!(kem_shared_secret_len(&oid), Some(32));
+        assert_eq!(kem_ciphertext_len(&oid), None);
+
+        assert_eq!(kem_shared_secret_len("1.2.3.4"), None);
+        assert_eq!(kem_ciphertext_len("1.2.3.4"), None);
+    }
+}
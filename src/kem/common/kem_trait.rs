@@ -2,6 +2,7 @@ use rand_core::CryptoRngCore;
 
 use crate::kem::common::kem_info::KemInfo;
 use crate::kem::common::kem_type::KemType;
+use crate::kem::common::shared_secret::SharedSecret;
 use crate::QubitCryptError;
 
 use crate::kem::common::config::oids::Oid;
@@ -55,6 +56,32 @@ pub trait Kem {
     /// A tuple containing the ciphertext and shared secret (ss, ct)
     fn encap(&mut self, pk: &[u8]) -> Result<(Vec<u8>, Vec<u8>)>;
 
+    /// Encapsulate a public key with caller-supplied coins instead of fresh randomness
+    ///
+    /// The same public key and coins always give the same shared secret and ciphertext, which
+    /// is what known answer tests need. Real encapsulations must use `encap`.
+    ///
+    /// The coins are the 32 byte message `m` for ML-KEM, the ephemeral private key for the EC
+    /// based KEMs and `m || eseed_x` (64 bytes) for X-Wing and X25519MLKEM768.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `coins` - The randomness of the encapsulation
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::NotImplemented` will be returned if the KEM doesn't support
+    /// deterministic encapsulation, `QubitCryptError::EncapFailed` if the coins don't have the
+    /// length the KEM expects
+    fn encap_deterministic(&mut self, _pk: &[u8], _coins: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        Err(QubitCryptError::NotImplemented)
+    }
+
     /// Decapsulate a ciphertext
     ///
     /// # Arguments
@@ -67,6 +94,34 @@ pub trait Kem {
     /// The shared secret
     fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>>;
 
+    /// Encapsulate a public key, returning the shared secret as a `SharedSecret`
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap_typed(&mut self, pk: &[u8]) -> Result<(SharedSecret, Vec<u8>)> {
+        let (ss, ct) = self.encap(pk)?;
+        Ok((SharedSecret::new(ss), ct))
+    }
+
+    /// Decapsulate a ciphertext, returning the shared secret as a `SharedSecret`
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    ///
+    /// # Returns
+    ///
+    /// The shared secret
+    fn decap_typed(&self, sk: &[u8], ct: &[u8]) -> Result<SharedSecret> {
+        Ok(SharedSecret::new(self.decap(sk, ct)?))
+    }
+
     /// Get KEM metadata information such as the key lengths,
     /// size of ciphertext, etc.
     ///
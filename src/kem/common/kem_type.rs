@@ -2,6 +2,7 @@ use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::kem::common::config::oids::Oid;
+use crate::utils::oid_map::OidMap;
 
 #[derive(Clone, Debug, PartialEq, EnumIter)]
 pub enum KemType {
@@ -65,6 +66,11 @@ pub enum KemType {
     /// id-MLKEM1024-X448
     MlKem1024X448,
     XWing,
+    /// The X25519MLKEM768 hybrid of TLS 1.3 (draft-ietf-tls-ecdhe-mlkem)
+    ///
+    /// TLS identifies it by its group (0x11EC), there is no assigned OID. Its OID is an
+    /// experimental one under the UUID arc, which is only understood by this crate.
+    X25519MlKem768,
 }
 
 impl KemType {
@@ -75,14 +81,51 @@ impl KemType {
     pub fn is_composite(&self) -> bool {
         !matches!(
             self,
-            KemType::MlKem512 | KemType::MlKem768 | KemType::MlKem1024
+            KemType::MlKem512
+                | KemType::MlKem768
+                | KemType::MlKem1024
+                | KemType::P256
+                | KemType::P384
+                | KemType::X25519
+                | KemType::BrainpoolP256r1
+                | KemType::BrainpoolP384r1
+                | KemType::X448
+                | KemType::RsaOAEP2048
+                | KemType::RsaOAEP3072
+                | KemType::RsaOAEP4096
+                | KemType::XWing
+                | KemType::X25519MlKem768
         )
     }
 
+    /// Get the post-quantum component of a composite KEM
+    ///
+    /// # Returns
+    ///
+    /// The ML-KEM type of the composite, or None if the type is not a composite
+    pub fn get_pq_type(&self) -> Option<KemType> {
+        match self {
+            KemType::MlKem512P256
+            | KemType::MlKem512BrainpoolP256r1
+            | KemType::MlKem512X25519
+            | KemType::MlKem512Rsa2048
+            | KemType::MlKem512Rsa3072 => Some(KemType::MlKem512),
+            KemType::MlKem768P256
+            | KemType::MlKem768Rsa2048
+            | KemType::MlKem768Rsa3072
+            | KemType::MlKem768Rsa4096
+            | KemType::MlKem768X25519
+            | KemType::MlKem768P384
+            | KemType::MlKem768BrainpoolP256r1 => Some(KemType::MlKem768),
+            KemType::MlKem1024P384 | KemType::MlKem1024BrainpoolP384r1 | KemType::MlKem1024X448 => {
+                Some(KemType::MlKem1024)
+            }
+            _ => None,
+        }
+    }
+
     pub fn from_oid(oid: &str) -> Option<KemType> {
-        let all_kem_types = KemType::all();
-        all_kem_types
-            .into_iter()
-            .find(|kem_type| kem_type.get_oid() == oid)
+        static OIDS: OidMap<KemType> = OidMap::new();
+        OIDS.get(oid, KemType::all, KemType::get_oid)
     }
 }
@@ -1,6 +1,9 @@
 pub mod config;
+pub mod dyn_kem;
 pub mod kdf;
 pub mod kem_info;
+pub mod kem_sizes;
 pub mod kem_trait;
 pub mod kem_type;
 pub mod macros;
+pub mod shared_secret;
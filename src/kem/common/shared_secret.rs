@@ -0,0 +1,56 @@
+use std::fmt;
+
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A shared secret produced by a KEM
+///
+/// The bytes are zeroized when the shared secret is dropped, and the `Debug`
+/// implementation does not print them, so the secret doesn't end up in logs by
+/// accident. Use `expose` to get the bytes. It deliberately does not implement
+/// `Clone`.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct SharedSecret(Vec<u8>);
+
+impl SharedSecret {
+    /// Create a new shared secret
+    ///
+    /// # Arguments
+    ///
+    /// * `ss` - The shared secret bytes
+    ///
+    /// # Returns
+    ///
+    /// A new shared secret, which takes ownership of the bytes
+    pub fn new(ss: Vec<u8>) -> Self {
+        Self(ss)
+    }
+
+    /// Get the bytes of the shared secret
+    ///
+    /// # Returns
+    ///
+    /// The shared secret bytes
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedSecret([REDACTED; {}])", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_secret_debug_is_redacted() {
+        let ss = SharedSecret::new(vec![0xab; 32]);
+        let debug = format!("{:?}", ss);
+        assert_eq!(debug, "SharedSecret([REDACTED; 32])");
+        assert!(!debug.contains("171"));
+        assert_eq!(ss.expose(), &[0xab; 32]);
+    }
+}
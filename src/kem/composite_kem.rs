@@ -24,9 +24,90 @@ pub struct CompositeKemManager {
     pq_kem: Box<KemManager>,
     /// The key derivation function
     kdf: Kdf,
+    /// An additional domain separation label for the combiner, if any
+    combiner_label: Option<Vec<u8>>,
 }
 
 impl CompositeKemManager {
+    /// Check that a composite ciphertext is well-formed for a composite public key, see
+    /// `KemManager::validate_ciphertext`
+    ///
+    /// Each component of the ciphertext is checked against the same component of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The composite public key
+    /// * `ct` - The composite ciphertext - CompositeCiphertextValue in ASN.1 format converted to
+    ///   DER
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` if the key or one of its components is invalid and
+    /// `QubitCryptError::InvalidCiphertext` if the ciphertext or one of its components is invalid
+    pub fn validate_ciphertext(&self, pk: &[u8], ct: &[u8]) -> Result<()> {
+        let c_pk = CompositePublicKey::from_der(&self.kem_info.oid, pk)?;
+        let c_ct = CompositeCiphertextValue::from_der(ct)
+            .map_err(|_| QubitCryptError::InvalidCiphertext)?;
+        self.trad_kem
+            .validate_ciphertext(&c_pk.get_trad_pk(), &c_ct.get_trad_ct())?;
+        self.pq_kem
+            .validate_ciphertext(&c_pk.get_pq_pk(), &c_ct.get_pq_ct())
+    }
+
+    /// Decapsulate a ciphertext, rejecting it if the traditional component is detectably invalid
+    ///
+    /// See `KemManager::decap_strict`. The post-quantum component still uses implicit rejection.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The composite secret key to decapsulate - CompositeKEMPrivateKey in ASN.1 format converted to DER
+    /// * `ct` - The composite ciphertext to decapsulate - CompositeCiphertextValue in ASN.1 format converted to DER
+    ///
+    /// # Returns
+    ///
+    /// The shared secret after applying the combiner function
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::DecapFailed` will be returned if the ciphertext is invalid
+    pub fn decap_strict(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+        self.decap_with(sk, ct, true)
+    }
+
+    /// Decapsulate a ciphertext, optionally using strict decapsulation for the traditional KEM
+    fn decap_with(&self, sk: &[u8], ct: &[u8], strict: bool) -> Result<Vec<u8>> {
+        // Deserialize the composite secret key
+        let c_sk = CompositePrivateKey::from_der(&self.kem_info.oid, sk)?;
+
+        // Deserialize the composite ciphertext
+        let c_ct =
+            CompositeCiphertextValue::from_der(ct).map_err(|_| QubitCryptError::DecapFailed)?;
+
+        // Decapsulate the ciphertext for the traditional KEM
+        let t_sk = c_sk.get_trad_sk()?.private_key;
+        let t_ss = if strict {
+            self.trad_kem.decap_strict(t_sk, &c_ct.get_trad_ct())?
+        } else {
+            self.trad_kem.decap(t_sk, &c_ct.get_trad_ct())?
+        };
+
+        // Decapsulate the ciphertext for the post-quantum KEM
+        let pq_ss = self
+            .pq_kem
+            .decap(c_sk.get_pq_sk()?.private_key, &c_ct.get_pq_ct())?;
+
+        // Get the trad PK
+        let t_pk = c_sk
+            .get_trad_sk()?
+            .public_key
+            .ok_or(QubitCryptError::DecapFailed)?;
+
+        // Get the shared secret using the combiner
+        let ss = self.combiner(&pq_ss, &t_ss, &c_ct.get_trad_ct(), t_pk)?;
+
+        Ok(ss)
+    }
+
     /// See the combiner function in the RFC:
     /// https://lamps-wg.github.io/draft-composite-kem/draft-ietf-lamps-pq-composite-kem.html
     ///
@@ -57,12 +138,56 @@ impl CompositeKemManager {
 
         let dom_sep = oid_to_der(&self.kem_info.oid)?;
         combined_ss.extend_from_slice(&dom_sep);
+        if let Some(label) = &self.combiner_label {
+            combined_ss.extend_from_slice(label);
+        }
 
         let ss = self.kdf.kdf(&combined_ss);
 
         Ok(ss)
     }
 
+    /// Create a composite KEM manager from its components, without a combiner label
+    ///
+    /// # Arguments
+    ///
+    /// * `kem_info` - The KEM metadata information
+    /// * `trad_kem_type` - The type of the traditional KEM
+    /// * `pq_kem_type` - The type of the post-quantum KEM
+    /// * `kdf_type` - The type of the KDF of the combiner
+    ///
+    /// # Returns
+    ///
+    /// A new composite KEM manager
+    fn from_components(
+        kem_info: KemInfo,
+        trad_kem_type: KemType,
+        pq_kem_type: KemType,
+        kdf_type: KdfType,
+    ) -> Result<Self> {
+        Ok(Self {
+            kem_info,
+            trad_kem: Box::new(KemManager::new(trad_kem_type)?),
+            pq_kem: Box::new(KemManager::new(pq_kem_type)?),
+            kdf: Kdf::new(kdf_type),
+            combiner_label: None,
+        })
+    }
+
+    /// Set an additional domain separation label for the combiner
+    ///
+    /// The label is appended to the combiner input after the OID of the composite KEM, so
+    /// that protocols built on the same composite KEM derive different shared secrets from
+    /// the same keys and ciphertexts. Both sides must use the same label, and the result is
+    /// no longer the shared secret defined by the composite KEM draft.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The domain separation label
+    pub fn set_combiner_label(&mut self, label: &[u8]) {
+        self.combiner_label = Some(label.to_vec());
+    }
+
     /// Generate a composite KEM keypair from constituent keys
     ///
     /// # Arguments
@@ -138,6 +263,139 @@ impl CompositeKemManager {
     }
 }
 
+impl CompositeKemManager {
+    /// Get the composite public key of a composite private key
+    ///
+    /// The public key of each component is derived from its private key
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The composite private key - CompositeKEMPrivateKey in ASN.1 format converted to DER
+    ///
+    /// # Returns
+    ///
+    /// The composite public key - CompositeKEMPublicKey in ASN.1 format converted to DER
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if a component can't be decoded
+    pub fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        let c_sk = CompositePrivateKey::from_der(&self.kem_info.oid, sk)?;
+        let pk_pq = self.pq_kem.get_public_key(c_sk.get_pq_sk()?.private_key)?;
+        let pk_trad = self
+            .trad_kem
+            .get_public_key(c_sk.get_trad_sk()?.private_key)?;
+
+        let c_pk = CompositePublicKey::new(&self.kem_info.oid, &pk_pq, &pk_trad);
+        c_pk.to_der()
+    }
+
+    /// Check that the components of a composite private key belong together
+    ///
+    /// The OID and the length of each component is checked against the component algorithms.
+    /// A KEM public key can't be derived from the private key, so the public key of each
+    /// component is taken from `c_pk` if provided, or else from the public key embedded in the
+    /// component, which is always present for the traditional component. Each component with
+    /// a public key must then decapsulate a ciphertext encapsulated to that public key to the
+    /// same shared secret. The embedded public key must also match `c_pk`.
+    ///
+    /// # Arguments
+    ///
+    /// * `c_sk` - The composite private key
+    /// * `c_pk` - The composite public key which should belong to the private key, if any
+    ///
+    /// # Returns
+    ///
+    /// True if the composite private key is consistent, false otherwise
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if a component can't be decoded
+    pub(crate) fn validate_private_key(
+        &self,
+        c_sk: &CompositePrivateKey,
+        c_pk: Option<&CompositePublicKey>,
+    ) -> Result<bool> {
+        if c_sk.get_oid() != self.kem_info.oid
+            || c_pk.is_some_and(|c_pk| c_pk.get_oid() != self.kem_info.oid)
+        {
+            return Ok(false);
+        }
+
+        let pq_sk = c_sk.get_pq_sk()?;
+        let trad_sk = c_sk.get_trad_sk()?;
+        let expected_pq_pk = c_pk.map(|c_pk| c_pk.get_pq_pk());
+        let expected_trad_pk = c_pk.map(|c_pk| c_pk.get_trad_pk());
+
+        for (kem, sk, expected_pk) in [
+            (&self.pq_kem, &pq_sk, expected_pq_pk),
+            (&self.trad_kem, &trad_sk, expected_trad_pk),
+        ] {
+            let info = kem.get_kem_info();
+            // The traditional component is written with the OID of the composite KEM
+            let oid = sk.algorithm.oid.to_string();
+            if (oid != info.oid && oid != self.kem_info.oid)
+                || info
+                    .sk_byte_len
+                    .is_some_and(|len| len != sk.private_key.len())
+            {
+                return Ok(false);
+            }
+
+            if let (Some(embedded), Some(expected)) = (sk.public_key, &expected_pk) {
+                if embedded != expected.as_slice() {
+                    return Ok(false);
+                }
+            }
+            let pk = match expected_pk.or_else(|| sk.public_key.map(|pk| pk.to_vec())) {
+                Some(pk) => pk,
+                None => continue,
+            };
+            if info.pk_byte_len.is_some_and(|len| len != pk.len()) {
+                return Ok(false);
+            }
+
+            let mut kem = KemManager::new(info.kem_type)?;
+            let (ss, ct) = match kem.encap(&pk) {
+                Ok(result) => result,
+                Err(_) => return Ok(false),
+            };
+            match kem.decap(sk.private_key, &ct) {
+                Ok(decapped) if openssl::memcmp::eq(&decapped, &ss) => {}
+                _ => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Encapsulate a public key, with the randomness taken from an RNG
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `rng` - The RNG to take the randomness of the encapsulation from
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    pub(crate) fn encap_with_rng(
+        &mut self,
+        pk: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let c_pk = CompositePublicKey::from_der(&self.kem_info.oid, pk)?;
+        let (t_ss, t_ct) = self.trad_kem.encap_with_rng(&c_pk.get_trad_pk(), rng)?;
+        let (pq_ss, pq_ct) = self.pq_kem.encap_with_rng(&c_pk.get_pq_pk(), rng)?;
+
+        let ct = CompositeCiphertextValue::new(&pq_ct, &t_ct);
+        let ct = ct.to_der().map_err(|_| QubitCryptError::EncapFailed)?;
+        let ss = self.combiner(&pq_ss, &t_ss, &t_ct, &c_pk.get_trad_pk())?;
+
+        Ok((ss, ct))
+    }
+}
+
 impl Kem for CompositeKemManager {
     /// Create a new KEM instance
     ///
@@ -152,99 +410,99 @@ impl Kem for CompositeKemManager {
         let kem_info = KemInfo::new(kem_type.clone());
         let result = match kem_type {
             // From old version
-            KemType::MlKem512P256 => Self {
+            KemType::MlKem512P256 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::P256)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem512)?),
-                kdf: Kdf::new(KdfType::Sha3_256),
-            },
-            KemType::MlKem512BrainpoolP256r1 => Self {
+                KemType::P256,
+                KemType::MlKem512,
+                KdfType::Sha3_256,
+            )?,
+            KemType::MlKem512BrainpoolP256r1 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::BrainpoolP256r1)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem512)?),
-                kdf: Kdf::new(KdfType::Sha3_256),
-            },
-            KemType::MlKem512X25519 => Self {
+                KemType::BrainpoolP256r1,
+                KemType::MlKem512,
+                KdfType::Sha3_256,
+            )?,
+            KemType::MlKem512X25519 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::X25519)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem512)?),
-                kdf: Kdf::new(KdfType::Sha3_256),
-            },
-            KemType::MlKem512Rsa2048 => Self {
+                KemType::X25519,
+                KemType::MlKem512,
+                KdfType::Sha3_256,
+            )?,
+            KemType::MlKem512Rsa2048 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::RsaOAEP2048)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem512)?),
-                kdf: Kdf::new(KdfType::Sha3_256),
-            },
-            KemType::MlKem512Rsa3072 => Self {
+                KemType::RsaOAEP2048,
+                KemType::MlKem512,
+                KdfType::Sha3_256,
+            )?,
+            KemType::MlKem512Rsa3072 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::RsaOAEP3072)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem512)?),
-                kdf: Kdf::new(KdfType::Sha3_256),
-            },
-            KemType::MlKem768P256 => Self {
+                KemType::RsaOAEP3072,
+                KemType::MlKem512,
+                KdfType::Sha3_256,
+            )?,
+            KemType::MlKem768P256 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::P256)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::Sha3_384),
-            },
+                KemType::P256,
+                KemType::MlKem768,
+                KdfType::Sha3_384,
+            )?,
 
             // From Editor's draft
-            KemType::MlKem768Rsa2048 => Self {
+            KemType::MlKem768Rsa2048 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::RsaOAEP2048)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::HkdfSha256),
-            },
-            KemType::MlKem768Rsa3072 => Self {
+                KemType::RsaOAEP2048,
+                KemType::MlKem768,
+                KdfType::HkdfSha256,
+            )?,
+            KemType::MlKem768Rsa3072 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::RsaOAEP3072)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::HkdfSha256),
-            },
-            KemType::MlKem768Rsa4096 => Self {
+                KemType::RsaOAEP3072,
+                KemType::MlKem768,
+                KdfType::HkdfSha256,
+            )?,
+            KemType::MlKem768Rsa4096 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::RsaOAEP4096)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::HkdfSha256),
-            },
-            KemType::MlKem768X25519 => Self {
+                KemType::RsaOAEP4096,
+                KemType::MlKem768,
+                KdfType::HkdfSha256,
+            )?,
+            // The editor's draft uses KdfType::Sha3_256
+            KemType::MlKem768X25519 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::X25519)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::Sha3_384),
-                // kdf: Kdf::new(KdfType::Sha3_256),  //In editor's draft
-            },
-            KemType::MlKem768P384 => Self {
+                KemType::X25519,
+                KemType::MlKem768,
+                KdfType::Sha3_384,
+            )?,
+            KemType::MlKem768P384 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::P384)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::HkdfSha384),
-            },
-            KemType::MlKem768BrainpoolP256r1 => Self {
+                KemType::P384,
+                KemType::MlKem768,
+                KdfType::HkdfSha384,
+            )?,
+            KemType::MlKem768BrainpoolP256r1 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::BrainpoolP256r1)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem768)?),
-                kdf: Kdf::new(KdfType::HkdfSha384),
-            },
-            KemType::MlKem1024P384 => Self {
+                KemType::BrainpoolP256r1,
+                KemType::MlKem768,
+                KdfType::HkdfSha384,
+            )?,
+            KemType::MlKem1024P384 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::P384)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem1024)?),
-                kdf: Kdf::new(KdfType::Sha3_512),
-            },
-            KemType::MlKem1024BrainpoolP384r1 => Self {
+                KemType::P384,
+                KemType::MlKem1024,
+                KdfType::Sha3_512,
+            )?,
+            KemType::MlKem1024BrainpoolP384r1 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::BrainpoolP384r1)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem1024)?),
-                kdf: Kdf::new(KdfType::Sha3_512),
-            },
-            KemType::MlKem1024X448 => Self {
+                KemType::BrainpoolP384r1,
+                KemType::MlKem1024,
+                KdfType::Sha3_512,
+            )?,
+            KemType::MlKem1024X448 => Self::from_components(
                 kem_info,
-                trad_kem: Box::new(KemManager::new(KemType::X448)?),
-                pq_kem: Box::new(KemManager::new(KemType::MlKem1024)?),
-                kdf: Kdf::new(KdfType::Sha3_512),
-            },
+                KemType::X448,
+                KemType::MlKem1024,
+                KdfType::Sha3_512,
+            )?,
             _ => {
                 return Err(QubitCryptError::NotImplemented);
             }
@@ -346,33 +604,7 @@ impl Kem for CompositeKemManager {
     ///
     /// The shared secret after applying the combiner function
     fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
-        // Deserialize the composite secret key
-        let c_sk = CompositePrivateKey::from_der(&self.kem_info.oid, sk)?;
-
-        // Deserialize the composite ciphertext
-        let c_ct =
-            CompositeCiphertextValue::from_der(ct).map_err(|_| QubitCryptError::DecapFailed)?;
-
-        // Decapsulate the ciphertext for the traditional KEM
-        let t_ss = self
-            .trad_kem
-            .decap(c_sk.get_trad_sk()?.private_key, &c_ct.get_trad_ct())?;
-
-        // Decapsulate the ciphertext for the post-quantum KEM
-        let pq_ss = self
-            .pq_kem
-            .decap(c_sk.get_pq_sk()?.private_key, &c_ct.get_pq_ct())?;
-
-        // Get the trad PK
-        let t_pk = c_sk
-            .get_trad_sk()?
-            .public_key
-            .ok_or(QubitCryptError::DecapFailed)?;
-
-        // Get the shared secret using the combiner
-        let ss = self.combiner(&pq_ss, &t_ss, &c_ct.get_trad_ct(), t_pk)?;
-
-        Ok(ss)
+        self.decap_with(sk, ct, false)
     }
 
     /// Get KEM metadata information such as the key lengths,
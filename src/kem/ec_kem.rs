@@ -5,7 +5,10 @@ use crate::kem::common::kem_type::KemType;
 use crate::utils::openssl_utils::{
     decaps_ec_based, decaps_pkey_based, encaps_ec_based, encaps_pkey_based, get_key_pair_ec_based,
     get_key_pair_ec_based_with_rng, get_key_pair_pkey_based, get_keypair_pkey_based_with_rng,
+    get_pk_from_sk_ec_based, get_pk_from_sk_pkey_based, is_sk_in_range_ec_based,
+    is_valid_point_ec_based, is_valid_point_pkey_based,
 };
+use crate::utils::rng::rng_from_default_source;
 use crate::QubitCryptError;
 use openssl::nid::Nid;
 use openssl::pkey::Id;
@@ -20,6 +23,92 @@ pub struct EcKemManager {
     pk_based_id: Option<Id>,
 }
 
+impl EcKemManager {
+    /// Check that a ciphertext is well-formed for a public key, see
+    /// `KemManager::validate_ciphertext`
+    ///
+    /// The ciphertext is the ephemeral public key, so both must be valid points: on the curve
+    /// and not the point at infinity for the NIST and Brainpool curves, and not of small order
+    /// for X25519 and X448.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key
+    /// * `ct` - The ciphertext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` if the public key is not a valid point and
+    /// `QubitCryptError::InvalidCiphertext` if the ciphertext is not a valid point
+    pub fn validate_ciphertext(&self, pk: &[u8], ct: &[u8]) -> Result<()> {
+        let is_valid = |point: &[u8]| {
+            if let Some(nid) = self.ec_based_nid {
+                is_valid_point_ec_based(point, nid).unwrap_or(false)
+            } else if let Some(id) = self.pk_based_id {
+                is_valid_point_pkey_based(point, id).unwrap_or(false)
+            } else {
+                false
+            }
+        };
+
+        if !is_valid(pk) {
+            return Err(QubitCryptError::InvalidPublicKey);
+        }
+        if !is_valid(ct) {
+            return Err(QubitCryptError::InvalidCiphertext);
+        }
+        Ok(())
+    }
+
+    /// Get the public key of a private key
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The private key
+    ///
+    /// # Returns
+    ///
+    /// The public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the private key is invalid
+    pub fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        let pk = if let Some(nid) = self.ec_based_nid {
+            get_pk_from_sk_ec_based(sk, nid)
+        } else if let Some(id) = self.pk_based_id {
+            get_pk_from_sk_pkey_based(sk, id)
+        } else {
+            return Err(QubitCryptError::NotImplemented);
+        };
+        pk.map_err(|_| QubitCryptError::InvalidPrivateKey)
+    }
+
+    /// Encapsulate a public key, with the randomness taken from an RNG
+    ///
+    /// The ephemeral key pair is generated with the RNG, and the ciphertext is its public key
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `rng` - The RNG to take the randomness of the encapsulation from
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    pub(crate) fn encap_with_rng(
+        &mut self,
+        pk: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let (epk, esk) = self.key_gen_with_rng(rng)?;
+        let ss = self
+            .decap(&esk, pk)
+            .map_err(|_| QubitCryptError::EncapFailed)?;
+        Ok((ss, epk))
+    }
+}
+
 impl Kem for EcKemManager {
     /// Create a new KEM instance
     ///
@@ -55,6 +144,10 @@ impl Kem for EcKemManager {
     /// pk, sk, ct, lengths are all in accordance with RFC9180.
     /// ss length is different from RFC9180 as it is not hashed.
     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+        if let Some(mut rng) = rng_from_default_source()? {
+            return self.key_gen_with_rng(&mut rng);
+        }
+
         if let Some(nid) = self.ec_based_nid {
             get_key_pair_ec_based(nid).map_err(|_| QubitCryptError::KeyPairGenerationFailed)
         } else if let Some(id) = self.pk_based_id {
@@ -107,6 +200,37 @@ impl Kem for EcKemManager {
         }
     }
 
+    /// Encapsulate a public key with a given ephemeral private key
+    ///
+    /// The ciphertext is the public key of the ephemeral private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `coins` - The ephemeral private key
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap_deterministic(&mut self, pk: &[u8], coins: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        if Some(coins.len()) != self.kem_info.sk_byte_len {
+            return Err(QubitCryptError::EncapFailed);
+        }
+
+        let epk = if let Some(nid) = self.ec_based_nid {
+            get_pk_from_sk_ec_based(coins, nid)
+        } else if let Some(id) = self.pk_based_id {
+            get_pk_from_sk_pkey_based(coins, id)
+        } else {
+            return Err(QubitCryptError::NotImplemented);
+        }
+        .map_err(|_| QubitCryptError::EncapFailed)?;
+        let ss = self
+            .decap(coins, pk)
+            .map_err(|_| QubitCryptError::EncapFailed)?;
+        Ok((ss, epk))
+    }
+
     /// Decapsulate a ciphertext
     ///
     /// # Arguments
@@ -117,8 +241,17 @@ impl Kem for EcKemManager {
     /// # Returns
     ///
     /// The shared secret (ss)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the secret key of a NIST or
+    /// Brainpool curve is not in the range [1, n-1]. X25519 and X448 secret keys are clamped as
+    /// defined in RFC 7748, so every 32 / 56 byte string is a valid secret key.
     fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
         if let Some(nid) = self.ec_based_nid {
+            if !is_sk_in_range_ec_based(sk, nid).map_err(|_| QubitCryptError::InvalidPrivateKey)? {
+                return Err(QubitCryptError::InvalidPrivateKey);
+            }
             decaps_ec_based(sk, ct, nid).map_err(|_| QubitCryptError::DecapFailed)
         } else if let Some(id) = self.pk_based_id {
             decaps_pkey_based(sk, ct, id).map_err(|_| QubitCryptError::DecapFailed)
@@ -182,4 +315,72 @@ mod tests {
         let kem = EcKemManager::new(KemType::X25519);
         test_kem!(kem);
     }
+
+    #[test]
+    fn test_ec_kem_rejects_out_of_range_sk() {
+        for kem_type in [
+            KemType::P256,
+            KemType::P384,
+            KemType::BrainpoolP256r1,
+            KemType::BrainpoolP384r1,
+        ] {
+            let mut kem = EcKemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (_, ct) = kem.encap(&pk).unwrap();
+
+            let all_ff = vec![0xFFu8; sk.len()];
+            assert_eq!(
+                kem.decap(&all_ff, &ct),
+                Err(QubitCryptError::InvalidPrivateKey)
+            );
+            let zero = vec![0u8; sk.len()];
+            assert_eq!(
+                kem.decap(&zero, &ct),
+                Err(QubitCryptError::InvalidPrivateKey)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ec_kem_encap_deterministic() {
+        // RFC 7748 Section 6.1, Bob encapsulates to Alice with his key pair as the ephemeral key
+        let alice_pk =
+            hex::decode("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+                .unwrap();
+        let bob_sk =
+            hex::decode("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb")
+                .unwrap();
+        let bob_pk =
+            hex::decode("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+                .unwrap();
+        let ss = hex::decode("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742")
+            .unwrap();
+
+        let mut kem = EcKemManager::new(KemType::X25519).unwrap();
+        assert_eq!(
+            kem.encap_deterministic(&alice_pk, &bob_sk).unwrap(),
+            (ss, bob_pk)
+        );
+        assert_eq!(
+            kem.encap_deterministic(&alice_pk, &bob_sk[1..]),
+            Err(QubitCryptError::EncapFailed)
+        );
+
+        for kem_type in [KemType::P256, KemType::P384, KemType::X448] {
+            let mut kem = EcKemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (_, esk) = kem.key_gen().unwrap();
+            let (ss, ct) = kem.encap_deterministic(&pk, &esk).unwrap();
+            assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+            assert_eq!(kem.encap_deterministic(&pk, &esk).unwrap(), (ss, ct));
+        }
+
+        // A NIST curve scalar must be in [1, n-1]
+        let mut kem = EcKemManager::new(KemType::P256).unwrap();
+        let (pk, _) = kem.key_gen().unwrap();
+        assert_eq!(
+            kem.encap_deterministic(&pk, &[0u8; 32]),
+            Err(QubitCryptError::EncapFailed)
+        );
+    }
 }
@@ -7,6 +7,9 @@ use crate::kem::composite_kem::CompositeKemManager;
 use crate::kem::ec_kem::EcKemManager;
 use crate::kem::ml_kem::MlKemManager;
 use crate::kem::rsa_kem::RsaKemManager;
+use crate::kem::x25519_ml_kem768::X25519MlKem768KemManager;
+use crate::kem::xwing::XWingKemManager;
+use crate::utils::registry;
 use crate::QubitCryptError;
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
@@ -28,7 +31,8 @@ const EC_KEM_TYPES: [KemType; 6] = [
     KemType::X448,
 ];
 
-const COMPOSITE_KEM_TYPES: [KemType; 9] = [
+const COMPOSITE_KEM_TYPES: [KemType; 10] = [
+    KemType::MlKem768P256,
     KemType::MlKem768Rsa2048,
     KemType::MlKem768Rsa3072,
     KemType::MlKem768Rsa4096,
@@ -50,6 +54,229 @@ pub enum KemManager {
     Ec(EcKemManager),
     /// Composite KEM manager
     Composite(CompositeKemManager),
+    /// X25519MLKEM768 KEM manager, the hybrid of TLS 1.3
+    X25519MlKem768(X25519MlKem768KemManager),
+    /// X-Wing KEM manager
+    XWing(XWingKemManager),
+    /// A custom KEM registered with `register_kem`
+    Custom(Box<dyn crate::kem::common::dyn_kem::DynKem + Send + Sync>),
+}
+
+impl KemManager {
+    /// Decapsulate a ciphertext, failing if the ciphertext is detectably invalid
+    ///
+    /// `decap` follows the failure semantics of each KEM, so an invalid ciphertext may or may
+    /// not produce an error. This is for protocols which must abort when decapsulation fails:
+    ///
+    /// * The ciphertext must have the exact length of the KEM, if the length is fixed
+    /// * EC KEMs reject ciphertexts which are not valid points, and points which result in an
+    ///   all-zero shared secret (e.g. small order points for X25519 and X448)
+    /// * RSA KEMs reject ciphertexts with invalid OAEP padding
+    /// * Composite KEMs apply the above to their traditional component
+    /// * X25519MLKEM768 and X-Wing reject an all-zero X25519 shared secret, as TLS does for
+    ///   X25519MLKEM768
+    ///
+    /// **Caveat:** ML-KEM uses implicit rejection (FIPS 203), so an invalid ML-KEM ciphertext
+    /// can't be detected. It decapsulates to a pseudorandom shared secret which won't match the
+    /// sender's, so the failure only shows up later, e.g. when authenticated decryption with the
+    /// derived key fails. This also holds for the ML-KEM component of a composite KEM.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    ///
+    /// # Returns
+    ///
+    /// The shared secret (ss)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::DecapFailed` will be returned if the ciphertext is invalid
+    pub fn decap_strict(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+        if let Some(ct_len) = self.get_kem_info().ct_byte_len {
+            if ct.len() != ct_len {
+                return Err(QubitCryptError::DecapFailed);
+            }
+        }
+
+        match self {
+            KemManager::Ml(kem) => kem.decap(sk, ct).map_err(|e| match e {
+                QubitCryptError::InvalidCiphertext => QubitCryptError::DecapFailed,
+                e => e,
+            }),
+            KemManager::Rsa(kem) => kem.decap(sk, ct),
+            KemManager::Ec(kem) => {
+                let ss = kem.decap(sk, ct)?;
+                if ss.iter().all(|b| *b == 0) {
+                    return Err(QubitCryptError::DecapFailed);
+                }
+                Ok(ss)
+            }
+            KemManager::Composite(kem) => kem.decap_strict(sk, ct),
+            KemManager::X25519MlKem768(kem) => kem.decap(sk, ct),
+            KemManager::XWing(kem) => kem.decap_strict(sk, ct),
+            KemManager::Custom(kem) => kem.decap(sk, ct),
+        }
+    }
+
+    /// Check that a ciphertext is well-formed for a public key, before decapsulating it
+    ///
+    /// This rejects ciphertexts which can't have been produced by `encap` with the public key,
+    /// as far as this can be told without the private key:
+    ///
+    /// * ML-KEM: the lengths of the key and the ciphertext, and the FIPS 203 modulus check of the
+    ///   key. Every ciphertext of the right length is well-formed.
+    /// * EC KEMs: the key and the ciphertext (the ephemeral public key) must be valid points,
+    ///   including not of small order for X25519 and X448
+    /// * RSA KEMs: the ciphertext must be an integer smaller than the modulus
+    /// * Composite KEMs, X25519MLKEM768 and X-Wing: both components, as above
+    ///
+    /// A ciphertext which passes may still fail to decapsulate, e.g. because of its RSA-OAEP
+    /// padding, or decapsulate to the wrong shared secret with ML-KEM's implicit rejection.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key the ciphertext was produced for
+    /// * `ct` - The ciphertext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` (or `QubitCryptError::InvalidEncapsulationKey` for
+    /// ML-KEM) will be returned if the public key is invalid, `QubitCryptError::InvalidCiphertext`
+    /// if the ciphertext is invalid
+    pub fn validate_ciphertext(&self, pk: &[u8], ct: &[u8]) -> Result<()> {
+        match self {
+            KemManager::Ml(kem) => kem.validate_ciphertext(pk, ct),
+            KemManager::Rsa(kem) => kem.validate_ciphertext(pk, ct),
+            KemManager::Ec(kem) => kem.validate_ciphertext(pk, ct),
+            KemManager::Composite(kem) => kem.validate_ciphertext(pk, ct),
+            KemManager::X25519MlKem768(kem) => kem.validate_ciphertext(pk, ct),
+            KemManager::XWing(kem) => kem.validate_ciphertext(pk, ct),
+            KemManager::Custom(kem) => kem.validate_ciphertext(pk, ct),
+        }
+    }
+
+    /// Get the public key of a private key
+    ///
+    /// The public key is derived from the private key. For ML-KEM it is the encapsulation key
+    /// embedded in the decapsulation key, for X-Wing it is re-derived from the seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The private key
+    ///
+    /// # Returns
+    ///
+    /// The public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the private key is invalid,
+    /// `QubitCryptError::NotImplemented` for custom KEMs
+    pub fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            KemManager::Ml(kem) => kem.get_public_key(sk),
+            KemManager::Rsa(kem) => kem.get_public_key(sk),
+            KemManager::Ec(kem) => kem.get_public_key(sk),
+            KemManager::Composite(kem) => kem.get_public_key(sk),
+            KemManager::X25519MlKem768(kem) => kem.get_public_key(sk),
+            KemManager::XWing(kem) => kem.get_public_key(sk),
+            KemManager::Custom(_) => Err(QubitCryptError::NotImplemented),
+        }
+    }
+
+    /// Set an additional domain separation label for the combiner of a composite KEM
+    ///
+    /// See `CompositeKemManager::set_combiner_label`. Encapsulation and decapsulation must use
+    /// the same label. X-Wing is not supported, its label is fixed by the X-Wing specification.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The domain separation label
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::UnsupportedOperation` will be returned if the KEM is not a composite KEM
+    pub fn set_combiner_label(&mut self, label: &[u8]) -> Result<()> {
+        match self {
+            KemManager::Composite(kem) => {
+                kem.set_combiner_label(label);
+                Ok(())
+            }
+            _ => Err(QubitCryptError::UnsupportedOperation),
+        }
+    }
+
+    /// Encapsulate a public key, with the randomness taken from an RNG
+    ///
+    /// With a seeded RNG the encapsulation is reproducible, which is used for deterministic
+    /// EnvelopedData.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `rng` - The RNG to take the randomness of the encapsulation from
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::NotImplemented` will be returned for custom KEMs, which can't be
+    /// given an RNG
+    pub(crate) fn encap_with_rng(
+        &mut self,
+        pk: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        match self {
+            KemManager::Ml(kem) => kem.encap_with_rng(pk, rng),
+            KemManager::Rsa(kem) => kem.encap_with_rng(pk, rng),
+            KemManager::Ec(kem) => kem.encap_with_rng(pk, rng),
+            KemManager::Composite(kem) => kem.encap_with_rng(pk, rng),
+            KemManager::X25519MlKem768(kem) => kem.encap_with_rng(pk, rng),
+            KemManager::XWing(kem) => kem.encap_with_rng(pk, rng),
+            KemManager::Custom(_) => Err(QubitCryptError::NotImplemented),
+        }
+    }
+
+    /// Generate `n` keypairs with the same manager, using the default RNG
+    ///
+    /// The manager is set up once for the whole batch, e.g. when provisioning many identities.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn key_gen_batch(&mut self, n: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        (0..n).map(|_| self.key_gen()).collect()
+    }
+
+    /// Generate `n` keypairs with the same manager, using a specified RNG
+    ///
+    /// The keypairs are generated one after the other from the RNG, so a seeded RNG always
+    /// gives the same keypairs in the same order.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of keypairs
+    /// * `rng` - The random number generator to use
+    ///
+    /// # Returns
+    ///
+    /// The keypairs (pk, sk)
+    pub fn key_gen_batch_with_rng(
+        &mut self,
+        n: usize,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        (0..n).map(|_| self.key_gen_with_rng(rng)).collect()
+    }
 }
 
 impl Kem for KemManager {
@@ -62,6 +289,10 @@ impl Kem for KemManager {
     /// # Returns
     ///
     /// A new KEM manager
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::NotImplemented` will be returned if there is no manager for the KEM type
     fn new(kem_type: KemType) -> Result<Self>
     where
         Self: Sized,
@@ -75,9 +306,11 @@ impl Kem for KemManager {
             _ if COMPOSITE_KEM_TYPES.contains(&kem_type) => {
                 KemManager::Composite(CompositeKemManager::new(kem_type)?)
             }
-            _ => {
-                panic!("Not implemented");
+            KemType::X25519MlKem768 => {
+                KemManager::X25519MlKem768(X25519MlKem768KemManager::new(kem_type)?)
             }
+            KemType::XWing => KemManager::XWing(XWingKemManager::new(kem_type)?),
+            _ => return Err(QubitCryptError::NotImplemented),
         })
     }
 
@@ -93,6 +326,9 @@ impl Kem for KemManager {
             KemManager::Rsa(kem) => kem.get_kem_info(),
             KemManager::Ec(kem) => kem.get_kem_info(),
             KemManager::Composite(kem) => kem.get_kem_info(),
+            KemManager::X25519MlKem768(kem) => kem.get_kem_info(),
+            KemManager::XWing(kem) => kem.get_kem_info(),
+            KemManager::Custom(kem) => kem.get_kem_info(),
         }
     }
 
@@ -111,6 +347,9 @@ impl Kem for KemManager {
             KemManager::Rsa(kem) => kem.key_gen_with_rng(rng),
             KemManager::Ec(kem) => kem.key_gen_with_rng(rng),
             KemManager::Composite(kem) => kem.key_gen_with_rng(rng),
+            KemManager::X25519MlKem768(kem) => kem.key_gen_with_rng(rng),
+            KemManager::XWing(kem) => kem.key_gen_with_rng(rng),
+            KemManager::Custom(kem) => kem.key_gen_with_rng(rng),
         }
     }
 
@@ -126,6 +365,9 @@ impl Kem for KemManager {
             KemManager::Rsa(kem) => kem.key_gen(),
             KemManager::Ec(kem) => kem.key_gen(),
             KemManager::Composite(kem) => kem.key_gen(),
+            KemManager::X25519MlKem768(kem) => kem.key_gen(),
+            KemManager::XWing(kem) => kem.key_gen(),
+            KemManager::Custom(kem) => kem.key_gen(),
         }
     }
 
@@ -144,6 +386,33 @@ impl Kem for KemManager {
             KemManager::Rsa(kem) => kem.encap(pk),
             KemManager::Ec(kem) => kem.encap(pk),
             KemManager::Composite(kem) => kem.encap(pk),
+            KemManager::X25519MlKem768(kem) => kem.encap(pk),
+            KemManager::XWing(kem) => kem.encap(pk),
+            KemManager::Custom(kem) => kem.encap(pk),
+        }
+    }
+
+    /// Encapsulate a public key with caller-supplied coins, see `Kem::encap_deterministic`
+    ///
+    /// The ML-KEM, EC based, X25519MLKEM768 and X-Wing KEMs support this, and custom KEMs which
+    /// implement it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `coins` - The randomness of the encapsulation
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap_deterministic(&mut self, pk: &[u8], coins: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        match self {
+            KemManager::Ml(kem) => kem.encap_deterministic(pk, coins),
+            KemManager::Ec(kem) => kem.encap_deterministic(pk, coins),
+            KemManager::X25519MlKem768(kem) => kem.encap_deterministic(pk, coins),
+            KemManager::XWing(kem) => kem.encap_deterministic(pk, coins),
+            KemManager::Custom(kem) => kem.encap_deterministic(pk, coins),
+            KemManager::Rsa(_) | KemManager::Composite(_) => Err(QubitCryptError::NotImplemented),
         }
     }
 
@@ -163,13 +432,44 @@ impl Kem for KemManager {
             KemManager::Rsa(kem) => kem.decap(ct, sk),
             KemManager::Ec(kem) => kem.decap(ct, sk),
             KemManager::Composite(kem) => kem.decap(ct, sk),
+            KemManager::X25519MlKem768(kem) => kem.decap(ct, sk),
+            KemManager::XWing(kem) => kem.decap(ct, sk),
+            KemManager::Custom(kem) => kem.decap(ct, sk),
         }
     }
+
+    /// Create a new KEM manager from an OID
+    ///
+    /// The built-in KEMs take precedence, then the custom KEMs registered with `register_kem`
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID of the KEM
+    ///
+    /// # Returns
+    ///
+    /// A new KEM manager
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidOid` will be returned if the OID is not a known KEM OID
+    fn new_from_oid(oid: &str) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        if let Some(kem_type) = KemType::from_oid(oid) {
+            return Self::new(kem_type);
+        }
+        registry::new_custom_kem(oid)
+            .map(KemManager::Custom)
+            .ok_or(QubitCryptError::InvalidOid)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::kem::common::config::oids::Oid;
     use crate::kem::common::kem_type::KemType;
 
     #[test]
@@ -186,4 +486,308 @@ mod tests {
             assert_eq!(kem.get_kem_info().kem_type, kem_type);
         }
     }
+
+    #[test]
+    fn test_kem_typed() {
+        let mut kem = KemManager::new(KemType::MlKem768).unwrap();
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (ss, ct) = kem.encap_typed(&pk).unwrap();
+        assert_eq!(ss.expose().len(), kem.get_kem_info().ss_byte_len);
+        assert_eq!(kem.decap_typed(&sk, &ct).unwrap().expose(), ss.expose());
+    }
+
+    #[test]
+    fn test_get_public_key() {
+        for kem_type in [
+            KemType::MlKem512,
+            KemType::RsaOAEP2048,
+            KemType::P256,
+            KemType::X25519,
+            KemType::X448,
+            KemType::MlKem768X25519,
+            KemType::MlKem768Rsa2048,
+            KemType::X25519MlKem768,
+            KemType::XWing,
+        ] {
+            let mut kem = KemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            assert_eq!(kem.get_public_key(&sk).unwrap(), pk);
+        }
+
+        let kem = KemManager::new(KemType::MlKem768).unwrap();
+        assert_eq!(
+            kem.get_public_key(&[0u8; 10]),
+            Err(QubitCryptError::InvalidPrivateKey)
+        );
+    }
+
+    #[test]
+    fn test_nist_curve_hybrids() {
+        use crate::kem::common::macros::test_kem;
+
+        for kem_type in [KemType::MlKem768P256, KemType::MlKem1024P384] {
+            test_kem!(KemManager::new(kem_type));
+        }
+
+        // The EC component of each ciphertext has the point size of its curve, so a P-256
+        // ciphertext doesn't decapsulate with a P-384 key
+        let mut p256 = KemManager::new(KemType::MlKem768P256).unwrap();
+        let mut p384 = KemManager::new(KemType::MlKem1024P384).unwrap();
+        let (pk_256, _) = p256.key_gen().unwrap();
+        let (_, sk_384) = p384.key_gen().unwrap();
+        let (_, ct_256) = p256.encap(&pk_256).unwrap();
+        assert!(p384.decap_strict(&sk_384, &ct_256).is_err());
+        assert!(p384.decap(&sk_384, &ct_256).is_err());
+    }
+
+    #[test]
+    fn test_kem_manager_standalone_ecdh_from_oid() {
+        for kem_type in [KemType::X25519, KemType::X448] {
+            let oid = kem_type.get_oid();
+            let mut kem = KemManager::new_from_oid(&oid).unwrap();
+            let info = kem.get_kem_info();
+            assert_eq!(info.kem_type, kem_type);
+
+            let (pk, sk) = kem.key_gen().unwrap();
+            assert_eq!(Some(pk.len()), info.pk_byte_len);
+            assert_eq!(Some(sk.len()), info.sk_byte_len);
+
+            let (ss, ct) = kem.encap(&pk).unwrap();
+            assert_eq!(ss.len(), info.ss_byte_len);
+            assert_eq!(Some(ct.len()), info.ct_byte_len);
+            assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+        }
+    }
+
+    #[test]
+    fn test_kem_manager_x25519_ml_kem768() {
+        use crate::kem::common::macros::test_kem;
+
+        let oid = KemType::X25519MlKem768.get_oid();
+        test_kem!(KemManager::new_from_oid(&oid));
+
+        let mut kem = KemManager::new_from_oid(&oid).unwrap();
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (ss, ct) = kem.encap(&pk).unwrap();
+        assert_eq!(kem.validate_ciphertext(&pk, &ct), Ok(()));
+        assert_eq!(kem.decap_strict(&sk, &ct).unwrap(), ss);
+        assert_eq!(
+            kem.validate_ciphertext(&pk, &ct[..ct.len() - 1]),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+    }
+
+    #[test]
+    fn test_kem_manager_xwing() {
+        use crate::kem::api::algorithm::KemAlgorithm;
+        use crate::kem::common::macros::test_kem;
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let oid = KemAlgorithm::XWing.get_oid();
+        assert_eq!(KemAlgorithm::from_oid(&oid), Some(KemAlgorithm::XWing));
+        assert!(!KemAlgorithm::XWing.is_composite());
+        test_kem!(KemManager::new_from_oid(&oid));
+
+        let mut kem = KemManager::new_from_oid(&oid).unwrap();
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (ss, ct) = kem.encap(&pk).unwrap();
+        assert_eq!(kem.validate_ciphertext(&pk, &ct), Ok(()));
+        assert_eq!(kem.decap_strict(&sk, &ct).unwrap(), ss);
+        assert_eq!(
+            kem.decap_strict(&sk, &ct[1..]),
+            Err(QubitCryptError::DecapFailed)
+        );
+
+        // A seeded RNG gives the same encapsulation
+        let mut rng = ChaCha20Rng::from_seed([5u8; 32]);
+        let (ss, ct) = kem.encap_with_rng(&pk, &mut rng).unwrap();
+        assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+        let mut rng = ChaCha20Rng::from_seed([5u8; 32]);
+        assert_eq!(kem.encap_with_rng(&pk, &mut rng).unwrap(), (ss, ct));
+    }
+
+    #[test]
+    fn test_kem_manager_unsupported_type() {
+        // The composites of the old draft have OIDs but no manager
+        assert!(matches!(
+            KemManager::new(KemType::MlKem512P256),
+            Err(QubitCryptError::NotImplemented)
+        ));
+        assert!(matches!(
+            KemManager::new_from_oid(&KemType::MlKem512P256.get_oid()),
+            Err(QubitCryptError::NotImplemented)
+        ));
+    }
+
+    #[test]
+    fn test_decap_strict() {
+        for kem_type in [KemType::X25519, KemType::P256, KemType::MlKem768X25519] {
+            let mut kem = KemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (ss, ct) = kem.encap(&pk).unwrap();
+            assert_eq!(kem.decap_strict(&sk, &ct).unwrap(), ss);
+
+            // Truncated ciphertext
+            assert_eq!(
+                kem.decap_strict(&sk, &ct[..ct.len() - 1]),
+                Err(QubitCryptError::DecapFailed)
+            );
+        }
+
+        // A small order X25519 point results in an all-zero shared secret
+        let kem = KemManager::new(KemType::X25519).unwrap();
+        let (_, sk) = KemManager::new(KemType::X25519).unwrap().key_gen().unwrap();
+        assert!(kem.decap_strict(&sk, &[0u8; 32]).is_err());
+
+        // The same for the X25519 component of the hybrids, the ML-KEM component is valid
+        for kem_type in [KemType::XWing, KemType::X25519MlKem768] {
+            let mut kem = KemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (_, mut ct) = kem.encap(&pk).unwrap();
+            let ct_len = ct.len();
+            ct[ct_len - 32..].copy_from_slice(&[0u8; 32]);
+            assert_eq!(
+                kem.decap_strict(&sk, &ct),
+                Err(QubitCryptError::DecapFailed)
+            );
+        }
+
+        // An invalid P-256 point
+        let kem = KemManager::new(KemType::P256).unwrap();
+        let (_, sk) = KemManager::new(KemType::P256).unwrap().key_gen().unwrap();
+        let mut bad_ct = vec![0x04];
+        bad_ct.extend_from_slice(&[0xFFu8; 64]);
+        assert_eq!(
+            kem.decap_strict(&sk, &bad_ct),
+            Err(QubitCryptError::DecapFailed)
+        );
+
+        // ML-KEM can't detect a tampered ciphertext, the shared secret just doesn't match
+        let mut kem = KemManager::new(KemType::MlKem768).unwrap();
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (ss, mut ct) = kem.encap(&pk).unwrap();
+        ct[0] ^= 1;
+        assert_ne!(kem.decap_strict(&sk, &ct).unwrap(), ss);
+        assert_eq!(
+            kem.decap_strict(&sk, &ct[1..]),
+            Err(QubitCryptError::DecapFailed)
+        );
+    }
+
+    #[test]
+    fn test_validate_ciphertext() {
+        for kem_type in [
+            KemType::MlKem512,
+            KemType::X25519,
+            KemType::X448,
+            KemType::P256,
+            KemType::RsaOAEP2048,
+            KemType::MlKem768X25519,
+            KemType::MlKem768Rsa2048,
+        ] {
+            let mut kem = KemManager::new(kem_type.clone()).unwrap();
+            let (pk, _) = kem.key_gen().unwrap();
+            let (_, ct) = kem.encap(&pk).unwrap();
+            assert_eq!(kem.validate_ciphertext(&pk, &ct), Ok(()), "{:?}", kem_type);
+            assert_eq!(
+                kem.validate_ciphertext(&pk, &ct[..ct.len() - 1]),
+                Err(QubitCryptError::InvalidCiphertext),
+                "{:?}",
+                kem_type
+            );
+        }
+
+        // Small order X25519 points
+        let mut kem = KemManager::new(KemType::X25519).unwrap();
+        let (pk, _) = kem.key_gen().unwrap();
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        for point in [[0u8; 32], one] {
+            assert_eq!(
+                kem.validate_ciphertext(&pk, &point),
+                Err(QubitCryptError::InvalidCiphertext)
+            );
+            assert_eq!(
+                kem.validate_ciphertext(&point, &pk),
+                Err(QubitCryptError::InvalidPublicKey)
+            );
+        }
+
+        // A P-256 point which is not on the curve
+        let mut kem = KemManager::new(KemType::P256).unwrap();
+        let (pk, _) = kem.key_gen().unwrap();
+        let mut bad_ct = vec![0x04];
+        bad_ct.extend_from_slice(&[0xFFu8; 64]);
+        assert_eq!(
+            kem.validate_ciphertext(&pk, &bad_ct),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+
+        // An RSA ciphertext which is not smaller than the modulus
+        let mut kem = KemManager::new(KemType::RsaOAEP2048).unwrap();
+        let (pk, _) = kem.key_gen().unwrap();
+        assert_eq!(
+            kem.validate_ciphertext(&pk, &[0xFFu8; 256]),
+            Err(QubitCryptError::InvalidCiphertext)
+        );
+
+        // An ML-KEM key which fails the modulus check
+        let mut kem = KemManager::new(KemType::MlKem512).unwrap();
+        let (mut pk, _) = kem.key_gen().unwrap();
+        let (_, ct) = kem.encap(&pk).unwrap();
+        pk[0] = 0xFF;
+        pk[1] |= 0x0F;
+        assert_eq!(
+            kem.validate_ciphertext(&pk, &ct),
+            Err(QubitCryptError::InvalidEncapsulationKey)
+        );
+    }
+
+    #[test]
+    fn test_encap_deterministic() {
+        for kem_type in [KemType::MlKem768, KemType::P256] {
+            let mut kem = KemManager::new(kem_type.clone()).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            // The ML-KEM message, or the EC ephemeral private key
+            let coins = match kem_type {
+                KemType::MlKem768 => vec![1u8; 32],
+                _ => kem.key_gen().unwrap().1,
+            };
+            let (ss, ct) = kem.encap_deterministic(&pk, &coins).unwrap();
+            assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+            assert_eq!(kem.encap_deterministic(&pk, &coins).unwrap(), (ss, ct));
+        }
+
+        for kem_type in [KemType::RsaOAEP2048, KemType::MlKem768X25519] {
+            let mut kem = KemManager::new(kem_type).unwrap();
+            let (pk, _) = kem.key_gen().unwrap();
+            assert_eq!(
+                kem.encap_deterministic(&pk, &[1u8; 32]),
+                Err(QubitCryptError::NotImplemented)
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_gen_batch() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        for kem_type in [KemType::MlKem768, KemType::X25519, KemType::MlKem768X25519] {
+            let mut kem = KemManager::new(kem_type).unwrap();
+            let keys = kem.key_gen_batch(3).unwrap();
+            assert_eq!(keys.len(), 3);
+            assert_ne!(keys[0], keys[1]);
+            for (pk, sk) in &keys {
+                let (ss, ct) = kem.encap(pk).unwrap();
+                assert_eq!(kem.decap(sk, &ct).unwrap(), ss);
+            }
+
+            let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+            let seeded = kem.key_gen_batch_with_rng(3, &mut rng).unwrap();
+            let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+            assert_eq!(kem.key_gen_batch_with_rng(3, &mut rng).unwrap(), seeded);
+        }
+    }
 }
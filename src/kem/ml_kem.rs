@@ -1,13 +1,12 @@
 use crate::kem::common::kem_info::KemInfo;
 use crate::kem::common::kem_trait::Kem;
 use crate::kem::common::kem_type::KemType;
+use crate::utils::rng::default_rng;
 use crate::QubitCryptError;
 use ml_kem::kem::Decapsulate;
 use ml_kem::kem::Encapsulate;
 use ml_kem::*;
-use rand_chacha::ChaCha20Rng;
 use rand_core::CryptoRngCore;
-use rand_core::SeedableRng;
 
 macro_rules! key_gen_ml {
     ($rng:expr, $curve:ident) => {{
@@ -18,7 +17,7 @@ macro_rules! key_gen_ml {
 
 macro_rules! encapsulate_ml {
     ($rng:expr, $curve:ident, $pk:expr) => {{
-        let ek = get_encapsulation_key_obj::<$curve>($pk.to_vec())?;
+        let ek = get_encapsulation_key_obj::<$curve>($pk)?;
         let (ct, ss) = ek.encapsulate(&mut $rng).unwrap();
         let ct = ct.as_slice().to_vec();
         let ss = ss.as_slice().to_vec();
@@ -26,8 +25,55 @@ macro_rules! encapsulate_ml {
     }};
 }
 
+macro_rules! encapsulate_ml_into {
+    ($rng:expr, $curve:ident, $pk:expr, $ss:expr, $ct:expr) => {{
+        let ek = get_encapsulation_key_obj::<$curve>($pk)?;
+        let (ct, ss) = ek.encapsulate(&mut $rng).unwrap();
+        $ct.copy_from_slice(ct.as_slice());
+        $ss.copy_from_slice(ss.as_slice());
+        Ok(())
+    }};
+}
+
 type Result<T> = std::result::Result<T, QubitCryptError>;
 
+/// The ML-KEM modulus q
+const ML_KEM_Q: u16 = 3329;
+
+/// Perform the encapsulation key check (modulus check) of FIPS 203, Section 7.2
+///
+/// The encapsulation key is ByteEncode12(t) || rho, where every coefficient of t is
+/// packed as a 12 bit little-endian value (two coefficients per three bytes). A key
+/// produced by an implementation with a different packing (e.g. big-endian) will
+/// usually contain coefficients which are not reduced modulo q, so this check catches
+/// such keys instead of silently producing a wrong shared secret.
+///
+/// # Arguments
+///
+/// * `pk` - The encoded encapsulation key
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidEncapsulationKey` if a coefficient is not in the range [0, q-1]
+fn check_encapsulation_key(pk: &[u8]) -> Result<()> {
+    // The last 32 bytes are the seed rho
+    let t_len = pk
+        .len()
+        .checked_sub(32)
+        .ok_or(QubitCryptError::InvalidPublicKey)?;
+    for chunk in pk[..t_len].chunks(3) {
+        if chunk.len() != 3 {
+            return Err(QubitCryptError::InvalidEncapsulationKey);
+        }
+        let c0 = u16::from(chunk[0]) | (u16::from(chunk[1] & 0x0f) << 8);
+        let c1 = (u16::from(chunk[1]) >> 4) | (u16::from(chunk[2]) << 4);
+        if c0 >= ML_KEM_Q || c1 >= ML_KEM_Q {
+            return Err(QubitCryptError::InvalidEncapsulationKey);
+        }
+    }
+    Ok(())
+}
+
 // Get the encapsulated key object for the post quantum key encapsulation mechanism
 ///
 /// # Arguments
@@ -37,10 +83,16 @@ type Result<T> = std::result::Result<T, QubitCryptError>;
 /// # Returns
 ///
 /// The encapsulated key object
-fn get_encapsulation_key_obj<K: KemCore>(pk: Vec<u8>) -> Result<K::EncapsulationKey> {
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidPublicKey` if the key has the wrong length and
+/// `QubitCryptError::InvalidEncapsulationKey` if it fails the FIPS 203 modulus check
+fn get_encapsulation_key_obj<K: KemCore>(pk: &[u8]) -> Result<K::EncapsulationKey> {
     // Deserialize the public key
-    let pk = Encoded::<K::EncapsulationKey>::try_from(pk.as_slice())
+    let pk = Encoded::<K::EncapsulationKey>::try_from(pk)
         .map_err(|_| QubitCryptError::InvalidPublicKey)?;
+    check_encapsulation_key(&pk)?;
     Ok(K::EncapsulationKey::from_bytes(&pk))
 }
 
@@ -71,12 +123,26 @@ fn get_decapsulation_key_obj<K: KemCore>(sk: &[u8]) -> Result<K::DecapsulationKe
 ///
 /// The shared secret (ss)
 fn decapsulate<K: KemCore>(sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+    let mut ss = vec![0u8; 32];
+    decapsulate_into::<K>(sk, ct, &mut ss)?;
+    Ok(ss)
+}
+
+/// Decapsulate a ciphertext into a buffer
+///
+/// # Arguments
+///
+/// * `sk` - The secret key to decapsulate with
+/// * `ct` - The encapsulated key to decapsulate
+/// * `ss` - The buffer for the shared secret, which must be 32 bytes long
+fn decapsulate_into<K: KemCore>(sk: &[u8], ct: &[u8], ss: &mut [u8]) -> Result<()> {
     let c = Ciphertext::<K>::try_from(ct).map_err(|_| QubitCryptError::InvalidCiphertext)?;
     let dk = get_decapsulation_key_obj::<K>(sk)?;
     let session_key = dk
         .decapsulate(&c)
         .map_err(|_| QubitCryptError::DecapFailed)?;
-    Ok(session_key.as_slice().to_vec())
+    ss.copy_from_slice(session_key.as_slice());
+    Ok(())
 }
 
 /// A KEM manager for the MlKem method
@@ -85,6 +151,111 @@ pub struct MlKemManager {
 }
 
 impl MlKemManager {
+    /// Check that a ciphertext is well-formed for a public key, see
+    /// `KemManager::validate_ciphertext`
+    ///
+    /// The ciphertext consists of compressed coefficients, and every bit string of the right
+    /// length decodes to coefficients in range, so only its length can be checked. The public
+    /// key gets the FIPS 203 modulus check.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The encapsulation key
+    /// * `ct` - The ciphertext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` if the key has the wrong length,
+    /// `QubitCryptError::InvalidEncapsulationKey` if it fails the modulus check and
+    /// `QubitCryptError::InvalidCiphertext` if the ciphertext has the wrong length
+    pub fn validate_ciphertext(&self, pk: &[u8], ct: &[u8]) -> Result<()> {
+        if Some(pk.len()) != self.kem_info.pk_byte_len {
+            return Err(QubitCryptError::InvalidPublicKey);
+        }
+        check_encapsulation_key(pk)?;
+        if Some(ct.len()) != self.kem_info.ct_byte_len {
+            return Err(QubitCryptError::InvalidCiphertext);
+        }
+        Ok(())
+    }
+
+    /// Encapsulate a public key, writing the shared secret and ciphertext into buffers
+    ///
+    /// Unlike `encap`, no vectors are allocated for the outputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `ss` - The buffer for the shared secret, which must be `ss_byte_len` bytes long
+    /// * `ct` - The buffer for the ciphertext, which must be `ct_byte_len` bytes long
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` will be returned if the public key is invalid
+    pub fn encap_into(&self, pk: &[u8], ss: &mut [u8], ct: &mut [u8]) -> Result<()> {
+        let mut rng = default_rng()?;
+        match self.kem_info.kem_type {
+            KemType::MlKem512 => encapsulate_ml_into!(rng, MlKem512, pk, ss, ct),
+            KemType::MlKem768 => encapsulate_ml_into!(rng, MlKem768, pk, ss, ct),
+            KemType::MlKem1024 => encapsulate_ml_into!(rng, MlKem1024, pk, ss, ct),
+            _ => Err(QubitCryptError::NotImplemented),
+        }
+    }
+
+    /// Decapsulate a ciphertext, writing the shared secret into a buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    /// * `ss` - The buffer for the shared secret, which must be `ss_byte_len` bytes long
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidCiphertext` will be returned if the ciphertext has the wrong
+    /// length
+    pub fn decap_into(&self, sk: &[u8], ct: &[u8], ss: &mut [u8]) -> Result<()> {
+        match self.kem_info.kem_type {
+            KemType::MlKem512 => decapsulate_into::<MlKem512>(sk, ct, ss),
+            KemType::MlKem768 => decapsulate_into::<MlKem768>(sk, ct, ss),
+            KemType::MlKem1024 => decapsulate_into::<MlKem1024>(sk, ct, ss),
+            _ => Err(QubitCryptError::NotImplemented),
+        }
+    }
+
+    /// Get the encapsulation key of a decapsulation key
+    ///
+    /// The decapsulation key embeds the encapsulation key (FIPS 203, Algorithm 16)
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The decapsulation key
+    ///
+    /// # Returns
+    ///
+    /// The encapsulation key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the key has the wrong length
+    pub fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        match self.kem_info.kem_type {
+            KemType::MlKem512 => Ok(get_decapsulation_key_obj::<MlKem512>(sk)?
+                .encapsulation_key()
+                .as_bytes()
+                .to_vec()),
+            KemType::MlKem768 => Ok(get_decapsulation_key_obj::<MlKem768>(sk)?
+                .encapsulation_key()
+                .as_bytes()
+                .to_vec()),
+            KemType::MlKem1024 => Ok(get_decapsulation_key_obj::<MlKem1024>(sk)?
+                .encapsulation_key()
+                .as_bytes()
+                .to_vec()),
+            _ => Err(QubitCryptError::NotImplemented),
+        }
+    }
+
     pub fn key_gen_deterministic(&self, d: &B32, z: &B32) -> Result<(Vec<u8>, Vec<u8>)> {
         match self.kem_info.kem_type {
             KemType::MlKem512 => {
@@ -102,6 +273,106 @@ impl MlKemManager {
             _ => Err(QubitCryptError::NotImplemented),
         }
     }
+
+    /// Check if decapsulating a ciphertext results in the implicit rejection shared
+    /// secret, i.e. if the ciphertext fails the re-encryption check of ML-KEM.Decaps
+    /// (FIPS 203, Algorithm 18).
+    ///
+    /// Decapsulation still succeeds for such a ciphertext, but returns J(z || c) instead
+    /// of the real shared secret. This helper compares the output of `decap` with J(z || c),
+    /// so decapsulation itself stays constant time. The result reveals the outcome of the
+    /// check, so it is only available to the test suite and with the `test-utils` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The secret key to decapsulate with
+    /// * `ct` - The ciphertext to decapsulate
+    ///
+    /// # Returns
+    ///
+    /// True if the ciphertext was implicitly rejected, false otherwise
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn decap_is_implicit_reject(&self, sk: &[u8], ct: &[u8]) -> Result<bool> {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+
+        let ss = self.decap(sk, ct)?;
+
+        // The implicit rejection value z is the last 32 bytes of the decapsulation key
+        let z = &sk[sk.len() - 32..];
+        let mut j = sha3::Shake256::default();
+        j.update(z);
+        j.update(ct);
+        let mut k_bar = [0u8; 32];
+        j.finalize_xof().read(&mut k_bar);
+
+        Ok(ss == k_bar)
+    }
+
+    /// Encapsulate a public key, with the randomness taken from an RNG
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `rng` - The RNG to take the randomness of the encapsulation from
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    pub(crate) fn encap_with_rng(
+        &mut self,
+        pk: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        match self.kem_info.kem_type {
+            KemType::MlKem512 => {
+                encapsulate_ml!(*rng, MlKem512, pk)
+            }
+            KemType::MlKem768 => {
+                encapsulate_ml!(*rng, MlKem768, pk)
+            }
+            KemType::MlKem1024 => {
+                encapsulate_ml!(*rng, MlKem1024, pk)
+            }
+            _ => {
+                panic!("Not implemented");
+            }
+        }
+    }
+}
+
+/// Check if decapsulating an ML-KEM ciphertext results in the implicit rejection shared
+/// secret, see `MlKemManager::decap_is_implicit_reject`
+///
+/// This reveals the outcome of the re-encryption check, so it's only available with the
+/// `test-utils` feature, e.g. to test the ciphertexts of another implementation.
+///
+/// # Arguments
+///
+/// * `algorithm` - The ML-KEM algorithm
+/// * `sk` - The secret key to decapsulate with
+/// * `ct` - The ciphertext to decapsulate
+///
+/// # Returns
+///
+/// True if the ciphertext was implicitly rejected, false otherwise
+///
+/// # Errors
+///
+/// `QubitCryptError::NotImplemented` will be returned if the algorithm is not ML-KEM
+#[cfg(feature = "test-utils")]
+pub fn decap_is_implicit_reject(
+    algorithm: &crate::kems::KemAlgorithm,
+    sk: &[u8],
+    ct: &[u8],
+) -> Result<bool> {
+    let kem_type = algorithm.get_kem_type();
+    if !matches!(
+        kem_type,
+        KemType::MlKem512 | KemType::MlKem768 | KemType::MlKem1024
+    ) {
+        return Err(QubitCryptError::NotImplemented);
+    }
+    MlKemManager::new(kem_type)?.decap_is_implicit_reject(sk, ct)
 }
 
 impl Kem for MlKemManager {
@@ -145,7 +416,7 @@ impl Kem for MlKemManager {
     ///
     /// A tuple containing the public and secret keys (pk, sk)
     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
-        let mut rng = ChaCha20Rng::from_entropy();
+        let mut rng = default_rng()?;
         self.key_gen_with_rng(&mut rng)
     }
 
@@ -159,20 +430,39 @@ impl Kem for MlKemManager {
     ///
     /// A tuple containing the shared secret and ciphertext (ss, ct)
     fn encap(&mut self, pk: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
-        let mut rng = ChaCha20Rng::from_entropy();
+        let mut rng = default_rng()?;
+        self.encap_with_rng(pk, &mut rng)
+    }
+
+    /// Encapsulate a public key with the ML-KEM message `m` instead of fresh randomness
+    /// (ML-KEM.Encaps_internal, FIPS 203, Algorithm 17)
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `coins` - The 32 byte message `m`
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    fn encap_deterministic(&mut self, pk: &[u8], coins: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let m = B32::try_from(coins).map_err(|_| QubitCryptError::EncapFailed)?;
+
+        macro_rules! encapsulate_deterministic_ml {
+            ($curve:ident) => {{
+                let ek = get_encapsulation_key_obj::<$curve>(pk)?;
+                let (ct, ss) = ek
+                    .encapsulate_deterministic(&m)
+                    .map_err(|_| QubitCryptError::EncapFailed)?;
+                Ok((ss.as_slice().to_vec(), ct.as_slice().to_vec()))
+            }};
+        }
+
         match self.kem_info.kem_type {
-            KemType::MlKem512 => {
-                encapsulate_ml!(rng, MlKem512, pk)
-            }
-            KemType::MlKem768 => {
-                encapsulate_ml!(rng, MlKem768, pk)
-            }
-            KemType::MlKem1024 => {
-                encapsulate_ml!(rng, MlKem1024, pk)
-            }
-            _ => {
-                panic!("Not implemented");
-            }
+            KemType::MlKem512 => encapsulate_deterministic_ml!(MlKem512),
+            KemType::MlKem768 => encapsulate_deterministic_ml!(MlKem768),
+            KemType::MlKem1024 => encapsulate_deterministic_ml!(MlKem1024),
+            _ => Err(QubitCryptError::NotImplemented),
         }
     }
 
@@ -231,4 +521,99 @@ mod tests {
         let kem = MlKemManager::new(KemType::MlKem1024);
         test_kem!(kem);
     }
+
+    #[test]
+    fn test_ml_kem_encapsulation_key_check() {
+        for kem_type in [KemType::MlKem512, KemType::MlKem768, KemType::MlKem1024] {
+            let mut kem = MlKemManager::new(kem_type).unwrap();
+            let (pk, _) = kem.key_gen().unwrap();
+            assert!(check_encapsulation_key(&pk).is_ok());
+
+            // The first coefficient is 0xfff, which is not reduced modulo q
+            let mut bad_pk = pk.clone();
+            bad_pk[0] = 0xff;
+            bad_pk[1] |= 0x0f;
+            let result = kem.encap(&bad_pk);
+            assert!(matches!(
+                result,
+                Err(QubitCryptError::InvalidEncapsulationKey)
+            ));
+
+            // q itself is also out of range (second coefficient of the first triple)
+            let mut bad_pk = pk.clone();
+            let q = ML_KEM_Q;
+            bad_pk[1] = (bad_pk[1] & 0x0f) | (((q & 0x0f) as u8) << 4);
+            bad_pk[2] = (q >> 4) as u8;
+            let result = kem.encap(&bad_pk);
+            assert!(matches!(
+                result,
+                Err(QubitCryptError::InvalidEncapsulationKey)
+            ));
+
+            // The seed rho is not checked
+            let mut pk_rho = pk.clone();
+            let len = pk_rho.len();
+            pk_rho[len - 1] = 0xff;
+            assert!(kem.encap(&pk_rho).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_ml_kem_implicit_reject() {
+        for kem_type in [KemType::MlKem512, KemType::MlKem768, KemType::MlKem1024] {
+            let mut kem = MlKemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (ss, ct) = kem.encap(&pk).unwrap();
+            assert!(!kem.decap_is_implicit_reject(&sk, &ct).unwrap());
+
+            // A modified ciphertext still decapsulates, but to a different shared secret
+            for i in [0, ct.len() / 2, ct.len() - 1] {
+                let mut bad_ct = ct.clone();
+                bad_ct[i] ^= 0x01;
+                let bad_ss = kem.decap(&sk, &bad_ct).unwrap();
+                assert_ne!(bad_ss, ss);
+                assert!(kem.decap_is_implicit_reject(&sk, &bad_ct).unwrap());
+            }
+        }
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_decap_is_implicit_reject() {
+        use crate::kems::KemAlgorithm;
+
+        let mut kem = MlKemManager::new(KemType::MlKem768).unwrap();
+        let (pk, sk) = kem.key_gen().unwrap();
+        let (_, mut ct) = kem.encap(&pk).unwrap();
+        assert!(!decap_is_implicit_reject(&KemAlgorithm::MlKem768, &sk, &ct).unwrap());
+        ct[0] ^= 0x01;
+        assert!(decap_is_implicit_reject(&KemAlgorithm::MlKem768, &sk, &ct).unwrap());
+        assert_eq!(
+            decap_is_implicit_reject(&KemAlgorithm::XWing, &sk, &ct).err(),
+            Some(QubitCryptError::NotImplemented)
+        );
+    }
+
+    #[test]
+    fn test_ml_kem_encap_deterministic() {
+        for kem_type in [KemType::MlKem512, KemType::MlKem768, KemType::MlKem1024] {
+            let mut kem = MlKemManager::new(kem_type).unwrap();
+            let (pk, sk) = kem.key_gen().unwrap();
+            let (ss, ct) = kem.encap_deterministic(&pk, &[5u8; 32]).unwrap();
+            assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+            assert_eq!(
+                kem.encap_deterministic(&pk, &[5u8; 32]).unwrap(),
+                (ss.clone(), ct.clone())
+            );
+
+            let (ss_2, ct_2) = kem.encap_deterministic(&pk, &[6u8; 32]).unwrap();
+            assert_ne!(ss_2, ss);
+            assert_ne!(ct_2, ct);
+
+            assert_eq!(
+                kem.encap_deterministic(&pk, &[5u8; 31]),
+                Err(QubitCryptError::EncapFailed)
+            );
+        }
+    }
 }
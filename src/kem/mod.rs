@@ -6,4 +6,5 @@ pub mod ec_kem;
 pub mod kem_manager;
 pub mod ml_kem;
 pub mod rsa_kem;
+pub mod x25519_ml_kem768;
 pub mod xwing;
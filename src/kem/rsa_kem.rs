@@ -1,15 +1,15 @@
-use rand::RngCore;
-use rand_chacha::ChaCha20Rng;
-use rand_core::{CryptoRngCore, SeedableRng};
+use rand_core::CryptoRngCore;
 use sha2::Sha256;
 
 use crate::kem::common::kem_trait::Kem;
 use crate::kem::common::kem_type::KemType;
+use crate::utils::rng::default_rng;
 use crate::{kem::common::kem_info::KemInfo, QubitCryptError};
 use rsa::{
     oaep::Oaep,
     pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey, EncodeRsaPublicKey},
-    RsaPrivateKey, RsaPublicKey,
+    traits::PublicKeyParts,
+    BigUint, RsaPrivateKey, RsaPublicKey,
 };
 
 type Result<T> = std::result::Result<T, QubitCryptError>;
@@ -19,6 +19,83 @@ pub struct RsaKemManager {
     kem_info: KemInfo,
 }
 
+impl RsaKemManager {
+    /// Check that a ciphertext is well-formed for a public key, see
+    /// `KemManager::validate_ciphertext`
+    ///
+    /// An RSA-OAEP ciphertext is an integer encoded with the length of the modulus, which must
+    /// be smaller than the modulus. The padding can only be checked with the private key.
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The PKCS#1 encoded public key
+    /// * `ct` - The ciphertext
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPublicKey` if the public key can't be decoded and
+    /// `QubitCryptError::InvalidCiphertext` if the ciphertext is out of range
+    pub fn validate_ciphertext(&self, pk: &[u8], ct: &[u8]) -> Result<()> {
+        let pub_key =
+            RsaPublicKey::from_pkcs1_der(pk).map_err(|_| QubitCryptError::InvalidPublicKey)?;
+        if ct.len() != pub_key.size() || BigUint::from_bytes_be(ct) >= *pub_key.n() {
+            return Err(QubitCryptError::InvalidCiphertext);
+        }
+        Ok(())
+    }
+
+    /// Get the public key of a private key
+    ///
+    /// # Arguments
+    ///
+    /// * `sk` - The PKCS#1 encoded private key
+    ///
+    /// # Returns
+    ///
+    /// The PKCS#1 encoded public key
+    ///
+    /// # Errors
+    ///
+    /// `QubitCryptError::InvalidPrivateKey` will be returned if the private key can't be decoded
+    pub fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+        let priv_key =
+            RsaPrivateKey::from_pkcs1_der(sk).map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+        let pk = priv_key
+            .to_public_key()
+            .to_pkcs1_der()
+            .map_err(|_| QubitCryptError::InvalidPrivateKey)?;
+        Ok(pk.to_vec())
+    }
+
+    /// Encapsulate a public key, with the randomness taken from an RNG
+    ///
+    /// # Arguments
+    ///
+    /// * `pk` - The public key to encapsulate
+    /// * `rng` - The RNG to take the randomness of the encapsulation from
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing the shared secret and ciphertext (ss, ct)
+    pub(crate) fn encap_with_rng(
+        &mut self,
+        pk: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        // Generate a shared secret (32 bits)
+        let mut ss = vec![0u8; 32];
+        rng.fill_bytes(&mut ss);
+
+        let pub_key =
+            RsaPublicKey::from_pkcs1_der(pk).map_err(|_| QubitCryptError::InvalidPublicKey)?;
+        let padding = Oaep::new_with_mgf_hash::<Sha256, Sha256>();
+        let ct = pub_key
+            .encrypt(rng, padding, &ss)
+            .map_err(|_| QubitCryptError::EncapFailed)?;
+        Ok((ss, ct))
+    }
+}
+
 impl Kem for RsaKemManager {
     /// Create a new KEM instance
     ///
@@ -79,7 +156,7 @@ impl Kem for RsaKemManager {
     ///
     /// A tuple containing the public and secret keys (pk, sk)
     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
-        let mut rng = ChaCha20Rng::from_entropy();
+        let mut rng = default_rng()?;
         self.key_gen_with_rng(&mut rng)
     }
 
@@ -106,18 +183,8 @@ impl Kem for RsaKemManager {
         | ss_len             | 256 bits                          |
         +--------------------+-----------------------------------+
          */
-        // Generate a shared secret (32 bits)
-        let mut ss = vec![0u8; 32];
-        let mut rng = ChaCha20Rng::from_entropy();
-        rng.fill_bytes(&mut ss);
-
-        let pub_key =
-            RsaPublicKey::from_pkcs1_der(pk).map_err(|_| QubitCryptError::InvalidPublicKey)?;
-        let padding = Oaep::new_with_mgf_hash::<Sha256, Sha256>();
-        let ct = pub_key
-            .encrypt(&mut rng, padding, &ss)
-            .map_err(|_| QubitCryptError::EncapFailed)?;
-        Ok((ss, ct))
+        let mut rng = default_rng()?;
+        self.encap_with_rng(pk, &mut rng)
     }
 
     /// Decapsulate a ciphertext
@@ -5,23 +5,44 @@ mod cea;
 mod cms;
 mod dsa;
 mod errors;
+mod file;
 mod kdf;
 mod kem;
 mod utils;
 mod wrap;
 
+pub use dsas::register_dsa;
 pub use errors::QubitCryptError;
+pub use kems::register_kem;
 
 /// Dealing with pure/composite certificates
 pub mod certificates {
+    pub use crate::asn1::batch_issuer::BatchIssuer;
+    pub use crate::asn1::bundle::read_all;
+    pub use crate::asn1::bundle::write_bundle;
+    pub use crate::asn1::bundle::MixedBundle;
     pub use crate::asn1::cert_builder::CertValidity;
     pub use crate::asn1::cert_builder::CertificateBuilder;
     pub use crate::asn1::cert_builder::Profile;
+    pub use crate::asn1::cert_builder::SerialStrategy;
     pub use crate::asn1::certificate::Certificate;
+    pub use crate::asn1::distinguished_name::DistinguishedName;
+    pub use crate::asn1::distinguished_name::IntoSubject;
+    pub use crate::asn1::name_constraints::NameConstraint;
+    pub use crate::asn1::pki::Pki;
+    pub use crate::asn1::signature::DsaSignature;
+    pub use crate::utils::clock::Clock;
+    pub use crate::utils::clock::FixedClock;
+    pub use crate::utils::clock::SystemClock;
 }
 
 /// Dealing with pure/composite keys
 pub mod keys {
+    pub use crate::asn1::composite_public_key::ComponentOrder;
+    pub use crate::asn1::composite_public_key::CompositeComponent;
+    pub use crate::asn1::composite_public_key::CompositePublicKey;
+    pub use crate::asn1::key_capabilities::KeyCapabilities;
+    pub use crate::asn1::key_format::KeyFormat;
     pub use crate::asn1::private_key::PrivateKey;
     pub use crate::asn1::public_key::PublicKey;
 }
@@ -30,12 +51,51 @@ pub mod keys {
 pub mod dsas {
     pub use crate::dsa::api::algorithm::DsaAlgorithm;
     pub use crate::dsa::api::key_generator::DsaKeyGenerator;
+    pub use crate::dsa::api::merkle_attestation::MerkleAttestation;
+    pub use crate::dsa::api::merkle_attestation::MerkleProof;
+    pub use crate::dsa::common::config::ml_dsa_parameters::MlDsaParameters;
+    pub use crate::dsa::common::dsa_info::DsaInfo;
+    pub use crate::dsa::common::dyn_dsa::dyn_dsa_from_oid;
+    pub use crate::dsa::common::dyn_dsa::DynDsa;
+    pub use crate::dsa::common::sig_encoding::SigEncoding;
+    pub use crate::utils::registry::register_dsa;
+    pub use crate::utils::registry::unregister_dsa;
 }
 
 /// Defines KEM types and key generation
 pub mod kems {
     pub use crate::kem::api::algorithm::KemAlgorithm;
+    pub use crate::kem::api::kem_context::KemContext;
     pub use crate::kem::api::key_generator::KemKeyGenerator;
+    pub use crate::kem::common::dyn_kem::dyn_composite_kem_with_label;
+    pub use crate::kem::common::dyn_kem::dyn_kem_from_oid;
+    pub use crate::kem::common::dyn_kem::DynKem;
+    pub use crate::kem::common::kem_info::KemInfo;
+    pub use crate::kem::common::shared_secret::SharedSecret;
+    #[cfg(feature = "test-utils")]
+    pub use crate::kem::ml_kem::decap_is_implicit_reject;
+    pub use crate::kem::xwing::XWingKemManager;
+    pub use crate::kem::xwing::XWingSplitKey;
+    pub use crate::utils::registry::register_kem;
+    pub use crate::utils::registry::unregister_kem;
+}
+
+/// Lightweight encryption of files to a KEM public key
+pub mod files {
+    pub use crate::file::file_encryption::decrypt_file;
+    pub use crate::file::file_encryption::encrypt_file;
+}
+
+/// Lookups of algorithm properties by OID
+pub mod oid {
+    pub use crate::kem::common::kem_sizes::kem_ciphertext_len;
+    pub use crate::kem::common::kem_sizes::kem_shared_secret_len;
+}
+
+/// Choosing the source of the randomness used to generate keys
+pub mod rng {
+    pub use crate::utils::rng::reset_default_source;
+    pub use crate::utils::rng::set_default_source;
 }
 
 /// Defines the types of key derivation functions
@@ -55,14 +115,22 @@ pub mod content {
     pub use crate::cms::api::AttributeValue;
     pub use crate::cms::api::AuthEnvelopedDataContent;
     pub use crate::cms::api::CertificateStore;
+    pub use crate::cms::api::CmsUtil;
     pub use crate::cms::api::CmsVersion;
     pub use crate::cms::api::ContentEncryptionAlgorithm;
     pub use crate::cms::api::ContentEncryptionAlgorithmAead;
+    pub use crate::cms::api::DigestAlgorithm;
     pub use crate::cms::api::DirectoryCertificateStore;
+    pub use crate::cms::api::EnvelopedContent;
     pub use crate::cms::api::EnvelopedDataContent;
     pub use crate::cms::api::KdfType;
+    pub use crate::cms::api::KemRecipientInfo;
     pub use crate::cms::api::ObjectIdentifier;
+    pub use crate::cms::api::RecipientInfo;
+    pub use crate::cms::api::RecipientSummary;
+    pub use crate::cms::api::RecipientType;
     pub use crate::cms::api::SetOfVec;
+    pub use crate::cms::api::SignedDataContent;
     pub use crate::cms::api::Tag;
     pub use crate::cms::api::Tagged;
     pub use crate::cms::api::UserKeyingMaterial;
@@ -0,0 +1,43 @@
+//! The source of the current time for certificate validity checks
+//!
+//! The validity checks use the system clock by default. The variants which take a `Clock`,
+//! such as `Certificate::is_valid_with`, take the current time from it instead, so that expiry
+//! can be tested with fixed reference times, or a certificate can be checked as of an earlier
+//! time. `CertValidity::new_at` is the counterpart for creating certificates.
+
+use chrono::{DateTime, Utc};
+
+/// A source of the current time
+pub trait Clock {
+    /// Get the current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock, which is the default clock of the validity checks
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock which is stopped at a fixed time
+///
+/// # Example
+/// ```
+/// use qubitcrypt::certificates::{Clock, FixedClock};
+///
+/// let time = chrono::DateTime::parse_from_rfc3339("2030-01-01T00:00:00Z").unwrap();
+/// let clock = FixedClock(time.into());
+/// assert_eq!(clock.now().to_rfc3339(), "2030-01-01T00:00:00+00:00");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -1 +1,5 @@
+pub mod clock;
+pub(crate) mod oid_map;
 pub mod openssl_utils;
+pub mod registry;
+pub mod rng;
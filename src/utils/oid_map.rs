@@ -0,0 +1,65 @@
+//! Lookup tables from OIDs to algorithm types
+//!
+//! The `from_oid` functions of the KEM and DSA types share this table, which is built on the
+//! first lookup instead of scanning all variants on every call.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A map from OIDs to values, built on the first lookup
+pub(crate) struct OidMap<T> {
+    map: OnceLock<HashMap<String, T>>,
+}
+
+impl<T: Clone> OidMap<T> {
+    /// Create an empty map, which can be used for a `static`
+    pub(crate) const fn new() -> OidMap<T> {
+        OidMap {
+            map: OnceLock::new(),
+        }
+    }
+
+    /// Look up the value for an OID
+    ///
+    /// If several values have the same OID, the first one wins, as in a scan of `values`.
+    ///
+    /// # Arguments
+    ///
+    /// * `oid` - The OID to look up
+    /// * `values` - All values, only called to build the map on the first lookup
+    /// * `get_oid` - Get the OID of a value
+    ///
+    /// # Returns
+    ///
+    /// The value or None if the OID is not found
+    pub(crate) fn get(
+        &self,
+        oid: &str,
+        values: impl FnOnce() -> Vec<T>,
+        get_oid: impl Fn(&T) -> String,
+    ) -> Option<T> {
+        let map = self.map.get_or_init(|| {
+            let mut map = HashMap::new();
+            for value in values() {
+                map.entry(get_oid(&value)).or_insert(value);
+            }
+            map
+        });
+        map.get(oid).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_value_wins() {
+        let map: OidMap<(&str, u8)> = OidMap::new();
+        let values = || vec![("1.2.3", 1), ("1.2.4", 2), ("1.2.3", 3)];
+        let get_oid = |value: &(&str, u8)| value.0.to_string();
+        assert_eq!(map.get("1.2.3", values, get_oid), Some(("1.2.3", 1)));
+        assert_eq!(map.get("1.2.4", values, get_oid), Some(("1.2.4", 2)));
+        assert_eq!(map.get("1.2.5", values, get_oid), None);
+    }
+}
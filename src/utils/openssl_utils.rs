@@ -104,6 +104,55 @@ pub fn get_ec_key_from_pk(id: Nid, pk: &[u8]) -> Result<EcKey<Public>> {
     Ok(pk)
 }
 
+/// Check that an encoded point is a valid public key using the EcKey API
+///
+/// # Arguments
+///
+/// * `point` - The point as an octet string
+/// * `nid` - The curve
+///
+/// # Returns
+///
+/// True if the point is on the curve and not the point at infinity, false otherwise
+pub fn is_valid_point_ec_based(point: &[u8], nid: Nid) -> Result<bool> {
+    match get_ec_key_from_pk(nid, point) {
+        Ok(key) => Ok(key.check_key().is_ok()),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Check that a point is a valid public key using the PKey API (X25519 or X448)
+///
+/// Every string of the right length is a point, so this checks that the point does not have
+/// small order. As the private keys are clamped to multiples of the cofactor, a key agreement
+/// with a small order point results in the all-zero shared secret for any private key, so a
+/// key agreement with a fresh key pair detects them.
+///
+/// # Arguments
+///
+/// * `point` - The raw point
+/// * `id` - The ID of the curve (X25519 or X448)
+///
+/// # Returns
+///
+/// True if the point has the right length and does not have small order, false otherwise
+pub fn is_valid_point_pkey_based(point: &[u8], id: Id) -> Result<bool> {
+    let pk = match PKey::public_key_from_raw_bytes(point, id) {
+        Ok(pk) => pk,
+        Err(_) => return Ok(false),
+    };
+    let (_, sk) = get_key_pair_pkey_based(id)?;
+    let sk = PKey::private_key_from_raw_bytes(&sk, id)?;
+    let mut deriver = Deriver::new(&sk)?;
+    if deriver.set_peer(&pk).is_err() {
+        return Ok(false);
+    }
+    match deriver.derive_to_vec() {
+        Ok(ss) => Ok(ss.iter().any(|b| *b != 0)),
+        Err(_) => Ok(false),
+    }
+}
+
 /// Decapsulate a ciphertext using the EcKey API
 ///
 /// # Arguments
@@ -437,6 +486,30 @@ pub fn get_pk_from_sk_ec_based(sk: &[u8], nid: Nid) -> Result<Vec<u8>> {
     )?)
 }
 
+/// Check that a secret key is a scalar in the range [1, n-1], where n is the order of the group.
+/// This method is used for curves supported by the `EcKey` API.
+///
+/// OpenSSL would otherwise accept a scalar outside of this range and silently reduce it, turning
+/// a corrupted key into a valid but different key.
+///
+/// # Arguments
+///
+/// * `sk` - The secret key
+/// * `nid` - The ID of the curve
+///
+/// # Returns
+///
+/// True if the secret key is in range, false otherwise
+pub fn is_sk_in_range_ec_based(sk: &[u8], nid: Nid) -> Result<bool> {
+    let mut ctx = BigNumContext::new()?;
+    let group = EcGroup::from_curve_name(nid)?;
+    let mut order = BigNum::new()?;
+    group.order(&mut order, &mut ctx)?;
+
+    let d = BigNum::from_slice(sk)?;
+    Ok(d >= BigNum::from_u32(1)? && d < order)
+}
+
 /// Get the public key from a secret key for a PKey based method (not used currently)
 ///
 /// # Arguments
@@ -0,0 +1,534 @@
+//! Custom KEMs and DSAs registered at runtime
+//!
+//! A KEM or DSA which is not built into the crate, e.g. an experimental hybrid, can be
+//! registered under its own OID. `KemManager::new_from_oid` and `DsaManager::new_from_oid` then
+//! create it, so it is used everywhere a key is resolved by its OID: `dyn_kem_from_oid`,
+//! `dyn_dsa_from_oid`, `PublicKey::encap`, `PublicKey::verify`, `PrivateKey::sign`,
+//! `PrivateKey::decap` and the KEM recipients of CMS.
+//!
+//! The built-in algorithms always take precedence: an OID of a built-in KEM or DSA can't be
+//! registered, so registering an algorithm never changes how existing keys are handled. The
+//! registries are shared by all threads.
+
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use spki::ObjectIdentifier;
+
+use crate::dsa::common::dsa_type::DsaType;
+use crate::dsa::common::dyn_dsa::DynDsa;
+use crate::kem::common::dyn_kem::DynKem;
+use crate::kem::common::kem_type::KemType;
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// A function creating a new instance of a custom KEM
+pub(crate) type KemFactory = Arc<dyn Fn() -> Box<dyn DynKem + Send + Sync> + Send + Sync>;
+
+/// A function creating a new instance of a custom DSA
+pub(crate) type DsaFactory = Arc<dyn Fn() -> Box<dyn DynDsa + Send + Sync> + Send + Sync>;
+
+/// The custom KEMs by OID
+static KEM_REGISTRY: RwLock<Vec<(String, KemFactory)>> = RwLock::new(Vec::new());
+
+/// The custom DSAs by OID
+static DSA_REGISTRY: RwLock<Vec<(String, DsaFactory)>> = RwLock::new(Vec::new());
+
+/// Register a custom KEM under an OID
+///
+/// Registering an OID again replaces the previous factory. Instances which were already
+/// created are not affected.
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM
+/// * `factory` - A function creating a new instance of the KEM, called whenever the OID is
+///   resolved
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidOid` will be returned if the OID is malformed, is the OID of a
+/// built-in KEM or DSA, or is registered as a custom DSA
+///
+/// # Example
+/// ```
+/// use qubitcrypt::kems::{dyn_kem_from_oid, register_kem, DynKem, KemAlgorithm, KemInfo};
+/// use qubitcrypt::keys::PublicKey;
+/// use qubitcrypt::QubitCryptError;
+/// use rand_core::CryptoRngCore;
+///
+/// const OID: &str = "1.3.6.1.4.1.99999.1.1";
+///
+/// /// An experimental KEM, here ML-KEM-768 under a private OID
+/// struct MyKem;
+///
+/// fn ml_kem() -> Box<dyn DynKem> {
+///     dyn_kem_from_oid(&KemAlgorithm::MlKem768.get_oid()).unwrap()
+/// }
+///
+/// impl DynKem for MyKem {
+///     fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>), QubitCryptError> {
+///         ml_kem().key_gen()
+///     }
+///     fn key_gen_with_rng(
+///         &mut self,
+///         rng: &mut dyn CryptoRngCore,
+///     ) -> Result<(Vec<u8>, Vec<u8>), QubitCryptError> {
+///         ml_kem().key_gen_with_rng(rng)
+///     }
+///     fn encap(&mut self, pk: &[u8]) -> Result<(Vec<u8>, Vec<u8>), QubitCryptError> {
+///         ml_kem().encap(pk)
+///     }
+///     fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>, QubitCryptError> {
+///         ml_kem().decap(sk, ct)
+///     }
+///     fn get_kem_info(&self) -> KemInfo {
+///         KemInfo::new_custom(KemAlgorithm::MlKem768, OID)
+///     }
+/// }
+///
+/// register_kem(OID, || Box::new(MyKem)).unwrap();
+///
+/// let mut kem = dyn_kem_from_oid(OID).unwrap();
+/// let (pk, sk) = kem.key_gen().unwrap();
+/// let (ss, ct) = PublicKey::new(OID, &pk).unwrap().encap().unwrap();
+/// assert_eq!(kem.decap(&sk, &ct).unwrap(), ss);
+///
+/// // The OIDs of built-in KEMs can't be taken over
+/// assert!(register_kem(&KemAlgorithm::MlKem768.get_oid(), || Box::new(MyKem)).is_err());
+/// ```
+pub fn register_kem(
+    oid: &str,
+    factory: impl Fn() -> Box<dyn DynKem + Send + Sync> + Send + Sync + 'static,
+) -> Result<()> {
+    check_custom_oid(oid)?;
+    // Both registries are always locked in the same order, KEMs first
+    let mut kems = write(&KEM_REGISTRY);
+    if find(&read(&DSA_REGISTRY), oid).is_some() {
+        return Err(QubitCryptError::InvalidOid);
+    }
+    register(&mut kems, oid, Arc::new(factory));
+    Ok(())
+}
+
+/// Register a custom DSA under an OID
+///
+/// Registering an OID again replaces the previous factory. Instances which were already
+/// created are not affected.
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the DSA
+/// * `factory` - A function creating a new instance of the DSA, called whenever the OID is
+///   resolved
+///
+/// # Errors
+///
+/// `QubitCryptError::InvalidOid` will be returned if the OID is malformed, is the OID of a
+/// built-in KEM or DSA, or is registered as a custom KEM
+pub fn register_dsa(
+    oid: &str,
+    factory: impl Fn() -> Box<dyn DynDsa + Send + Sync> + Send + Sync + 'static,
+) -> Result<()> {
+    check_custom_oid(oid)?;
+    let kems = read(&KEM_REGISTRY);
+    let mut dsas = write(&DSA_REGISTRY);
+    if find(&kems, oid).is_some() {
+        return Err(QubitCryptError::InvalidOid);
+    }
+    register(&mut dsas, oid, Arc::new(factory));
+    Ok(())
+}
+
+/// Remove a custom KEM registered with `register_kem`
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM
+///
+/// # Returns
+///
+/// True if a KEM was registered under the OID, false otherwise
+pub fn unregister_kem(oid: &str) -> bool {
+    unregister(&mut write(&KEM_REGISTRY), oid)
+}
+
+/// Remove a custom DSA registered with `register_dsa`
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the DSA
+///
+/// # Returns
+///
+/// True if a DSA was registered under the OID, false otherwise
+pub fn unregister_dsa(oid: &str) -> bool {
+    unregister(&mut write(&DSA_REGISTRY), oid)
+}
+
+/// Create an instance of the custom KEM registered under an OID
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the KEM
+///
+/// # Returns
+///
+/// The new instance, or None if no KEM is registered under the OID
+pub(crate) fn new_custom_kem(oid: &str) -> Option<Box<dyn DynKem + Send + Sync>> {
+    // The factory is called without holding the lock, in case it resolves OIDs itself
+    let factory = find(&read(&KEM_REGISTRY), oid)?;
+    Some(factory())
+}
+
+/// An instance of a custom DSA
+///
+/// `DsaManager` is `Clone`, so the instance keeps its factory to create a new instance when
+/// it is cloned.
+pub(crate) struct CustomDsa {
+    factory: DsaFactory,
+    dsa: Box<dyn DynDsa + Send + Sync>,
+}
+
+impl CustomDsa {
+    /// Get the instance
+    pub(crate) fn get(&self) -> &(dyn DynDsa + Send + Sync) {
+        self.dsa.as_ref()
+    }
+
+    /// Get the instance mutably
+    pub(crate) fn get_mut(&mut self) -> &mut (dyn DynDsa + Send + Sync) {
+        self.dsa.as_mut()
+    }
+}
+
+impl Clone for CustomDsa {
+    fn clone(&self) -> Self {
+        CustomDsa {
+            factory: self.factory.clone(),
+            dsa: (self.factory)(),
+        }
+    }
+}
+
+/// Create an instance of the custom DSA registered under an OID
+///
+/// # Arguments
+///
+/// * `oid` - The OID of the DSA
+///
+/// # Returns
+///
+/// The new instance, or None if no DSA is registered under the OID
+pub(crate) fn new_custom_dsa(oid: &str) -> Option<CustomDsa> {
+    let factory = find(&read(&DSA_REGISTRY), oid)?;
+    let dsa = factory();
+    Some(CustomDsa { factory, dsa })
+}
+
+/// Check if a custom KEM is registered under an OID
+pub(crate) fn is_custom_kem_oid(oid: &str) -> bool {
+    find(&read(&KEM_REGISTRY), oid).is_some()
+}
+
+/// Check if a custom DSA is registered under an OID
+pub(crate) fn is_custom_dsa_oid(oid: &str) -> bool {
+    find(&read(&DSA_REGISTRY), oid).is_some()
+}
+
+/// Check that an OID is well-formed and not used by a built-in algorithm
+fn check_custom_oid(oid: &str) -> Result<()> {
+    ObjectIdentifier::new(oid).map_err(|_| QubitCryptError::InvalidOid)?;
+    let is_built_in = KemType::from_oid(oid).is_some() || DsaType::from_oid(oid).is_some();
+    if is_built_in {
+        return Err(QubitCryptError::InvalidOid);
+    }
+    Ok(())
+}
+
+/// Add or replace a factory in a registry
+fn register<F>(registry: &mut Vec<(String, F)>, oid: &str, factory: F) {
+    match registry
+        .iter_mut()
+        .find(|(custom_oid, _)| custom_oid == oid)
+    {
+        Some((_, existing)) => *existing = factory,
+        None => registry.push((oid.to_string(), factory)),
+    }
+}
+
+/// Remove a factory from a registry
+fn unregister<F>(registry: &mut Vec<(String, F)>, oid: &str) -> bool {
+    let len = registry.len();
+    registry.retain(|(custom_oid, _)| custom_oid != oid);
+    registry.len() != len
+}
+
+/// Find the factory for an OID in a registry
+fn find<F: Clone>(registry: &[(String, F)], oid: &str) -> Option<F> {
+    registry
+        .iter()
+        .find(|(custom_oid, _)| custom_oid == oid)
+        .map(|(_, factory)| factory.clone())
+}
+
+/// Lock a registry for reading, which stays usable if a thread panicked while holding the lock
+fn read<T>(registry: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    registry.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Lock a registry for writing, which stays usable if a thread panicked while holding the lock
+fn write<T>(registry: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    registry.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::CryptoRngCore;
+
+    use super::*;
+    use crate::certificates::{BatchIssuer, CertValidity, CertificateBuilder, Profile};
+    use crate::cms::cms_util::CmsUtil;
+    use crate::content::{ContentEncryptionAlgorithm, EnvelopedDataContent};
+    use crate::dsa::common::dsa_trait::Dsa;
+    use crate::dsa::dsa_manager::DsaManager;
+    use crate::dsas::{dyn_dsa_from_oid, DsaAlgorithm, DsaInfo, DsaKeyGenerator};
+    use crate::kdfs::KdfType;
+    use crate::kem::common::kem_trait::Kem;
+    use crate::kem::kem_manager::KemManager;
+    use crate::kems::{dyn_kem_from_oid, KemAlgorithm, KemInfo};
+    use crate::keys::{PrivateKey, PublicKey};
+    use crate::wraps::WrapType;
+
+    /// ML-KEM-512 under a custom OID
+    struct TestKem(KemManager, &'static str);
+
+    impl DynKem for TestKem {
+        fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+            Kem::key_gen(&mut self.0)
+        }
+
+        fn key_gen_with_rng(
+            &mut self,
+            mut rng: &mut dyn CryptoRngCore,
+        ) -> Result<(Vec<u8>, Vec<u8>)> {
+            Kem::key_gen_with_rng(&mut self.0, &mut rng)
+        }
+
+        fn encap(&mut self, pk: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+            Kem::encap(&mut self.0, pk)
+        }
+
+        fn decap(&self, sk: &[u8], ct: &[u8]) -> Result<Vec<u8>> {
+            Kem::decap(&self.0, sk, ct)
+        }
+
+        fn get_kem_info(&self) -> KemInfo {
+            KemInfo::new_custom(KemAlgorithm::MlKem512, self.1)
+        }
+    }
+
+    /// ML-DSA-44 under a custom OID
+    struct TestDsa(DsaManager, &'static str);
+
+    impl DynDsa for TestDsa {
+        fn key_gen(&mut self) -> Result<(Vec<u8>, Vec<u8>)> {
+            Dsa::key_gen(&mut self.0)
+        }
+
+        fn key_gen_with_rng(
+            &mut self,
+            mut rng: &mut dyn CryptoRngCore,
+        ) -> Result<(Vec<u8>, Vec<u8>)> {
+            Dsa::key_gen_with_rng(&mut self.0, &mut rng)
+        }
+
+        fn sign(&self, sk: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
+            Dsa::sign(&self.0, sk, msg)
+        }
+
+        fn verify(&self, pk: &[u8], msg: &[u8], signature: &[u8]) -> Result<bool> {
+            Dsa::verify(&self.0, pk, msg, signature)
+        }
+
+        fn get_dsa_info(&self) -> DsaInfo {
+            DsaInfo::new_custom(DsaAlgorithm::MlDsa44, self.1)
+        }
+
+        fn get_public_key(&self, sk: &[u8]) -> Result<Vec<u8>> {
+            Dsa::get_public_key(&self.0, sk)
+        }
+    }
+
+    fn test_kem(oid: &'static str) -> Box<dyn DynKem + Send + Sync> {
+        let kem = KemManager::new(KemAlgorithm::MlKem512.get_kem_type()).unwrap();
+        Box::new(TestKem(kem, oid))
+    }
+
+    fn test_dsa(oid: &'static str) -> Box<dyn DynDsa + Send + Sync> {
+        let dsa = DsaManager::new(DsaAlgorithm::MlDsa44.get_dsa_type()).unwrap();
+        Box::new(TestDsa(dsa, oid))
+    }
+
+    #[test]
+    fn test_custom_kem() {
+        const OID: &str = "1.3.6.1.4.1.99999.2.1";
+        assert!(matches!(
+            KemManager::new_from_oid(OID),
+            Err(QubitCryptError::InvalidOid)
+        ));
+        register_kem(OID, || test_kem(OID)).unwrap();
+
+        let mut kem = dyn_kem_from_oid(OID).unwrap();
+        assert_eq!(kem.get_kem_info().oid, OID);
+        let (pk, sk) = kem.key_gen().unwrap();
+        let pk = PublicKey::new(OID, &pk).unwrap();
+        let sk = PrivateKey::new(OID, &sk).unwrap();
+        assert!(pk.capabilities().can_encap);
+        let (ss, ct) = pk.encap().unwrap();
+        assert_eq!(sk.decap(&ct).unwrap(), ss);
+
+        // The custom KEM can receive CMS content
+        let (root_pk, root_sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let validity = CertValidity::new(None, "2035-01-01T00:00:00Z").unwrap();
+        let root = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=Root",
+            root_pk,
+            &root_sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let profile = Profile::Leaf {
+            issuer: root.get_subject(),
+            enable_key_agreement: false,
+            enable_key_encipherment: true,
+        };
+        let cert =
+            CertificateBuilder::new(profile, None, validity, "CN=Leaf", pk.clone(), &root_sk)
+                .unwrap()
+                .build()
+                .unwrap();
+        let mut builder =
+            EnvelopedDataContent::get_builder(ContentEncryptionAlgorithm::Aes128Cbc).unwrap();
+        builder
+            .kem_recipient(&cert, &KdfType::HkdfWithSha256, &WrapType::Aes128, None)
+            .unwrap()
+            .content(b"abc")
+            .unwrap();
+        let data = builder.build().unwrap();
+        assert_eq!(CmsUtil::decrypt_kemri(&data, &sk, &cert).unwrap(), b"abc");
+
+        assert!(unregister_kem(OID));
+        assert!(!unregister_kem(OID));
+        assert!(dyn_kem_from_oid(OID).is_err());
+        assert_eq!(pk.encap(), Err(QubitCryptError::UnsupportedOperation));
+    }
+
+    #[test]
+    fn test_custom_dsa() {
+        const OID: &str = "1.3.6.1.4.1.99999.2.2";
+        register_dsa(OID, || test_dsa(OID)).unwrap();
+
+        let mut dsa = dyn_dsa_from_oid(OID).unwrap();
+        assert_eq!(dsa.get_dsa_info().oid, OID);
+        let (pk, sk) = dsa.key_gen().unwrap();
+        let pk = PublicKey::new(OID, &pk).unwrap();
+        let sk = PrivateKey::new(OID, &sk).unwrap();
+        let sig = sk.sign(b"message").unwrap();
+        assert!(pk.verify(b"message", &sig).unwrap());
+        assert!(!pk.verify(b"other message", &sig).unwrap());
+
+        // The custom DSA can issue certificates
+        let validity = CertValidity::new(None, "2034-01-01T00:00:00Z").unwrap();
+        let root = CertificateBuilder::new(
+            Profile::Root,
+            None,
+            validity.clone(),
+            "CN=root",
+            pk.clone(),
+            &sk,
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let (leaf_pk, _) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        let profile = Profile::Leaf {
+            issuer: root.get_subject(),
+            enable_key_agreement: false,
+            enable_key_encipherment: false,
+        };
+        let leaf = BatchIssuer::new(&sk)
+            .unwrap()
+            .issue(profile, "CN=leaf", leaf_pk, validity)
+            .unwrap();
+        assert!(root.verify_child(&leaf).unwrap());
+
+        // A clone is a new instance of the same DSA
+        let manager = DsaManager::new_from_oid(OID).unwrap();
+        let clone = manager.clone();
+        assert_eq!(Dsa::get_dsa_info(&clone).oid, OID);
+        assert!(Dsa::verify(&clone, pk.get_key(), b"message", &sig).unwrap());
+        assert_eq!(
+            manager.sign_prehashed(sk.get_key(), &[0u8; 64], "2.16.840.1.101.3.4.2.3", b""),
+            Err(QubitCryptError::UnsupportedOperation)
+        );
+
+        assert!(unregister_dsa(OID));
+        assert!(dyn_dsa_from_oid(OID).is_err());
+    }
+
+    #[test]
+    fn test_register_errors() {
+        let kem_oid = "1.3.6.1.4.1.99999.2.3";
+        let dsa_oid = "1.3.6.1.4.1.99999.2.4";
+
+        // The built-in algorithms win
+        for oid in [
+            KemAlgorithm::MlKem768.get_oid(),
+            DsaAlgorithm::MlDsa44.get_oid(),
+        ] {
+            assert_eq!(
+                register_kem(&oid, move || test_kem(kem_oid)),
+                Err(QubitCryptError::InvalidOid)
+            );
+            assert_eq!(
+                register_dsa(&oid, move || test_dsa(dsa_oid)),
+                Err(QubitCryptError::InvalidOid)
+            );
+        }
+        assert_eq!(
+            register_kem("not an oid", move || test_kem(kem_oid)),
+            Err(QubitCryptError::InvalidOid)
+        );
+
+        // An OID is either a KEM or a DSA
+        register_kem(kem_oid, move || test_kem(kem_oid)).unwrap();
+        register_dsa(dsa_oid, move || test_dsa(dsa_oid)).unwrap();
+        assert_eq!(
+            register_dsa(kem_oid, move || test_dsa(kem_oid)),
+            Err(QubitCryptError::InvalidOid)
+        );
+        assert_eq!(
+            register_kem(dsa_oid, move || test_kem(dsa_oid)),
+            Err(QubitCryptError::InvalidOid)
+        );
+
+        // Registering again replaces the factory
+        register_kem(kem_oid, move || test_kem("1.2.3")).unwrap();
+        assert_eq!(
+            dyn_kem_from_oid(kem_oid).unwrap().get_kem_info().oid,
+            "1.2.3"
+        );
+
+        assert!(unregister_kem(kem_oid));
+        assert!(unregister_dsa(dsa_oid));
+    }
+}
@@ -0,0 +1,232 @@
+//! The source of the randomness used to generate keys
+//!
+//! By default keys are generated with a `ChaCha20Rng` seeded from the operating system, or
+//! with OpenSSL's RNG for the algorithms implemented by OpenSSL. An application which must
+//! use a specific entropy source, e.g. a hardware RNG such as RDSEED or an HSM, can set it
+//! as the default source. Every default RNG is then a `ChaCha20Rng` seeded from it, including
+//! the ones for the algorithms otherwise implemented with OpenSSL's RNG.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRngCore, SeedableRng};
+use zeroize::Zeroize;
+
+use crate::QubitCryptError;
+
+type Result<T> = std::result::Result<T, QubitCryptError>;
+
+/// The entropy source set by the application, if any
+static DEFAULT_SOURCE: Mutex<Option<Box<dyn CryptoRngCore + Send>>> = Mutex::new(None);
+
+/// Set the entropy source which seeds the default RNG of key generation
+///
+/// The source replaces any source set before and is used by all threads. Functions which
+/// take an RNG, such as `key_gen_with_rng`, use that RNG instead.
+///
+/// # Arguments
+///
+/// * `source` - The entropy source
+///
+/// # Example
+/// ```
+/// use qubitcrypt::dsas::{DsaAlgorithm, DsaKeyGenerator};
+/// use qubitcrypt::rng;
+/// use rand_chacha::ChaCha20Rng;
+/// use rand_core::SeedableRng;
+///
+/// // Any CryptoRng can be used, e.g. one reading a hardware RNG
+/// rng::set_default_source(Box::new(ChaCha20Rng::from_entropy()));
+/// let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44).generate().unwrap();
+/// rng::reset_default_source();
+/// ```
+pub fn set_default_source(source: Box<dyn CryptoRngCore + Send>) {
+    *lock_source() = Some(source);
+}
+
+/// Remove the entropy source set with `set_default_source`, restoring the built-in sources
+pub fn reset_default_source() {
+    *lock_source() = None;
+}
+
+/// Get a `ChaCha20Rng` seeded from the entropy source set with `set_default_source`
+///
+/// # Returns
+///
+/// The seeded RNG, or `None` if no entropy source is set
+///
+/// # Errors
+///
+/// `QubitCryptError::RngFailed` will be returned if the entropy source fails
+pub(crate) fn rng_from_default_source() -> Result<Option<ChaCha20Rng>> {
+    match lock_source().as_mut() {
+        Some(source) => seed_rng(source.as_mut()).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Get the default RNG for key generation
+///
+/// This is a `ChaCha20Rng` seeded from the entropy source set with `set_default_source`, or
+/// from the operating system if there is none.
+///
+/// # Errors
+///
+/// `QubitCryptError::RngFailed` will be returned if the entropy source fails
+pub(crate) fn default_rng() -> Result<ChaCha20Rng> {
+    match rng_from_default_source()? {
+        Some(rng) => Ok(rng),
+        None => Ok(ChaCha20Rng::from_entropy()),
+    }
+}
+
+/// Seed a `ChaCha20Rng` from an entropy source
+fn seed_rng(source: &mut dyn CryptoRngCore) -> Result<ChaCha20Rng> {
+    let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+    source
+        .try_fill_bytes(&mut seed)
+        .map_err(|_| QubitCryptError::RngFailed)?;
+    let rng = ChaCha20Rng::from_seed(seed);
+    seed.zeroize();
+    Ok(rng)
+}
+
+/// Lock the entropy source, which stays usable if a thread panicked while holding the lock
+fn lock_source() -> MutexGuard<'static, Option<Box<dyn CryptoRngCore + Send>>> {
+    DEFAULT_SOURCE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use rand_core::{CryptoRng, RngCore};
+
+    use super::*;
+    use crate::dsas::{DsaAlgorithm, DsaKeyGenerator};
+    use crate::kem::common::kem_trait::Kem;
+    use crate::kem::common::kem_type::KemType;
+    use crate::kem::xwing::XWingKemManager;
+    use crate::kems::{KemAlgorithm, KemKeyGenerator};
+
+    /// An entropy source which counts how often it is used
+    struct CountingSource {
+        rng: ChaCha20Rng,
+        count: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    impl RngCore for CountingSource {
+        fn next_u32(&mut self) -> u32 {
+            self.rng.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.rng.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.try_fill_bytes(dest).unwrap()
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand_core::Error> {
+            if self.fail {
+                return Err(rand_core::Error::new("entropy source failure"));
+            }
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.rng.try_fill_bytes(dest)
+        }
+    }
+
+    impl CryptoRng for CountingSource {}
+
+    /// Serializes the tests which install an entropy source
+    static SOURCE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    /// An entropy source installed for one test
+    ///
+    /// The previous source is restored when the guard is dropped, also if the test panics, so
+    /// the source doesn't leak into the tests which run afterwards.
+    struct SourceGuard {
+        previous: Option<Box<dyn CryptoRngCore + Send>>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl SourceGuard {
+        fn install(source: Box<dyn CryptoRngCore + Send>) -> SourceGuard {
+            let lock = SOURCE_TEST_LOCK
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let previous = lock_source().take();
+            set_default_source(source);
+            SourceGuard {
+                previous,
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for SourceGuard {
+        fn drop(&mut self) {
+            match self.previous.take() {
+                Some(previous) => set_default_source(previous),
+                None => reset_default_source(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_source() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let guard = SourceGuard::install(Box::new(CountingSource {
+            rng: ChaCha20Rng::from_seed([7u8; 32]),
+            count: count.clone(),
+            fail: false,
+        }));
+
+        // Other tests may generate keys at the same time, so only a lower bound can be checked
+        let (pk, sk) = DsaKeyGenerator::new(DsaAlgorithm::MlDsa44)
+            .generate()
+            .unwrap();
+        assert!(pk
+            .verify(b"message", &sk.sign(b"message").unwrap())
+            .unwrap());
+        assert!(count.load(Ordering::SeqCst) >= 1);
+
+        // Including the algorithms which otherwise use OpenSSL's RNG
+        let before = count.load(Ordering::SeqCst);
+        XWingKemManager::new(KemType::XWing)
+            .unwrap()
+            .key_gen()
+            .unwrap();
+        KemKeyGenerator::new(KemAlgorithm::MlKem768P384)
+            .generate()
+            .unwrap();
+        assert!(count.load(Ordering::SeqCst) >= before + 3);
+
+        drop(guard);
+        assert!(rng_from_default_source().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_seed_rng() {
+        let mut source = ChaCha20Rng::from_seed([1u8; 32]);
+        let mut a = seed_rng(&mut source).unwrap();
+        let mut source = ChaCha20Rng::from_seed([1u8; 32]);
+        let mut b = seed_rng(&mut source).unwrap();
+        assert_eq!(a.next_u64(), b.next_u64());
+
+        let mut failing = CountingSource {
+            rng: ChaCha20Rng::from_seed([1u8; 32]),
+            count: Arc::new(AtomicUsize::new(0)),
+            fail: true,
+        };
+        assert!(matches!(
+            seed_rng(&mut failing),
+            Err(QubitCryptError::RngFailed)
+        ));
+    }
+}
@@ -36,4 +36,12 @@ impl WrapType {
             .into_iter()
             .find(|wrap_type| wrap_type.get_oid() == oid)
     }
+
+    /// Get the security strength of the key wrap in bits, which is its key size
+    pub(crate) fn security_strength(&self) -> u16 {
+        match self {
+            WrapType::Aes128 => 128,
+            WrapType::Aes256 => 256,
+        }
+    }
 }